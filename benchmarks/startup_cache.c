@@ -0,0 +1,68 @@
+// Process startup with and without the relocation cache.
+//
+// Each iteration re-executes this binary with a "child" argument, which exits
+// immediately, and waits for it; the time covers exec, loading, relocation
+// and teardown. The child runs with MIROS_SYSTEM_FALLBACK=1 so the system
+// libc joins the dependency graph and there is real binding work to skip.
+// Under glibc both phases measure the same thing and serve as a baseline.
+//
+// Process control goes through raw syscalls (like the clock in bench.h) so
+// both builds take the same path regardless of what either libc exports.
+//
+// Output is CSV on stdout: phase,iterations,total_ns
+
+#include "bench.h"
+
+#define CACHE_DIRECTORY "/tmp/miros-startup-cache"
+
+static long raw_syscall(long number, long first, long second, long third, long fourth) {
+    long result;
+    register long r10 __asm__("r10") = fourth;
+    __asm__ volatile (
+        "syscall"
+        : "=a"(result)
+        : "0"(number), "D"(first), "S"(second), "d"(third), "r"(r10)
+        : "rcx", "r11", "memory"
+    );
+    return result;
+}
+
+static char *self_path = "/proc/self/exe";
+
+// Forks, execs the child with `environment` and waits for it to exit.
+static void spawn(char **environment) {
+    char *arguments[] = {self_path, "child", 0};
+    long pid = raw_syscall(57, 0, 0, 0, 0);  // SYS_fork
+    if (pid == 0) {
+        raw_syscall(59, (long)self_path, (long)arguments, (long)environment, 0);  // SYS_execve
+        raw_syscall(60, 127, 0, 0, 0);  // SYS_exit
+    }
+    if (pid < 0) abort();
+    int status = 0;
+    if (raw_syscall(61, pid, (long)&status, 0, 0) != pid || status != 0) abort();  // SYS_wait4
+}
+
+// ── phases ──────────────────────────────────────────────────────────────
+
+static void run(const char *phase, char **environment) {
+    const uint64_t iterations = 500;
+    uint64_t start = monotonic_ns();
+    for (uint64_t index = 0; index < iterations; index++) {
+        spawn(environment);
+    }
+    emit(phase, iterations, monotonic_ns() - start);
+}
+
+int main(int argument_count, char **arguments) {
+    if (argument_count > 1) return 0;
+
+    char *cold[] = {"MIROS_SYSTEM_FALLBACK=1", 0};
+    char *cached[] = {"MIROS_SYSTEM_FALLBACK=1", "MIROS_RELOCATION_CACHE=" CACHE_DIRECTORY, 0};
+
+    raw_syscall(83, (long)CACHE_DIRECTORY, 0755, 0, 0);  // SYS_mkdir; EEXIST is fine
+    spawn(cached);  // populate the cache so every timed run replays it
+
+    run("exec_cold", cold);
+    run("exec_cached", cached);
+    return 0;
+}
@@ -0,0 +1,41 @@
+// An LD_AUDIT module: traces loads and redirects the program's `puts` through a prefixing wrapper.
+#define _GNU_SOURCE
+#include <link.h>
+#include <stdio.h>
+#include <string.h>
+
+static int objects_opened = 0;
+
+static int audited_puts(const char *message) {
+  fputs("[audited] ", stdout);
+  return puts(message);
+}
+
+unsigned int la_version(unsigned int version) { return version; }
+
+unsigned int la_objopen(struct link_map *map, Lmid_t lmid, uintptr_t *cookie) {
+  (void)map;
+  (void)lmid;
+  (void)cookie;
+  objects_opened++;
+  return LA_FLG_BINDTO | LA_FLG_BINDFROM;
+}
+
+void la_preinit(uintptr_t *cookie) {
+  (void)cookie;
+  printf("[audit] %d objects opened\n", objects_opened);
+}
+
+uintptr_t la_symbind64(Elf64_Sym *sym, unsigned int ndx, uintptr_t *refcook,
+                       uintptr_t *defcook, unsigned int *flags,
+                       const char *symname) {
+  (void)ndx;
+  (void)refcook;
+  (void)defcook;
+  // Only PLT bindings are offered, and the linker never calls la_pltenter or la_pltexit.
+  unsigned int no_plt_hooks = LA_SYMB_NOPLTENTER | LA_SYMB_NOPLTEXIT;
+  if ((*flags & no_plt_hooks) == no_plt_hooks && strcmp(symname, "puts") == 0) {
+    return (uintptr_t)audited_puts;
+  }
+  return sym->st_value;
+}
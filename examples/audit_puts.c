@@ -0,0 +1,7 @@
+// Run as `LD_AUDIT=examples/bin/audit_module examples/bin/audit_puts`; the auditor rebinds `puts`.
+#include <stdio.h>
+
+int main(void) {
+  puts("hello from the audited program");
+  return 0;
+}
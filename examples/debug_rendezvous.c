@@ -0,0 +1,51 @@
+// Walks the debugger rendezvous the way gdb's `info sharedlibrary` does: DT_DEBUG → r_debug → link_map chain.
+#include <link.h>
+#include <stdio.h>
+#include <string.h>
+
+int main(void) {
+  // The static linker reserves DT_DEBUG in the executable; the interpreter fills it in.
+  struct r_debug *rendezvous = NULL;
+  for (ElfW(Dyn) *entry = _DYNAMIC; entry->d_tag != DT_NULL; entry++) {
+    if (entry->d_tag == DT_DEBUG) {
+      rendezvous = (struct r_debug *)entry->d_un.d_ptr;
+    }
+  }
+  if (rendezvous != &_r_debug) {
+    puts("DT_DEBUG FAILED");
+    return 1;
+  }
+  if (rendezvous->r_version != 1 || rendezvous->r_state != RT_CONSISTENT ||
+      rendezvous->r_brk == 0 || rendezvous->r_ldbase == 0) {
+    puts("r_debug fields FAILED");
+    return 1;
+  }
+
+  // The main program comes first with an empty name, the interpreter last.
+  struct link_map *map = rendezvous->r_map;
+  if (map == NULL || map->l_prev != NULL || strcmp(map->l_name, "") != 0 ||
+      map->l_ld != _DYNAMIC) {
+    puts("main link_map FAILED");
+    return 1;
+  }
+
+  int count = 0;
+  struct link_map *last = NULL;
+  for (; map != NULL; map = map->l_next) {
+    if (map->l_prev != last) {
+      puts("l_prev FAILED");
+      return 1;
+    }
+    printf("%s\n", map->l_name[0] ? map->l_name : "[program]");
+    last = map;
+    count++;
+  }
+  if (last->l_addr != rendezvous->r_ldbase) {
+    puts("interpreter link_map FAILED");
+    return 1;
+  }
+
+  printf("%d objects\n", count);
+  puts("rendezvous ok");
+  return 0;
+}
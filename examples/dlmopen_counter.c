@@ -0,0 +1,4 @@
+// Loaded twice by `dlmopen_isolation`, once per namespace; each copy keeps its own count.
+static int count = 0;
+
+int increment(void) { return ++count; }
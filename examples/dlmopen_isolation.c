@@ -0,0 +1,41 @@
+// Opens two copies of one library with dlmopen(LM_ID_NEWLM, ...), found by bare name through the program's RUNPATH,
+// and checks that they keep separate state in separate namespaces.
+#define _GNU_SOURCE
+#include <dlfcn.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #condition);  \
+      exit(1);                                                                 \
+    }                                                                          \
+  } while (0)
+
+int main(void) {
+  void *first = dlmopen(LM_ID_NEWLM, "dlmopen_counter", RTLD_NOW);
+  CHECK(first != NULL);
+  void *second = dlmopen(LM_ID_NEWLM, "dlmopen_counter", RTLD_NOW);
+  CHECK(second != NULL && second != first);
+
+  int (*first_increment)(void) = (int (*)(void))dlsym(first, "increment");
+  int (*second_increment)(void) = (int (*)(void))dlsym(second, "increment");
+  CHECK(first_increment != NULL && second_increment != NULL);
+  CHECK(first_increment != second_increment);
+  CHECK(first_increment() == 1 && first_increment() == 2);
+  CHECK(second_increment() == 1);
+
+  Lmid_t first_namespace = LM_ID_BASE;
+  Lmid_t second_namespace = LM_ID_BASE;
+  CHECK(dlinfo(first, RTLD_DI_LMID, &first_namespace) == 0);
+  CHECK(dlinfo(second, RTLD_DI_LMID, &second_namespace) == 0);
+  CHECK(first_namespace != LM_ID_BASE && second_namespace != LM_ID_BASE);
+  CHECK(first_namespace != second_namespace);
+
+  CHECK(dlmopen(LM_ID_NEWLM, "no_such_library", RTLD_NOW) == NULL);
+  CHECK(dlerror() != NULL);
+
+  puts("dlmopen isolation ok");
+  return 0;
+}
@@ -0,0 +1,91 @@
+// Loads a shared object carried inside the program, never touching disk: from its bytes with miros_dlmopen_memory, and
+// from a memfd with miros_dlmopen_fd. Each copy gets a namespace of its own, resolves symbols, and has working TLS.
+#define _GNU_SOURCE
+#include <dlfcn.h>
+#include <link.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #condition);  \
+      exit(1);                                                                 \
+    }                                                                          \
+  } while (0)
+
+// xtask builds the plugin first, beside this program.
+__asm__(".pushsection .rodata\n"
+        ".balign 16\n"
+        ".globl plugin_image\n"
+        ".hidden plugin_image\n"
+        "plugin_image:\n"
+        ".incbin \"examples/bin/dlmopen_plugin\"\n"
+        ".globl plugin_image_end\n"
+        ".hidden plugin_image_end\n"
+        "plugin_image_end:\n"
+        ".popsection\n");
+extern const unsigned char plugin_image[];
+extern const unsigned char plugin_image_end[];
+
+typedef void *(*open_memory_function)(const void *, size_t, const char *);
+typedef void *(*open_fd_function)(int, const char *);
+typedef int (*next_function)(void);
+
+static void *next_on_new_thread(void *next) {
+  return (void *)(long)((next_function)next)();
+}
+
+// The plugin's thread-local starts at its initial value on every thread, and counts up from there.
+static void check_plugin(void *handle) {
+  CHECK(handle != NULL);
+  Lmid_t namespace = LM_ID_BASE;
+  CHECK(dlinfo(handle, RTLD_DI_LMID, &namespace) == 0 && namespace != LM_ID_BASE);
+
+  next_function next = (next_function)dlsym(handle, "plugin_next");
+  size_t (*length)(const char *) = (size_t(*)(const char *))dlsym(handle, "plugin_length");
+  CHECK(next != NULL && length != NULL);
+  CHECK(length("memory") == 6);
+  CHECK(next() == 42 && next() == 43);
+
+  pthread_t thread;
+  void *result = NULL;
+  CHECK(pthread_create(&thread, NULL, next_on_new_thread, (void *)next) == 0);
+  CHECK(pthread_join(thread, &result) == 0);
+  CHECK((long)result == 42);
+  CHECK(next() == 44);
+}
+
+int main(void) {
+  size_t size = (size_t)(plugin_image_end - plugin_image);
+  CHECK(size > 0);
+
+  open_memory_function open_memory =
+      (open_memory_function)dlsym(RTLD_DEFAULT, "miros_dlmopen_memory");
+  open_fd_function open_fd = (open_fd_function)dlsym(RTLD_DEFAULT, "miros_dlmopen_fd");
+  CHECK(open_memory != NULL && open_fd != NULL);
+
+  void *from_memory = open_memory(plugin_image, size, "plugin");
+  check_plugin(from_memory);
+  struct link_map *map = NULL;
+  CHECK(dlinfo(from_memory, RTLD_DI_LINKMAP, &map) == 0);
+  CHECK(strcmp(map->l_name, "memfd:plugin") == 0);
+
+  // The descriptor is only borrowed: the object stays usable once it is closed.
+  int memfd = memfd_create("plugin", 0);
+  CHECK(memfd >= 0);
+  CHECK(write(memfd, plugin_image, size) == (ssize_t)size);
+  void *from_fd = open_fd(memfd, "plugin-from-fd");
+  CHECK(close(memfd) == 0);
+  CHECK(from_fd != from_memory);
+  check_plugin(from_fd);
+
+  CHECK(open_memory(plugin_image, 16, "truncated") == NULL && dlerror() != NULL);
+
+  puts("dlmopen memory ok");
+  return 0;
+}
@@ -0,0 +1,8 @@
+// Embedded in `dlmopen_memory` and loaded from its bytes: it has a thread-local and calls into libc.
+#include <string.h>
+
+__thread int plugin_counter = 42;
+
+int plugin_next(void) { return plugin_counter++; }
+
+size_t plugin_length(const char *text) { return strlen(text); }
@@ -0,0 +1,129 @@
+// Exercises the legacy DT_INIT/DT_FINI entry points (`-Wl,-init=`/`-Wl,-fini=`), their order against the
+// preinit, init and fini arrays and atexit handlers, and the rejection of a shared object's DT_PREINIT_ARRAY.
+#define _GNU_SOURCE
+#include <dlfcn.h>
+#include <elf.h>
+#include <limits.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+// Destructors still run after a failed check; this stops the last one reporting success.
+static int check_failed = 0;
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #condition);  \
+      check_failed = 1;                                                        \
+      exit(1);                                                                 \
+    }                                                                          \
+  } while (0)
+
+static char events[16];
+static size_t event_count = 0;
+
+static void record(char event) {
+  if (event_count < sizeof events - 1) {
+    events[event_count++] = event;
+  }
+}
+
+static void preinit(int argc, char **argv, char **envp) {
+  (void)argc;
+  (void)argv;
+  (void)envp;
+  record('P');
+}
+__attribute__((section(".preinit_array"), used)) static void (*preinit_entry)(
+    int, char **, char **) = preinit;
+
+// Named by `-Wl,-init=`; runs after the preinit array and before the init array.
+void legacy_init(void) { record('I'); }
+
+__attribute__((constructor)) static void constructor(void) { record('C'); }
+
+static int contains(const char *text, const char *part) {
+  for (; *text != '\0'; text++) {
+    if (strncmp(text, part, strlen(part)) == 0) {
+      return 1;
+    }
+  }
+  return 0;
+}
+
+static void first_handler(void) { record('a'); }
+static void second_handler(void) { record('b'); }
+
+__attribute__((destructor)) static void destructor(void) { record('D'); }
+
+// Named by `-Wl,-fini=`; the program's last destructor, after the atexit handlers and the fini array.
+void legacy_fini(void) {
+  record('F');
+  if (check_failed) {
+    return;
+  }
+  if (strcmp(events, "PICbaDF") != 0) {
+    fprintf(stderr, "exit order was %s, expected PICbaDF\n", events);
+    exit(1);
+  }
+  puts("init fini ok");
+}
+
+// ld refuses `.preinit_array` in a shared object, so this copies `init_fini_library` into a memfd
+// and retags its init array as a preinit array. Returns the memfd.
+static int library_with_preinit_array(void) {
+  char path[PATH_MAX];
+  ssize_t length = readlink("/proc/self/exe", path, sizeof path - 1);
+  CHECK(length > 0);
+  path[length] = '\0';
+  strcpy(strrchr(path, '/') + 1, "init_fini_library");
+
+  FILE *library = fopen(path, "rb");
+  CHECK(library != NULL);
+  static unsigned char bytes[1 << 20];
+  size_t size = fread(bytes, 1, sizeof bytes, library);
+  CHECK(size > 0 && size < sizeof bytes);
+  fclose(library);
+
+  const Elf64_Ehdr *header = (const Elf64_Ehdr *)bytes;
+  const Elf64_Phdr *program_headers = (const Elf64_Phdr *)(bytes + header->e_phoff);
+  int retagged = 0;
+  for (int i = 0; i < header->e_phnum; i++) {
+    if (program_headers[i].p_type != PT_DYNAMIC) {
+      continue;
+    }
+    Elf64_Dyn *entry = (Elf64_Dyn *)(bytes + program_headers[i].p_offset);
+    for (; entry->d_tag != DT_NULL; entry++) {
+      if (entry->d_tag == DT_INIT_ARRAY) {
+        entry->d_tag = DT_PREINIT_ARRAY;
+        retagged++;
+      } else if (entry->d_tag == DT_INIT_ARRAYSZ) {
+        entry->d_tag = DT_PREINIT_ARRAYSZ;
+        retagged++;
+      }
+    }
+  }
+  CHECK(retagged == 2);
+
+  int memfd = memfd_create("init_fini_library", 0);
+  CHECK(memfd >= 0);
+  CHECK(write(memfd, bytes, size) == (ssize_t)size);
+  return memfd;
+}
+
+int main(void) {
+  CHECK(strcmp(events, "PIC") == 0);
+  CHECK(atexit(first_handler) == 0);
+  CHECK(atexit(second_handler) == 0);
+
+  // Only a program's preinit array ever runs, so a shared object with one is refused.
+  char path[64];
+  snprintf(path, sizeof path, "/proc/self/fd/%d", library_with_preinit_array());
+  CHECK(dlmopen(LM_ID_NEWLM, path, RTLD_NOW) == NULL);
+  const char *error = dlerror();
+  CHECK(error != NULL && contains(error, "DT_PREINIT_ARRAY"));
+  return 0;
+}
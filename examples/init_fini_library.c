@@ -0,0 +1,6 @@
+// The shared object `init_fini` retags into one with a DT_PREINIT_ARRAY, from its init array.
+#include <stdio.h>
+
+__attribute__((constructor)) static void constructor(void) {
+  puts("init_fini_library: constructor ran");
+}
@@ -0,0 +1,59 @@
+// Built with `musl-gcc`: musl's headers and crt1.o, so every call below goes through the musl personality. errno is
+// per-thread, and `strerror_r` is POSIX's, returning a status rather than the message.
+#include <errno.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #condition);  \
+      exit(1);                                                                 \
+    }                                                                          \
+  } while (0)
+
+static pthread_mutex_t lock = PTHREAD_MUTEX_INITIALIZER;
+static int total;
+
+// Fails an open on this thread, then checks the main thread's failure left this thread's errno alone.
+static void *count_and_fail(void *argument) {
+  CHECK(errno == 0);
+  CHECK(open("/nonexistent/musl_stdio", O_RDONLY) == -1 && errno == ENOENT);
+  for (int i = 0; i < 1000; i++) {
+    pthread_mutex_lock(&lock);
+    total += (int)(long)argument;
+    pthread_mutex_unlock(&lock);
+  }
+  return NULL;
+}
+
+int main(void) {
+  char buffer[64];
+  CHECK(snprintf(buffer, sizeof buffer, "%s %d %5.2f", "musl", 42, 3.14159) == 13);
+  CHECK(strcmp(buffer, "musl 42  3.14") == 0);
+
+  errno = 0;
+  CHECK(close(-1) == -1 && errno == EBADF);
+  CHECK(strerror_r(ENOENT, buffer, sizeof buffer) == 0);
+  CHECK(strcmp(buffer, "No such file or directory") == 0);
+  CHECK(strerror_r(ENOENT, buffer, 4) == ERANGE);
+
+  errno = 0;
+  pthread_t threads[4];
+  for (long i = 0; i < 4; i++) {
+    CHECK(pthread_create(&threads[i], NULL, count_and_fail, (void *)(i + 1)) == 0);
+  }
+  for (int i = 0; i < 4; i++) {
+    CHECK(pthread_join(threads[i], NULL) == 0);
+  }
+  CHECK(errno == 0);
+  CHECK(total == 10000);
+
+  CHECK(fputs("musl ", stdout) >= 0);
+  CHECK(printf("stdio ok\n") == 9);
+  return 0;
+}
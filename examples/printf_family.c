@@ -0,0 +1,60 @@
+// Exercises the rest of the printf family under miros: snprintf truncation and return values, asprintf, dprintf,
+// numbered arguments, %m, wide %ls/%lc and the ' flag.
+#define _GNU_SOURCE
+#include <errno.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <wchar.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      printf("FAILED line %d: %s\n", __LINE__, #condition);                   \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+static int format_with(char *buffer, size_t size, const char *format, ...) {
+  va_list args;
+  va_start(args, format);
+  int result = vsnprintf(buffer, size, format, args);
+  va_end(args);
+  return result;
+}
+
+int main(void) {
+  char buffer[64];
+
+  memset(buffer, 'x', sizeof buffer);
+  CHECK(snprintf(buffer, 4, "%d", 123456) == 6);
+  CHECK(strcmp(buffer, "123") == 0 && buffer[4] == 'x');
+  CHECK(snprintf(NULL, 0, "%s, %s", "hello", "world") == 12);
+  CHECK(format_with(buffer, sizeof buffer, "%05.1f|%-4s|", 2.25, "ab") == 11);
+  CHECK(strcmp(buffer, "002.2|ab  |") == 0);
+
+  char *allocated = NULL;
+  CHECK(asprintf(&allocated, "%s=%#x", "mask", 255) == 9);
+  CHECK(strcmp(allocated, "mask=0xff") == 0);
+  free(allocated);
+
+  CHECK(snprintf(buffer, sizeof buffer, "%2$s %1$s %2$s", "b", "a") == 5);
+  CHECK(strcmp(buffer, "a b a") == 0);
+  CHECK(snprintf(buffer, sizeof buffer, "%1$*2$.*3$f|", 3.14159, 8, 2) == 9);
+  CHECK(strcmp(buffer, "    3.14|") == 0);
+
+  errno = ENOENT;
+  snprintf(buffer, sizeof buffer, "[%m]");
+  CHECK(strcmp(buffer, "[No such file or directory]") == 0);
+
+  CHECK(snprintf(buffer, sizeof buffer, "%ls %.2ls %lc", L"wide", L"text",
+                 (wint_t)L'!') == 9);
+  CHECK(strcmp(buffer, "wide te !") == 0);
+  CHECK(snprintf(buffer, sizeof buffer, "%'d", 1234567) == 7);
+
+  fflush(stdout);
+  CHECK(dprintf(STDOUT_FILENO, "printf family %s\n", "ok") == 17);
+  return 0;
+}
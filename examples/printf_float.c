@@ -0,0 +1,43 @@
+// Exercises floating-point printf under miros: %f %e %g %a for double and long double, with long doubles mixed among
+// doubles and integers so that every argument has to come from the right place in the va_list.
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK_FORMAT(expected, ...)                                            \
+  do {                                                                         \
+    char buffer[256];                                                          \
+    sprintf(buffer, __VA_ARGS__);                                              \
+    if (strcmp(buffer, expected) != 0) {                                       \
+      printf("FAILED line %d: got \"%s\", want \"%s\"\n", __LINE__, buffer,   \
+             expected);                                                        \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+int main(void) {
+  CHECK_FORMAT("3.141593", "%f", 3.14159265358979);
+  CHECK_FORMAT("1.000e+01", "%.3e", 9.9996);
+  CHECK_FORMAT("0.000123 1e+06", "%.3g %g", 0.0001234, 1e6);
+  CHECK_FORMAT("-0x1.999999999999ap-4", "%a", -0.1);
+  CHECK_FORMAT("+000001.50|", "%+010.2f|", 1.5);
+  CHECK_FORMAT("-inf NAN", "%f %F", -INFINITY, NAN);
+  CHECK_FORMAT("10000000000000000000000", "%.0f", 1e22);
+
+  CHECK_FORMAT("1.100000", "%Lf", 1.1L);
+  CHECK_FORMAT("0.1000000000000000000013553", "%.25Lf", 0.1L);
+  CHECK_FORMAT("1.000000e+4000", "%Le", 1e4000L);
+#if defined(__x86_64__)
+  CHECK_FORMAT("0xc.ccccccccccccccdp-7", "%La", 0.1L);
+#endif
+
+  // Interleaved: long doubles sit in a different part of the va_list than doubles and integers.
+  CHECK_FORMAT("1 2.5 3 4.25 5 6.125 7", "%d %Lg %d %g %d %Lg %d", 1, 2.5L, 3,
+               4.25, 5, 6.125L, 7);
+  CHECK_FORMAT("0.5 1.5 2.5 3.5 4.5 5.5 6.5 7.5 8.5 9.5",
+               "%g %g %g %g %g %g %g %g %Lg %g", 0.5, 1.5, 2.5, 3.5, 4.5, 5.5,
+               6.5, 7.5, 8.5L, 9.5);
+
+  puts("printf float ok");
+  return 0;
+}
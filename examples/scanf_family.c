@@ -0,0 +1,85 @@
+// Exercises the scanf family under miros: sscanf conversions, fscanf and scanf over a FILE with the unread byte
+// left for the next read, %m allocation, and strtod/strtof/strtold.
+#define _GNU_SOURCE
+#include <errno.h>
+#include <math.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      printf("FAILED line %d: %s\n", __LINE__, #condition);                   \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+static int scan_with(const char *input, const char *format, ...) {
+  va_list args;
+  va_start(args, format);
+  int result = vsscanf(input, format, args);
+  va_end(args);
+  return result;
+}
+
+int main(void) {
+  int number = 0, count = 0;
+  unsigned hex = 0;
+  long long wide = 0;
+  double real = 0;
+  char word[16], set[16], chars[4] = "...";
+
+  CHECK(sscanf("  -42 0x1F 077", "%d %x %lli%n", &number, &hex, &wide, &count) == 3);
+  CHECK(number == -42 && hex == 31 && wide == 63 && count == 14);
+  CHECK(sscanf("3.25e2xyz", "%lf%n", &real, &count) == 1 && real == 325.0 && count == 6);
+  CHECK(sscanf("0x1.8p1 inf", "%lf", &real) == 1 && real == 3.0);
+  CHECK(sscanf("key=value;rest", "%15[^=]=%15[^;];%2c", word, set, chars) == 3);
+  CHECK(strcmp(word, "key") == 0 && strcmp(set, "value") == 0 && memcmp(chars, "re.", 3) == 0);
+  CHECK(sscanf("abc", "%d", &number) == 0);
+  CHECK(sscanf("", "%d", &number) == EOF);
+  CHECK(sscanf("12 34", "%*d %d", &number) == 1 && number == 34);
+  CHECK(scan_with("7 apples", "%d %5s", &number, word) == 2 && number == 7 && strcmp(word, "apple") == 0);
+
+  char *allocated = NULL;
+  CHECK(sscanf("  dynamic string", "%ms", &allocated) == 1);
+  CHECK(strcmp(allocated, "dynamic") == 0);
+  free(allocated);
+
+  char *end;
+  CHECK(strtod("  1e-3x", &end) == 0.001 && *end == 'x');
+  CHECK(strtof("0.1", NULL) == 0.1f);
+  CHECK(strtold("0.1", NULL) == 0.1L);
+  errno = 0;
+  CHECK(isinf(strtod("1e999", NULL)) && errno == ERANGE);
+  CHECK(strtod("nope", &end) == 0 && strcmp(end, "nope") == 0);
+
+  // fscanf leaves the byte that ended each item in the stream.
+  const char *path = "/tmp/miros_scanf_family.txt";
+  FILE *file = fopen(path, "w");
+  CHECK(file != NULL);
+  CHECK(fputs("10,20;rest of line\n5 6\n", file) >= 0);
+  CHECK(fclose(file) == 0);
+
+  file = fopen(path, "r");
+  CHECK(file != NULL);
+  int first = 0, second = 0;
+  CHECK(fscanf(file, "%d,%d", &first, &second) == 2 && first == 10 && second == 20);
+  CHECK(fgetc(file) == ';');
+  CHECK(fscanf(file, "%*[^\n]") == 0 && fgetc(file) == '\n');
+  CHECK(fscanf(file, "%d %d", &first, &second) == 2 && first == 5 && second == 6);
+  CHECK(fscanf(file, "%d", &first) == EOF && feof(file));
+  CHECK(fclose(file) == 0);
+
+  // scanf reads stdin, here the same file.
+  CHECK(freopen(path, "r", stdin) == stdin);
+  CHECK(scanf("%d,%d;%4s", &first, &second, word) == 3);
+  CHECK(first == 10 && second == 20 && strcmp(word, "rest") == 0);
+  CHECK(getchar() == ' ');
+  unlink(path);
+
+  puts("scanf family ok");
+  return 0;
+}
@@ -0,0 +1,166 @@
+// Exercises memory and custom streams under miros: fmemopen in its modes, open_memstream and open_wmemstream growing
+// and publishing their buffers, and fopencookie with read, write and missing callbacks.
+#define _GNU_SOURCE
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <wchar.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      printf("FAILED line %d: %s\n", __LINE__, #condition);                   \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+struct sink {
+  char bytes[64];
+  size_t length;
+};
+
+static ssize_t shout(void *cookie, const char *buffer, size_t size) {
+  struct sink *sink = cookie;
+  for (size_t i = 0; i < size && sink->length < sizeof sink->bytes - 1; i++) {
+    char c = buffer[i];
+    sink->bytes[sink->length++] = c >= 'a' && c <= 'z' ? c - 'a' + 'A' : c;
+  }
+  return size;
+}
+
+static int closed;
+
+static int close_sink(void *cookie) {
+  (void)cookie;
+  closed++;
+  return 0;
+}
+
+static ssize_t count_up(void *cookie, char *buffer, size_t size) {
+  int *next = cookie;
+  size_t produced = 0;
+  while (produced < size && *next < 10) {
+    buffer[produced++] = '0' + (*next)++;
+  }
+  return produced;
+}
+
+int main(void) {
+  // fmemopen "w": output arrives on flush, NUL-terminated.
+  char fixed[16];
+  memset(fixed, 'x', sizeof fixed);
+  FILE *memory = fmemopen(fixed, sizeof fixed, "w");
+  CHECK(memory != NULL);
+  CHECK(fileno(memory) == -1);
+  CHECK(fprintf(memory, "n=%d", 42) == 4);
+  CHECK(fflush(memory) == 0);
+  CHECK(strcmp(fixed, "n=42") == 0);
+  CHECK(ftell(memory) == 4);
+  CHECK(fseek(memory, 2, SEEK_SET) == 0);
+  CHECK(fputs("77", memory) >= 0);
+  CHECK(fclose(memory) == 0);
+  CHECK(strcmp(fixed, "n=77") == 0);
+
+  // Too much for the buffer: cut short with the NUL in the last byte, and an error.
+  memory = fmemopen(fixed, 8, "w");
+  CHECK(memory != NULL);
+  fputs("0123456789", memory);
+  CHECK(fflush(memory) == EOF && ferror(memory));
+  CHECK(memcmp(fixed, "0123456\0", 8) == 0);
+  fclose(memory);
+
+  // "r" reads the whole buffer, "a" appends after the first NUL.
+  char text[] = "alpha beta";
+  memory = fmemopen(text, strlen(text), "r");
+  CHECK(memory != NULL);
+  char word[16];
+  CHECK(fgets(word, sizeof word, memory) && strcmp(word, "alpha beta") == 0);
+  CHECK(fgetc(memory) == EOF && feof(memory));
+  CHECK(fseek(memory, -4, SEEK_END) == 0);
+  CHECK(fgets(word, sizeof word, memory) && strcmp(word, "beta") == 0);
+  CHECK(fclose(memory) == 0);
+
+  char appended[16] = "ab";
+  memory = fmemopen(appended, sizeof appended, "a");
+  CHECK(memory != NULL && ftell(memory) == 2);
+  CHECK(fputs("cd", memory) >= 0);
+  CHECK(fclose(memory) == 0);
+  CHECK(strcmp(appended, "abcd") == 0);
+
+  // A null buffer is the stream's own.
+  memory = fmemopen(NULL, 32, "w+");
+  CHECK(memory != NULL);
+  CHECK(fputs("scratch", memory) >= 0);
+  rewind(memory);
+  CHECK(fgets(word, sizeof word, memory) && strcmp(word, "scratch") == 0);
+  CHECK(fclose(memory) == 0);
+
+  errno = 0;
+  CHECK(fmemopen(fixed, sizeof fixed, "q") == NULL && errno == EINVAL);
+
+  // open_memstream: *location and *size follow each flush; the program frees the buffer.
+  char *grown = NULL;
+  size_t size = 0;
+  FILE *stream = open_memstream(&grown, &size);
+  CHECK(stream != NULL);
+  CHECK(fprintf(stream, "hello") == 5);
+  CHECK(fflush(stream) == 0);
+  CHECK(size == 5 && strcmp(grown, "hello") == 0);
+  for (int i = 0; i < 1000; i++) {
+    fprintf(stream, "%03d", i);
+  }
+  CHECK(fflush(stream) == 0);
+  CHECK(size == 3005 && strlen(grown) == 3005);
+  CHECK(memcmp(grown + 3002, "999", 3) == 0);
+  // The size reported is the position once it moves back, and the string ends there.
+  CHECK(fseek(stream, 5, SEEK_SET) == 0);
+  CHECK(fflush(stream) == 0 && size == 5);
+  CHECK(fclose(stream) == 0);
+  CHECK(size == 5 && strcmp(grown, "hello") == 0);
+  free(grown);
+
+  // A seek back truncates where SEEK_END counts from; a flush leaves the bytes after the position alone.
+  stream = open_memstream(&grown, &size);
+  CHECK(stream != NULL);
+  CHECK(fputs("abc123", stream) >= 0);
+  CHECK(fseek(stream, 1, SEEK_SET) == 0);
+  CHECK(fputc('Z', stream) == 'Z');
+  CHECK(fflush(stream) == 0);
+  CHECK(size == 2 && memcmp(grown, "aZc123", 6) == 0);
+  CHECK(fseek(stream, 0, SEEK_END) == 0);
+  CHECK(fclose(stream) == 0);
+  CHECK(size == 2 && strcmp(grown, "aZ") == 0);
+  free(grown);
+
+  wchar_t *wide = NULL;
+  size_t wide_size = 1;
+  stream = open_wmemstream(&wide, &wide_size);
+  CHECK(stream != NULL);
+  CHECK(fclose(stream) == 0);
+  CHECK(wide != NULL && wide_size == 0 && wide[0] == L'\0');
+  free(wide);
+
+  // fopencookie: writes through the program's callback; closing calls its close.
+  struct sink sink = {0};
+  cookie_io_functions_t sink_functions = {.write = shout, .close = close_sink};
+  FILE *cookie_stream = fopencookie(&sink, "w", sink_functions);
+  CHECK(cookie_stream != NULL);
+  CHECK(fprintf(cookie_stream, "quiet %s", "words") == 11);
+  CHECK(sink.length == 0);
+  CHECK(fflush(cookie_stream) == 0);
+  CHECK(strcmp(sink.bytes, "QUIET WORDS") == 0);
+  CHECK(fseek(cookie_stream, 0, SEEK_SET) == -1);
+  CHECK(fclose(cookie_stream) == 0 && closed == 1);
+
+  int next = 0;
+  cookie_io_functions_t source_functions = {.read = count_up};
+  cookie_stream = fopencookie(&next, "r", source_functions);
+  CHECK(cookie_stream != NULL);
+  CHECK(fgets(word, sizeof word, cookie_stream) && strcmp(word, "0123456789") == 0);
+  CHECK(feof(cookie_stream));
+  CHECK(fclose(cookie_stream) == 0);
+
+  puts("stdio memory ok");
+  return 0;
+}
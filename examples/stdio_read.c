@@ -0,0 +1,86 @@
+// Exercises read-side stdio under miros: fopen/fclose, fgets, getc (inlined at -O2), ungetc, getline, fread,
+// feof/ferror/clearerr and fileno, plus an "r+" stream switching from reading to writing.
+#define _GNU_SOURCE
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      printf("FAILED line %d: %s\n", __LINE__, #condition);                   \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+int main(void) {
+  const char *path = "/tmp/miros_stdio_read.txt";
+
+  FILE *out = fopen(path, "w");
+  CHECK(out != NULL);
+  CHECK(fileno(out) > 2);
+  CHECK(fputs("first line\nsecond\nthird line that is longer\n", out) >= 0);
+  // Past the stream's buffer, so fread below takes the direct path.
+  for (int i = 0; i < 20000; i++) {
+    fputc('a' + i % 26, out);
+  }
+  CHECK(fgetc(out) == EOF && ferror(out)); // write-only
+  CHECK(fclose(out) == 0);
+
+  FILE *in = fopen(path, "r");
+  CHECK(in != NULL);
+
+  char line[64];
+  CHECK(fgets(line, sizeof line, in) == line);
+  CHECK(strcmp(line, "first line\n") == 0);
+
+  // A short buffer splits a line: `size - 1` bytes, terminated.
+  CHECK(fgets(line, 4, in) == line);
+  CHECK(strcmp(line, "sec") == 0);
+
+  int c = getc_unlocked(in);
+  CHECK(c == 'o');
+  CHECK(ungetc('O', in) == 'O');
+  CHECK(getc(in) == 'O');
+
+  char *owned = NULL;
+  size_t capacity = 0;
+  CHECK(getline(&owned, &capacity, in) == 3);
+  CHECK(strcmp(owned, "nd\n") == 0);
+  CHECK(getdelim(&owned, &capacity, ' ', in) == 6);
+  CHECK(strcmp(owned, "third ") == 0);
+  CHECK(getline(&owned, &capacity, in) == 20);
+  free(owned);
+
+  static char bulk[20001];
+  CHECK(fread(bulk, 1, sizeof bulk, in) == 20000);
+  CHECK(bulk[0] == 'a' && bulk[19999] == 'a' + 19999 % 26);
+  CHECK(feof(in) && !ferror(in));
+  CHECK(fgetc(in) == EOF);
+  clearerr(in);
+  CHECK(!feof(in));
+  CHECK(fclose(in) == 0);
+
+  // Reading then writing on "r+": the write lands where the reader stopped, not at the end of the read-ahead.
+  FILE *both = fopen(path, "r+");
+  CHECK(both != NULL);
+  CHECK(fgets(line, sizeof line, both) == line);
+  CHECK(fputs("SECOND", both) >= 0);
+  CHECK(fclose(both) == 0);
+
+  FILE *check = fopen(path, "r");
+  CHECK(check != NULL);
+  CHECK(fgets(line, sizeof line, check) && fgets(line, sizeof line, check));
+  CHECK(strcmp(line, "SECOND\n") == 0);
+  CHECK(fclose(check) == 0);
+
+  errno = 0;
+  CHECK(fopen("/nonexistent/miros", "r") == NULL && errno == ENOENT);
+  CHECK(fopen(path, "q") == NULL && errno == EINVAL);
+
+  unlink(path);
+  puts("stdio read ok");
+  return 0;
+}
@@ -0,0 +1,89 @@
+// Exercises stream positioning and buffering control under miros: setvbuf with a program-supplied buffer, fseeko and
+// ftello across reads and writes, rewind, fgetpos/fsetpos, fdopen and freopen.
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      printf("FAILED line %d: %s\n", __LINE__, #condition);                   \
+      return 1;                                                                \
+    }                                                                          \
+  } while (0)
+
+static char user_buffer[256];
+
+int main(void) {
+  const char *path = "/tmp/miros_stdio_seek.txt";
+
+  FILE *file = fopen(path, "w+");
+  CHECK(file != NULL);
+  CHECK(setvbuf(file, user_buffer, _IOFBF, sizeof user_buffer) == 0);
+  CHECK(fputs("0123456789", file) >= 0);
+  // Still in the program's buffer, but ftell counts it.
+  CHECK(memcmp(user_buffer, "0123456789", 10) == 0);
+  CHECK(ftello(file) == 10);
+
+  CHECK(fseeko(file, 2, SEEK_SET) == 0);
+  CHECK(fgetc(file) == '2');
+  CHECK(ftell(file) == 3);
+  CHECK(fseek(file, 2, SEEK_CUR) == 0);
+  CHECK(fgetc(file) == '5');
+
+  // Switching from reading to writing after a seek writes where the reader stopped.
+  CHECK(fseek(file, 0, SEEK_CUR) == 0);
+  CHECK(fputc('X', file) == 'X');
+  CHECK(fseek(file, -1, SEEK_END) == 0);
+  CHECK(fgetc(file) == '9' && fgetc(file) == EOF && feof(file));
+
+  fpos_t position;
+  CHECK(fseek(file, 4, SEEK_SET) == 0 && !feof(file));
+  CHECK(fgetpos(file, &position) == 0);
+  CHECK(fgetc(file) == '4');
+  CHECK(fsetpos(file, &position) == 0);
+  CHECK(fgetc(file) == '4' && fgetc(file) == '5' && fgetc(file) == 'X');
+
+  rewind(file);
+  char line[16] = {0};
+  CHECK(fread(line, 1, 10, file) == 10);
+  CHECK(strcmp(line, "012345X789") == 0);
+
+  errno = 0;
+  CHECK(fseek(file, 0, 42) == -1 && errno == EINVAL);
+  CHECK(setvbuf(file, NULL, 7, 0) != 0);
+  CHECK(fclose(file) == 0);
+
+  // fdopen: the descriptor's access mode has to allow the stream's.
+  int descriptor = open(path, O_RDONLY);
+  CHECK(descriptor >= 0);
+  errno = 0;
+  CHECK(fdopen(descriptor, "w") == NULL && errno == EINVAL);
+  FILE *wrapped = fdopen(descriptor, "r");
+  CHECK(wrapped != NULL && fileno(wrapped) == descriptor);
+  CHECK(setvbuf(wrapped, NULL, _IONBF, 0) == 0);
+  CHECK(fgetc(wrapped) == '0');
+  // Unbuffered, so the descriptor has moved just one byte.
+  CHECK(lseek(descriptor, 0, SEEK_CUR) == 1);
+  CHECK(fclose(wrapped) == 0);
+
+  // freopen keeps the stream and its descriptor number.
+  FILE *stream = fopen(path, "r");
+  CHECK(stream != NULL);
+  int number = fileno(stream);
+  CHECK(freopen(path, "a", stream) == stream);
+  CHECK(fileno(stream) == number);
+  CHECK(fputs("tail", stream) >= 0);
+  CHECK(freopen(NULL, "r", stream) == stream);
+  CHECK(fseek(stream, -4, SEEK_END) == 0);
+  CHECK(fgets(line, sizeof line, stream) && strcmp(line, "tail") == 0);
+  CHECK(fclose(stream) == 0);
+
+  unlink(path);
+  setvbuf(stdout, NULL, _IOFBF, 1 << 16);
+  puts("stdio seek ok");
+  return 0;
+}
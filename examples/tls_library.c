@@ -0,0 +1,8 @@
+// A shared object with thread-locals, built with the default TLS model for one: general-dynamic through
+// `__tls_get_addr` on x86_64, TLS descriptors on aarch64.
+__thread int library_counter = 7;
+__thread char library_buffer[64];
+
+int library_next(void) { return library_counter++; }
+
+char *library_buffer_address(void) { return library_buffer; }
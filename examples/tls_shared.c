@@ -0,0 +1,50 @@
+// Thread-locals defined in a shared object the program links against: the library reaches them through its own TLS
+// model, the program through initial-exec, and every thread gets a fresh copy of each.
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define CHECK(condition)                                                       \
+  do {                                                                         \
+    if (!(condition)) {                                                        \
+      fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #condition);  \
+      exit(1);                                                                 \
+    }                                                                          \
+  } while (0)
+
+extern __thread int library_counter;
+extern __thread char library_buffer[64];
+int library_next(void);
+char *library_buffer_address(void);
+
+// Both views of the thread's copies agree, start from their initial values, and count from there.
+static void *check_thread_locals(void *unused) {
+  (void)unused;
+  CHECK(library_buffer_address() == library_buffer);
+  CHECK(library_buffer[0] == 0 && library_buffer[63] == 0);
+  CHECK(library_next() == 7 && library_counter == 8);
+  library_counter = 20;
+  CHECK(library_next() == 20);
+  library_buffer[0] = 'x';
+  return library_buffer_address();
+}
+
+int main(void) {
+  char *main_buffer = check_thread_locals(NULL);
+
+  pthread_t threads[4];
+  for (int i = 0; i < 4; i++) {
+    CHECK(pthread_create(&threads[i], NULL, check_thread_locals, NULL) == 0);
+  }
+  for (int i = 0; i < 4; i++) {
+    void *thread_buffer = NULL;
+    CHECK(pthread_join(threads[i], &thread_buffer) == 0);
+    CHECK(thread_buffer != main_buffer);
+  }
+
+  // The threads wrote only their own copies.
+  CHECK(library_counter == 21 && library_buffer[0] == 'x' && library_buffer[1] == 0);
+
+  puts("tls shared ok");
+  return 0;
+}
@@ -0,0 +1,47 @@
+// Exercises the time and CPU queries that miros routes through the vDSO, and reports the per-call cost.
+#define _GNU_SOURCE
+#include <sched.h>
+#include <stdio.h>
+#include <sys/time.h>
+#include <time.h>
+
+#define ITERATIONS 1000000
+
+int main(void) {
+  struct timespec start, previous, now, resolution;
+  if (clock_gettime(CLOCK_MONOTONIC, &start) != 0) {
+    puts("clock_gettime FAILED");
+    return 1;
+  }
+
+  previous = start;
+  for (int i = 0; i < ITERATIONS; i++) {
+    clock_gettime(CLOCK_MONOTONIC, &now);
+    if (now.tv_sec < previous.tv_sec ||
+        (now.tv_sec == previous.tv_sec && now.tv_nsec < previous.tv_nsec)) {
+      puts("CLOCK_MONOTONIC went backwards: FAILED");
+      return 1;
+    }
+    previous = now;
+  }
+  long elapsed = (now.tv_sec - start.tv_sec) * 1000000000L + (now.tv_nsec - start.tv_nsec);
+
+  struct timeval tv;
+  time_t seconds = time(NULL);
+  if (gettimeofday(&tv, NULL) != 0 || tv.tv_sec - seconds > 1) {
+    puts("gettimeofday/time FAILED");
+    return 1;
+  }
+  if (clock_getres(CLOCK_MONOTONIC, &resolution) != 0 ||
+      (resolution.tv_sec == 0 && resolution.tv_nsec == 0)) {
+    puts("clock_getres FAILED");
+    return 1;
+  }
+  if (sched_getcpu() < 0) {
+    puts("sched_getcpu FAILED");
+    return 1;
+  }
+
+  printf("clock_gettime: %ld ns/call\n", elapsed / ITERATIONS);
+  return 0;
+}
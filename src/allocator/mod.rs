@@ -25,7 +25,8 @@ pub(crate) const GUARD_PAGE_PROTECTION: ProtectionFlags = ProtectionFlags::ZERO;
 pub(crate) const ANONYMOUS_PRIVATE_MAP: MapFlags =
     MapFlags::ZERO.with_private(true).with_anonymous(true);
 
-#[cfg_attr(not(test), link_section = ".init_array")]
+// A static image's `.init_array` is the program's too and runs late, so there bootstrap calls this directly.
+#[cfg_attr(not(any(test, fuzzing, miros_static)), link_section = ".init_array")]
 #[used]
 pub(crate) static INIT_ALLOCATOR: InitArrayFunction = init_allocator;
 
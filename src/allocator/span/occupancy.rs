@@ -1,5 +1,4 @@
 use std::{
-    arch::x86_64::_pdep_u64,
     iter::from_fn,
     ops::Deref,
     sync::atomic::{Atomic, Ordering},
@@ -22,6 +21,21 @@ fn word_and_bit(slot_index: u16) -> (usize, usize) {
     )
 }
 
+/// The lowest `count` set bits of `word`: PDEP where BMI2 has it, clearing one bit at a time elsewhere.
+#[cfg(target_arch = "x86_64")]
+fn lowest_set_bits(word: BitmapWord, count: u32) -> BitmapWord {
+    unsafe { std::arch::x86_64::_pdep_u64((1 << count) - 1, word) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn lowest_set_bits(word: BitmapWord, count: u32) -> BitmapWord {
+    let mut above = word;
+    for _ in 0..count {
+        above &= above.wrapping_sub(1);
+    }
+    word ^ above
+}
+
 pub struct Occupancy<T> {
     summary: T,
     bitmap: [T; BITMAP_WORD_COUNT],
@@ -74,7 +88,7 @@ impl Occupancy<BitmapWord> {
         } else {
             let bit_rotation = ((random >> 6) & 63) as u32;
             let rotated = free.rotate_right(bit_rotation);
-            let window = unsafe { _pdep_u64((1 << max) - 1, rotated) };
+            let window = lowest_set_bits(rotated, max);
             window.rotate_left(bit_rotation)
         };
 
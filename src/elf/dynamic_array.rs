@@ -30,12 +30,14 @@ pub enum DynamicTag {
     Rela = 7,
     RelaSz = 8,
     RelaEnt = 9,
+    StrSz = 10,
     SymEnt = 11,
     Init = 12,
     Fini = 13,
     Rpath = 15,
     Rel = 17,
     PltRel = 20,
+    Debug = 21,
     TextRel = 22,
     JmpRel = 23,
     InitArray = 25,
@@ -48,8 +50,14 @@ pub enum DynamicTag {
     PreInitArraySz = 33,
     RelrSz = 35,
     Relr = 36,
+    RelrEnt = 37,
     GnuHash = 0x6ffffef5,
+    VerSym = 0x6ffffff0,
     Flags1 = 0x6ffffffb,
+    VerDef = 0x6ffffffc,
+    VerDefNum = 0x6ffffffd,
+    VerNeed = 0x6ffffffe,
+    VerNeedNum = 0x6fffffff,
 }
 
 /// A union resolved by the d_tag field of the parent dynamic array item.
@@ -76,11 +84,20 @@ impl DynamicArrayItem {
 
 pub struct DynamicArrayIter {
     current: *const DynamicArrayItem,
+    /// Items left before the end of `PT_DYNAMIC`; a missing `DT_NULL` stops here instead of running off the segment.
+    remaining: usize,
 }
 
 impl DynamicArrayIter {
     pub unsafe fn new(start: *const DynamicArrayItem) -> Self {
-        Self { current: start }
+        Self::with_len(start, usize::MAX)
+    }
+
+    pub unsafe fn with_len(start: *const DynamicArrayItem, len: usize) -> Self {
+        Self {
+            current: start,
+            remaining: len,
+        }
     }
 }
 
@@ -88,11 +105,15 @@ impl Iterator for DynamicArrayIter {
     type Item = DynamicArrayItem;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
         let item = unsafe { *self.current };
         if item.d_tag() == Ok(DynamicTag::Null) {
             return None;
         }
         self.current = unsafe { self.current.add(1) };
+        self.remaining -= 1;
         Some(item)
     }
 }
@@ -1,5 +1,41 @@
+use crate::elf::program_header::ProgramHeader;
+
+pub const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+pub const ELFCLASS32: u8 = 1;
+pub const ELFCLASS64: u8 = 2;
+pub const ELFDATA2LSB: u8 = 1;
+pub const EV_CURRENT: u8 = 1;
+
+pub const ET_EXEC: u16 = 2;
 pub const ET_DYN: u16 = 3;
 
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+
+#[cfg(target_pointer_width = "64")]
+const NATIVE_CLASS: u8 = ELFCLASS64;
+#[cfg(target_pointer_width = "32")]
+const NATIVE_CLASS: u8 = ELFCLASS32;
+
+#[cfg(target_arch = "x86_64")]
+const NATIVE_MACHINE: u16 = EM_X86_64;
+#[cfg(target_arch = "aarch64")]
+const NATIVE_MACHINE: u16 = EM_AARCH64;
+
+/// Why an `ElfHeader` was rejected; each variant carries the offending field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfHeaderError {
+    BadMagic([u8; 4]),
+    UnsupportedClass(u8),
+    UnsupportedDataEncoding(u8),
+    UnsupportedVersion(u32),
+    UnsupportedMachine(u16),
+    /// Only position-independent (`ET_DYN`) objects can be mapped at a base of our choosing.
+    UnsupportedType(u16),
+    ProgramHeaderSize(u16),
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Default, PartialEq)]
 pub struct ElfHeader {
@@ -16,7 +52,7 @@ pub struct ElfHeader {
     pub e_ident: [u8; 16],
     /// The Elf file type, see the ET_.* constants.
     pub e_type: u16,
-    /// The target archectecture, see the TODO constants.
+    /// The target archectecture, see the EM_.* constants.
     pub e_machine: u16,
     /// The Elf format version, only version one is currently available.
     pub e_version: u32,
@@ -41,3 +77,111 @@ pub struct ElfHeader {
     /// The index into the section header table at which the string table resides.
     pub e_shstrndx: u16,
 }
+
+impl ElfHeader {
+    /// Checks that this header describes a shared object this build of miros can map and run.
+    pub fn validate(&self) -> Result<(), ElfHeaderError> {
+        let magic = [
+            self.e_ident[0],
+            self.e_ident[1],
+            self.e_ident[2],
+            self.e_ident[3],
+        ];
+        if magic != ELF_MAGIC {
+            return Err(ElfHeaderError::BadMagic(magic));
+        }
+        if self.e_ident[4] != NATIVE_CLASS {
+            return Err(ElfHeaderError::UnsupportedClass(self.e_ident[4]));
+        }
+        if self.e_ident[5] != ELFDATA2LSB {
+            return Err(ElfHeaderError::UnsupportedDataEncoding(self.e_ident[5]));
+        }
+        if self.e_ident[6] != EV_CURRENT {
+            return Err(ElfHeaderError::UnsupportedVersion(self.e_ident[6] as u32));
+        }
+        if self.e_version != EV_CURRENT as u32 {
+            return Err(ElfHeaderError::UnsupportedVersion(self.e_version));
+        }
+        if self.e_machine != NATIVE_MACHINE {
+            return Err(ElfHeaderError::UnsupportedMachine(self.e_machine));
+        }
+        if self.e_type != ET_DYN {
+            return Err(ElfHeaderError::UnsupportedType(self.e_type));
+        }
+        if self.e_phentsize as usize != size_of::<ProgramHeader>() {
+            return Err(ElfHeaderError::ProgramHeaderSize(self.e_phentsize));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared_object_header() -> ElfHeader {
+        let mut e_ident = [0; 16];
+        e_ident[..4].copy_from_slice(&ELF_MAGIC);
+        e_ident[4] = ELFCLASS64;
+        e_ident[5] = ELFDATA2LSB;
+        e_ident[6] = EV_CURRENT;
+        ElfHeader {
+            e_ident,
+            e_type: ET_DYN,
+            e_machine: EM_X86_64,
+            e_version: 1,
+            e_phentsize: size_of::<ProgramHeader>() as u16,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_accepts_a_native_shared_object() {
+        assert_eq!(shared_object_header().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_names_the_first_bad_field() {
+        let mut header = shared_object_header();
+        header.e_ident[1] = b'X';
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::BadMagic([0x7f, b'X', b'L', b'F']))
+        );
+
+        let mut header = shared_object_header();
+        header.e_ident[4] = ELFCLASS32;
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::UnsupportedClass(ELFCLASS32))
+        );
+
+        let mut header = shared_object_header();
+        header.e_ident[5] = 2;
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::UnsupportedDataEncoding(2))
+        );
+
+        let mut header = shared_object_header();
+        header.e_machine = 183;
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::UnsupportedMachine(183))
+        );
+
+        let mut header = shared_object_header();
+        header.e_type = ET_EXEC;
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::UnsupportedType(ET_EXEC))
+        );
+
+        let mut header = shared_object_header();
+        header.e_phentsize = 32;
+        assert_eq!(
+            header.validate(),
+            Err(ElfHeaderError::ProgramHeaderSize(32))
+        );
+    }
+}
@@ -1,7 +1,9 @@
 pub mod dynamic_array;
 pub mod header;
+pub mod note;
 pub mod program_header;
 pub mod relocate;
 pub mod section;
 pub mod string_table;
 pub mod symbol;
+pub mod version;
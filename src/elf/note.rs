@@ -0,0 +1,69 @@
+/// `n_type` of the note `ld --build-id` emits, under the name `GNU`.
+pub const NT_GNU_BUILD_ID: u32 = 3;
+
+/// The fixed part of an ELF note; the name and descriptor follow, each ending padded (from the note's start) to the segment's alignment.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct NoteHeader {
+    pub n_namesz: u32,
+    pub n_descsz: u32,
+    pub n_type: u32,
+}
+
+/// Finds the GNU build ID in the contents of one `PT_NOTE` segment.
+///
+/// `alignment` is the segment's `p_align`: 4 for classic notes, 8 for segments holding `.note.gnu.property`.
+pub fn gnu_build_id(notes: &[u8], alignment: usize) -> Option<&[u8]> {
+    let alignment = alignment.max(4);
+    let padded = |length: usize| length.checked_next_multiple_of(alignment);
+
+    let mut rest = notes;
+    while rest.len() >= size_of::<NoteHeader>() {
+        let header = unsafe { rest.as_ptr().cast::<NoteHeader>().read_unaligned() };
+        let name_start = size_of::<NoteHeader>();
+        let name_end = name_start + header.n_namesz as usize;
+        let descriptor_start = padded(name_end)?;
+        let descriptor_end = descriptor_start.checked_add(header.n_descsz as usize)?;
+        let next = padded(descriptor_end)?;
+
+        let name = rest.get(name_start..name_end)?;
+        let descriptor = rest.get(descriptor_start..descriptor_end)?;
+        if header.n_type == NT_GNU_BUILD_ID && name == b"GNU\0" {
+            return Some(descriptor);
+        }
+        rest = rest.get(next..).unwrap_or_default();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(name: &[u8], n_type: u32, descriptor: &[u8], alignment: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for field in [name.len() as u32, descriptor.len() as u32, n_type] {
+            bytes.extend_from_slice(&field.to_ne_bytes());
+        }
+        for part in [name, descriptor] {
+            bytes.extend_from_slice(part);
+            bytes.resize(bytes.len().next_multiple_of(alignment), 0);
+        }
+        bytes
+    }
+
+    #[test]
+    fn skips_other_notes_to_the_build_id() {
+        let mut notes = note(b"GNU\0", 5, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12], 8);
+        notes.extend(note(b"GNU\0", NT_GNU_BUILD_ID, &[0xAB; 20], 8));
+        assert_eq!(gnu_build_id(&notes, 8), Some(&[0xAB; 20][..]));
+        // The last 4 bytes are padding; cutting one more truncates the descriptor itself.
+        assert_eq!(gnu_build_id(&notes[..notes.len() - 5], 8), None);
+    }
+
+    #[test]
+    fn ignores_build_ids_from_other_vendors() {
+        let notes = note(b"Go\0\0", NT_GNU_BUILD_ID, &[7; 8], 4);
+        assert_eq!(gnu_build_id(&notes, 4), None);
+    }
+}
@@ -33,6 +33,60 @@ impl Rela {
     }
 }
 
+/// Decodes a `DT_RELR` table into the offsets of the words that need the load base added.
+///
+/// An even entry is the offset of one such word; an odd entry is a bitmap whose bits 1..=63 mark the 63 words after the last address seen.
+pub struct RelrIter<'a> {
+    entries: std::slice::Iter<'a, usize>,
+    /// The offset the next bitmap's bit 1 refers to.
+    next_offset: usize,
+    /// Bits of the current bitmap not yet consumed, shifted so bit 0 is `bitmap_offset`.
+    bitmap: usize,
+    bitmap_offset: usize,
+}
+
+impl<'a> RelrIter<'a> {
+    const WORD: usize = size_of::<usize>();
+
+    pub fn new(entries: &'a [usize]) -> Self {
+        Self {
+            entries: entries.iter(),
+            next_offset: 0,
+            bitmap: 0,
+            bitmap_offset: 0,
+        }
+    }
+}
+
+impl Iterator for RelrIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            while self.bitmap != 0 {
+                let marked = self.bitmap & 1 != 0;
+                let offset = self.bitmap_offset;
+                self.bitmap >>= 1;
+                self.bitmap_offset = self.bitmap_offset.wrapping_add(Self::WORD);
+                if marked {
+                    return Some(offset);
+                }
+            }
+
+            let entry = *self.entries.next()?;
+            if entry & 1 == 0 {
+                self.next_offset = entry.wrapping_add(Self::WORD);
+                return Some(entry);
+            }
+            self.bitmap = entry >> 1;
+            self.bitmap_offset = self.next_offset;
+            self.next_offset = self
+                .next_offset
+                .wrapping_add((usize::BITS as usize - 1) * Self::WORD);
+        }
+    }
+}
+
 #[cfg(target_arch = "x86_64")]
 pub mod relocations {
     // Variables in relocation formulae:
@@ -103,6 +157,108 @@ pub mod relocations {
     pub const R_X86_64_TPOFF32: u32 = 23;
     /// GOT entry for GOTTPOFF (initial-exec TP offset via GOT) | u32
     pub const R_X86_64_GOTTPOFF: u32 = 22;
+
+    /// The `R_X86_64_*` name of a relocation type, for diagnostics.
+    pub fn relocation_name(r_type: u32) -> Option<&'static str> {
+        Some(match r_type {
+            R_X86_64_NONE => "R_X86_64_NONE",
+            R_X86_64_64 => "R_X86_64_64",
+            R_X86_64_PC32 => "R_X86_64_PC32",
+            R_X86_64_COPY => "R_X86_64_COPY",
+            R_X86_64_GLOB_DAT => "R_X86_64_GLOB_DAT",
+            R_X86_64_JUMP_SLOT => "R_X86_64_JUMP_SLOT",
+            R_X86_64_RELATIVE => "R_X86_64_RELATIVE",
+            R_X86_64_IRELATIVE => "R_X86_64_IRELATIVE",
+            R_X86_64_DTPMOD64 => "R_X86_64_DTPMOD64",
+            R_X86_64_DTPOFF64 => "R_X86_64_DTPOFF64",
+            R_X86_64_TPOFF64 => "R_X86_64_TPOFF64",
+            _ => return None,
+        })
+    }
+
+    // The architecture's equivalents of the relocations the generic stages apply:
+    pub const R_ABSOLUTE: u32 = R_X86_64_64;
+    pub const R_COPY: u32 = R_X86_64_COPY;
+    pub const R_GLOB_DAT: u32 = R_X86_64_GLOB_DAT;
+    pub const R_JUMP_SLOT: u32 = R_X86_64_JUMP_SLOT;
+    pub const R_RELATIVE: u32 = R_X86_64_RELATIVE;
+    pub const R_IRELATIVE: u32 = R_X86_64_IRELATIVE;
+    pub const R_TPOFF: u32 = R_X86_64_TPOFF64;
+    pub const R_DTPMOD: u32 = R_X86_64_DTPMOD64;
+    pub const R_DTPOFF: u32 = R_X86_64_DTPOFF64;
+}
+
+#[cfg(target_arch = "aarch64")]
+pub mod relocations {
+    // Same formula variables as x86-64; see "ELF for the Arm 64-bit Architecture" for the full table.
+
+    // aarch64 dynamic relocation types:
+    /// | None
+    pub const R_AARCH64_NONE: u32 = 0;
+    /// S + B + A | u64
+    pub const R_AARCH64_ABS64: u32 = 257;
+    /// | None
+    pub const R_AARCH64_COPY: u32 = 1024;
+    /// S + B + A | u64
+    pub const R_AARCH64_GLOB_DAT: u32 = 1025;
+    /// S + B + A | u64
+    pub const R_AARCH64_JUMP_SLOT: u32 = 1026;
+    /// B + A | u64
+    pub const R_AARCH64_RELATIVE: u32 = 1027;
+    /// The returned value from the function located at (B + A) | u64
+    pub const R_AARCH64_IRELATIVE: u32 = 1032;
+
+    // Thread-Local Storage ABI:
+    /// Module index for TLS | u64
+    pub const R_AARCH64_TLS_DTPMOD64: u32 = 1028;
+    /// Offset within TLS module | u64
+    pub const R_AARCH64_TLS_DTPREL64: u32 = 1029;
+    /// TP-relative offset (variant I: positive from TP) | u64
+    pub const R_AARCH64_TLS_TPREL64: u32 = 1030;
+    /// TLS descriptor: a resolver and its argument | u64 x 2
+    pub const R_AARCH64_TLSDESC: u32 = 1031;
+
+    /// The `R_AARCH64_*` name of a relocation type, for diagnostics.
+    pub fn relocation_name(r_type: u32) -> Option<&'static str> {
+        Some(match r_type {
+            R_AARCH64_NONE => "R_AARCH64_NONE",
+            R_AARCH64_ABS64 => "R_AARCH64_ABS64",
+            R_AARCH64_COPY => "R_AARCH64_COPY",
+            R_AARCH64_GLOB_DAT => "R_AARCH64_GLOB_DAT",
+            R_AARCH64_JUMP_SLOT => "R_AARCH64_JUMP_SLOT",
+            R_AARCH64_RELATIVE => "R_AARCH64_RELATIVE",
+            R_AARCH64_IRELATIVE => "R_AARCH64_IRELATIVE",
+            R_AARCH64_TLS_DTPMOD64 => "R_AARCH64_TLS_DTPMOD64",
+            R_AARCH64_TLS_DTPREL64 => "R_AARCH64_TLS_DTPREL64",
+            R_AARCH64_TLS_TPREL64 => "R_AARCH64_TLS_TPREL64",
+            R_AARCH64_TLSDESC => "R_AARCH64_TLSDESC",
+            _ => return None,
+        })
+    }
+
+    // The architecture's equivalents of the relocations the generic stages apply:
+    pub const R_ABSOLUTE: u32 = R_AARCH64_ABS64;
+    pub const R_COPY: u32 = R_AARCH64_COPY;
+    pub const R_GLOB_DAT: u32 = R_AARCH64_GLOB_DAT;
+    pub const R_JUMP_SLOT: u32 = R_AARCH64_JUMP_SLOT;
+    pub const R_RELATIVE: u32 = R_AARCH64_RELATIVE;
+    pub const R_IRELATIVE: u32 = R_AARCH64_IRELATIVE;
+    pub const R_TPOFF: u32 = R_AARCH64_TLS_TPREL64;
+    pub const R_DTPMOD: u32 = R_AARCH64_TLS_DTPMOD64;
+    pub const R_DTPOFF: u32 = R_AARCH64_TLS_DTPREL64;
 }
 
 pub use relocations::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relr_decodes_addresses_and_bitmaps() {
+        // 0x1000, then a bitmap marking the 1st and 3rd words after it, then a bitmap continuing 63 words on.
+        let entries = [0x1000, 0b1011, 0b11, 0x2000];
+        let offsets: Vec<usize> = RelrIter::new(&entries).collect();
+        assert_eq!(offsets, [0x1000, 0x1008, 0x1018, 0x1008 + 63 * 8, 0x2000]);
+    }
+}
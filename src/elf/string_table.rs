@@ -1,4 +1,6 @@
-use std::ptr;
+use std::{ptr, slice, str};
+
+use crate::error::MirosError;
 
 /// A collection of null-terminated strings stored in contiguous memory.
 ///
@@ -25,28 +27,97 @@ use std::ptr;
 /// |   32  |  None  |
 /// |   33  |  Fish  |
 /// ```
-pub struct StringTable(*const u8);
+pub struct StringTable {
+    start: *const u8,
+    /// `DT_STRSZ`, when the object provides it; every lookup stays inside it.
+    size: usize,
+}
 
 impl StringTable {
     /// Creates a new `StringTable` from a `*const u8` pointer to the start of the string table.
     pub unsafe fn new(string_table_pointer: *const u8) -> Self {
-        Self(string_table_pointer)
+        Self::with_size(string_table_pointer, usize::MAX)
+    }
+
+    /// Creates a `StringTable` whose lookups never read past `size` bytes.
+    pub unsafe fn with_size(string_table_pointer: *const u8, size: usize) -> Self {
+        Self {
+            start: string_table_pointer,
+            size,
+        }
     }
 
-    /// Retrieves a string from the table at the specified byte offset.
+    /// Retrieves a string from the table at the specified byte offset; out-of-bounds offsets read as `""`.
     pub unsafe fn get(&self, index: usize) -> &str {
         &*self.get_wide_pointer(index)
     }
 
     /// Retrieves a raw wide pointer to the string at the specified byte offset.
     pub unsafe fn get_wide_pointer(&self, index: usize) -> *const str {
-        let string_start = self.0.add(index);
-        let length = (0..).find(|&index| *string_start.add(index) == 0).unwrap();
-        ptr::slice_from_raw_parts(string_start, length) as *const str
+        self.bytes(index)
+            .map_or(ptr::slice_from_raw_parts(self.start, 0), |bytes| {
+                bytes as *const [u8]
+            }) as *const str
+    }
+
+    /// Like `get`, but reports an offset past the table, a missing terminator or invalid UTF-8.
+    pub unsafe fn try_get(&self, index: usize) -> Result<&str, MirosError> {
+        let bytes = self
+            .bytes(index)
+            .ok_or(MirosError::StringOutOfBounds(index))?;
+        str::from_utf8(bytes).map_err(|_| MirosError::InvalidString(index))
+    }
+
+    unsafe fn bytes(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.size {
+            return None;
+        }
+        let string_start = self.start.add(index);
+        let length = (0..self.size - index).find(|&index| *string_start.add(index) == 0)?;
+        Some(slice::from_raw_parts(string_start, length))
     }
 
     /// Extracts the inner pointer to the next item consuming the `StringTable`.
     pub fn into_inner(self) -> *const u8 {
-        self.0
+        self.start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &[u8] = b"\0Hello\0World!\0\xff\0Fish";
+
+    #[test]
+    fn get_reads_up_to_the_terminator() {
+        let table = unsafe { StringTable::with_size(TABLE.as_ptr(), TABLE.len()) };
+        unsafe {
+            assert_eq!(table.get(0), "");
+            assert_eq!(table.get(1), "Hello");
+            assert_eq!(table.get(3), "llo");
+            assert_eq!(table.try_get(7).unwrap(), "World!");
+        }
+    }
+
+    #[test]
+    fn lookups_never_leave_the_table() {
+        let table = unsafe { StringTable::with_size(TABLE.as_ptr(), TABLE.len()) };
+        unsafe {
+            // "Fish" runs into the end of the table without a terminator.
+            assert_eq!(table.get(16), "");
+            assert!(matches!(
+                table.try_get(16),
+                Err(MirosError::StringOutOfBounds(16))
+            ));
+            assert!(matches!(
+                table.try_get(TABLE.len()),
+                Err(MirosError::StringOutOfBounds(_))
+            ));
+            assert!(matches!(
+                table.try_get(14),
+                Err(MirosError::InvalidString(14))
+            ));
+        }
     }
 }
@@ -25,6 +25,8 @@ pub enum SymbolBinding {
     Local = 0,
     Global = 1,
     Weak = 2,
+    /// GNU extension (C++ inline and template statics): one definition process-wide, whatever the lookup scope.
+    GnuUnique = 10,
 }
 
 #[bitfield(u8)]
@@ -85,11 +87,11 @@ impl Symbol {
         self.st_shndx != 0
     }
 
-    /// Has a known non-local binding. Symbols with OS / processor-specific bindings are conservatively excluded.
+    /// Has a known non-local binding. Symbols with other OS / processor-specific bindings are conservatively excluded.
     pub fn is_public(&self) -> bool {
         matches!(
             self.binding(),
-            Ok(SymbolBinding::Global | SymbolBinding::Weak)
+            Ok(SymbolBinding::Global | SymbolBinding::Weak | SymbolBinding::GnuUnique)
         )
     }
 
@@ -105,18 +107,41 @@ impl Symbol {
     }
 }
 
-pub struct SymbolTable(*const Symbol);
+pub struct SymbolTable {
+    start: *const Symbol,
+    /// The entry count, when it can be recovered; `try_get` refuses indices past it.
+    count: Option<usize>,
+}
 
 impl SymbolTable {
     pub fn new(symbol_table_pointer: *const Symbol) -> Self {
-        Self(symbol_table_pointer)
+        Self::with_count(symbol_table_pointer, None)
+    }
+
+    pub fn with_count(symbol_table_pointer: *const Symbol, count: Option<usize>) -> Self {
+        Self {
+            start: symbol_table_pointer,
+            count,
+        }
     }
 
     pub unsafe fn get(&self, index: usize) -> Symbol {
-        *self.0.add(index)
+        *self.start.add(index)
+    }
+
+    // Indices come straight from the file (relocations, hash chains); reject any past the dynsym before dereferencing.
+    pub unsafe fn try_get(&self, index: usize) -> Option<Symbol> {
+        if self.count.is_some_and(|count| index >= count) {
+            return None;
+        }
+        Some(self.get(index))
+    }
+
+    pub fn count(&self) -> Option<usize> {
+        self.count
     }
 
     pub fn into_inner(self) -> *const Symbol {
-        self.0
+        self.start
     }
 }
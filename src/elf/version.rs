@@ -0,0 +1,260 @@
+use std::ffi::c_void;
+
+/// `.gnu.version` indices 0 and 1 mean "local" and "global, unversioned"; neither names a requirement.
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// Set on `.gnu.version` entries for non-default (`foo@V` rather than `foo@@V`) definitions.
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// One `.gnu.version_r` record per needed file, chained by `vn_next`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Verneed {
+    pub vn_version: u16,
+    /// Number of `Vernaux` records hanging off this one.
+    pub vn_cnt: u16,
+    /// String table offset of the needed file's name.
+    pub vn_file: u32,
+    /// Byte offset from this record to its first `Vernaux`.
+    pub vn_aux: u32,
+    /// Byte offset from this record to the next `Verneed`, or 0.
+    pub vn_next: u32,
+}
+
+/// One required version of a needed file.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Vernaux {
+    pub vna_hash: u32,
+    pub vna_flags: u16,
+    /// The `.gnu.version` index symbols use to refer to this version.
+    pub vna_other: u16,
+    /// String table offset of the version name.
+    pub vna_name: u32,
+    /// Byte offset from this record to the next `Vernaux`, or 0.
+    pub vna_next: u32,
+}
+
+/// One `.gnu.version_d` record per version this object defines, chained by `vd_next`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Verdef {
+    pub vd_version: u16,
+    pub vd_flags: u16,
+    /// The `.gnu.version` index of this version.
+    pub vd_ndx: u16,
+    /// Number of `Verdaux` records; the first names this version, the rest its parents.
+    pub vd_cnt: u16,
+    pub vd_hash: u32,
+    /// Byte offset from this record to its first `Verdaux`.
+    pub vd_aux: u32,
+    /// Byte offset from this record to the next `Verdef`, or 0.
+    pub vd_next: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Verdaux {
+    /// String table offset of the version name.
+    pub vda_name: u32,
+    pub vda_next: u32,
+}
+
+/// The `DT_VERSYM`, `DT_VERNEED` and `DT_VERDEF` tables of an object, every read bounds-checked against the mapped image.
+pub struct SymbolVersions {
+    base: *const c_void,
+    image_size: usize,
+    versym_offset: usize,
+    verneed_offset: Option<usize>,
+    verneed_count: usize,
+    verdef_offset: Option<usize>,
+    verdef_count: usize,
+}
+
+impl SymbolVersions {
+    pub unsafe fn new(base: *const c_void, image_size: usize, versym_offset: usize) -> Self {
+        Self {
+            base,
+            image_size,
+            versym_offset,
+            verneed_offset: None,
+            verneed_count: 0,
+            verdef_offset: None,
+            verdef_count: 0,
+        }
+    }
+
+    pub fn with_requirements(
+        mut self,
+        verneed_offset: Option<usize>,
+        verneed_count: usize,
+    ) -> Self {
+        self.verneed_offset = verneed_offset;
+        self.verneed_count = verneed_count;
+        self
+    }
+
+    pub fn with_definitions(mut self, verdef_offset: Option<usize>, verdef_count: usize) -> Self {
+        self.verdef_offset = verdef_offset;
+        self.verdef_count = verdef_count;
+        self
+    }
+
+    /// Reads a `T` at `offset` into the image, or `None` if any byte of it falls outside.
+    unsafe fn read<T: Copy>(&self, offset: usize) -> Option<T> {
+        offset
+            .checked_add(size_of::<T>())
+            .filter(|&end| end <= self.image_size)
+            .map(|_| self.base.byte_add(offset).cast::<T>().read_unaligned())
+    }
+
+    /// The symbol's `.gnu.version` index, or `None` if it is local or unversioned.
+    unsafe fn version_index(&self, symbol_index: usize) -> Option<u16> {
+        let versym_entry = symbol_index
+            .checked_mul(size_of::<u16>())
+            .and_then(|offset| offset.checked_add(self.versym_offset))?;
+        let version_index = self.read::<u16>(versym_entry)? & !VERSYM_HIDDEN;
+        (version_index > VER_NDX_GLOBAL).then_some(version_index)
+    }
+
+    /// The string table offset of the version the symbol at `symbol_index` was linked against, if it names one.
+    pub unsafe fn required_name(&self, symbol_index: usize) -> Option<usize> {
+        let version_index = self.version_index(symbol_index)?;
+
+        let mut verneed_offset = self.verneed_offset?;
+        for _ in 0..self.verneed_count {
+            let verneed = self.read::<Verneed>(verneed_offset)?;
+            let mut vernaux_offset = verneed_offset.checked_add(verneed.vn_aux as usize)?;
+            for _ in 0..verneed.vn_cnt {
+                let vernaux = self.read::<Vernaux>(vernaux_offset)?;
+                if vernaux.vna_other == version_index {
+                    return Some(vernaux.vna_name as usize);
+                }
+                vernaux_offset = vernaux_offset.checked_add(vernaux.vna_next as usize)?;
+            }
+            verneed_offset = verneed_offset.checked_add(verneed.vn_next as usize)?;
+        }
+        None
+    }
+
+    /// The string table offset of the version the symbol at `symbol_index` is defined under, if it has one.
+    pub unsafe fn defined_name(&self, symbol_index: usize) -> Option<usize> {
+        let version_index = self.version_index(symbol_index)?;
+
+        let mut verdef_offset = self.verdef_offset?;
+        for _ in 0..self.verdef_count {
+            let verdef = self.read::<Verdef>(verdef_offset)?;
+            if verdef.vd_ndx == version_index {
+                let verdaux_offset = verdef_offset.checked_add(verdef.vd_aux as usize)?;
+                return self
+                    .read::<Verdaux>(verdaux_offset)
+                    .map(|verdaux| verdaux.vda_name as usize);
+            }
+            verdef_offset = verdef_offset.checked_add(verdef.vd_next as usize)?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out `.gnu.version` for three symbols followed by one `Verneed` with two `Vernaux` records.
+    fn image() -> Vec<u8> {
+        let mut image = Vec::new();
+        for version_index in [0u16, 2, 3 | VERSYM_HIDDEN] {
+            image.extend_from_slice(&version_index.to_ne_bytes());
+        }
+        image.extend_from_slice(&[0; 2]);
+
+        let verneed = Verneed {
+            vn_version: 1,
+            vn_cnt: 2,
+            vn_file: 1,
+            vn_aux: size_of::<Verneed>() as u32,
+            vn_next: 0,
+        };
+        let vernaux = |other, name, next| Vernaux {
+            vna_hash: 0,
+            vna_flags: 0,
+            vna_other: other,
+            vna_name: name,
+            vna_next: next,
+        };
+        unsafe {
+            image.extend_from_slice(as_bytes(&verneed));
+            image.extend_from_slice(as_bytes(&vernaux(2, 10, size_of::<Vernaux>() as u32)));
+            image.extend_from_slice(as_bytes(&vernaux(3, 20, 0)));
+        }
+        image
+    }
+
+    unsafe fn as_bytes<T>(value: &T) -> &[u8] {
+        std::slice::from_raw_parts((value as *const T).cast(), size_of::<T>())
+    }
+
+    #[test]
+    fn required_name_follows_the_version_index() {
+        let image = image();
+        let requirements = unsafe { SymbolVersions::new(image.as_ptr().cast(), image.len(), 0) }
+            .with_requirements(Some(8), 1);
+        unsafe {
+            assert_eq!(requirements.required_name(0), None);
+            assert_eq!(requirements.required_name(1), Some(10));
+            assert_eq!(requirements.required_name(2), Some(20));
+        }
+    }
+
+    #[test]
+    fn required_name_stays_inside_the_image() {
+        let image = image();
+        let requirements =
+            unsafe { SymbolVersions::new(image.as_ptr().cast(), image.len() - 1, 0) }
+                .with_requirements(Some(8), 1);
+        unsafe {
+            // The second `Vernaux` is cut short by one byte.
+            assert_eq!(requirements.required_name(2), None);
+            assert_eq!(requirements.required_name(usize::MAX), None);
+        }
+    }
+
+    #[test]
+    fn defined_name_reads_the_first_verdaux() {
+        let mut image = Vec::new();
+        for version_index in [1u16, 2 | VERSYM_HIDDEN] {
+            image.extend_from_slice(&version_index.to_ne_bytes());
+        }
+        image.extend_from_slice(&[0; 4]);
+
+        let verdef = |ndx, next| Verdef {
+            vd_version: 1,
+            vd_flags: 0,
+            vd_ndx: ndx,
+            vd_cnt: 1,
+            vd_hash: 0,
+            vd_aux: size_of::<Verdef>() as u32,
+            vd_next: next,
+        };
+        let record = (size_of::<Verdef>() + size_of::<Verdaux>()) as u32;
+        unsafe {
+            image.extend_from_slice(as_bytes(&verdef(1, record)));
+            image.extend_from_slice(as_bytes(&Verdaux {
+                vda_name: 5,
+                vda_next: 0,
+            }));
+            image.extend_from_slice(as_bytes(&verdef(2, 0)));
+            image.extend_from_slice(as_bytes(&Verdaux {
+                vda_name: 30,
+                vda_next: 0,
+            }));
+        }
+
+        let versions = unsafe { SymbolVersions::new(image.as_ptr().cast(), image.len(), 0) }
+            .with_definitions(Some(8), 2);
+        unsafe {
+            assert_eq!(versions.defined_name(0), None);
+            assert_eq!(versions.defined_name(1), Some(30));
+            assert_eq!(versions.required_name(1), None);
+        }
+    }
+}
@@ -1,12 +1,325 @@
-use crate::{elf::dynamic_array::DynamicTag, start::auxiliary_vector::AuxiliaryVectorType};
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+use crate::{
+    elf::{dynamic_array::DynamicTag, header::ElfHeaderError, relocate::relocation_name},
+    libc::errno::Errno,
+    objects::namespace::Lmid,
+    start::{self, auxiliary_vector::AuxiliaryVectorType},
+};
 
 #[derive(Debug)]
 pub enum MirosError {
     MissingAuxvEntry(AuxiliaryVectorType),
     MissingDynamicEntry(DynamicTag),
-    DependencyNotFound(String),
-    ElfReadError(String),
-    UndefinedSymbol(String),
+    DependencyNotFound {
+        name: String,
+        /// The object whose DT_NEEDED named it; `None` for the main program.
+        requester: Option<PathBuf>,
+        /// Every candidate path probed, in search order; empty for a literal path or an auditor veto.
+        searched: Vec<PathBuf>,
+        /// Why the last candidate failed to open.
+        errno: Errno,
+    },
+    /// A system call on an object failed; `action` reads as "cannot <action>".
+    Io {
+        action: &'static str,
+        errno: Errno,
+    },
+    InvalidElfHeader(ElfHeaderError),
+    /// `e_phoff`/`e_phnum` describe a table that is not inside the file's first loadable bytes.
+    ProgramHeaderTableOutOfBounds,
+    /// The object has no `PT_LOAD` segments, so there is nothing to map.
+    NoLoadableSegments,
+    /// The `PT_LOAD` at this program-header index reaches past the end of the file, or has `p_filesz > p_memsz`.
+    SegmentOutOfBounds(usize),
+    /// The `PT_LOAD` at this program-header index has a file offset and address that disagree modulo the page size.
+    MisalignedSegment(usize),
+    MissingDynamicSegment,
+    /// `PT_DYNAMIC` lies outside the mapped image.
+    DynamicSegmentOutOfBounds,
+    /// The table named by this tag (with its size, where one is given) reaches outside the mapped image.
+    DynamicEntryOutOfBounds(DynamicTag),
+    /// `DT_SYMENT`, `DT_RELAENT` or `DT_PLTREL` disagrees with the only layout this target uses.
+    UnexpectedDynamicValue(DynamicTag),
+    /// The hash table named by this tag has an impossible header (no buckets, an empty bloom filter, a bad shift).
+    MalformedHashTable(DynamicTag),
+    /// A string-table offset past `DT_STRSZ`, or a string without a terminating NUL.
+    StringOutOfBounds(usize),
+    /// A string-table entry that is not valid UTF-8.
+    InvalidString(usize),
+    UndefinedSymbol {
+        name: String,
+        /// The version the requester was linked against (`DT_VERNEED`), if any.
+        version: Option<String>,
+        /// The object holding the reference; `None` for the main program.
+        requester: Option<PathBuf>,
+        /// The relocation type that needed the symbol; `None` for lookups outside relocation.
+        relocation: Option<u32>,
+    },
     SymbolIndexOutOfBounds(usize),
     TlsAllocationFailed,
+    /// An interception rule that is not `pattern=target`; `source` is the config file or variable it came from.
+    InvalidInterceptionRule {
+        source: String,
+        rule: String,
+    },
+    /// `dlmopen` was given a namespace id that was never handed out.
+    InvalidNamespace(Lmid),
+    /// `dlmopen` asked an existing namespace for an object it does not hold; only a new namespace loads from disk.
+    NotInNamespace {
+        name: String,
+        namespace: Lmid,
+    },
+    /// A shared object has `DT_PREINIT_ARRAY`, which only the program may have.
+    PreinitArrayInSharedObject,
+    /// Wraps an error with the path of the object being loaded when it happened.
+    InObject {
+        path: PathBuf,
+        error: Box<MirosError>,
+    },
+}
+
+impl MirosError {
+    /// Exit status for a program that failed to start, as glibc's ld.so uses.
+    pub const EXIT_CODE: i32 = 127;
+
+    pub fn undefined_symbol(name: impl Into<String>) -> Self {
+        Self::UndefinedSymbol {
+            name: name.into(),
+            version: None,
+            requester: None,
+            relocation: None,
+        }
+    }
+
+    /// Attributes this error to the object at `path`, unless it already names one.
+    pub fn in_object(self, path: impl Into<PathBuf>) -> Self {
+        match self {
+            Self::InObject { .. }
+            | Self::DependencyNotFound { .. }
+            | Self::UndefinedSymbol {
+                requester: Some(_), ..
+            } => self,
+            error => Self::InObject {
+                path: path.into(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The leading phrase glibc uses for this class of failure.
+    fn category(&self) -> &'static str {
+        match self {
+            Self::InObject { error, .. } => error.category(),
+            Self::UndefinedSymbol {
+                relocation: Some(_),
+                ..
+            }
+            | Self::SymbolIndexOutOfBounds(_) => "relocation error",
+            Self::UndefinedSymbol { .. } => "symbol lookup error",
+            Self::MissingAuxvEntry(_) | Self::InvalidInterceptionRule { .. } => "fatal error",
+            _ => "error while loading shared libraries",
+        }
+    }
+
+    fn fmt_detail(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InObject { path, error } => {
+                write!(f, "{}: ", path.display())?;
+                error.fmt_detail(f)
+            }
+            Self::DependencyNotFound {
+                name,
+                requester,
+                searched,
+                errno,
+            } => {
+                write!(f, "{name}: cannot open shared object file: {errno}")?;
+                write!(f, " (needed by {}", object_name(requester))?;
+                if !searched.is_empty() {
+                    f.write_str("; searched ")?;
+                    for (index, path) in searched.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str(", ")?;
+                        }
+                        write!(f, "{}", path.display())?;
+                    }
+                }
+                f.write_str(")")
+            }
+            Self::UndefinedSymbol {
+                name,
+                version,
+                requester,
+                relocation,
+            } => {
+                write!(f, "{}: undefined symbol: {name}", object_name(requester))?;
+                if let Some(version) = version {
+                    write!(f, ", version {version}")?;
+                }
+                match relocation.map(|r_type| (r_type, relocation_name(r_type))) {
+                    Some((_, Some(name))) => write!(f, " (relocation {name})"),
+                    Some((r_type, None)) => write!(f, " (relocation type {r_type})"),
+                    None => Ok(()),
+                }
+            }
+            Self::Io { action, errno } => write!(f, "cannot {action}: {errno}"),
+            Self::InvalidElfHeader(error) => match error {
+                ElfHeaderError::BadMagic(_) => f.write_str("invalid ELF header"),
+                ElfHeaderError::UnsupportedClass(class) => {
+                    write!(f, "wrong ELF class: ELFCLASS{}", class * 32)
+                }
+                ElfHeaderError::UnsupportedDataEncoding(encoding) => {
+                    write!(f, "ELF file data encoding not little-endian ({encoding})")
+                }
+                ElfHeaderError::UnsupportedVersion(version) => {
+                    write!(f, "ELF file version {version} does not match current one")
+                }
+                ElfHeaderError::UnsupportedMachine(machine) => {
+                    write!(f, "ELF file machine {machine} does not match this host")
+                }
+                ElfHeaderError::UnsupportedType(e_type) => {
+                    write!(f, "only ET_DYN objects can be loaded (e_type {e_type})")
+                }
+                ElfHeaderError::ProgramHeaderSize(size) => {
+                    write!(f, "ELF file's phentsize {size} not the expected size")
+                }
+            },
+            Self::ProgramHeaderTableOutOfBounds => {
+                f.write_str("program header table is not inside the loaded image")
+            }
+            Self::NoLoadableSegments => f.write_str("object has no loadable segments"),
+            Self::SegmentOutOfBounds(index) => {
+                write!(f, "program header {index} extends past the end of the file")
+            }
+            Self::MisalignedSegment(index) => write!(
+                f,
+                "program header {index}: ELF load command address/offset not page-aligned"
+            ),
+            Self::MissingDynamicSegment => f.write_str("object has no dynamic section"),
+            Self::DynamicSegmentOutOfBounds => {
+                f.write_str("dynamic section is not inside the loaded image")
+            }
+            Self::MissingDynamicEntry(tag) => write!(f, "missing dynamic entry DT_{tag:?}"),
+            Self::DynamicEntryOutOfBounds(tag) => {
+                write!(f, "DT_{tag:?} is not inside the loaded image")
+            }
+            Self::UnexpectedDynamicValue(tag) => write!(f, "unsupported DT_{tag:?} value"),
+            Self::MalformedHashTable(tag) => write!(f, "malformed DT_{tag:?} table"),
+            Self::StringOutOfBounds(offset) => {
+                write!(f, "string table offset {offset} is out of bounds")
+            }
+            Self::InvalidString(offset) => {
+                write!(f, "string at table offset {offset} is not valid UTF-8")
+            }
+            Self::SymbolIndexOutOfBounds(index) => {
+                write!(f, "symbol index {index} is past the end of .dynsym")
+            }
+            Self::TlsAllocationFailed => f.write_str("cannot allocate TLS block"),
+            Self::InvalidInterceptionRule { source, rule } => {
+                write!(f, "invalid interception rule `{rule}` in {source}")
+            }
+            Self::MissingAuxvEntry(entry) => {
+                write!(f, "missing auxiliary vector entry AT_{entry:?}")
+            }
+            Self::PreinitArrayInSharedObject => {
+                f.write_str("shared object has a DT_PREINIT_ARRAY, which only executables may have")
+            }
+            Self::InvalidNamespace(namespace) => {
+                write!(f, "invalid target namespace {namespace} in dlmopen()")
+            }
+            Self::NotInNamespace { name, namespace } => write!(
+                f,
+                "{name}: not loaded in namespace {namespace}, and only a new namespace can load objects"
+            ),
+        }
+    }
+
+    /// What `dlerror` reports: the detail without the category, and without "needed by" when `dlmopen` itself asked.
+    pub fn dlerror_message(&self) -> String {
+        struct Detail<'a>(&'a MirosError);
+
+        impl Display for Detail<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                self.0.fmt_detail(f)
+            }
+        }
+
+        match self {
+            Self::DependencyNotFound {
+                name,
+                requester: None,
+                errno,
+                ..
+            } => format!("{name}: cannot open shared object file: {errno}"),
+            error => Detail(error).to_string(),
+        }
+    }
+}
+
+/// glibc-style: `error while loading shared libraries: libfoo.so: cannot open shared object file: ...`.
+impl Display for MirosError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.category())?;
+        self.fmt_detail(f)
+    }
+}
+
+fn object_name(path: &Option<PathBuf>) -> std::path::Display<'_> {
+    path.as_deref().unwrap_or(start::program_name()).display()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dependency_not_found_reads_like_glibc() {
+        let error = MirosError::DependencyNotFound {
+            name: "libfoo.so".to_string(),
+            requester: None,
+            searched: vec![PathBuf::from("/lib/libfoo.so")],
+            errno: Errno::NOENT,
+        };
+        assert_eq!(
+            error.to_string(),
+            "error while loading shared libraries: libfoo.so: cannot open shared object file: \
+             No such file or directory (needed by main program; searched /lib/libfoo.so)"
+        );
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn undefined_symbol_names_requester_version_and_relocation() {
+        let error = MirosError::UndefinedSymbol {
+            name: "frobnicate".to_string(),
+            version: Some("FOO_1.0".to_string()),
+            requester: Some(PathBuf::from("/usr/lib/libbar.so")),
+            relocation: Some(crate::elf::relocate::R_X86_64_JUMP_SLOT),
+        };
+        assert_eq!(
+            error.to_string(),
+            "relocation error: /usr/lib/libbar.so: undefined symbol: frobnicate, version FOO_1.0 \
+             (relocation R_X86_64_JUMP_SLOT)"
+        );
+        assert_eq!(
+            MirosError::undefined_symbol("frobnicate").to_string(),
+            "symbol lookup error: main program: undefined symbol: frobnicate"
+        );
+    }
+
+    #[test]
+    fn in_object_prefixes_the_path_once() {
+        let error = MirosError::InvalidElfHeader(ElfHeaderError::UnsupportedMachine(183))
+            .in_object("/tmp/libarm.so")
+            .in_object("/tmp/other.so");
+        assert_eq!(
+            error.to_string(),
+            "error while loading shared libraries: /tmp/libarm.so: \
+             ELF file machine 183 does not match this host"
+        );
+    }
 }
@@ -0,0 +1,96 @@
+//! Entry points for the cargo-fuzz targets in `fuzz/`; each treats the input as a mapped image and must never read outside it.
+
+use std::{ffi::c_void, ptr};
+
+use crate::{
+    elf::{
+        dynamic_array::{DynamicArrayItem, DynamicArrayIter},
+        header::ElfHeader,
+        string_table::StringTable,
+        symbol::{Symbol, SymbolTable},
+    },
+    objects::object_data::{hash_tables::HashTable, DynamicFields},
+};
+
+const PROBE_NAMES: &[&str] = &[
+    "",
+    "printf",
+    "__libc_start_main",
+    "_ITM_registerTMCloneTable",
+];
+
+/// Copies `data` into 8-byte aligned storage, as a real mapping would be.
+fn aligned_image(data: &[u8]) -> Vec<u64> {
+    let mut image = vec![0u64; data.len().div_ceil(size_of::<u64>())];
+    unsafe { ptr::copy_nonoverlapping(data.as_ptr(), image.as_mut_ptr().cast(), data.len()) };
+    image
+}
+
+pub fn elf_header(data: &[u8]) {
+    if data.len() < size_of::<ElfHeader>() {
+        return;
+    }
+    let header = unsafe { ptr::read_unaligned(data.as_ptr().cast::<ElfHeader>()) };
+    let _ = header.validate();
+}
+
+/// Walks the input as a dynamic array, then parses it into `DynamicFields` with the input as the whole image.
+pub fn dynamic_array(data: &[u8]) {
+    let image = aligned_image(data);
+    let base = image.as_ptr() as *const c_void;
+    let dynamic_len = data.len() / size_of::<DynamicArrayItem>();
+
+    unsafe {
+        DynamicArrayIter::with_len(base.cast(), dynamic_len).for_each(|item| {
+            let _ = item.d_tag();
+        });
+
+        if let Ok(fields) =
+            DynamicFields::from_dynamic_array(base, base.cast(), dynamic_len, data.len())
+        {
+            for &name in PROBE_NAMES {
+                if let Some(hash_table) = &fields.hash_table {
+                    let _ = hash_table.lookup(name, &fields.symbol_table, &fields.string_table);
+                }
+            }
+            let _ = fields.dependencies();
+        }
+    }
+}
+
+/// The first byte picks SysV or GNU; the table starts at offset 8 and the symbol and string tables alias the whole input.
+pub fn hash_table(data: &[u8]) {
+    let Some((&selector, _)) = data.split_first() else {
+        return;
+    };
+    let image = aligned_image(data);
+    let base = image.as_ptr() as *const c_void;
+
+    unsafe {
+        let table = match selector & 1 {
+            0 => HashTable::from_sysv(base, 8, data.len()),
+            _ => HashTable::from_gnu(base, 8, data.len()),
+        };
+        let Ok(table) = table else {
+            return;
+        };
+
+        let symbol_table =
+            SymbolTable::with_count(base.cast(), Some(data.len() / size_of::<Symbol>()));
+        let string_table = StringTable::with_size(base.cast(), data.len());
+        let _ = table.symbol_count();
+        for &name in PROBE_NAMES {
+            let _ = table.lookup(name, &symbol_table, &string_table);
+        }
+    }
+}
+
+pub fn string_table(data: &[u8]) {
+    let table = unsafe { StringTable::with_size(data.as_ptr(), data.len()) };
+    for index in 0..=data.len() {
+        unsafe {
+            let _ = table.get(index);
+            let _ = table.try_get(index);
+        }
+    }
+}
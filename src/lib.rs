@@ -8,6 +8,8 @@
 #![feature(stmt_expr_attributes)]
 #![feature(maybe_uninit_array_assume_init)]
 #![feature(ptr_metadata)]
+#![feature(core_intrinsics)]
+#![allow(internal_features)]
 #![allow(dead_code)]
 #![allow(suspicious_runtime_symbol_definitions)]
 // The libm TT muncher recurses once per exported symbol (~120).
@@ -24,6 +26,8 @@
 mod allocator;
 mod elf;
 mod error;
+#[cfg(fuzzing)]
+pub mod fuzz;
 mod io_macros;
 mod libc;
 mod objects;
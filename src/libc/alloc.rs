@@ -18,13 +18,13 @@ fn allocation_or_nomem(result: Option<NonNull<u8>>) -> *mut c_void {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
     signature_matches_libc!(libc::malloc(size));
     allocation_or_nomem(primary().alloc(Layout::from_size_align_unchecked(size, 1)))
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut c_void {
     signature_matches_libc!(libc::calloc(count, size));
     let total = match count.checked_mul(size) {
@@ -37,7 +37,7 @@ unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut c_void {
     allocation_or_nomem(primary().alloc_zeroed(Layout::from_size_align_unchecked(total, 1)))
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn realloc(pointer: *mut c_void, size: usize) -> *mut c_void {
     signature_matches_libc!(libc::realloc(pointer, size));
     match primary().realloc(pointer as *mut u8, size) {
@@ -52,13 +52,13 @@ unsafe extern "C" fn realloc(pointer: *mut c_void, size: usize) -> *mut c_void {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn free(pointer: *mut c_void) {
     signature_matches_libc!(libc::free(pointer));
     primary().free(pointer as *mut u8)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn posix_memalign(
     memory_pointer: *mut *mut c_void,
     alignment: usize,
@@ -91,13 +91,13 @@ unsafe fn aligned_allocation(alignment: usize, size: usize) -> *mut c_void {
     allocation_or_nomem(primary().alloc(Layout::from_size_align_unchecked(size, alignment)))
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn aligned_alloc(alignment: usize, size: usize) -> *mut c_void {
     signature_matches_libc!(libc::aligned_alloc(alignment, size));
     aligned_allocation(alignment, size)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memalign(alignment: usize, size: usize) -> *mut c_void {
     signature_matches_libc!(libc::memalign(alignment, size));
     aligned_allocation(alignment, size)
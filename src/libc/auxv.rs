@@ -11,7 +11,7 @@ use crate::{
     syscall::Syscall,
 };
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn getauxval(auxv_type: c_ulong) -> c_ulong {
     signature_matches_libc!(libc::getauxval(auxv_type));
     get_auxiliary_value(auxv_type as usize).unwrap_or(0) as c_ulong
@@ -27,7 +27,7 @@ enum SysconfName {
     ProcessorsOnline = 84,
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn sysconf(name: c_int) -> c_long {
     signature_matches_libc!(libc::sysconf(name));
     use SysconfName::*;
@@ -0,0 +1,239 @@
+use std::{
+    cell::Cell,
+    ffi::{c_char, c_int, c_ulonglong, c_void, CStr, CString},
+    fmt::Display,
+    intrinsics,
+    os::fd::BorrowedFd,
+    ptr::null_mut,
+    slice,
+};
+
+use crate::{
+    error::MirosError,
+    objects::{
+        namespace::{self, Lmid, Source, LM_ID_NEWLM},
+        rendezvous::LinkMap,
+    },
+    signature_matches_libc,
+};
+
+const RTLD_NOLOAD: c_int = 0x4;
+const RTLD_NEXT: *mut c_void = usize::MAX as *mut c_void;
+const RTLD_DI_LMID: c_int = 1;
+const RTLD_DI_LINKMAP: c_int = 2;
+
+/// What the next `dlerror` returns.
+#[thread_local]
+static PENDING_ERROR: Cell<Option<CString>> = Cell::new(None);
+/// What `dlerror` returned last, kept alive until the next call as POSIX requires.
+#[thread_local]
+static RETURNED_ERROR: Cell<Option<CString>> = Cell::new(None);
+
+fn set_error(message: impl Display) {
+    PENDING_ERROR.set(CString::new(message.to_string()).ok());
+}
+
+/// `LM_ID_NEWLM` loads `filename` and its dependencies into a new namespace; any other id only finds what that namespace
+/// already holds. Objects are never bound lazily, so `RTLD_LAZY` and `RTLD_NOW` are the same.
+///
+/// There is no `dlopen`: nothing can be loaded into the base namespace once the program runs, and a `dlopen` that
+/// only finds what is already loaded would pass for one that works.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dlmopen(lmid: Lmid, filename: *const c_char, flags: c_int) -> *mut c_void {
+    signature_matches_libc!(libc::dlmopen(lmid, filename, flags));
+    let Some(name) = object_name(filename) else {
+        return null_mut();
+    };
+
+    let opened = match lmid {
+        LM_ID_NEWLM if flags & RTLD_NOLOAD != 0 => {
+            set_error("RTLD_NOLOAD cannot create a namespace");
+            return null_mut();
+        }
+        // Searched for the way the calling object would search for it.
+        LM_ID_NEWLM => namespace::open_new(Source::Search {
+            name,
+            caller: intrinsics::return_address().addr(),
+        })
+        .map(|(_, handle)| handle),
+        lmid => namespace::find_loaded(lmid, name),
+    };
+    handle_or_error(opened)
+}
+
+/// Like `dlmopen(LM_ID_NEWLM, ...)`, for an object the caller already has open as `fd`; it goes by `filename`, and the
+/// descriptor stays the caller's to close.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn miros_dlmopen_fd(fd: c_int, filename: *const c_char) -> *mut c_void {
+    let Some(name) = object_name(filename) else {
+        return null_mut();
+    };
+    if fd < 0 {
+        set_error("invalid file descriptor");
+        return null_mut();
+    }
+    let opened = namespace::open_new(Source::Fd(BorrowedFd::borrow_raw(fd), name));
+    handle_or_error(opened.map(|(_, handle)| handle))
+}
+
+/// Like `dlmopen(LM_ID_NEWLM, ...)`, for an object held in memory as the `size` bytes at `bytes`: it is mapped from a
+/// memfd, never touching disk, and goes by `memfd:<filename>`.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn miros_dlmopen_memory(
+    bytes: *const c_void,
+    size: usize,
+    filename: *const c_char,
+) -> *mut c_void {
+    let Some(name) = object_name(filename) else {
+        return null_mut();
+    };
+    if bytes.is_null() {
+        set_error("no object bytes");
+        return null_mut();
+    }
+    let bytes = slice::from_raw_parts(bytes.cast::<u8>(), size);
+    handle_or_error(namespace::open_new(Source::Memory(bytes, name)).map(|(_, handle)| handle))
+}
+
+/// The name an object is opened by, or `None` with the error set.
+unsafe fn object_name<'a>(filename: *const c_char) -> Option<&'a str> {
+    if filename.is_null() {
+        set_error("a file name is needed");
+        return None;
+    }
+    let name = CStr::from_ptr(filename).to_str().ok();
+    if name.is_none() {
+        set_error("file name is not valid UTF-8");
+    }
+    name
+}
+
+fn handle_or_error(opened: Result<*mut LinkMap, MirosError>) -> *mut c_void {
+    match opened {
+        Ok(handle) => handle.cast(),
+        Err(error) => {
+            set_error(error.dlerror_message());
+            null_mut()
+        }
+    }
+}
+
+/// Searches the namespace holding `handle` (see [`namespace::symbol`]); `RTLD_DEFAULT` searches the base namespace.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void {
+    signature_matches_libc!(libc::dlsym(handle, symbol));
+    if handle == RTLD_NEXT {
+        set_error("RTLD_NEXT is unsupported");
+        return null_mut();
+    }
+    let Ok(name) = CStr::from_ptr(symbol).to_str() else {
+        set_error("symbol name is not valid UTF-8");
+        return null_mut();
+    };
+
+    let handle = (!handle.is_null()).then(|| handle.cast::<LinkMap>());
+    match namespace::symbol(handle, name) {
+        Some(Ok(address)) => address.cast_mut(),
+        Some(Err(error)) => {
+            set_error(error.dlerror_message());
+            null_mut()
+        }
+        None => {
+            set_error("invalid handle");
+            null_mut()
+        }
+    }
+}
+
+/// Objects are never unloaded, as if all were opened with `RTLD_NODELETE`; this only checks the handle.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dlclose(handle: *mut c_void) -> c_int {
+    signature_matches_libc!(libc::dlclose(handle));
+    if namespace::namespace_of(handle.cast()).is_none() {
+        set_error("invalid handle");
+        return -1;
+    }
+    0
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dlerror() -> *mut c_char {
+    signature_matches_libc!(libc::dlerror());
+    let message = PENDING_ERROR.take();
+    let pointer = message
+        .as_ref()
+        .map_or(null_mut(), |message| message.as_ptr().cast_mut());
+    RETURNED_ERROR.set(message);
+    pointer
+}
+
+/// Answers `RTLD_DI_LMID` and `RTLD_DI_LINKMAP`; a handle is its object's link map.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dlinfo(handle: *mut c_void, request: c_int, info: *mut c_void) -> c_int {
+    signature_matches_libc!(libc::dlinfo(handle, request, info));
+    let Some(lmid) = namespace::namespace_of(handle.cast()) else {
+        set_error("invalid handle");
+        return -1;
+    };
+    match request {
+        RTLD_DI_LMID => *info.cast::<Lmid>() = lmid,
+        RTLD_DI_LINKMAP => *info.cast::<*mut LinkMap>() = handle.cast(),
+        request => {
+            set_error(format_args!("unsupported dlinfo request {request}"));
+            return -1;
+        }
+    }
+    0
+}
+
+/// glibc's `struct dl_find_object`, without `dlfo_eh_dbase` and `dlfo_eh_count`: neither architecture has them.
+#[repr(C)]
+struct DlFindObject {
+    dlfo_flags: c_ulonglong,
+    dlfo_map_start: *mut c_void,
+    dlfo_map_end: *mut c_void,
+    dlfo_link_map: *mut LinkMap,
+    dlfo_eh_frame: *mut c_void,
+    __dlfo_reserved: [c_ulonglong; 7],
+}
+
+/// Finds the object `address` lies in, for unwinders (libgcc's, since glibc 2.35) looking up its `.eh_frame_hdr`.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn _dl_find_object(address: *mut c_void, result: *mut DlFindObject) -> c_int {
+    let Some(found) = namespace::find_object(address.addr()) else {
+        return -1;
+    };
+    *result = DlFindObject {
+        dlfo_flags: 0,
+        dlfo_map_start: found.mapped_range.start as *mut c_void,
+        dlfo_map_end: found.mapped_range.end as *mut c_void,
+        dlfo_link_map: found.link_map,
+        dlfo_eh_frame: found
+            .eh_frame_header
+            .map_or(null_mut(), |eh_frame_header| eh_frame_header.cast_mut()),
+        __dlfo_reserved: [0; 7],
+    };
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dlerror_reports_each_failure_once() {
+        assert!(unsafe { dlinfo(null_mut(), RTLD_DI_LMID, null_mut()) } == -1);
+        let message = unsafe { dlerror() };
+        assert_eq!(unsafe { CStr::from_ptr(message) }, c"invalid handle");
+        assert!(unsafe { dlerror() }.is_null());
+    }
+
+    #[test]
+    fn dl_find_object_misses_addresses_outside_every_object() {
+        let mut result = std::mem::MaybeUninit::<DlFindObject>::uninit();
+        assert_eq!(
+            unsafe { _dl_find_object(8 as *mut c_void, result.as_mut_ptr()) },
+            -1
+        );
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
     start::environment_variables::EnvironmentIter,
 };
 
-#[cfg_attr(not(test), export_name = "__environ")]
+#[cfg_attr(not(any(test, fuzzing)), export_name = "__environ")]
 #[allow(non_upper_case_globals)]
 static environ: AtomicPtr<*mut u8> = AtomicPtr::new(ptr::null_mut());
 
@@ -33,7 +33,7 @@ pub unsafe fn get_environ_pointer() -> *mut *mut u8 {
     AtomicPtr::from_ptr(ENVIRON.as_ptr()).load(Ordering::Relaxed)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn getenv(variable_name_pointer: *const u8) -> *const u8 {
     signature_matches_libc!(libc::getenv(variable_name_pointer.cast()).cast());
 
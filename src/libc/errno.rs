@@ -3,14 +3,17 @@ use std::{cell::Cell, fmt::Display, io::Write};
 
 use libc;
 
-use crate::signature_matches_libc;
+use crate::{
+    signature_matches_libc,
+    start::personality::{personality, Personality},
+};
 
 #[repr(transparent)]
 #[doc(alias = "errno")]
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct Errno(pub(crate) u32);
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 #[thread_local]
 #[allow(non_upper_case_globals)]
 pub static errno: Cell<Errno> = Cell::new(Errno(0));
@@ -30,12 +33,32 @@ impl Errno {
     pub const DEADLK: Self = Self(linux_raw_sys::errno::EDEADLK);
     // POSIX name; the kernel calls it EOPNOTSUPP.
     pub const NOTSUP: Self = Self(linux_raw_sys::errno::EOPNOTSUPP);
+    pub const NOENT: Self = Self(linux_raw_sys::errno::ENOENT);
+    pub const ACCES: Self = Self(linux_raw_sys::errno::EACCES);
+    pub const NOTDIR: Self = Self(linux_raw_sys::errno::ENOTDIR);
+    pub const ISDIR: Self = Self(linux_raw_sys::errno::EISDIR);
+    pub const NOEXEC: Self = Self(linux_raw_sys::errno::ENOEXEC);
+    pub const IO: Self = Self(linux_raw_sys::errno::EIO);
+    pub const NOSPC: Self = Self(linux_raw_sys::errno::ENOSPC);
+    pub const SPIPE: Self = Self(linux_raw_sys::errno::ESPIPE);
+    pub const OVERFLOW: Self = Self(linux_raw_sys::errno::EOVERFLOW);
+    pub const ILSEQ: Self = Self(linux_raw_sys::errno::EILSEQ);
+    pub const RANGE: Self = Self(linux_raw_sys::errno::ERANGE);
 
     pub fn into_raw(self) -> u32 {
         self.0
     }
 }
 
+impl From<&std::io::Error> for Errno {
+    /// Errors that never reached the kernel (e.g. a short read) have no errno; they read as `EIO`.
+    fn from(error: &std::io::Error) -> Self {
+        error
+            .raw_os_error()
+            .map_or(Errno::IO, |raw| Errno(raw as u32))
+    }
+}
+
 impl From<Errno> for c_int {
     fn from(error: Errno) -> Self {
         error.0 as Self
@@ -56,6 +79,17 @@ impl Display for Errno {
             Errno::INVAL => "Invalid argument",
             Errno::BADF => "Bad file descriptor",
             Errno::NOMEM => "Not enough space",
+            Errno::NOENT => "No such file or directory",
+            Errno::ACCES => "Permission denied",
+            Errno::NOTDIR => "Not a directory",
+            Errno::ISDIR => "Is a directory",
+            Errno::NOEXEC => "Executable file format error",
+            Errno::IO => "I/O error",
+            Errno::NOSPC => "No space left on a device",
+            Errno::SPIPE => "Invalid seek",
+            Errno::OVERFLOW => "Value too large to be stored in data type",
+            Errno::ILSEQ => "Illegal byte sequence",
+            Errno::RANGE => "Result too large",
             ref unknown_errno => {
                 return write!(f, "Unknown error: {}", Into::<u32>::into(unknown_errno))
             }
@@ -64,13 +98,13 @@ impl Display for Errno {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __errno_location() -> *mut Errno {
     signature_matches_libc!(std::mem::transmute(libc::__errno_location()));
     errno.as_ptr()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __xpg_strerror_r(errnum: Errno, buffer: *mut u8, length: usize) -> i32 {
     signature_matches_libc!(libc::strerror_r(
         std::mem::transmute(errnum),
@@ -86,3 +120,45 @@ unsafe extern "C" fn __xpg_strerror_r(errnum: Errno, buffer: *mut u8, length: us
         0
     }
 }
+
+/// One name, two ABIs: glibc binaries bind `strerror_r` to the GNU variant, which returns the message, and musl binaries to
+/// POSIX's, which returns a status (glibc's `__xpg_strerror_r`). The status comes back in the same register.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn strerror_r(errnum: Errno, buffer: *mut u8, length: usize) -> *mut u8 {
+    match personality() {
+        Personality::Glibc => gnu_strerror_r(errnum, buffer, length),
+        Personality::Musl => {
+            std::ptr::without_provenance_mut(__xpg_strerror_r(errnum, buffer, length) as usize)
+        }
+    }
+}
+
+/// The message always lands in `buffer` here, truncated to fit; glibc would hand back a static string for known errors.
+unsafe fn gnu_strerror_r(errnum: Errno, buffer: *mut u8, length: usize) -> *mut u8 {
+    if length == 0 {
+        return c"".as_ptr().cast_mut().cast();
+    }
+    let message = errnum.to_string();
+    let copied = message.len().min(length - 1);
+    std::ptr::copy_nonoverlapping(message.as_ptr(), buffer, copied);
+    *buffer.add(copied) = 0;
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[test]
+    fn gnu_strerror_r_truncates_into_the_buffer() {
+        let mut buffer = [0xffu8; 8];
+        let message = unsafe { gnu_strerror_r(Errno::NOENT, buffer.as_mut_ptr(), buffer.len()) };
+        assert_eq!(message, buffer.as_mut_ptr());
+        assert_eq!(CStr::from_bytes_until_nul(&buffer).unwrap(), c"No such");
+
+        let empty = unsafe { gnu_strerror_r(Errno::NOENT, buffer.as_mut_ptr(), 0) };
+        assert_eq!(unsafe { CStr::from_ptr(empty.cast()) }, c"");
+    }
+}
@@ -107,7 +107,7 @@ impl DirectoryStream {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn opendir(pathname: *const c_char) -> *mut DirectoryStream {
     signature_matches_libc!(std::mem::transmute(libc::opendir(pathname)));
 
@@ -142,7 +142,7 @@ unsafe extern "C" fn opendir(pathname: *const c_char) -> *mut DirectoryStream {
     stream
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn readdir(stream: *mut DirectoryStream) -> *mut DirectoryEntry {
     signature_matches_libc!(std::mem::transmute(libc::readdir(std::mem::transmute(
         stream
@@ -151,7 +151,7 @@ unsafe extern "C" fn readdir(stream: *mut DirectoryStream) -> *mut DirectoryEntr
     (*stream).next_entry()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn readdir64(stream: *mut DirectoryStream) -> *mut DirectoryEntry {
     signature_matches_libc!(std::mem::transmute(libc::readdir64(std::mem::transmute(
         stream
@@ -160,7 +160,7 @@ unsafe extern "C" fn readdir64(stream: *mut DirectoryStream) -> *mut DirectoryEn
     (*stream).next_entry()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn closedir(stream: *mut DirectoryStream) -> i32 {
     signature_matches_libc!(libc::closedir(std::mem::transmute(stream)));
 
@@ -179,7 +179,7 @@ unsafe extern "C" fn closedir(stream: *mut DirectoryStream) -> i32 {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn dirfd(stream: *const DirectoryStream) -> i32 {
     signature_matches_libc!(libc::dirfd(std::mem::transmute(stream)));
 
@@ -22,7 +22,7 @@ pub enum FCntlCommand {
     DuplicateFileDescriptorCloseOnExec = 1030,
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fcntl(
     file_descriptor: BorrowedFd<'_>,
     command: FCntlCommand,
@@ -1,4 +1,8 @@
-use std::os::fd::{AsRawFd, BorrowedFd};
+use std::{
+    ffi::c_char,
+    mem::size_of,
+    os::fd::{AsRawFd, BorrowedFd},
+};
 
 use crate::{
     libc::errno::{set_errno, Errno},
@@ -8,6 +12,7 @@ use crate::{
 
 // TODO: structure fields with proper types (e.g. enums for st_mode, bitfields for permissions)
 #[repr(C)]
+#[cfg(target_arch = "x86_64")]
 pub struct FileStatus {
     pub device: u64,
     pub inode: u64,
@@ -29,7 +34,34 @@ pub struct FileStatus {
     _reserved: [u64; 3],
 }
 
-#[cfg_attr(not(test), no_mangle)]
+/// The asm-generic `struct stat`, which packs `mode` and `hard_link_count` and narrows `block_size`.
+#[repr(C)]
+#[cfg(target_arch = "aarch64")]
+pub struct FileStatus {
+    pub device: u64,
+    pub inode: u64,
+    pub mode: u32,
+    pub hard_link_count: u32,
+    pub user_id: u32,
+    pub group_id: u32,
+    pub device_type: u64,
+    _pad0: u64,
+    pub size_in_bytes: i64,
+    pub block_size: i32,
+    _pad1: i32,
+    pub block_count: i64,
+    pub access_time_seconds: u64,
+    pub access_time_nanoseconds: u64,
+    pub modification_time_seconds: u64,
+    pub modification_time_nanoseconds: u64,
+    pub change_time_seconds: u64,
+    pub change_time_nanoseconds: u64,
+    _reserved: [u32; 2],
+}
+
+const _: () = assert!(size_of::<FileStatus>() == size_of::<libc::stat64>());
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fstat64(
     file_descriptor: BorrowedFd<'_>,
     file_status_pointer: *mut FileStatus,
@@ -53,14 +85,23 @@ unsafe extern "C" fn fstat64(
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn stat64(pathname: *const i8, file_status_pointer: *mut FileStatus) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn stat64(pathname: *const c_char, file_status_pointer: *mut FileStatus) -> i32 {
     signature_matches_libc!(libc::stat64(
         std::mem::transmute(pathname),
         std::mem::transmute(file_status_pointer),
     ));
 
+    #[cfg(target_arch = "x86_64")]
     let result = syscall!(Syscall::Stat, pathname, file_status_pointer);
+    #[cfg(target_arch = "aarch64")]
+    let result = syscall!(
+        Syscall::NewFStatAt,
+        libc::AT_FDCWD,
+        pathname,
+        file_status_pointer,
+        0
+    );
 
     if result < 0 {
         set_errno(Errno(result.abs() as u32));
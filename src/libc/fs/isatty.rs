@@ -22,7 +22,7 @@ pub fn file_descriptor_isatty(file_descriptor: BorrowedFd<'_>) -> bool {
     result == 0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn isatty(file_descriptor: BorrowedFd<'_>) -> i32 {
     signature_matches_libc!(libc::isatty(file_descriptor.as_raw_fd()));
 
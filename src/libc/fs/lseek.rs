@@ -3,7 +3,7 @@ use std::os::fd::{AsRawFd, BorrowedFd};
 
 use crate::{libc::translate_syscall_result, signature_matches_libc, syscall, syscall::Syscall};
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn lseek64(file_descriptor: BorrowedFd<'_>, offset: i64, whence: c_int) -> i64 {
     signature_matches_libc!(libc::lseek64(
         std::mem::transmute(file_descriptor),
@@ -14,3 +14,14 @@ unsafe extern "C" fn lseek64(file_descriptor: BorrowedFd<'_>, offset: i64, whenc
     let result = syscall!(Syscall::LSeek, file_descriptor.as_raw_fd(), offset, whence);
     translate_syscall_result(result) as i64
 }
+
+// LFS alias: `off_t` is 64 bits on 64-bit targets.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn lseek(file_descriptor: BorrowedFd<'_>, offset: i64, whence: c_int) -> i64 {
+    signature_matches_libc!(libc::lseek(
+        std::mem::transmute(file_descriptor),
+        offset,
+        whence
+    ));
+    lseek64(file_descriptor, offset, whence)
+}
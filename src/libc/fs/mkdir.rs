@@ -0,0 +1,13 @@
+use std::ffi::c_char;
+
+use crate::{
+    libc::{fs::open::AT_FDCWD, translate_syscall_result},
+    signature_matches_libc, syscall,
+    syscall::Syscall,
+};
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn mkdir(pathname: *const c_char, mode: libc::mode_t) -> i32 {
+    signature_matches_libc!(libc::mkdir(pathname, mode));
+    translate_syscall_result(syscall!(Syscall::MkdirAt, AT_FDCWD, pathname, mode)) as i32
+}
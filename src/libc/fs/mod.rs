@@ -1,11 +1,16 @@
 mod close;
 mod dir;
-mod fcntl;
+pub mod fcntl;
 pub mod fstat;
 pub mod isatty;
 mod lseek;
-mod open;
+mod mkdir;
+pub mod open;
 mod pread;
+mod pwrite;
 mod read;
+mod readlink;
+mod rename;
 mod statx;
+mod unlink;
 mod write;
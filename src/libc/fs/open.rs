@@ -1,11 +1,11 @@
-use std::ffi::VaList;
+use std::ffi::{c_char, VaList};
 
 use arbitrary_int::{u12, u3};
 use bitbybit::{bitenum, bitfield};
 
 use crate::{libc::translate_syscall_result, signature_matches_libc, syscall, syscall::Syscall};
 
-const AT_FDCWD: isize = -100;
+pub(crate) const AT_FDCWD: isize = -100;
 
 #[bitfield(u3)]
 struct UnixPermissionClass {
@@ -52,7 +52,7 @@ struct UnixFileMode {
     file_type: Option<UnixFileType>,
 }
 
-unsafe fn open_file(pathname: *const i8, flags: OFlags, mut args: VaList) -> i32 {
+unsafe fn open_file(pathname: *const c_char, flags: OFlags, mut args: VaList) -> i32 {
     let mode = if flags.create() || flags.create_unnamed_temporary_file() {
         UnixFileMode::new_with_raw_value(args.next_arg::<u32>())
             .permissions()
@@ -66,32 +66,32 @@ unsafe fn open_file(pathname: *const i8, flags: OFlags, mut args: VaList) -> i32
     translate_syscall_result(result) as i32
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn open64(pathname: *const i8, flags: OFlags, args: VaList) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn open64(pathname: *const c_char, flags: OFlags, args: ...) -> i32 {
     signature_matches_libc!(libc::open64(
-        std::mem::transmute(pathname),
-        std::mem::transmute(flags),
-        args,
+        pathname,
+        std::mem::transmute::<OFlags, i32>(flags),
+        args
     ));
     open_file(pathname, flags, args)
 }
 
 // LFS alias: `open` is `open64` on x86_64, where O_LARGEFILE is a no-op.
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn open(pathname: *const i8, flags: OFlags, args: VaList) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn open(pathname: *const c_char, flags: OFlags, args: ...) -> i32 {
     signature_matches_libc!(libc::open(
-        std::mem::transmute(pathname),
-        std::mem::transmute(flags),
-        args,
+        pathname,
+        std::mem::transmute::<OFlags, i32>(flags),
+        args
     ));
     open_file(pathname, flags, args)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_RDONLY: AccessMode = AccessMode::ReadOnly;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_WRONLY: AccessMode = AccessMode::WriteOnly;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_RDWR: AccessMode = AccessMode::ReadAndWrite;
 
 #[bitenum(u2)]
@@ -102,57 +102,63 @@ pub enum AccessMode {
 }
 
 // TODO: clean up these value definitions...
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_CREAT: u32 = 64;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_EXCL: u32 = 128;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_NOCTTY: u32 = 256;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_TRUNC: u32 = 512;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_APPEND: u32 = 1024;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_NONBLOCK: u32 = 2048;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_DSYNC: u32 = 4096;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static FASYNC: u32 = 8192;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_DIRECT: u32 = 16384;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_LARGEFILE: u32 = 32768;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_DIRECTORY: u32 = 1 << 16;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_NOFOLLOW: u32 = 131072;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_NOATIME: u32 = 262144;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_CLOEXEC: u32 = 524288;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static __O_SYNC: u32 = 1048576;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_SYNC: u32 = 1052672;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_PATH: u32 = 2097152;
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_TMPFILE: u32 = 1 << 22 | O_DIRECTORY; // O_TMPFILE should always be passed with O_DIRECTORY
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub static O_NDELAY: u32 = 2048;
 
 #[bitfield(u32)]
 pub struct OFlags {
     #[bits(0..=1, rw)]
     access_mode: Option<AccessMode>,
-    #[bit(7, rw)]
+    #[bit(6, rw)]
     create: bool,
-    #[bit(8, rw)]
+    #[bit(7, rw)]
     require_create: bool,
-    #[bit(9, rw)]
+    #[bit(8, rw)]
     do_not_make_controlling_terminal: bool,
-    #[bit(18, rw)]
+    #[bit(9, rw)]
+    truncate: bool,
+    #[bit(10, rw)]
+    append: bool,
+    #[bit(17, rw)]
     do_not_follow_symbolic_link: bool,
+    #[bit(19, rw)]
+    close_on_exec: bool,
     #[bit(22, rw)]
     create_unnamed_temporary_file: bool,
 }
@@ -5,7 +5,7 @@ use std::{
 
 use crate::{signature_matches_libc, syscall, syscall::Syscall};
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pread64(
     file_descriptor: BorrowedFd<'_>,
     buffer_pointer: *mut c_void,
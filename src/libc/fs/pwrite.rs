@@ -0,0 +1,29 @@
+use std::{
+    ffi::c_void,
+    os::fd::{AsRawFd, BorrowedFd},
+};
+
+use crate::{signature_matches_libc, syscall, syscall::Syscall};
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn pwrite64(
+    file_descriptor: BorrowedFd<'_>,
+    buffer_pointer: *const c_void,
+    buffer_length_in_bytes: usize,
+    offset: i64,
+) -> isize {
+    signature_matches_libc!(libc::pwrite64(
+        std::mem::transmute(file_descriptor),
+        buffer_pointer.cast(),
+        buffer_length_in_bytes,
+        offset
+    ));
+
+    syscall!(
+        Syscall::PWrite64,
+        file_descriptor.as_raw_fd(),
+        buffer_pointer,
+        buffer_length_in_bytes,
+        offset
+    )
+}
@@ -0,0 +1,37 @@
+use std::ffi::c_char;
+
+use crate::{
+    libc::{fs::open::AT_FDCWD, translate_syscall_result},
+    signature_matches_libc, syscall,
+    syscall::Syscall,
+};
+
+/// Like the syscall, never NUL-terminates `buffer`; the return value is the only length.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn readlink(pathname: *const c_char, buffer: *mut c_char, size: usize) -> isize {
+    signature_matches_libc!(libc::readlink(pathname, buffer, size));
+    translate_syscall_result(syscall!(
+        Syscall::ReadLinkAt,
+        AT_FDCWD,
+        pathname,
+        buffer,
+        size
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readlink_reports_the_target_length() {
+        let mut buffer = [0 as c_char; 256];
+        let length = unsafe { readlink(c"/proc/self/exe".as_ptr(), buffer.as_mut_ptr(), 256) };
+        let expected = std::fs::read_link("/proc/self/exe").unwrap();
+        assert_eq!(length as usize, expected.as_os_str().len());
+        assert_eq!(
+            unsafe { readlink(c"/nonexistent".as_ptr(), buffer.as_mut_ptr(), 256) },
+            -1
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use std::ffi::c_char;
+
+use crate::{
+    libc::{fs::open::AT_FDCWD, translate_syscall_result},
+    signature_matches_libc, syscall,
+    syscall::Syscall,
+};
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn rename(old_path: *const c_char, new_path: *const c_char) -> i32 {
+    signature_matches_libc!(libc::rename(old_path, new_path));
+    translate_syscall_result(syscall!(
+        Syscall::RenameAt,
+        AT_FDCWD,
+        old_path,
+        AT_FDCWD,
+        new_path
+    )) as i32
+}
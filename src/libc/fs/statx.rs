@@ -5,7 +5,7 @@ use core::{
 
 use crate::{libc::translate_syscall_result, signature_matches_libc, syscall, syscall::Syscall};
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn statx(
     directory_fd: c_int,
     pathname: *const c_char,
@@ -0,0 +1,24 @@
+use std::ffi::c_char;
+
+use crate::{
+    libc::{fs::open::AT_FDCWD, translate_syscall_result},
+    signature_matches_libc, syscall,
+    syscall::Syscall,
+};
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn unlink(pathname: *const c_char) -> i32 {
+    signature_matches_libc!(libc::unlink(pathname));
+    translate_syscall_result(syscall!(Syscall::UnlinkAt, AT_FDCWD, pathname, 0)) as i32
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn rmdir(pathname: *const c_char) -> i32 {
+    signature_matches_libc!(libc::rmdir(pathname));
+    translate_syscall_result(syscall!(
+        Syscall::UnlinkAt,
+        AT_FDCWD,
+        pathname,
+        libc::AT_REMOVEDIR
+    )) as i32
+}
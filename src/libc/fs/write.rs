@@ -6,7 +6,7 @@ pub const STD_IN: i32 = 0;
 pub const STD_OUT: i32 = 1;
 pub const STD_ERR: i32 = 2;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn write(
     file_descriptor: i32,
     buffer_pointer: *const c_void,
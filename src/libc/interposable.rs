@@ -1,4 +1,7 @@
-use core::sync::atomic::{AtomicPtr, Ordering};
+use core::{
+    ffi::c_void,
+    sync::atomic::{AtomicPtr, Ordering},
+};
 
 use linkme::distributed_slice;
 
@@ -29,6 +32,9 @@ impl<T> InterposableCell<T> {
 
 pub trait Bindable: Sync {
     fn bind(&self, graph: &ObjectDataGraph);
+
+    /// Where the slot currently points, if `name` is one of this cell's aliases.
+    fn bound_address(&self, name: &str) -> Option<*const c_void>;
 }
 
 impl<T> Bindable for InterposableCell<T> {
@@ -43,6 +49,12 @@ impl<T> Bindable for InterposableCell<T> {
             self.rebind(address.cast_mut().cast());
         }
     }
+
+    fn bound_address(&self, name: &str) -> Option<*const c_void> {
+        self.exported_names
+            .contains(&name)
+            .then(|| self.as_ptr().cast_const().cast())
+    }
 }
 
 #[distributed_slice]
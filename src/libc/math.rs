@@ -13,14 +13,14 @@ use crate::libc::interposable::{Bindable, InterposableCell, INTERPOSABLE_CELLS};
 macro_rules! forward_to_libm {
     () => {};
     ($name:ident($($argument:ident: $argument_type:ty),*) -> $return_type:ty; $($rest:tt)*) => {
-        #[cfg_attr(not(test), no_mangle)]
+        #[cfg_attr(not(any(test, fuzzing)), no_mangle)]
         unsafe extern "C" fn $name($($argument: $argument_type),*) -> $return_type {
             libm::$name($($argument),*)
         }
         forward_to_libm! { $($rest)* }
     };
     ($name:ident($($argument:ident: $argument_type:ty),*) -> $return_type:ty { *$out:ident: $out_type:ty } $($rest:tt)*) => {
-        #[cfg_attr(not(test), no_mangle)]
+        #[cfg_attr(not(any(test, fuzzing)), no_mangle)]
         unsafe extern "C" fn $name($($argument: $argument_type,)* $out: *mut $out_type) -> $return_type {
             let (result, through_pointer) = libm::$name($($argument),*);
             *$out = through_pointer;
@@ -29,7 +29,7 @@ macro_rules! forward_to_libm {
         forward_to_libm! { $($rest)* }
     };
     ($name:ident($($argument:ident: $argument_type:ty),*) { $(*$out:ident: $out_type:ty),* } $($rest:tt)*) => {
-        #[cfg_attr(not(test), no_mangle)]
+        #[cfg_attr(not(any(test, fuzzing)), no_mangle)]
         unsafe extern "C" fn $name($($argument: $argument_type,)* $($out: *mut $out_type),*) {
             ($(*$out),*) = libm::$name($($argument),*);
         }
@@ -165,7 +165,7 @@ forward_to_libm! {
 }
 
 // POSIX: lgamma reports Γ's sign through this global.
-#[cfg_attr(not(test), export_name = "__signgam")]
+#[cfg_attr(not(any(test, fuzzing)), export_name = "__signgam")]
 #[allow(non_upper_case_globals)]
 static signgam: AtomicI32 = AtomicI32::new(0);
 
@@ -177,14 +177,14 @@ static SIGNGAM_CELL: &'static dyn Bindable = &SIGNGAM;
 
 // Manifest exceptions: the crate names differ (lgamma → lgamma_r) and the sign must land in signgam atomically.
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn lgamma(value: f64) -> f64 {
     let (result, sign) = libm::lgamma_r(value);
     AtomicI32::from_ptr(SIGNGAM.as_ptr()).store(sign, Ordering::Relaxed);
     result
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn lgammaf(value: f32) -> f32 {
     let (result, sign) = libm::lgammaf_r(value);
     AtomicI32::from_ptr(SIGNGAM.as_ptr()).store(sign, Ordering::Relaxed);
@@ -0,0 +1,37 @@
+use std::ffi::{c_char, c_int, c_uint};
+
+use crate::{libc::translate_syscall_result, signature_matches_libc, syscall, syscall::Syscall};
+
+pub const MFD_CLOEXEC: c_uint = 0x0001;
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+pub unsafe extern "C" fn memfd_create(name: *const c_char, flags: c_uint) -> c_int {
+    signature_matches_libc!(libc::memfd_create(name, flags));
+
+    let result = syscall!(Syscall::MemfdCreate, name, flags);
+    translate_syscall_result(result) as c_int
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memfd_create_returns_a_writable_anonymous_file() {
+        let descriptor = unsafe { memfd_create(c"miros-test".as_ptr(), MFD_CLOEXEC) };
+        assert_ne!(descriptor, -1);
+
+        let bytes = b"in memory";
+        assert_eq!(
+            unsafe { libc::write(descriptor, bytes.as_ptr().cast(), bytes.len()) },
+            bytes.len() as isize
+        );
+        let mut read_back = [0u8; 9];
+        assert_eq!(
+            unsafe { libc::pread(descriptor, read_back.as_mut_ptr().cast(), 9, 0) },
+            9
+        );
+        assert_eq!(&read_back, bytes);
+        assert_eq!(unsafe { libc::close(descriptor) }, 0);
+    }
+}
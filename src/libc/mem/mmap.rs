@@ -8,7 +8,7 @@ use crate::{
 };
 
 // TODO: add error handling
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn mmap(
     pointer: *mut u8,
     size: usize,
@@ -40,7 +40,7 @@ pub unsafe extern "C" fn mmap(
 }
 
 // LFS alias: on x86_64 the offset is already 64-bit, so this is `mmap` verbatim.
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn mmap64(
     pointer: *mut u8,
     size: usize,
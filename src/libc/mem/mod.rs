@@ -8,6 +8,9 @@ use crate::{io_macros::syscall_debug_assert, signature_matches_libc, syscall, sy
 mod mmap;
 pub use mmap::mmap;
 
+mod memfd;
+pub use memfd::{memfd_create, MFD_CLOEXEC};
+
 mod mremap;
 pub use mremap::{mremap, MreMapFlags};
 
@@ -48,7 +51,7 @@ pub struct MapFlags {
 }
 
 // TODO: add error handling
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe fn munmap(pointer: *mut u8, size: usize) -> i32 {
     signature_matches_libc!(libc::munmap(pointer.cast(), size));
 
@@ -57,7 +60,7 @@ pub unsafe fn munmap(pointer: *mut u8, size: usize) -> i32 {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn mprotect(
     pointer: *mut u8,
     size: usize,
@@ -79,7 +82,7 @@ pub unsafe extern "C" fn mprotect(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memcpy(
     destination: *mut u8,
     source: *const u8,
@@ -91,6 +94,12 @@ unsafe extern "C" fn memcpy(
         std::mem::transmute(number_of_bytes_to_copy)
     )));
 
+    copy_forward(destination, source, number_of_bytes_to_copy);
+    destination
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn copy_forward(destination: *mut u8, source: *const u8, number_of_bytes_to_copy: usize) {
     asm!(
         "rep movsb",
         inout("rdi") destination => _,
@@ -98,10 +107,61 @@ unsafe extern "C" fn memcpy(
         inout("rcx") number_of_bytes_to_copy => _,
         options(nostack, preserves_flags)
     );
-    destination
 }
 
-#[cfg_attr(not(test), no_mangle)]
+// A byte loop in asm, since the compiler would turn the same loop in Rust back into a call to `memcpy`.
+#[cfg(target_arch = "aarch64")]
+unsafe fn copy_forward(destination: *mut u8, source: *const u8, number_of_bytes_to_copy: usize) {
+    asm!(
+        "cbz {count}, 2f",
+        "1:",
+        "ldrb {byte:w}, [{source}], #1",
+        "strb {byte:w}, [{destination}], #1",
+        "subs {count}, {count}, #1",
+        "b.ne 1b",
+        "2:",
+        destination = inout(reg) destination => _,
+        source = inout(reg) source => _,
+        count = inout(reg) number_of_bytes_to_copy => _,
+        byte = out(reg) _,
+        options(nostack)
+    );
+}
+
+/// Copies from the last byte down, for a `destination` that overlaps the end of `source`.
+#[cfg(target_arch = "x86_64")]
+unsafe fn copy_backward(destination: *mut u8, source: *const u8, number_of_bytes_to_copy: usize) {
+    asm!(
+        "std",
+        "rep movsb",
+        "cld",
+        inout("rdi") destination.add(number_of_bytes_to_copy - 1) => _,
+        inout("rsi") source.add(number_of_bytes_to_copy - 1) => _,
+        inout("rcx") number_of_bytes_to_copy => _,
+        options(nostack)
+    );
+}
+
+/// Copies from the last byte down, for a `destination` that overlaps the end of `source`.
+#[cfg(target_arch = "aarch64")]
+unsafe fn copy_backward(destination: *mut u8, source: *const u8, number_of_bytes_to_copy: usize) {
+    asm!(
+        "cbz {count}, 2f",
+        "1:",
+        "ldrb {byte:w}, [{source}, #-1]!",
+        "strb {byte:w}, [{destination}, #-1]!",
+        "subs {count}, {count}, #1",
+        "b.ne 1b",
+        "2:",
+        destination = inout(reg) destination.add(number_of_bytes_to_copy) => _,
+        source = inout(reg) source.add(number_of_bytes_to_copy) => _,
+        count = inout(reg) number_of_bytes_to_copy => _,
+        byte = out(reg) _,
+        options(nostack)
+    );
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memset(
     destination: *mut u8,
     single_byte_thats_32_bits_for_some_fucking_reason: u32, // I hate this stupid fucking API... Like why?
@@ -115,6 +175,7 @@ unsafe extern "C" fn memset(
 
     // SAFETY: Yes, I know...
     let byte = single_byte_thats_32_bits_for_some_fucking_reason as u8;
+    #[cfg(target_arch = "x86_64")]
     asm!(
         "rep stosb",
         inout("rdi") destination => _,
@@ -123,10 +184,23 @@ unsafe extern "C" fn memset(
         options(nostack, preserves_flags)
 
     );
+    #[cfg(target_arch = "aarch64")]
+    asm!(
+        "cbz {count}, 2f",
+        "1:",
+        "strb {byte:w}, [{destination}], #1",
+        "subs {count}, {count}, #1",
+        "b.ne 1b",
+        "2:",
+        destination = inout(reg) destination => _,
+        byte = in(reg) byte as u32,
+        count = inout(reg) number_of_bytes_to_set => _,
+        options(nostack)
+    );
     destination
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memmove(
     destination: *mut u8,
     source: *const u8,
@@ -141,28 +215,14 @@ unsafe extern "C" fn memmove(
     let overlapping = (destination > source as *mut u8)
         && (destination < source.byte_add(number_of_bytes_to_copy) as *mut u8);
     if overlapping {
-        asm!(
-            "std",
-            "rep movsb",
-            "cld",
-            inout("rdi") destination.add(number_of_bytes_to_copy - 1) => _,
-            inout("rsi") source.add(number_of_bytes_to_copy - 1) => _,
-            inout("rcx") number_of_bytes_to_copy => _,
-            options(nostack)
-        );
+        copy_backward(destination, source, number_of_bytes_to_copy);
     } else {
-        asm!(
-            "rep movsb",
-            inout("rdi") destination => _,
-            inout("rsi") source => _,
-            inout("rcx") number_of_bytes_to_copy => _,
-            options(nostack, preserves_flags)
-        );
+        copy_forward(destination, source, number_of_bytes_to_copy);
     }
     destination
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn bcmp(
     left_pointer: *const u8,
     right_pointer: *const u8,
@@ -171,7 +231,7 @@ unsafe extern "C" fn bcmp(
     memcmp(left_pointer, right_pointer, length_of_comparison)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memcmp(
     left_pointer: *const u8,
     right_pointer: *const u8,
@@ -184,6 +244,7 @@ unsafe extern "C" fn memcmp(
     ));
 
     let ordering: i32;
+    #[cfg(target_arch = "x86_64")]
     asm!(
         // `seta` writes only the low byte; the `sbb` is full-width, so the register must start zeroed.
         "xor {ordering:e}, {ordering:e}",
@@ -197,5 +258,30 @@ unsafe extern "C" fn memcmp(
         ordering = out(reg) ordering,
         options(nostack)
     );
+    #[cfg(target_arch = "aarch64")]
+    asm!(
+        "mov {ordering:w}, #0",
+        "cbz {count}, 3f",
+        "1:",
+        "ldrb {left_byte:w}, [{left}], #1",
+        "ldrb {right_byte:w}, [{right}], #1",
+        "cmp {left_byte:w}, {right_byte:w}",
+        "b.ne 2f",
+        "subs {count}, {count}, #1",
+        "b.ne 1b",
+        "b 3f",
+        // 1 if left's byte is higher, -1 if lower.
+        "2:",
+        "cset {ordering:w}, hi",
+        "csinv {ordering:w}, {ordering:w}, wzr, hs",
+        "3:",
+        left = inout(reg) left_pointer => _,
+        right = inout(reg) right_pointer => _,
+        count = inout(reg) length_of_comparison => _,
+        left_byte = out(reg) _,
+        right_byte = out(reg) _,
+        ordering = out(reg) ordering,
+        options(nostack)
+    );
     ordering
 }
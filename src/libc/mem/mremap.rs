@@ -12,7 +12,7 @@ pub struct MreMapFlags {
     dont_unmap: bool,
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn mremap(
     old_address: *mut u8,
     old_size: usize,
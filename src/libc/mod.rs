@@ -3,6 +3,7 @@
 mod alloc;
 mod auxv;
 mod builtins;
+mod dlfcn;
 pub mod environ;
 // TODO: Add error handling for these things:
 mod fs;
@@ -17,8 +18,9 @@ mod str;
 mod syscall;
 mod threads;
 mod time;
+pub mod vdso;
 
-mod errno;
+pub mod errno;
 
 /// The kernel reports errors as -errno; the C ABI reports them through the thread-local errno.
 pub(crate) fn translate_syscall_result(result: isize) -> isize {
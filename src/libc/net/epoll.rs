@@ -13,7 +13,23 @@ net_syscall_pass_through! {
         epoll_create1(0)
     }
     fn epoll_ctl(epoll_descriptor: c_int, operation: c_int, target: c_int, event: *mut linux_raw_sys::general::epoll_event) -> c_int = Syscall::EpollCtl;
+    #[cfg(target_arch = "x86_64")]
     fn epoll_wait(epoll_descriptor: c_int, events: *mut linux_raw_sys::general::epoll_event, max_events: c_int, timeout_milliseconds: c_int) -> c_int = Syscall::EpollWait;
+    // arm64 only has `epoll_pwait`; a null mask leaves the signal mask alone.
+    #[cfg(target_arch = "aarch64")]
+    fn epoll_wait(epoll_descriptor: c_int, events: *mut linux_raw_sys::general::epoll_event, max_events: c_int, timeout_milliseconds: c_int) -> c_int {
+        crate::signature_matches_libc!(libc::epoll_wait(epoll_descriptor, events.cast(), max_events, timeout_milliseconds));
+        let result = crate::syscall!(
+            crate::syscall::Syscall::EpollPWait,
+            epoll_descriptor,
+            events,
+            max_events,
+            timeout_milliseconds,
+            std::ptr::null::<u8>(),
+            0
+        );
+        crate::libc::translate_syscall_result(result) as c_int
+    }
 }
 
 #[cfg(test)]
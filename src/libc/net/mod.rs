@@ -26,8 +26,9 @@ mod transfer;
 // is written out in full. The `Syscall::X` form maps a negative result to errno + -1.
 macro_rules! net_syscall_pass_through {
     () => {};
-    (fn $name:ident($($argument:ident: $argument_type:ty),* $(,)?) -> $return_type:ty = Syscall::$syscall:ident; $($rest:tt)*) => {
-        #[cfg_attr(not(test), no_mangle)]
+    ($(#[$attribute:meta])* fn $name:ident($($argument:ident: $argument_type:ty),* $(,)?) -> $return_type:ty = Syscall::$syscall:ident; $($rest:tt)*) => {
+        $(#[$attribute])*
+        #[cfg_attr(not(any(test, fuzzing)), no_mangle)]
         pub(crate) unsafe extern "C" fn $name($($argument: $argument_type),*) -> $return_type {
             $crate::signature_matches_libc!(libc::$name($(std::mem::transmute($argument)),*));
 
@@ -36,8 +37,9 @@ macro_rules! net_syscall_pass_through {
         }
         net_syscall_pass_through! { $($rest)* }
     };
-    (fn $name:ident($($argument:ident: $argument_type:ty),* $(,)?) -> $return_type:ty { $($body:tt)* } $($rest:tt)*) => {
-        #[cfg_attr(not(test), no_mangle)]
+    ($(#[$attribute:meta])* fn $name:ident($($argument:ident: $argument_type:ty),* $(,)?) -> $return_type:ty { $($body:tt)* } $($rest:tt)*) => {
+        $(#[$attribute])*
+        #[cfg_attr(not(any(test, fuzzing)), no_mangle)]
         pub(crate) unsafe extern "C" fn $name($($argument: $argument_type),*) -> $return_type {
             $($body)*
         }
@@ -5,8 +5,36 @@ use crate::libc::net::net_syscall_pass_through;
 #[allow(non_camel_case_types)]
 pub(crate) type nfds_t = u64;
 
+// The kernel's sigset is 8 bytes, not glibc's 128.
+#[cfg(target_arch = "aarch64")]
+const KERNEL_SIGSET_SIZE: usize = 8;
+
 net_syscall_pass_through! {
+    #[cfg(target_arch = "x86_64")]
     fn poll(fds: *mut linux_raw_sys::general::pollfd, count: nfds_t, timeout_milliseconds: c_int) -> c_int = Syscall::Poll;
+    // arm64 only has `ppoll`, which takes a timespec and no timeout as null.
+    #[cfg(target_arch = "aarch64")]
+    fn poll(fds: *mut linux_raw_sys::general::pollfd, count: nfds_t, timeout_milliseconds: c_int) -> c_int {
+        crate::signature_matches_libc!(libc::poll(fds.cast(), count, timeout_milliseconds));
+        let timeout = linux_raw_sys::general::__kernel_timespec {
+            tv_sec: (timeout_milliseconds / 1000).into(),
+            tv_nsec: (timeout_milliseconds % 1000 * 1_000_000).into(),
+        };
+        let timeout_pointer = if timeout_milliseconds < 0 {
+            std::ptr::null()
+        } else {
+            &timeout as *const linux_raw_sys::general::__kernel_timespec
+        };
+        let result = crate::syscall!(
+            crate::syscall::Syscall::PPoll,
+            fds,
+            count,
+            timeout_pointer,
+            std::ptr::null::<u8>(),
+            KERNEL_SIGSET_SIZE
+        );
+        crate::libc::translate_syscall_result(result) as c_int
+    }
 }
 
 #[cfg(test)]
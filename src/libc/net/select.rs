@@ -4,7 +4,36 @@ use crate::libc::net::net_syscall_pass_through;
 
 // fd_set is a 1024-bit kernel-mutated bitmap; glibc's timeval layout matches the kernel's.
 net_syscall_pass_through! {
+    #[cfg(target_arch = "x86_64")]
     fn select(count: c_int, read_fds: *mut c_void, write_fds: *mut c_void, except_fds: *mut c_void, timeout: *mut c_void) -> c_int = Syscall::Select;
+    // arm64 only has `pselect6`, which takes (and, like `select`, updates) a timespec.
+    #[cfg(target_arch = "aarch64")]
+    fn select(count: c_int, read_fds: *mut c_void, write_fds: *mut c_void, except_fds: *mut c_void, timeout: *mut c_void) -> c_int {
+        crate::signature_matches_libc!(libc::select(count, read_fds.cast(), write_fds.cast(), except_fds.cast(), timeout.cast()));
+        let timeval = timeout.cast::<libc::timeval>();
+        let mut remaining = linux_raw_sys::general::__kernel_timespec { tv_sec: 0, tv_nsec: 0 };
+        let remaining_pointer = if timeval.is_null() {
+            std::ptr::null_mut()
+        } else {
+            remaining.tv_sec = (*timeval).tv_sec;
+            remaining.tv_nsec = (*timeval).tv_usec * 1000;
+            &mut remaining as *mut linux_raw_sys::general::__kernel_timespec
+        };
+        let result = crate::syscall!(
+            crate::syscall::Syscall::PSelect6,
+            count,
+            read_fds,
+            write_fds,
+            except_fds,
+            remaining_pointer,
+            std::ptr::null::<u8>()
+        );
+        if !timeval.is_null() {
+            (*timeval).tv_sec = remaining.tv_sec;
+            (*timeval).tv_usec = remaining.tv_nsec / 1000;
+        }
+        crate::libc::translate_syscall_result(result) as c_int
+    }
 }
 
 #[cfg(test)]
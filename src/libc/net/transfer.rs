@@ -1,6 +1,23 @@
-use std::ffi::{c_int, c_void};
+use std::{
+    ffi::{c_int, c_void},
+    ptr,
+};
 
-use crate::libc::net::{net_syscall_pass_through, sockaddr, socklen_t};
+use linux_raw_sys::net::{cmsghdr, msghdr};
+
+use crate::{
+    libc::{
+        errno::{set_errno, Errno},
+        net::{net_syscall_pass_through, sockaddr, socklen_t},
+        translate_syscall_result,
+    },
+    start::personality::{personality, Personality},
+    syscall,
+    syscall::Syscall,
+};
+
+/// musl's `sendmsg` copies control data through a buffer this size, failing larger messages with `ENOMEM`.
+const MUSL_CONTROL_LIMIT: usize = 1024;
 
 // send is sendto without a destination; recv is recvfrom without a source address.
 net_syscall_pass_through! {
@@ -12,11 +29,72 @@ net_syscall_pass_through! {
     fn recv(socket: c_int, buffer: *mut c_void, length: usize, flags: c_int) -> isize {
         recvfrom(socket, buffer, length, flags, std::ptr::null_mut(), std::ptr::null_mut())
     }
-    fn sendmsg(socket: c_int, message: *const linux_raw_sys::net::msghdr, flags: c_int) -> isize = Syscall::SendMsg;
-    fn recvmsg(socket: c_int, message: *mut linux_raw_sys::net::msghdr, flags: c_int) -> isize = Syscall::RecvMsg;
+    fn sendmsg(socket: c_int, message: *const msghdr, flags: c_int) -> isize {
+        match personality() {
+            Personality::Musl if !message.is_null() => musl_sendmsg(socket, message, flags),
+            _ => translate_syscall_result(syscall!(Syscall::SendMsg, socket, message, flags)),
+        }
+    }
+    fn recvmsg(socket: c_int, message: *mut msghdr, flags: c_int) -> isize {
+        match personality() {
+            Personality::Musl if !message.is_null() => musl_recvmsg(socket, message, flags),
+            _ => translate_syscall_result(syscall!(Syscall::RecvMsg, socket, message, flags)),
+        }
+    }
     fn shutdown(socket: c_int, how: c_int) -> c_int = Syscall::Shutdown;
 }
 
+// musl declares `msghdr`'s and `cmsghdr`'s lengths as 32-bit fields beside padding a program may leave unset, where the
+// kernel reads a `size_t`. As musl's own wrappers do, these clear the padding in copies.
+
+unsafe fn musl_sendmsg(socket: c_int, message: *const msghdr, flags: c_int) -> isize {
+    let mut header = without_musl_padding(*message);
+    let mut control = [0usize; MUSL_CONTROL_LIMIT / size_of::<usize>()];
+    if header.msg_controllen > 0 {
+        if header.msg_controllen > MUSL_CONTROL_LIMIT {
+            set_errno(Errno::NOMEM);
+            return -1;
+        }
+        ptr::copy_nonoverlapping(
+            header.msg_control.cast::<u8>(),
+            control.as_mut_ptr().cast::<u8>(),
+            header.msg_controllen,
+        );
+        header.msg_control = control.as_mut_ptr().cast();
+        clear_control_padding(&header);
+    }
+    translate_syscall_result(syscall!(Syscall::SendMsg, socket, &raw const header, flags))
+}
+
+/// The kernel's updates (`msg_namelen`, `msg_controllen`, `msg_flags`) land in the copy, which is written back whole.
+unsafe fn musl_recvmsg(socket: c_int, message: *mut msghdr, flags: c_int) -> isize {
+    let mut header = without_musl_padding(*message);
+    let result =
+        translate_syscall_result(syscall!(Syscall::RecvMsg, socket, &raw mut header, flags));
+    *message = header;
+    result
+}
+
+fn without_musl_padding(mut header: msghdr) -> msghdr {
+    header.msg_iovlen &= u32::MAX as usize;
+    header.msg_controllen &= u32::MAX as usize;
+    header
+}
+
+/// Walks the control messages as `CMSG_NXTHDR` does, clearing the padding above each `cmsg_len`.
+unsafe fn clear_control_padding(header: &msghdr) {
+    let mut offset = 0;
+    while offset + size_of::<cmsghdr>() <= header.msg_controllen {
+        let control_message = header.msg_control.byte_add(offset).cast::<cmsghdr>();
+        (*control_message).cmsg_len &= u32::MAX as usize;
+        let length = (*control_message).cmsg_len;
+        if length < size_of::<cmsghdr>() {
+            break;
+        }
+        offset += length.next_multiple_of(size_of::<usize>());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +186,63 @@ mod tests {
         assert_eq!(unsafe { libc::close(pair[0]) }, 0);
         assert_eq!(unsafe { libc::close(pair[1]) }, 0);
     }
+
+    #[test]
+    fn musl_sendmsg_recvmsg_ignore_length_padding() {
+        let pair = stream_pair();
+        let garbage = 0xdead_beef_usize << 32;
+
+        let mut payload = *b"fd";
+        let mut iov = linux_raw_sys::general::iovec {
+            iov_base: payload.as_mut_ptr().cast(),
+            iov_len: payload.len() as _,
+        };
+        // One SCM_RIGHTS message carrying a descriptor, its length padded the way a musl program might leave it.
+        let mut control = [0usize; 3];
+        unsafe {
+            let control_message = control.as_mut_ptr().cast::<cmsghdr>();
+            (*control_message).cmsg_len = (size_of::<cmsghdr>() + size_of::<c_int>()) | garbage;
+            (*control_message).cmsg_level = libc::SOL_SOCKET;
+            (*control_message).cmsg_type = libc::SCM_RIGHTS;
+            *control_message.add(1).cast::<c_int>() = pair[0];
+        }
+        let message = msghdr {
+            msg_name: ptr::null_mut(),
+            msg_namelen: 0,
+            msg_iov: (&raw mut iov).cast(),
+            msg_iovlen: 1 | garbage,
+            msg_control: control.as_mut_ptr().cast(),
+            msg_controllen: size_of_val(&control) | garbage,
+            msg_flags: 0,
+        };
+        assert_eq!(unsafe { musl_sendmsg(pair[0], &message, 0) }, 2);
+
+        let mut reply = [0u8; 2];
+        let mut iov = linux_raw_sys::general::iovec {
+            iov_base: reply.as_mut_ptr().cast(),
+            iov_len: reply.len() as _,
+        };
+        let mut received_control = [0usize; 3];
+        let mut message = msghdr {
+            msg_iov: (&raw mut iov).cast(),
+            msg_control: received_control.as_mut_ptr().cast(),
+            msg_controllen: size_of_val(&received_control) | garbage,
+            ..message
+        };
+        assert_eq!(unsafe { musl_recvmsg(pair[1], &mut message, 0) }, 2);
+        assert_eq!(&reply, b"fd");
+        assert_eq!(message.msg_iovlen, 1);
+        let received = unsafe {
+            *received_control
+                .as_ptr()
+                .cast::<cmsghdr>()
+                .add(1)
+                .cast::<c_int>()
+        };
+        assert!(received > pair[1]);
+
+        for descriptor in [pair[0], pair[1], received] {
+            assert_eq!(unsafe { libc::close(descriptor) }, 0);
+        }
+    }
 }
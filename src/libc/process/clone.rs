@@ -54,6 +54,7 @@ pub unsafe fn clone3(
     entry_argument: *mut c_void,
 ) -> isize {
     let result: isize;
+    #[cfg(target_arch = "x86_64")]
     asm!(
         "syscall",
         "test eax, eax",
@@ -76,11 +77,33 @@ pub unsafe fn clone3(
         out("r11") _,
         options(nostack),
     );
+    #[cfg(target_arch = "aarch64")]
+    asm!(
+        "svc 0",
+        "cbnz x0, 2f",
+
+        // child: call entry_function(entry_argument) directly, with no frame or return address to unwind into
+        "mov x0, {entry_argument}",
+        "mov x29, xzr",
+        "mov x30, xzr",
+        "blr {entry_function}",
+        "brk #0",
+
+        // parent: result already in x0
+        "2:",
+        entry_function = in(reg) entry_function,
+        entry_argument = in(reg) entry_argument,
+        in("x8") Syscall::Clone3 as usize,
+        inlateout("x0") args => result,
+        in("x1") size_of::<Clone3Args>(),
+        out("x30") _,
+        options(nostack),
+    );
 
     result
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn clone(
     entry_function: extern "C" fn(*mut c_void) -> i32,
     child_stack: *mut c_void,
@@ -105,6 +128,7 @@ pub unsafe extern "C" fn clone(
     let child_tid_pointer: *mut ThreadID = varargs.next_arg();
 
     let result: isize;
+    #[cfg(target_arch = "x86_64")]
     asm!(
         "syscall",
         "test eax, eax",
@@ -132,6 +156,33 @@ pub unsafe extern "C" fn clone(
         out("r11") _,
         options(nostack),
     );
+    // arm64's clone takes the TLS before the child TID, the reverse of x86-64's.
+    #[cfg(target_arch = "aarch64")]
+    asm!(
+        "svc 0",
+        "cbnz x0, 2f",
+
+        // child
+        "mov x0, {entry_function}",
+        "mov x1, {entry_argument}",
+        "mov x29, xzr",
+        "bl {clone_entry_trampoline}",
+        "brk #0",
+
+        // parent
+        "2:",
+        entry_function = in(reg) entry_function,
+        entry_argument = in(reg) entry_argument,
+        clone_entry_trampoline = sym clone_entry_trampoline,
+        in("x8") Syscall::Clone as usize,
+        inlateout("x0") flags as u32 as usize => result,
+        in("x1") ((child_stack as usize) & !0xF),
+        in("x2") parent_tid_pointer,
+        in("x3") thread_local_storage,
+        in("x4") child_tid_pointer,
+        out("x30") _,
+        options(nostack),
+    );
 
     if result < 0 {
         set_errno(Errno((-result) as u32));
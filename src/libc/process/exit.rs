@@ -5,7 +5,7 @@ use crate::{
 };
 
 enum HandlerKind {
-    Plain(extern "C" fn()),
+    Plain(unsafe extern "C" fn()),
     WithObject(unsafe extern "C" fn(*mut c_void)),
 }
 
@@ -30,16 +30,20 @@ fn register(handler: ExitHandler) -> i32 {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn atexit(function: extern "C" fn()) -> i32 {
-    signature_matches_libc!(libc::atexit(function));
+pub(crate) fn register_at_exit(function: unsafe extern "C" fn()) -> i32 {
     register(ExitHandler {
         kind: HandlerKind::Plain(function),
         object: ptr::null_mut(),
     })
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn atexit(function: extern "C" fn()) -> i32 {
+    signature_matches_libc!(libc::atexit(function));
+    register_at_exit(function)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __cxa_atexit(
     function: unsafe extern "C" fn(*mut c_void),
     object: *mut c_void,
@@ -70,7 +74,7 @@ pub(crate) unsafe fn run_exit_sequence() {
     flush_all_streams();
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn exit(code: i32) -> ! {
     signature_matches_libc!(libc::exit(code));
     run_exit_sequence();
@@ -1,8 +1,11 @@
 use std::ffi::c_void;
 
-use crate::syscall::exit::exit;
+use crate::{
+    start::personality::{personality, Personality},
+    syscall::exit::exit,
+};
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __libc_start_main(
     main: unsafe extern "C" fn(i32, *const *const u8, *const *const u8) -> i32,
     argc: i32,
@@ -13,6 +16,19 @@ unsafe extern "C" fn __libc_start_main(
     _stack_end: *const c_void,
 ) -> ! {
     let envp = argv.offset(argc as isize + 1);
+
+    // musl's `crt1.o` passes `_init` and `_fini`, then a null where glibc's passes the loader's `rtld_fini`, so a musl
+    // program's destructors are registered regardless.
+    let rtld_fini = match personality() {
+        Personality::Glibc => rtld_fini,
+        Personality::Musl => Some(super::rtld_fini as unsafe extern "C" fn()),
+    };
+
+    // As glibc does, so `exit` runs destructors too, after every handler `main` registers.
+    if let Some(rtld_fini) = rtld_fini {
+        super::exit::register_at_exit(rtld_fini);
+    }
+
     let exit_code = main(argc, argv, envp);
 
     // Matching glibc's exit: the calling thread's `thread_local` dtors run before the atexit chain; key destructors don't run for the main thread.
@@ -20,10 +36,5 @@ unsafe extern "C" fn __libc_start_main(
 
     super::exit::run_exit_sequence();
 
-    // TODO: Register rtld_fini (fini array functions) to run at exit.
-    if let Some(rtld_fini) = rtld_fini {
-        rtld_fini();
-    }
-
     exit(exit_code as usize);
 }
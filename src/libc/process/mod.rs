@@ -2,8 +2,11 @@ pub mod clone;
 mod exit;
 mod libc_start_main;
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn rtld_fini() {}
+/// Handed to `_start`'s caller in `rdx`; `__libc_start_main` registers it to run at exit.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn rtld_fini() {
+    crate::objects::strategies::init_array::run_finalizers();
+}
 
 use std::{arch::asm, cell::Cell};
 
@@ -12,7 +15,7 @@ use crate::{signature_matches_libc, syscall, syscall::Syscall};
 pub type ProcessID = i32;
 pub type SignalNumber = i32;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn getpid() -> ProcessID {
     signature_matches_libc!(std::mem::transmute(libc::getpid()));
 
@@ -20,7 +23,7 @@ pub unsafe extern "C" fn getpid() -> ProcessID {
     result as ProcessID
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn raise(signal_number: SignalNumber) -> i32 {
     signature_matches_libc!(libc::raise(signal_number));
 
@@ -31,7 +34,19 @@ unsafe extern "C" fn raise(signal_number: SignalNumber) -> i32 {
     result as i32
 }
 
-#[cfg_attr(not(test), no_mangle)]
+/// arm64 compilers read the stack protector canary from this global rather than the TCB. It is written once, before
+/// anything is relocated against it, so a program's COPY of it holds the same value.
+#[cfg(target_arch = "aarch64")]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+#[allow(non_upper_case_globals)]
+static mut __stack_chk_guard: usize = 0;
+
+#[cfg(target_arch = "aarch64")]
+pub unsafe fn set_stack_guard(canary: usize) {
+    __stack_chk_guard = canary;
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __stack_chk_fail() -> ! {
     const STACK_SMASH_MESSAGE: &[u8] = b"Stack Smashing Detected... Terminating!\n";
     syscall!(
@@ -43,7 +58,7 @@ unsafe extern "C" fn __stack_chk_fail() -> ! {
     abort();
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn abort() -> ! {
     #[thread_local]
     static ABORT_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
@@ -51,7 +66,10 @@ unsafe extern "C" fn abort() -> ! {
     // SAFETY: We must make sure this function is not called recursively.
     if ABORT_IN_PROGRESS.replace(true) {
         // I think I was called recursively; bye. o7
+        #[cfg(target_arch = "x86_64")]
         asm!("ud2", options(noreturn, nostack));
+        #[cfg(target_arch = "aarch64")]
+        asm!("udf #0", options(noreturn, nostack));
     }
 
     raise(libc::SIGABRT);
@@ -60,5 +78,8 @@ unsafe extern "C" fn abort() -> ! {
     // But why the fuck would you return normally from a sigabort?!?
     // If you do, you're fucking retarded and have an invalid instruction coming your way. ┗(▀̿ĺ̯▀̿ ̿)┓  ●~*
 
+    #[cfg(target_arch = "x86_64")]
     asm!("ud2", options(noreturn, nostack));
+    #[cfg(target_arch = "aarch64")]
+    asm!("udf #0", options(noreturn, nostack));
 }
@@ -0,0 +1,148 @@
+use std::ffi::{c_int, c_void};
+
+use super::{
+    file::SEEK_CUR,
+    memory_stream::{FixedMemory, GrowingMemory},
+    IoFile,
+};
+use crate::{
+    libc::{
+        errno::{set_errno, Errno},
+        translate_syscall_result,
+    },
+    syscall,
+    syscall::Syscall,
+};
+
+/// Where a stream's bytes go once they leave its buffer, glibc's `_IO_jump_t` low-level hooks. The buffer logic in
+/// `file.rs` is shared; only these four operations differ.
+pub(super) enum Backend {
+    /// `fileno`, through the kernel.
+    Descriptor,
+    /// `fmemopen`: a buffer of fixed size.
+    FixedMemory(Box<FixedMemory>),
+    /// `open_memstream`/`open_wmemstream`: a buffer that grows and is handed to the program.
+    GrowingMemory(Box<GrowingMemory>),
+    /// `fopencookie`: the program's callbacks.
+    Cookie(Box<CookieStream>),
+}
+
+type CookieRead = unsafe extern "C" fn(*mut c_void, *mut u8, usize) -> isize;
+type CookieWrite = unsafe extern "C" fn(*mut c_void, *const u8, usize) -> isize;
+type CookieSeek = unsafe extern "C" fn(*mut c_void, *mut i64, c_int) -> c_int;
+type CookieClose = unsafe extern "C" fn(*mut c_void) -> c_int;
+
+/// glibc `cookie_io_functions_t`; any of the callbacks may be null.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(super) struct CookieFunctions {
+    read: Option<CookieRead>,
+    write: Option<CookieWrite>,
+    seek: Option<CookieSeek>,
+    close: Option<CookieClose>,
+}
+
+pub(super) struct CookieStream {
+    pub(super) cookie: *mut c_void,
+    pub(super) functions: CookieFunctions,
+}
+
+impl IoFile {
+    /// One read from the backend: bytes read, 0 at end of file, or -1 with `errno` set.
+    pub(super) unsafe fn backend_read(&mut self, destination: *mut u8, length: usize) -> isize {
+        match &mut self.backend {
+            Backend::Descriptor => loop {
+                let result = syscall!(Syscall::Read, self.fileno, destination, length);
+                if result != -(Errno::INTR.into_raw() as isize) {
+                    break translate_syscall_result(result);
+                }
+            },
+            Backend::FixedMemory(memory) => memory.read(destination, length),
+            Backend::GrowingMemory(_) => {
+                set_errno(Errno::BADF);
+                -1
+            }
+            // Without a read callback, reads fail, as in glibc.
+            Backend::Cookie(stream) => match stream.functions.read {
+                Some(read) => read(stream.cookie, destination, length),
+                None => -1,
+            },
+        }
+    }
+
+    /// One write to the backend: bytes accepted, or -1 with `errno` set. A short count is an error for every backend
+    /// but the descriptor, which the caller retries.
+    pub(super) unsafe fn backend_write(&mut self, source: *const u8, length: usize) -> isize {
+        match &mut self.backend {
+            Backend::Descriptor => loop {
+                let result = syscall!(Syscall::Write, self.fileno, source, length);
+                if result != -(Errno::INTR.into_raw() as isize) {
+                    break translate_syscall_result(result);
+                }
+            },
+            Backend::FixedMemory(memory) => memory.write(source, length),
+            Backend::GrowingMemory(memory) => memory.write(source, length),
+            // Without a write callback, glibc fails the write rather than discarding it.
+            Backend::Cookie(stream) => match stream.functions.write {
+                Some(write) => write(stream.cookie, source, length),
+                None => 0,
+            },
+        }
+    }
+
+    /// Repositions the backend: the new offset, or -1 with `errno` set.
+    pub(super) unsafe fn backend_seek(&mut self, offset: i64, whence: i32) -> i64 {
+        match &mut self.backend {
+            Backend::Descriptor => {
+                translate_syscall_result(syscall!(Syscall::LSeek, self.fileno, offset, whence))
+                    as i64
+            }
+            Backend::FixedMemory(memory) => memory.seek(offset, whence),
+            Backend::GrowingMemory(memory) => memory.seek(offset, whence),
+            Backend::Cookie(stream) => {
+                let Some(seek) = stream.functions.seek else {
+                    set_errno(Errno::SPIPE);
+                    return -1;
+                };
+                let mut position = offset;
+                if seek(stream.cookie, &mut position, whence) < 0 {
+                    return -1;
+                }
+                position
+            }
+        }
+    }
+
+    /// The backend's current offset, for `ftell`.
+    pub(super) unsafe fn backend_offset(&mut self) -> i64 {
+        self.backend_seek(0, SEEK_CUR)
+    }
+
+    /// Releases the backend for `fclose`: 0, or -1 with `errno` set. The stream reverts to the closed descriptor -1.
+    pub(super) unsafe fn backend_close(&mut self) -> i32 {
+        let backend = std::mem::replace(&mut self.backend, Backend::Descriptor);
+        let file_descriptor = std::mem::replace(&mut self.fileno, -1);
+        match backend {
+            Backend::Descriptor => {
+                translate_syscall_result(syscall!(Syscall::Close, file_descriptor)) as i32
+            }
+            Backend::FixedMemory(memory) => {
+                memory.close();
+                0
+            }
+            Backend::GrowingMemory(memory) => {
+                memory.close();
+                0
+            }
+            Backend::Cookie(stream) => match stream.functions.close {
+                Some(close) => close(stream.cookie),
+                None => 0,
+            },
+        }
+    }
+
+    /// Whether the backend is a descriptor, which decides terminal line buffering and the default buffer size.
+    pub(super) fn has_descriptor(&self) -> bool {
+        matches!(self.backend, Backend::Descriptor)
+    }
+}
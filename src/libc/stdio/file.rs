@@ -2,10 +2,10 @@ use std::{
     alloc,
     mem::MaybeUninit,
     os::fd::{AsRawFd, BorrowedFd},
-    ptr,
+    ptr, slice,
 };
 
-use super::{IoFile, BUFFER_SIZE, EOF};
+use super::{stdout_ptr, with_stream_lock, IoFile, BUFFER_SIZE, EOF};
 use crate::{
     libc::{
         errno::{set_errno, Errno},
@@ -15,6 +15,10 @@ use crate::{
     syscall::Syscall,
 };
 
+pub(super) const SEEK_SET: i32 = 0;
+pub(super) const SEEK_CUR: i32 = 1;
+pub(super) const SEEK_END: i32 = 2;
+
 impl IoFile {
     /// Idempotent: picks buffering mode (glibc's line-buffered iff a tty) and allocates once.
     unsafe fn ensure_buffer(&mut self) {
@@ -22,15 +26,26 @@ impl IoFile {
             return;
         }
 
-        let file_descriptor = BorrowedFd::borrow_raw(self.fileno);
-
-        if !self.flags.unbuffered() {
+        if self.has_descriptor() && !self.flags.unbuffered() {
+            let file_descriptor = BorrowedFd::borrow_raw(self.fileno);
             self.flags = self
                 .flags
                 .with_line_buffered(file_descriptor_isatty(file_descriptor));
         }
 
-        let size = optimal_buffer_size(file_descriptor);
+        self.allocate_buffer(self.default_buffer_size());
+    }
+
+    /// The descriptor's preferred I/O size; memory and cookie streams take `BUFFER_SIZE`.
+    unsafe fn default_buffer_size(&self) -> usize {
+        if self.has_descriptor() {
+            optimal_buffer_size(BorrowedFd::borrow_raw(self.fileno))
+        } else {
+            BUFFER_SIZE
+        }
+    }
+
+    unsafe fn allocate_buffer(&mut self, size: usize) {
         let layout = alloc::Layout::from_size_align(size, 16).unwrap();
         let buffer = alloc::alloc(layout);
         if buffer.is_null() {
@@ -38,6 +53,7 @@ impl IoFile {
         }
         self.buf_base = buffer;
         self.buf_end = buffer.add(size);
+        self.flags = self.flags.with_user_buffer(false);
     }
 
     unsafe fn begin_write(&mut self) {
@@ -45,6 +61,7 @@ impl IoFile {
             return;
         }
         self.ensure_buffer();
+        self.end_read();
         self.write_base = self.buf_base;
         self.write_ptr = self.buf_base;
         self.write_end = if self.flags.line_buffered() || self.flags.unbuffered() {
@@ -60,11 +77,9 @@ impl IoFile {
         let mut cursor = self.write_base;
         while cursor < self.write_ptr {
             let remaining = self.write_ptr.offset_from(cursor) as usize;
-            let written = syscall!(Syscall::Write, self.fileno, cursor, remaining);
-            if written < 0 {
-                if written == -(Errno::INTR.into_raw() as isize) {
-                    continue;
-                }
+            let written = self.backend_write(cursor, remaining);
+            // A backend that accepts nothing (a full `fmemopen` buffer, a cookie without `write`) has failed.
+            if written <= 0 {
                 self.flags = self.flags.with_err_seen(true);
                 return -1;
             }
@@ -107,10 +122,10 @@ impl IoFile {
         *self.write_ptr = c as u8;
         self.write_ptr = self.write_ptr.add(1);
 
-        if self.flags.unbuffered() || (self.flags.line_buffered() && c as u8 == b'\n') {
-            if self.flush_buffer() < 0 {
-                return EOF;
-            }
+        if (self.flags.unbuffered() || (self.flags.line_buffered() && c as u8 == b'\n'))
+            && self.flush_buffer() < 0
+        {
+            return EOF;
         }
         c & 0xff
     }
@@ -135,13 +150,304 @@ impl IoFile {
             remaining = &remaining[take..];
 
             // Line-buffered flush is whole-buffer, not up to the last newline — more eager than glibc, still POSIX-legal.
-            if self.flags.unbuffered() || (self.flags.line_buffered() && chunk.contains(&b'\n')) {
-                if self.flush_buffer() < 0 {
+            if (self.flags.unbuffered() || (self.flags.line_buffered() && chunk.contains(&b'\n')))
+                && self.flush_buffer() < 0
+            {
+                break;
+            }
+        }
+        bytes.len() - remaining.len()
+    }
+}
+
+impl IoFile {
+    /// False if this stream is write-only (`NO_READS`); caller must treat that as failure.
+    unsafe fn readable(&mut self) -> bool {
+        if self.flags.no_reads() {
+            self.flags = self.flags.with_err_seen(true);
+            set_errno(Errno::BADF);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Hands the shared buffer to the read side, flushing what a write left in it first.
+    unsafe fn begin_read(&mut self) -> bool {
+        if !self.readable() {
+            return false;
+        }
+        self.ensure_buffer();
+        if self.flags.currently_putting() {
+            if self.flush_buffer() < 0 {
+                return false;
+            }
+            self.discard_write_pointers();
+        }
+        if self.read_base.is_null() {
+            self.reset_read_buffer();
+        }
+        true
+    }
+
+    /// Gives up buffered input before a write: the descriptor is moved back over what was read ahead but not consumed,
+    /// so the write lands where the reader stopped. Pipes and terminals cannot seek, and keep their position.
+    pub(super) unsafe fn end_read(&mut self) {
+        let unread = self.unread_count();
+        if unread > 0 {
+            self.backend_seek(-unread as i64, SEEK_CUR);
+        }
+        self.discard_read_buffer();
+    }
+
+    /// Bytes read ahead from the descriptor that the program has not consumed.
+    unsafe fn unread_count(&self) -> isize {
+        if self.read_ptr.is_null() {
+            0
+        } else {
+            self.read_end.offset_from(self.read_ptr)
+        }
+    }
+
+    unsafe fn discard_read_buffer(&mut self) {
+        self.read_base = ptr::null_mut();
+        self.read_ptr = ptr::null_mut();
+        self.read_end = ptr::null_mut();
+    }
+
+    pub(super) unsafe fn discard_write_pointers(&mut self) {
+        self.write_base = ptr::null_mut();
+        self.write_ptr = ptr::null_mut();
+        self.write_end = ptr::null_mut();
+        self.flags = self.flags.with_currently_putting(false);
+    }
+
+    unsafe fn reset_read_buffer(&mut self) {
+        self.read_base = self.buf_base;
+        self.read_ptr = self.buf_base;
+        self.read_end = self.buf_base;
+    }
+
+    /// The bytes read ahead and not yet consumed.
+    pub(super) unsafe fn buffered_input(&self) -> &[u8] {
+        if self.read_ptr.is_null() {
+            return &[];
+        }
+        slice::from_raw_parts(
+            self.read_ptr,
+            self.read_end.offset_from(self.read_ptr) as usize,
+        )
+    }
+
+    pub(super) unsafe fn consume(&mut self, count: usize) {
+        self.read_ptr = self.read_ptr.add(count);
+    }
+
+    /// One backend read; 0 on end of file or failure, which set `_IO_EOF_SEEN` and `_IO_ERR_SEEN`.
+    ///
+    /// As glibc does, a read on a line-buffered or unbuffered stream (a terminal, typically) flushes `stdout` first, so a
+    /// prompt is visible before the program blocks for the answer.
+    unsafe fn read_backend(&mut self, destination: *mut u8, length: usize) -> usize {
+        if self.flags.line_buffered() || self.flags.unbuffered() {
+            let stdout = stdout_ptr();
+            if !ptr::eq(self, stdout) {
+                with_stream_lock(stdout, |file| unsafe {
+                    if file.flags.line_buffered() {
+                        file.flush_buffer();
+                    }
+                });
+            }
+        }
+
+        let result = self.backend_read(destination, length);
+        if result < 0 {
+            self.flags = self.flags.with_err_seen(true);
+            return 0;
+        }
+        if result == 0 {
+            self.flags = self.flags.with_eof_seen(true);
+        }
+        result as usize
+    }
+
+    /// The next byte without consuming it, refilling the buffer once it is spent. End of file is sticky, as in glibc:
+    /// nothing more is read until `clearerr` (or `ungetc`) resets it.
+    pub(super) unsafe fn underflow(&mut self) -> i32 {
+        if !self.begin_read() {
+            return EOF;
+        }
+        if self.read_ptr < self.read_end {
+            return *self.read_ptr as i32;
+        }
+        if self.flags.eof_seen() {
+            return EOF;
+        }
+
+        self.reset_read_buffer();
+        let filled = self.read_backend(self.buf_base, self.read_ahead_size());
+        if filled == 0 {
+            return EOF;
+        }
+        self.read_end = self.buf_base.add(filled);
+        *self.read_ptr as i32
+    }
+
+    /// How much one refill asks for. An unbuffered stream reads a byte at a time, as glibc's one-byte buffer does, so
+    /// the descriptor never moves past what the program took.
+    unsafe fn read_ahead_size(&self) -> usize {
+        if self.flags.unbuffered() {
+            1
+        } else {
+            self.buf_end.offset_from(self.buf_base) as usize
+        }
+    }
+
+    /// `underflow`, consuming the byte.
+    pub(super) unsafe fn uflow(&mut self) -> i32 {
+        let byte = self.underflow();
+        if byte != EOF {
+            self.read_ptr = self.read_ptr.add(1);
+        }
+        byte
+    }
+
+    /// Returns bytes read — short of `destination.len()` only at end of file or on error. Reads of a buffer's worth or
+    /// more go straight to the destination.
+    pub(super) unsafe fn read_bytes(&mut self, destination: &mut [u8]) -> usize {
+        if !self.begin_read() {
+            return 0;
+        }
+
+        let mut copied = 0;
+        while copied < destination.len() {
+            let buffered = self.buffered_input();
+            if !buffered.is_empty() {
+                let take = buffered.len().min(destination.len() - copied);
+                destination[copied..copied + take].copy_from_slice(&buffered[..take]);
+                self.consume(take);
+                copied += take;
+                continue;
+            }
+            if self.flags.eof_seen() {
+                break;
+            }
+
+            let remaining = destination.len() - copied;
+            if remaining >= self.read_ahead_size() {
+                let filled = self.read_backend(destination[copied..].as_mut_ptr(), remaining);
+                if filled == 0 {
                     break;
                 }
+                copied += filled;
+            } else if self.underflow() == EOF {
+                break;
             }
         }
-        bytes.len() - remaining.len()
+        copied
+    }
+
+    /// Pushes `c` back to be read next, clearing end of file. One byte of pushback is always possible; more only while
+    /// the buffer has room in front of (or behind) what is unread.
+    pub(super) unsafe fn unread(&mut self, c: i32) -> i32 {
+        if c == EOF || !self.begin_read() {
+            return EOF;
+        }
+
+        if self.read_ptr > self.read_base {
+            self.read_ptr = self.read_ptr.sub(1);
+        } else if self.read_end < self.buf_end {
+            let unread = self.read_end.offset_from(self.read_ptr) as usize;
+            ptr::copy(self.read_ptr, self.read_ptr.add(1), unread);
+            self.read_end = self.read_end.add(1);
+        } else {
+            return EOF;
+        }
+        *self.read_ptr = c as u8;
+        self.flags = self.flags.with_eof_seen(false);
+        c & 0xff
+    }
+
+    /// Frees the buffer `ensure_buffer` or `setvbuf` allocated; one the program supplied is only let go of.
+    pub(super) unsafe fn release_buffer(&mut self) {
+        if self.buf_base.is_null() {
+            return;
+        }
+        if !self.flags.user_buffer() {
+            let size = self.buf_end.offset_from(self.buf_base) as usize;
+            alloc::dealloc(
+                self.buf_base,
+                alloc::Layout::from_size_align_unchecked(size, 16),
+            );
+        }
+        self.buf_base = ptr::null_mut();
+        self.buf_end = ptr::null_mut();
+        self.flags = self.flags.with_user_buffer(false);
+    }
+
+    /// Lets go of the buffer after flushing output and giving back read-ahead, so the next access allocates afresh.
+    pub(super) unsafe fn detach_buffer(&mut self) -> bool {
+        if self.flush_buffer() < 0 {
+            return false;
+        }
+        self.discard_write_pointers();
+        self.end_read();
+        self.release_buffer();
+        true
+    }
+
+    /// Swaps in `buffer`, or a fresh allocation of `size` bytes when it is null, in place of the current one. A zero
+    /// `size` means the default for the descriptor.
+    pub(super) unsafe fn replace_buffer(&mut self, buffer: *mut u8, size: usize) -> bool {
+        if !self.detach_buffer() {
+            return false;
+        }
+
+        if buffer.is_null() || size == 0 {
+            let size = match size {
+                0 => self.default_buffer_size(),
+                size => size,
+            };
+            self.allocate_buffer(size);
+        } else {
+            self.buf_base = buffer;
+            self.buf_end = buffer.add(size);
+            self.flags = self.flags.with_user_buffer(true);
+        }
+        true
+    }
+
+    /// Repositions the backend for `fseek`: output is flushed and read-ahead dropped, with `SEEK_CUR` counted from
+    /// where the reader stopped. Clears end of file. Returns the new offset, or -1 with `errno` set.
+    pub(super) unsafe fn seek(&mut self, offset: i64, whence: i32) -> i64 {
+        if self.flush_buffer() < 0 {
+            return -1;
+        }
+        let offset = if whence == SEEK_CUR {
+            offset - self.unread_count() as i64
+        } else {
+            offset
+        };
+        let result = self.backend_seek(offset, whence);
+        if result < 0 {
+            return -1;
+        }
+        self.discard_read_buffer();
+        self.flags = self.flags.with_eof_seen(false);
+        result
+    }
+
+    /// `ftell`'s offset: the backend's, less read-ahead not yet consumed, plus output not yet flushed.
+    pub(super) unsafe fn tell(&mut self) -> i64 {
+        let result = self.backend_offset();
+        if result < 0 {
+            return -1;
+        }
+        let pending = if self.write_ptr.is_null() {
+            0
+        } else {
+            self.write_ptr.offset_from(self.write_base)
+        };
+        result - self.unread_count() as i64 + pending as i64
     }
 }
 
@@ -164,7 +470,19 @@ fn optimal_buffer_size(file_descriptor: BorrowedFd<'_>) -> usize {
 }
 
 /// The extern fallback glibc's inlined `putc_unlocked` calls. Caller owns the lock.
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __overflow(stream: *mut IoFile, c: i32) -> i32 {
     (*stream).overflow(c)
 }
+
+/// The extern fallback glibc's inlined `getc_unlocked` calls once `read_ptr` reaches `read_end`. Caller owns the lock.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn __uflow(stream: *mut IoFile) -> i32 {
+    (*stream).uflow()
+}
+
+/// Like `__uflow`, without consuming the byte.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn __underflow(stream: *mut IoFile) -> i32 {
+    (*stream).underflow()
+}
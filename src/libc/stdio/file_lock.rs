@@ -8,7 +8,7 @@ use bitbybit::bitfield;
 
 use crate::{
     syscall,
-    syscall::{futex::FutexOperation, thread_pointer::get_thread_pointer, Syscall},
+    syscall::{futex::FutexOperation, Syscall},
     tls::thread_control_block::ThreadControlBlock,
 };
 
@@ -48,8 +48,7 @@ impl FileLock {
     }
 
     pub fn lock(&self) {
-        let tid =
-            u31::new(unsafe { (*get_thread_pointer().cast::<ThreadControlBlock>()).tid } as u32);
+        let tid = u31::new(unsafe { (*ThreadControlBlock::current()).tid } as u32);
         let held = LockWord::ZERO.with_owner(tid).raw_value();
 
         match self
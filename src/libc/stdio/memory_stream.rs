@@ -0,0 +1,416 @@
+use std::{
+    alloc,
+    ffi::{c_char, CStr},
+    ptr,
+};
+
+use super::{
+    backend::Backend,
+    base_flags,
+    file::{SEEK_CUR, SEEK_END, SEEK_SET},
+    link_stream,
+    stream_open::parse_mode,
+    IoFile,
+};
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
+
+/// Resolves `offset`/`whence` against `position` and `end`, rejecting anything before the start or past `limit`.
+fn resolve_seek(offset: i64, whence: i32, position: usize, end: usize, limit: usize) -> i64 {
+    let base = match whence {
+        SEEK_SET => 0,
+        SEEK_CUR => position as i64,
+        SEEK_END => end as i64,
+        _ => {
+            set_errno(Errno::INVAL);
+            return -1;
+        }
+    };
+    match base.checked_add(offset) {
+        Some(target) if target >= 0 && target as u64 <= limit as u64 => target,
+        _ => {
+            set_errno(Errno::INVAL);
+            -1
+        }
+    }
+}
+
+/// `fmemopen(NULL, size, ...)`'s allocation; glibc accepts a zero size, which still gets a byte.
+fn owned_layout(size: usize) -> alloc::Layout {
+    alloc::Layout::from_size_align(size.max(1), 1).unwrap()
+}
+
+/// glibc's `fmemopen` cookie: `size` bytes, of which the first `end` hold data.
+pub(super) struct FixedMemory {
+    buffer: *mut u8,
+    size: usize,
+    position: usize,
+    end: usize,
+    append: bool,
+    /// `fmemopen(NULL, ...)` allocated the buffer, and closing frees it.
+    owned: bool,
+}
+
+impl FixedMemory {
+    /// Reads stop at the end of the data.
+    pub(super) unsafe fn read(&mut self, destination: *mut u8, length: usize) -> isize {
+        let count = length.min(self.end.saturating_sub(self.position));
+        ptr::copy_nonoverlapping(self.buffer.add(self.position), destination, count);
+        self.position += count;
+        count as isize
+    }
+
+    /// glibc's rules: a write that does not fit is cut short, and fails with `ENOSPC` once nothing fits; data that
+    /// grows is followed by a NUL if there is room, and a write that fills the buffer in a non-append stream puts the
+    /// NUL in its last byte.
+    pub(super) unsafe fn write(&mut self, source: *const u8, length: usize) -> isize {
+        let adds_terminator = length == 0 || *source.add(length - 1) != 0;
+        let position = if self.append { self.end } else { self.position };
+        let mut count = length;
+        if position + count > self.size {
+            if self.position + adds_terminator as usize >= self.size {
+                set_errno(Errno::NOSPC);
+                return 0;
+            }
+            count = self.size - position;
+        }
+
+        ptr::copy_nonoverlapping(source, self.buffer.add(position), count);
+        self.position = position + count;
+        if self.position > self.end {
+            self.end = self.position;
+            if self.end < self.size && adds_terminator {
+                *self.buffer.add(self.end) = 0;
+            } else if !self.append && adds_terminator {
+                *self.buffer.add(self.size - 1) = 0;
+            }
+        }
+        count as isize
+    }
+
+    /// Anywhere within the buffer, with `SEEK_END` counted from the end of the data.
+    pub(super) fn seek(&mut self, offset: i64, whence: i32) -> i64 {
+        let target = resolve_seek(offset, whence, self.position, self.end, self.size);
+        if target >= 0 {
+            self.position = target as usize;
+        }
+        target
+    }
+
+    pub(super) unsafe fn close(&self) {
+        if self.owned {
+            alloc::dealloc(self.buffer, owned_layout(self.size));
+        }
+    }
+}
+
+/// `open_memstream`'s buffer, in units of `unit_size` bytes (`wchar_t` for `open_wmemstream`). It always has room for a
+/// terminating zero unit after the position, every unit past the furthest write is zero, and the program's
+/// `*location`/`*size_location` track it after every flush.
+pub(super) struct GrowingMemory {
+    location: *mut *mut u8,
+    size_location: *mut usize,
+    unit_size: usize,
+    buffer: *mut u8,
+    /// In units, like `length` and `position`; always more than both, for the terminator.
+    capacity: usize,
+    /// Where `SEEK_END` counts from. As in glibc, a seek moves it to the new position, discarding anything after.
+    length: usize,
+    position: usize,
+}
+
+/// glibc's first `open_memstream` allocation, in units.
+const INITIAL_MEMSTREAM_CAPACITY: usize = 64;
+
+impl GrowingMemory {
+    /// Allocates through `libc::calloc`/`libc::realloc`, which bind to miros's own allocator once linked, so the
+    /// program can `free` the buffer. `None` (with `errno` set) if that fails.
+    unsafe fn new(
+        location: *mut *mut u8,
+        size_location: *mut usize,
+        unit_size: usize,
+    ) -> Option<Self> {
+        let buffer = libc::calloc(INITIAL_MEMSTREAM_CAPACITY, unit_size).cast::<u8>();
+        if buffer.is_null() {
+            set_errno(Errno::NOMEM);
+            return None;
+        }
+        let memory = Self {
+            location,
+            size_location,
+            unit_size,
+            buffer,
+            capacity: INITIAL_MEMSTREAM_CAPACITY,
+            length: 0,
+            position: 0,
+        };
+        memory.publish();
+        Some(memory)
+    }
+
+    /// What glibc tells the program on a flush: the buffer, and the position as its size. Nothing is terminated here, so
+    /// after a seek back the data already written past the position is still in the buffer.
+    unsafe fn publish(&self) {
+        *self.location = self.buffer;
+        *self.size_location = self.position;
+    }
+
+    unsafe fn reserve(&mut self, units: usize) -> bool {
+        if units < self.capacity {
+            return true;
+        }
+        let capacity = (units + 1).max(self.capacity * 2);
+        let Some(bytes) = capacity.checked_mul(self.unit_size) else {
+            set_errno(Errno::NOMEM);
+            return false;
+        };
+        let buffer = libc::realloc(self.buffer.cast(), bytes).cast::<u8>();
+        if buffer.is_null() {
+            set_errno(Errno::NOMEM);
+            return false;
+        }
+        let old_bytes = self.capacity * self.unit_size;
+        ptr::write_bytes(buffer.add(old_bytes), 0, bytes - old_bytes);
+        self.buffer = buffer;
+        self.capacity = capacity;
+        true
+    }
+
+    /// Stores each byte as one unit: a wide stream's bytes are widened as the C locale's single-byte characters, since
+    /// miros has no wide-oriented output functions to write `wchar_t` directly.
+    pub(super) unsafe fn write(&mut self, source: *const u8, length: usize) -> isize {
+        if !self.reserve(self.position + length) {
+            return -1;
+        }
+        // A seek past the furthest write leaves a gap, which `reserve` zeroed and which stays zero.
+        if self.unit_size == 1 {
+            ptr::copy_nonoverlapping(source, self.buffer.add(self.position), length);
+        } else {
+            let units = self.buffer.cast::<u32>().add(self.position);
+            for index in 0..length {
+                units.add(index).write(*source.add(index) as u32);
+            }
+        }
+        self.position += length;
+        if self.position > self.length {
+            self.length = self.position;
+        }
+        self.publish();
+        length as isize
+    }
+
+    /// Anywhere from the start on; past the end the gap reads as zeros.
+    pub(super) unsafe fn seek(&mut self, offset: i64, whence: i32) -> i64 {
+        let target = resolve_seek(
+            offset,
+            whence,
+            self.position,
+            self.length,
+            isize::MAX as usize,
+        );
+        if target >= 0 {
+            if !self.reserve(target as usize) {
+                return -1;
+            }
+            self.position = target as usize;
+            self.length = self.position;
+            self.publish();
+        }
+        target
+    }
+
+    /// The buffer now belongs to the program, terminated at the position as glibc's `fclose` leaves it.
+    pub(super) unsafe fn close(&self) {
+        ptr::write_bytes(
+            self.buffer.add(self.position * self.unit_size),
+            0,
+            self.unit_size,
+        );
+        self.publish();
+    }
+}
+
+/// `fmemopen` with glibc's semantics: a null `buffer` is allocated (zeroed) and freed on close; `w` truncates it to an
+/// empty string; `a` starts at the first NUL; `r` takes all `size` bytes as data.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fmemopen(buffer: *mut u8, size: usize, mode: *const c_char) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::fmemopen(
+        buffer.cast(),
+        size,
+        mode
+    )));
+
+    let mode = CStr::from_ptr(mode).to_bytes();
+    let Some((_, stream_flags)) = parse_mode(mode) else {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    };
+    let owned = buffer.is_null();
+    let buffer = if owned {
+        let allocation = alloc::alloc_zeroed(owned_layout(size));
+        if allocation.is_null() {
+            set_errno(Errno::NOMEM);
+            return ptr::null_mut();
+        }
+        allocation
+    } else {
+        buffer
+    };
+    if mode[0] == b'w' && !owned {
+        *buffer = 0;
+    }
+    let end = match mode[0] {
+        b'r' => size,
+        _ => (0..size)
+            .find(|&index| *buffer.add(index) == 0)
+            .unwrap_or(size),
+    };
+    let append = mode[0] == b'a';
+
+    let mut stream = Box::new(IoFile::new(-1, stream_flags, ptr::null_mut()));
+    stream.backend = Backend::FixedMemory(Box::new(FixedMemory {
+        buffer,
+        size,
+        position: if append { end } else { 0 },
+        end,
+        append,
+        owned,
+    }));
+    let stream = Box::into_raw(stream);
+    link_stream(stream);
+    stream
+}
+
+unsafe fn open_growing_stream(
+    location: *mut *mut u8,
+    size_location: *mut usize,
+    unit_size: usize,
+) -> *mut IoFile {
+    if location.is_null() || size_location.is_null() {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    }
+    let Some(memory) = GrowingMemory::new(location, size_location, unit_size) else {
+        return ptr::null_mut();
+    };
+
+    let mut stream = Box::new(IoFile::new(
+        -1,
+        base_flags().with_no_reads(true),
+        ptr::null_mut(),
+    ));
+    stream.backend = Backend::GrowingMemory(Box::new(memory));
+    let stream = Box::into_raw(stream);
+    link_stream(stream);
+    stream
+}
+
+/// A write-only stream into a growing buffer; after `fflush` or `fclose`, `*location` is the NUL-terminated buffer and
+/// `*size_location` its size. The program frees the buffer.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn open_memstream(
+    location: *mut *mut c_char,
+    size_location: *mut usize,
+) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::open_memstream(
+        location,
+        size_location
+    )));
+    open_growing_stream(location.cast(), size_location, 1)
+}
+
+/// `open_memstream` in `wchar_t` units, both in the buffer and in `*size_location`.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn open_wmemstream(
+    location: *mut *mut libc::wchar_t,
+    size_location: *mut usize,
+) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::open_wmemstream(
+        location,
+        size_location
+    )));
+    open_growing_stream(location.cast(), size_location, size_of::<libc::wchar_t>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::slice;
+
+    use super::*;
+
+    #[test]
+    fn fixed_memory_writes_follow_glibc_termination() {
+        let mut bytes = *b"xxxxxx";
+        let mut memory = FixedMemory {
+            buffer: bytes.as_mut_ptr(),
+            size: bytes.len(),
+            position: 0,
+            end: 0,
+            append: false,
+            owned: false,
+        };
+        unsafe {
+            assert_eq!(memory.write(b"abc".as_ptr(), 3), 3);
+            assert_eq!(&bytes, b"abc\0xx");
+            // Cut short, with the NUL in the last byte.
+            assert_eq!(memory.write(b"defg".as_ptr(), 4), 3);
+            assert_eq!(&bytes, b"abcde\0");
+            assert_eq!(memory.write(b"h".as_ptr(), 1), 0);
+        }
+        assert_eq!(memory.seek(-2, SEEK_END), 4);
+        assert_eq!(memory.seek(1, SEEK_CUR), 5);
+        assert_eq!(memory.seek(7, SEEK_SET), -1);
+    }
+
+    #[test]
+    fn growing_memory_publishes_position_and_buffer() {
+        let mut location = ptr::null_mut();
+        let mut size = usize::MAX;
+        unsafe {
+            let mut memory = GrowingMemory::new(&mut location, &mut size, 1).unwrap();
+            assert_eq!(size, 0);
+            let long = [b'z'; 100];
+            assert_eq!(memory.write(long.as_ptr(), long.len()), 100);
+            assert_eq!(size, 100);
+            assert_eq!(*location.add(100), 0);
+
+            // Past the end, the gap is zeros and the size follows the position.
+            assert_eq!(memory.seek(5, SEEK_END), 105);
+            assert_eq!(size, 105);
+            assert_eq!(memory.write(b"!".as_ptr(), 1), 1);
+            assert_eq!(size, 106);
+            assert_eq!(*location.add(102), 0);
+            assert_eq!(*location.add(105), b'!');
+            assert_eq!(*location.add(106), 0);
+
+            memory.close();
+            libc::free(location.cast());
+        }
+    }
+
+    #[test]
+    fn growing_memory_truncates_at_a_seek_back_like_glibc() {
+        let mut location = ptr::null_mut();
+        let mut size = usize::MAX;
+        unsafe {
+            let mut memory = GrowingMemory::new(&mut location, &mut size, 1).unwrap();
+            assert_eq!(memory.write(b"abcdef".as_ptr(), 6), 6);
+            assert_eq!(memory.seek(2, SEEK_SET), 2);
+            assert_eq!(memory.seek(0, SEEK_END), 2);
+            assert_eq!(size, 2);
+
+            // Overwriting leaves what follows in place until the stream is closed.
+            assert_eq!(memory.seek(1, SEEK_SET), 1);
+            assert_eq!(memory.write(b"Z".as_ptr(), 1), 1);
+            assert_eq!(size, 2);
+            assert_eq!(slice::from_raw_parts(location, 7), b"aZcdef\0");
+
+            memory.close();
+            assert_eq!(size, 2);
+            assert_eq!(slice::from_raw_parts(location, 3), b"aZ\0");
+            libc::free(location.cast());
+        }
+    }
+}
@@ -2,13 +2,24 @@ use std::{mem::offset_of, ptr};
 
 use bitbybit::bitfield;
 
-use crate::signature_matches_libc;
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
 
+mod backend;
 mod file;
 mod file_lock;
+mod memory_stream;
+mod reader;
+mod stream_buffering;
+mod stream_open;
+mod stream_read;
+mod stream_seek;
 mod stream_write;
 mod writer;
 
+use backend::Backend;
 use file_lock::FileLock;
 pub(crate) use writer::vfprintf;
 
@@ -18,8 +29,13 @@ pub const BUFFER_SIZE: usize = 8192;
 /// glibc `_IO_FILE._flags`; miros interprets only these bits. `magic` (0xFBAD) fills the high half.
 #[bitfield(u32)]
 struct IoFlags {
+    /// The program supplied the buffer through `setvbuf`; stdio never frees it.
+    #[bit(0, rw)]
+    user_buffer: bool,
     #[bit(1, rw)]
     unbuffered: bool,
+    #[bit(2, rw)]
+    no_reads: bool,
     #[bit(3, rw)]
     no_writes: bool,
     #[bit(4, rw)]
@@ -60,6 +76,7 @@ pub struct IoFile {
     fileno: i32,
     chain: *mut IoFile,
     lock: FileLock,
+    backend: Backend,
 }
 
 unsafe impl Send for IoFile {}
@@ -80,6 +97,7 @@ impl IoFile {
             fileno,
             chain,
             lock: FileLock::new(),
+            backend: Backend::Descriptor,
         }
     }
 }
@@ -92,23 +110,28 @@ const fn base_flags() -> IoFlags {
 static mut STDIN_FILE: IoFile = IoFile::new(0, base_flags().with_no_writes(true), ptr::null_mut());
 static mut STDOUT_FILE: IoFile = IoFile::new(
     1,
-    base_flags().with_line_buffered(true),
+    base_flags().with_no_reads(true).with_line_buffered(true),
     &raw mut STDERR_FILE,
 );
-static mut STDERR_FILE: IoFile =
-    IoFile::new(2, base_flags().with_unbuffered(true), &raw mut STDIN_FILE);
+static mut STDERR_FILE: IoFile = IoFile::new(
+    2,
+    base_flags().with_no_reads(true).with_unbuffered(true),
+    &raw mut STDIN_FILE,
+);
 
 static mut STREAM_LIST_HEAD: *mut IoFile = &raw mut STDOUT_FILE;
+/// Guards `STREAM_LIST_HEAD` and the `chain` links; taken before any stream's own lock.
+static STREAM_LIST_LOCK: FileLock = FileLock::new();
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 #[allow(non_upper_case_globals)]
 static mut stdin: *mut IoFile = &raw mut STDIN_FILE;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 #[allow(non_upper_case_globals)]
 static mut stdout: *mut IoFile = &raw mut STDOUT_FILE;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 #[allow(non_upper_case_globals)]
 static mut stderr: *mut IoFile = &raw mut STDERR_FILE;
 
@@ -116,6 +139,10 @@ pub(crate) unsafe fn stdout_ptr() -> *mut IoFile {
     *(&raw const stdout)
 }
 
+pub(crate) unsafe fn stdin_ptr() -> *mut IoFile {
+    *(&raw const stdin)
+}
+
 /// The one place stdio locking lives; the `*_unlocked` API and inlined fast paths bypass it.
 pub(super) unsafe fn with_stream_lock<R>(
     stream: *mut IoFile,
@@ -128,14 +155,38 @@ pub(super) unsafe fn with_stream_lock<R>(
 }
 
 pub(crate) unsafe fn flush_all_streams() {
+    STREAM_LIST_LOCK.lock();
     let mut stream = *(&raw const STREAM_LIST_HEAD);
     while !stream.is_null() {
         with_stream_lock(stream, |file| unsafe { file.flush_buffer() });
         stream = (*stream).chain;
     }
+    STREAM_LIST_LOCK.unlock();
+}
+
+/// Puts a newly opened stream at the head of the list `flush_all_streams` walks.
+unsafe fn link_stream(stream: *mut IoFile) {
+    STREAM_LIST_LOCK.lock();
+    (*stream).chain = *(&raw const STREAM_LIST_HEAD);
+    *(&raw mut STREAM_LIST_HEAD) = stream;
+    STREAM_LIST_LOCK.unlock();
+}
+
+/// Takes a closing stream out of the list; one that is not linked (closed twice) is left alone.
+unsafe fn unlink_stream(stream: *mut IoFile) {
+    STREAM_LIST_LOCK.lock();
+    let mut link = &raw mut STREAM_LIST_HEAD;
+    while !(*link).is_null() {
+        if *link == stream {
+            *link = (*stream).chain;
+            break;
+        }
+        link = &raw mut (**link).chain;
+    }
+    STREAM_LIST_LOCK.unlock();
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fflush(stream: *mut IoFile) -> i32 {
     signature_matches_libc!(libc::fflush(core::mem::transmute(stream)));
 
@@ -144,5 +195,59 @@ unsafe extern "C" fn fflush(stream: *mut IoFile) -> i32 {
         return 0;
     }
 
-    with_stream_lock(stream, |file| unsafe { file.flush_buffer() })
+    with_stream_lock(stream, |file| unsafe {
+        let flushed = file.flush_buffer();
+        // An input stream's descriptor is synced to where the program stopped reading, as glibc does.
+        file.end_read();
+        flushed
+    })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn feof(stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::feof(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| file.flags.eof_seen() as i32)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn feof_unlocked(stream: *mut IoFile) -> i32 {
+    (*stream).flags.eof_seen() as i32
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ferror(stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::ferror(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| file.flags.err_seen() as i32)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ferror_unlocked(stream: *mut IoFile) -> i32 {
+    (*stream).flags.err_seen() as i32
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn clearerr(stream: *mut IoFile) {
+    signature_matches_libc!(libc::clearerr(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| clearerr_unlocked(file));
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn clearerr_unlocked(stream: *mut IoFile) {
+    (*stream).flags = (*stream).flags.with_eof_seen(false).with_err_seen(false);
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fileno(stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::fileno(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe { fileno_unlocked(file) })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fileno_unlocked(stream: *mut IoFile) -> i32 {
+    // Memory and cookie streams have no descriptor.
+    let file_descriptor = (*stream).fileno;
+    if file_descriptor < 0 {
+        set_errno(Errno::BADF);
+    }
+    file_descriptor
 }
@@ -0,0 +1,46 @@
+use std::ffi::{c_char, VaList};
+
+use super::{stdin_ptr, with_stream_lock, IoFile, EOF};
+use crate::{
+    libc::str::{scanf::scan_from, strtod::ByteSource},
+    signature_matches_libc,
+};
+
+/// Adapts the scanf engine onto an `IoFile`. A byte is looked at in the buffer and only consumed once a conversion
+/// takes it, so the byte that ends an item stays there for the next read: the one byte of pushback C asks for.
+struct FileReader<'a>(&'a mut IoFile);
+
+impl ByteSource for FileReader<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        let byte = unsafe { self.0.underflow() };
+        (byte != EOF).then_some(byte as u8)
+    }
+
+    fn advance(&mut self) {
+        unsafe { self.0.consume(1) }
+    }
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vfscanf(stream: *mut IoFile, format: *const c_char, args: VaList<'_>) -> i32 {
+    with_stream_lock(stream, |file| unsafe {
+        scan_from(&mut FileReader(file), format, args)
+    })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fscanf(stream: *mut IoFile, format: *const c_char, args: ...) -> i32 {
+    signature_matches_libc!(libc::fscanf(core::mem::transmute(stream), format, args));
+    vfscanf(stream, format, args)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vscanf(format: *const c_char, args: VaList<'_>) -> i32 {
+    vfscanf(stdin_ptr(), format, args)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn scanf(format: *const c_char, args: ...) -> i32 {
+    signature_matches_libc!(libc::scanf(format, args));
+    vfscanf(stdin_ptr(), format, args)
+}
@@ -0,0 +1,69 @@
+use std::{ffi::c_char, ptr};
+
+use super::{with_stream_lock, IoFile, BUFFER_SIZE};
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
+
+const FULLY_BUFFERED: i32 = 0;
+const LINE_BUFFERED: i32 = 1;
+const UNBUFFERED: i32 = 2;
+
+/// Buffered modes install their buffer now, so the terminal check on first use no longer overrides the choice.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn setvbuf(
+    stream: *mut IoFile,
+    buffer: *mut c_char,
+    mode: i32,
+    size: usize,
+) -> i32 {
+    signature_matches_libc!(libc::setvbuf(
+        core::mem::transmute(stream),
+        buffer,
+        mode,
+        size
+    ));
+
+    if !matches!(mode, FULLY_BUFFERED | LINE_BUFFERED | UNBUFFERED) {
+        set_errno(Errno::INVAL);
+        return -1;
+    }
+    let installed = with_stream_lock(stream, |file| unsafe {
+        file.flags = file
+            .flags
+            .with_line_buffered(mode == LINE_BUFFERED)
+            .with_unbuffered(mode == UNBUFFERED);
+        if mode == UNBUFFERED {
+            file.detach_buffer()
+        } else {
+            file.replace_buffer(buffer.cast(), size)
+        }
+    });
+    if installed {
+        0
+    } else {
+        -1
+    }
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn setbuf(stream: *mut IoFile, buffer: *mut c_char) {
+    signature_matches_libc!(libc::setbuf(core::mem::transmute(stream), buffer));
+    setbuffer(stream, buffer, BUFFER_SIZE);
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn setbuffer(stream: *mut IoFile, buffer: *mut c_char, size: usize) {
+    let mode = if buffer.is_null() {
+        UNBUFFERED
+    } else {
+        FULLY_BUFFERED
+    };
+    setvbuf(stream, buffer, mode, size);
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn setlinebuf(stream: *mut IoFile) {
+    setvbuf(stream, ptr::null_mut(), LINE_BUFFERED, 0);
+}
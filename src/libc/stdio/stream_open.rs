@@ -0,0 +1,322 @@
+use std::{
+    ffi::{c_char, c_void, CStr},
+    ptr,
+};
+
+use super::{
+    backend::{Backend, CookieFunctions, CookieStream},
+    base_flags,
+    file::SEEK_END,
+    link_stream, unlink_stream, with_stream_lock, IoFile, IoFlags, EOF, STDERR_FILE, STDIN_FILE,
+    STDOUT_FILE,
+};
+use crate::{
+    libc::{
+        errno::{set_errno, Errno},
+        fs::{
+            fcntl::FCntlCommand,
+            open::{AccessMode, OFlags, AT_FDCWD},
+        },
+        translate_syscall_result,
+    },
+    signature_matches_libc, syscall,
+    syscall::Syscall,
+};
+
+/// `0666`, narrowed by the umask as for any `open(2)`.
+const NEW_FILE_MODE: u32 = 0o666;
+
+/// `fopen`'s mode: `r`, `w` or `a`, then any of `+` (read and write), `x` (`O_EXCL`), `e` (`O_CLOEXEC`) and `b`;
+/// glibc's other extensions (`m`, `c`, `,ccs=`) are accepted and ignored. `None` for anything else, which is `EINVAL`.
+pub(super) fn parse_mode(mode: &[u8]) -> Option<(OFlags, IoFlags)> {
+    let (&first, modifiers) = mode.split_first()?;
+    let mut open_flags = match first {
+        b'r' => OFlags::ZERO,
+        b'w' => OFlags::ZERO.with_create(true).with_truncate(true),
+        b'a' => OFlags::ZERO.with_create(true).with_append(true),
+        _ => return None,
+    };
+    let mut read_write = false;
+    for &modifier in modifiers.iter().take_while(|&&modifier| modifier != b',') {
+        match modifier {
+            b'+' => read_write = true,
+            b'x' => open_flags = open_flags.with_require_create(true),
+            b'e' => open_flags = open_flags.with_close_on_exec(true),
+            _ => (),
+        }
+    }
+
+    let (access_mode, stream_flags) = match (first, read_write) {
+        (_, true) => (AccessMode::ReadAndWrite, base_flags()),
+        (b'r', false) => (AccessMode::ReadOnly, base_flags().with_no_writes(true)),
+        (_, false) => (AccessMode::WriteOnly, base_flags().with_no_reads(true)),
+    };
+    Some((open_flags.with_access_mode(access_mode), stream_flags))
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fopen(pathname: *const c_char, mode: *const c_char) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::fopen(pathname, mode)));
+
+    let Some((open_flags, stream_flags)) = parse_mode(CStr::from_ptr(mode).to_bytes()) else {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    };
+    let result = syscall!(
+        Syscall::OpenAt,
+        AT_FDCWD,
+        pathname,
+        open_flags.raw_value(),
+        NEW_FILE_MODE
+    );
+    let file_descriptor = translate_syscall_result(result);
+    if file_descriptor < 0 {
+        return ptr::null_mut();
+    }
+
+    let stream = Box::into_raw(Box::new(IoFile::new(
+        file_descriptor as i32,
+        stream_flags,
+        ptr::null_mut(),
+    )));
+    link_stream(stream);
+    stream
+}
+
+// LFS alias: `fopen` is `fopen64` on 64-bit targets.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fopen64(pathname: *const c_char, mode: *const c_char) -> *mut IoFile {
+    fopen(pathname, mode)
+}
+
+/// Wraps an open descriptor in a stream. Its access mode must allow what `mode` asks for; `a` turns on `O_APPEND`, but
+/// the other open flags are the descriptor's own.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fdopen(file_descriptor: i32, mode: *const c_char) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::fdopen(file_descriptor, mode)));
+
+    let Some((open_flags, stream_flags)) = parse_mode(CStr::from_ptr(mode).to_bytes()) else {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    };
+    let result = translate_syscall_result(syscall!(
+        Syscall::FCntl,
+        file_descriptor,
+        FCntlCommand::GetOpenFlags.raw_value()
+    ));
+    if result < 0 {
+        return ptr::null_mut();
+    }
+    let descriptor_flags = OFlags::new_with_raw_value(result as u32);
+    let allowed = match descriptor_flags.access_mode() {
+        Ok(AccessMode::ReadAndWrite) => true,
+        Ok(AccessMode::ReadOnly) => stream_flags.no_writes(),
+        Ok(AccessMode::WriteOnly) => stream_flags.no_reads(),
+        Err(_) => false,
+    };
+    if !allowed {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    }
+    if open_flags.append() && !descriptor_flags.append() {
+        let result = syscall!(
+            Syscall::FCntl,
+            file_descriptor,
+            FCntlCommand::SetOpenFlags.raw_value(),
+            descriptor_flags.with_append(true).raw_value()
+        );
+        if translate_syscall_result(result) < 0 {
+            return ptr::null_mut();
+        }
+    }
+
+    let stream = Box::into_raw(Box::new(IoFile::new(
+        file_descriptor,
+        stream_flags,
+        ptr::null_mut(),
+    )));
+    link_stream(stream);
+    stream
+}
+
+/// A stream over the program's callbacks; reads, writes and seeks go through `functions` with `cookie`.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fopencookie(
+    cookie: *mut c_void,
+    mode: *const c_char,
+    functions: CookieFunctions,
+) -> *mut IoFile {
+    // `libc` binds no `fopencookie` or `cookie_io_functions_t` to check the signature against.
+    let Some((open_flags, stream_flags)) = parse_mode(CStr::from_ptr(mode).to_bytes()) else {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    };
+    let mut stream = Box::new(IoFile::new(-1, stream_flags, ptr::null_mut()));
+    stream.backend = Backend::Cookie(Box::new(CookieStream { cookie, functions }));
+    // glibc seeks an "a" cookie stream to its end once, up front.
+    if open_flags.append() {
+        stream.backend_seek(0, SEEK_END);
+    }
+    let stream = Box::into_raw(stream);
+    link_stream(stream);
+    stream
+}
+
+/// Reopens `stream` on `pathname` under the same descriptor number, so `freopen(path, "r", stdin)` redirects fd 0 too.
+/// A null `pathname` reopens the current file with the new mode, through `/proc/self/fd`. On failure the stream is
+/// closed, as with glibc.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn freopen(
+    pathname: *const c_char,
+    mode: *const c_char,
+    stream: *mut IoFile,
+) -> *mut IoFile {
+    signature_matches_libc!(core::mem::transmute(libc::freopen(
+        pathname,
+        mode,
+        core::mem::transmute(stream)
+    )));
+
+    let Some((open_flags, stream_flags)) = parse_mode(CStr::from_ptr(mode).to_bytes()) else {
+        set_errno(Errno::INVAL);
+        return ptr::null_mut();
+    };
+
+    let reopened = with_stream_lock(stream, |file| unsafe {
+        file.flush_buffer();
+        file.end_read();
+        file.release_buffer();
+        let old_descriptor = file.fileno;
+
+        let own_path = descriptor_path(old_descriptor);
+        let pathname = if pathname.is_null() {
+            own_path.as_ptr().cast::<c_char>()
+        } else {
+            pathname
+        };
+        let new_descriptor = translate_syscall_result(syscall!(
+            Syscall::OpenAt,
+            AT_FDCWD,
+            pathname,
+            open_flags.raw_value(),
+            NEW_FILE_MODE
+        ));
+        // Leaves `fileno` at -1, so a failure below cannot have `fclose` close a number handed out since.
+        file.backend_close();
+        if new_descriptor < 0 {
+            return false;
+        }
+
+        // A memory or cookie stream had no number to keep.
+        let new_descriptor = new_descriptor as i32;
+        if old_descriptor < 0 {
+            file.fileno = new_descriptor;
+        } else {
+            if new_descriptor != old_descriptor {
+                let close_on_exec = if open_flags.close_on_exec() {
+                    OFlags::ZERO.with_close_on_exec(true).raw_value()
+                } else {
+                    0
+                };
+                let result = syscall!(Syscall::Dup3, new_descriptor, old_descriptor, close_on_exec);
+                syscall!(Syscall::Close, new_descriptor);
+                if translate_syscall_result(result) < 0 {
+                    return false;
+                }
+            }
+            file.fileno = old_descriptor;
+        }
+
+        // The lock and list link stay; the rest is as a fresh `fopen` leaves it.
+        file.discard_write_pointers();
+        file.flags = stream_flags;
+        true
+    });
+
+    if reopened {
+        stream
+    } else {
+        fclose(stream);
+        ptr::null_mut()
+    }
+}
+
+/// `/proc/self/fd/<descriptor>`, terminated.
+fn descriptor_path(descriptor: i32) -> [u8; 32] {
+    const PREFIX: &[u8] = b"/proc/self/fd/";
+    let mut path = [0; 32];
+    path[..PREFIX.len()].copy_from_slice(PREFIX);
+    let digits = descriptor.to_string();
+    path[PREFIX.len()..][..digits.len()].copy_from_slice(digits.as_bytes());
+    path
+}
+
+/// Flushes and closes the stream, then frees it; the standard streams are statics, so only their buffers are freed.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fclose(stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::fclose(core::mem::transmute(stream)));
+
+    unlink_stream(stream);
+    let (flushed, closed) = with_stream_lock(stream, |file| unsafe {
+        let flushed = file.flush_buffer();
+        // Like glibc, leave a shared descriptor where this stream's reader stopped, not where it read ahead to.
+        file.end_read();
+        file.release_buffer();
+        (flushed, file.backend_close())
+    });
+
+    let standard_streams = [
+        &raw mut STDIN_FILE,
+        &raw mut STDOUT_FILE,
+        &raw mut STDERR_FILE,
+    ];
+    if !standard_streams.contains(&stream) {
+        drop(Box::from_raw(stream));
+    }
+
+    if flushed < 0 || closed < 0 {
+        EOF
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mode_maps_fopen_modes_to_open_flags() {
+        let (open_flags, stream_flags) = parse_mode(b"r").unwrap();
+        assert!(matches!(open_flags.access_mode(), Ok(AccessMode::ReadOnly)));
+        assert!(stream_flags.no_writes() && !stream_flags.no_reads());
+
+        let (open_flags, stream_flags) = parse_mode(b"wbx").unwrap();
+        assert!(matches!(
+            open_flags.access_mode(),
+            Ok(AccessMode::WriteOnly)
+        ));
+        assert!(open_flags.create() && open_flags.truncate() && open_flags.require_create());
+        assert!(stream_flags.no_reads() && !stream_flags.no_writes());
+
+        let (open_flags, stream_flags) = parse_mode(b"a+e,ccs=UTF-8").unwrap();
+        assert!(matches!(
+            open_flags.access_mode(),
+            Ok(AccessMode::ReadAndWrite)
+        ));
+        assert!(open_flags.append() && open_flags.close_on_exec() && !open_flags.truncate());
+        assert!(!stream_flags.no_reads() && !stream_flags.no_writes());
+
+        assert!(parse_mode(b"").is_none());
+        assert!(parse_mode(b"+r").is_none());
+    }
+
+    #[test]
+    fn descriptor_path_is_terminated() {
+        let path = descriptor_path(1023);
+        assert_eq!(
+            CStr::from_bytes_until_nul(&path).unwrap().to_bytes(),
+            b"/proc/self/fd/1023"
+        );
+    }
+}
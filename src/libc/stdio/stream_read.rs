@@ -0,0 +1,268 @@
+use std::{
+    ffi::{c_char, c_int, c_void},
+    ptr, slice,
+};
+
+use super::{stdin_ptr, with_stream_lock, IoFile, EOF};
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
+
+/// glibc's first `getline` allocation.
+const INITIAL_LINE_CAPACITY: usize = 120;
+
+/// Unlocked single-byte get.
+unsafe fn get_byte(file: &mut IoFile) -> i32 {
+    if file.read_ptr < file.read_end {
+        let byte = *file.read_ptr;
+        file.read_ptr = file.read_ptr.add(1);
+        byte as i32
+    } else {
+        file.uflow()
+    }
+}
+
+/// Locked/unlocked `get_byte` shared by the `getc` family.
+unsafe fn get_stream_byte(stream: *mut IoFile, lock: bool) -> i32 {
+    if lock {
+        with_stream_lock(stream, |file| unsafe { get_byte(file) })
+    } else {
+        get_byte(&mut *stream)
+    }
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgetc(stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::fgetc(core::mem::transmute(stream)));
+    get_stream_byte(stream, true)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getc(stream: *mut IoFile) -> i32 {
+    // `libc` has no `getc` (a C macro), but `getc`/`fgetc` share the glibc signature `fgetc` checks.
+    fgetc(stream)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getchar() -> i32 {
+    signature_matches_libc!(libc::getchar());
+    fgetc(stdin_ptr())
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgetc_unlocked(stream: *mut IoFile) -> i32 {
+    get_stream_byte(stream, false)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getc_unlocked(stream: *mut IoFile) -> i32 {
+    get_stream_byte(stream, false)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getchar_unlocked() -> i32 {
+    get_stream_byte(stdin_ptr(), false)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ungetc(character: i32, stream: *mut IoFile) -> i32 {
+    signature_matches_libc!(libc::ungetc(character, core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe { file.unread(character) })
+}
+
+/// Reads up to `size - 1` bytes, stopping after a newline, and terminates them. Null if nothing was read or a read
+/// failed partway, as glibc does.
+unsafe fn read_line(file: &mut IoFile, buffer: *mut c_char, size: c_int) -> *mut c_char {
+    if size <= 0 {
+        return ptr::null_mut();
+    }
+    let limit = size as usize - 1;
+    let had_error = file.flags.err_seen();
+
+    let mut copied = 0;
+    while copied < limit {
+        if file.underflow() == EOF {
+            break;
+        }
+        let buffered = file.buffered_input();
+        let take = buffered.len().min(limit - copied);
+        let (take, found_newline) = match buffered[..take].iter().position(|&byte| byte == b'\n') {
+            Some(newline) => (newline + 1, true),
+            None => (take, false),
+        };
+        ptr::copy_nonoverlapping(buffered.as_ptr(), buffer.add(copied).cast::<u8>(), take);
+        file.consume(take);
+        copied += take;
+        if found_newline {
+            break;
+        }
+    }
+
+    if (copied == 0 && limit > 0) || (file.flags.err_seen() && !had_error) {
+        return ptr::null_mut();
+    }
+    *buffer.add(copied) = 0;
+    buffer
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgets(buffer: *mut c_char, size: c_int, stream: *mut IoFile) -> *mut c_char {
+    signature_matches_libc!(libc::fgets(buffer, size, core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe { read_line(file, buffer, size) })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgets_unlocked(
+    buffer: *mut c_char,
+    size: c_int,
+    stream: *mut IoFile,
+) -> *mut c_char {
+    read_line(&mut *stream, buffer, size)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fread(
+    pointer: *mut c_void,
+    size: usize,
+    count: usize,
+    stream: *mut IoFile,
+) -> usize {
+    signature_matches_libc!(libc::fread(
+        pointer,
+        size,
+        count,
+        core::mem::transmute(stream)
+    ));
+    fread_common(pointer, size, count, stream, true)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fread_unlocked(
+    pointer: *mut c_void,
+    size: usize,
+    count: usize,
+    stream: *mut IoFile,
+) -> usize {
+    fread_common(pointer, size, count, stream, false)
+}
+
+unsafe fn fread_common(
+    pointer: *mut c_void,
+    size: usize,
+    count: usize,
+    stream: *mut IoFile,
+    lock: bool,
+) -> usize {
+    let Some(total) = size.checked_mul(count) else {
+        return 0;
+    };
+    if total == 0 {
+        return 0;
+    }
+
+    let bytes = slice::from_raw_parts_mut(pointer as *mut u8, total);
+    let read = if lock {
+        with_stream_lock(stream, |file| unsafe { file.read_bytes(bytes) })
+    } else {
+        (*stream).read_bytes(bytes)
+    };
+    // fread reports whole items, not bytes; a trailing partial item stays in the caller's buffer.
+    read / size
+}
+
+/// Reads through the next `delimiter` into `*line`, growing it with `realloc` as `getline` promises: the caller frees
+/// it with `free`. Returns the length read, or -1 at end of file or on error.
+unsafe fn read_delimited(
+    file: &mut IoFile,
+    line: *mut *mut c_char,
+    capacity: *mut usize,
+    delimiter: u8,
+) -> isize {
+    if line.is_null() || capacity.is_null() {
+        set_errno(Errno::INVAL);
+        return -1;
+    }
+    if (*line).is_null() || *capacity == 0 {
+        // `libc::realloc` binds to miros's own allocator once linked, the one the caller's `free` reaches.
+        let allocation = libc::realloc((*line).cast(), INITIAL_LINE_CAPACITY).cast::<c_char>();
+        if allocation.is_null() {
+            set_errno(Errno::NOMEM);
+            return -1;
+        }
+        *line = allocation;
+        *capacity = INITIAL_LINE_CAPACITY;
+    }
+
+    let mut length = 0;
+    loop {
+        if file.underflow() == EOF {
+            break;
+        }
+        let buffered = file.buffered_input();
+        let (take, found_delimiter) = match buffered.iter().position(|&byte| byte == delimiter) {
+            Some(index) => (index + 1, true),
+            None => (buffered.len(), false),
+        };
+
+        // Room for the chunk and the terminator, doubling so long lines stay linear.
+        let needed = length + take + 1;
+        if needed > *capacity {
+            let new_capacity = needed.max(*capacity * 2);
+            let allocation = libc::realloc((*line).cast(), new_capacity).cast::<c_char>();
+            if allocation.is_null() {
+                set_errno(Errno::NOMEM);
+                return -1;
+            }
+            *line = allocation;
+            *capacity = new_capacity;
+        }
+
+        ptr::copy_nonoverlapping(buffered.as_ptr(), (*line).add(length).cast::<u8>(), take);
+        file.consume(take);
+        length += take;
+        if found_delimiter {
+            break;
+        }
+    }
+
+    *(*line).add(length) = 0;
+    if length == 0 {
+        -1
+    } else {
+        length as isize
+    }
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getdelim(
+    line: *mut *mut c_char,
+    capacity: *mut usize,
+    delimiter: c_int,
+    stream: *mut IoFile,
+) -> isize {
+    // `libc` binds no `getdelim`; `getline`, which shares its shape, checks the signature.
+    with_stream_lock(stream, |file| unsafe {
+        read_delimited(file, line, capacity, delimiter as u8)
+    })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn __getdelim(
+    line: *mut *mut c_char,
+    capacity: *mut usize,
+    delimiter: c_int,
+    stream: *mut IoFile,
+) -> isize {
+    getdelim(line, capacity, delimiter, stream)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getline(
+    line: *mut *mut c_char,
+    capacity: *mut usize,
+    stream: *mut IoFile,
+) -> isize {
+    signature_matches_libc!(libc::getline(line, capacity, core::mem::transmute(stream)));
+    getdelim(line, capacity, b'\n' as c_int, stream)
+}
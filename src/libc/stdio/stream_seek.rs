@@ -0,0 +1,110 @@
+use std::mem::size_of;
+
+use super::{
+    file::{SEEK_CUR, SEEK_END, SEEK_SET},
+    with_stream_lock, IoFile,
+};
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
+
+/// glibc `fpos_t`: the offset, then an `mbstate_t` miros never has reason to fill in.
+#[repr(C)]
+struct FilePosition {
+    offset: i64,
+    state: u64,
+}
+
+const _: () = assert!(size_of::<FilePosition>() == size_of::<libc::fpos_t>());
+
+unsafe fn seek_stream(stream: *mut IoFile, offset: i64, whence: i32) -> i32 {
+    if !matches!(whence, SEEK_SET | SEEK_CUR | SEEK_END) {
+        set_errno(Errno::INVAL);
+        return -1;
+    }
+    let result = with_stream_lock(stream, |file| unsafe { file.seek(offset, whence) });
+    if result < 0 {
+        -1
+    } else {
+        0
+    }
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fseek(stream: *mut IoFile, offset: i64, whence: i32) -> i32 {
+    signature_matches_libc!(libc::fseek(core::mem::transmute(stream), offset, whence));
+    seek_stream(stream, offset, whence)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fseeko(stream: *mut IoFile, offset: i64, whence: i32) -> i32 {
+    signature_matches_libc!(libc::fseeko(core::mem::transmute(stream), offset, whence));
+    seek_stream(stream, offset, whence)
+}
+
+// LFS alias: `off_t` is already 64 bits on 64-bit targets.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fseeko64(stream: *mut IoFile, offset: i64, whence: i32) -> i32 {
+    seek_stream(stream, offset, whence)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ftell(stream: *mut IoFile) -> i64 {
+    signature_matches_libc!(libc::ftell(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe { file.tell() })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ftello(stream: *mut IoFile) -> i64 {
+    signature_matches_libc!(libc::ftello(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe { file.tell() })
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn ftello64(stream: *mut IoFile) -> i64 {
+    ftello(stream)
+}
+
+/// `fseek(stream, 0, SEEK_SET)`, which also forgets an earlier error.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn rewind(stream: *mut IoFile) {
+    signature_matches_libc!(libc::rewind(core::mem::transmute(stream)));
+    with_stream_lock(stream, |file| unsafe {
+        file.seek(0, SEEK_SET);
+        file.flags = file.flags.with_err_seen(false);
+    });
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgetpos(stream: *mut IoFile, position: *mut FilePosition) -> i32 {
+    signature_matches_libc!(libc::fgetpos(
+        core::mem::transmute(stream),
+        core::mem::transmute(position)
+    ));
+    let offset = with_stream_lock(stream, |file| unsafe { file.tell() });
+    if offset < 0 {
+        return -1;
+    }
+    position.write(FilePosition { offset, state: 0 });
+    0
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fgetpos64(stream: *mut IoFile, position: *mut FilePosition) -> i32 {
+    fgetpos(stream, position)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fsetpos(stream: *mut IoFile, position: *const FilePosition) -> i32 {
+    signature_matches_libc!(libc::fsetpos(
+        core::mem::transmute(stream),
+        core::mem::transmute(position)
+    ));
+    seek_stream(stream, (*position).offset, SEEK_SET)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fsetpos64(stream: *mut IoFile, position: *const FilePosition) -> i32 {
+    fsetpos(stream, position)
+}
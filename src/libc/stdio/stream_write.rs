@@ -1,4 +1,8 @@
-use std::{ffi::CStr, os::raw::c_void, slice};
+use std::{
+    ffi::{c_char, CStr},
+    os::raw::c_void,
+    slice,
+};
 
 use super::{stdout_ptr, with_stream_lock, IoFile, EOF};
 use crate::signature_matches_libc;
@@ -23,40 +27,40 @@ unsafe fn write_stream(stream: *mut IoFile, bytes: &[u8], lock: bool) -> usize {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fputc(character: i32, stream: *mut IoFile) -> i32 {
     signature_matches_libc!(libc::fputc(character, core::mem::transmute(stream)));
     with_stream_lock(stream, |file| unsafe { put_byte(file, character) })
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn putc(character: i32, stream: *mut IoFile) -> i32 {
     // `libc` has no `putc` (a C macro), but `putc`/`fputc` share the glibc signature `fputc` checks.
     fputc(character, stream)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn putchar(character: i32) -> i32 {
     signature_matches_libc!(libc::putchar(character));
     fputc(character, stdout_ptr())
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fputc_unlocked(character: i32, stream: *mut IoFile) -> i32 {
     put_byte(&mut *stream, character)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn putc_unlocked(character: i32, stream: *mut IoFile) -> i32 {
     fputc_unlocked(character, stream)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn putchar_unlocked(character: i32) -> i32 {
     fputc_unlocked(character, stdout_ptr())
 }
 
-unsafe fn fputs_common(string: *const i8, stream: *mut IoFile, lock: bool) -> i32 {
+unsafe fn fputs_common(string: *const c_char, stream: *mut IoFile, lock: bool) -> i32 {
     let bytes = CStr::from_ptr(string).to_bytes();
     if write_stream(stream, bytes, lock) == bytes.len() {
         bytes.len() as i32
@@ -65,19 +69,19 @@ unsafe fn fputs_common(string: *const i8, stream: *mut IoFile, lock: bool) -> i3
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn fputs(string: *const i8, stream: *mut IoFile) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fputs(string: *const c_char, stream: *mut IoFile) -> i32 {
     signature_matches_libc!(libc::fputs(string, core::mem::transmute(stream)));
     fputs_common(string, stream, true)
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn fputs_unlocked(string: *const i8, stream: *mut IoFile) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fputs_unlocked(string: *const c_char, stream: *mut IoFile) -> i32 {
     fputs_common(string, stream, false)
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn puts(string: *const i8) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn puts(string: *const c_char) -> i32 {
     signature_matches_libc!(libc::puts(string));
     let bytes = CStr::from_ptr(string).to_bytes();
 
@@ -93,7 +97,7 @@ unsafe extern "C" fn puts(string: *const i8) -> i32 {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fwrite(
     pointer: *const c_void,
     size: usize,
@@ -109,7 +113,7 @@ unsafe extern "C" fn fwrite(
     fwrite_common(pointer, size, count, stream, true)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn fwrite_unlocked(
     pointer: *const c_void,
     size: usize,
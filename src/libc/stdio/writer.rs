@@ -1,5 +1,5 @@
 use std::{
-    ffi::VaList,
+    ffi::{c_char, VaList},
     io::{self, Write},
 };
 
@@ -19,10 +19,10 @@ impl Write for FileWriter<'_> {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub(crate) unsafe extern "C" fn vfprintf(
     stream: *mut IoFile,
-    format: *const i8,
+    format: *const c_char,
     args: VaList<'_>,
 ) -> i32 {
     with_stream_lock(stream, |file| unsafe {
@@ -30,13 +30,13 @@ pub(crate) unsafe extern "C" fn vfprintf(
     })
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn fprintf(stream: *mut IoFile, format: *const i8, args: ...) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn fprintf(stream: *mut IoFile, format: *const c_char, args: ...) -> i32 {
     signature_matches_libc!(libc::fprintf(core::mem::transmute(stream), format, args));
     vfprintf(stream, format, args)
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn vprintf(format: *const i8, args: VaList<'_>) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vprintf(format: *const c_char, args: VaList<'_>) -> i32 {
     vfprintf(stdout_ptr(), format, args)
 }
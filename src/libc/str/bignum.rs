@@ -0,0 +1,259 @@
+//! Just enough unsigned bignum for exact conversions between binary floating point and decimal, both ways:
+//! little-endian `u32` limbs.
+
+use std::cmp::Ordering;
+
+/// Nine decimal digits, the most a `u32` limb holds.
+pub(crate) const CHUNK: u32 = 1_000_000_000;
+
+#[derive(Clone)]
+pub(crate) struct BigUint(Vec<u32>);
+
+impl BigUint {
+    pub fn from_u128(value: u128) -> Self {
+        Self((0..4).map(|limb| (value >> (32 * limb)) as u32).collect())
+    }
+
+    /// The number whose decimal digits (values 0–9, most significant first) are `digits`.
+    pub fn from_decimal_digits(digits: &[u8]) -> Self {
+        let mut value = Self(Vec::with_capacity(digits.len() / 9 + 1));
+        for chunk in digits.chunks(9) {
+            let chunk_value = chunk
+                .iter()
+                .fold(0, |value, &digit| value * 10 + digit as u32);
+            value.multiply_small(10u32.pow(chunk.len() as u32));
+            value.add_small(chunk_value);
+        }
+        value
+    }
+
+    pub fn shifted_left(self, shift: u32) -> Self {
+        let (limbs, bits) = ((shift / 32) as usize, shift % 32);
+        let mut result = vec![0; limbs + self.0.len() + 1];
+        for (index, &limb) in self.0.iter().enumerate() {
+            let wide = (limb as u64) << bits;
+            result[index + limbs] |= wide as u32;
+            result[index + limbs + 1] |= (wide >> 32) as u32;
+        }
+        Self(result)
+    }
+
+    /// The value modulo `2^bits`, with room for it to grow back to `bits` plus a limb.
+    pub fn low_bits(mut self, bits: u32) -> Self {
+        let limbs = bits.div_ceil(32) as usize + 1;
+        self.0.resize(limbs.max(self.0.len()), 0);
+        self.clear_from_bit(bits);
+        self.0.truncate(limbs);
+        self
+    }
+
+    fn clear_from_bit(&mut self, bit: u32) {
+        let (limb, offset) = ((bit / 32) as usize, bit % 32);
+        if limb < self.0.len() {
+            self.0[limb] &= (1 << offset) - 1;
+            for high in &mut self.0[limb + 1..] {
+                *high = 0;
+            }
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&limb| limb == 0)
+    }
+
+    /// Multiplies in place, growing by a limb when the product needs one.
+    pub fn multiply_small(&mut self, factor: u32) {
+        let mut carry = 0u64;
+        for limb in &mut self.0 {
+            let product = *limb as u64 * factor as u64 + carry;
+            *limb = product as u32;
+            carry = product >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    pub fn add_small(&mut self, addend: u32) {
+        let mut carry = addend;
+        for limb in &mut self.0 {
+            let (sum, overflowed) = limb.overflowing_add(carry);
+            *limb = sum;
+            if !overflowed {
+                return;
+            }
+            carry = 1;
+        }
+        if carry != 0 {
+            self.0.push(carry);
+        }
+    }
+
+    pub fn multiply_pow10(&mut self, mut power: u32) {
+        while power >= 9 {
+            self.multiply_small(CHUNK);
+            power -= 9;
+        }
+        self.multiply_small(10u32.pow(power));
+    }
+
+    /// Removes and returns the bits from `bit` up, which fit in a `u32` after one `multiply_small`.
+    pub fn take_bits_above(&mut self, bit: u32) -> u32 {
+        let (limb, offset) = ((bit / 32) as usize, bit % 32);
+        let low = self.0.get(limb).copied().unwrap_or(0) as u64;
+        let high = self.0.get(limb + 1).copied().unwrap_or(0) as u64;
+        let value = ((high << 32 | low) >> offset) as u32;
+        self.clear_from_bit(bit);
+        value
+    }
+
+    /// Divides by `divisor` in place, returning the remainder.
+    pub fn divide_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.0.iter_mut().rev() {
+            let current = remainder << 32 | *limb as u64;
+            *limb = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        remainder as u32
+    }
+
+    /// Decimal digits (values 0–9), most significant first, without leading zeros; empty for zero.
+    pub fn into_decimal_digits(mut self) -> Vec<u8> {
+        let mut chunks = Vec::new();
+        while !self.is_zero() {
+            chunks.push(self.divide_small(CHUNK));
+            self.trim();
+        }
+        let mut digits = Vec::with_capacity(chunks.len() * 9);
+        for chunk in chunks.iter().rev() {
+            for position in (0..9).rev() {
+                let digit = (chunk / 10u32.pow(position) % 10) as u8;
+                if !digits.is_empty() || digit != 0 {
+                    digits.push(digit);
+                }
+            }
+        }
+        digits
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    /// The position of the highest set bit plus one; 0 for zero.
+    pub fn bit_length(&self) -> u32 {
+        self.0.iter().rposition(|&limb| limb != 0).map_or(0, |top| {
+            top as u32 * 32 + (32 - self.0[top].leading_zeros())
+        })
+    }
+
+    /// `count` (at most 128) bits starting at `start`.
+    pub fn bits(&self, start: u32, count: u32) -> u128 {
+        let (first, offset) = ((start / 32) as usize, start % 32);
+        let limb = |index: usize| self.0.get(first + index).copied().unwrap_or(0) as u128;
+        let mut value =
+            (0..4).fold(0, |value, index| value | limb(index) << (32 * index)) >> offset;
+        if offset > 0 {
+            value |= limb(4) << (128 - offset);
+        }
+        if count < 128 {
+            value &= (1 << count) - 1;
+        }
+        value
+    }
+
+    /// Whether any bit below `bit` is set.
+    pub fn any_bits_below(&self, bit: u32) -> bool {
+        let (limb, offset) = ((bit / 32) as usize, bit % 32);
+        self.0[..limb.min(self.0.len())].iter().any(|&low| low != 0)
+            || self
+                .0
+                .get(limb)
+                .is_some_and(|&partial| partial & ((1 << offset) - 1) != 0)
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        let length = self.0.len().max(other.0.len());
+        (0..length)
+            .rev()
+            .map(|index| {
+                let left = self.0.get(index).copied().unwrap_or(0);
+                let right = other.0.get(index).copied().unwrap_or(0);
+                left.cmp(&right)
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Subtracts `other`, which must not be larger.
+    fn subtract(&mut self, other: &Self) {
+        let mut borrow = 0i64;
+        for (index, limb) in self.0.iter_mut().enumerate() {
+            let difference =
+                *limb as i64 - other.0.get(index).copied().unwrap_or(0) as i64 - borrow;
+            *limb = difference as u32;
+            borrow = (difference < 0) as i64;
+        }
+    }
+
+    fn shift_right_one(&mut self) {
+        let mut carry = 0;
+        for limb in self.0.iter_mut().rev() {
+            let next_carry = *limb & 1;
+            *limb = *limb >> 1 | carry << 31;
+            carry = next_carry;
+        }
+    }
+
+    /// Long division by `divisor`, for a quotient known to fit in 128 bits: the quotient, and whether anything was
+    /// left over.
+    pub fn divide(mut self, divisor: &Self) -> (u128, bool) {
+        let quotient_bits = (self.bit_length() + 1).saturating_sub(divisor.bit_length());
+        debug_assert!(quotient_bits <= 128);
+        let mut shifted = divisor
+            .clone()
+            .shifted_left(quotient_bits.saturating_sub(1));
+        let mut quotient = 0u128;
+        for bit in (0..quotient_bits).rev() {
+            if self.compare(&shifted).is_ge() {
+                self.subtract(&shifted);
+                quotient |= 1 << bit;
+            }
+            shifted.shift_right_one();
+        }
+        (quotient, !self.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigUint;
+
+    #[test]
+    fn decimal_digits_round_trip() {
+        let digits =
+            b"\x01\x02\x03\x04\x05\x06\x07\x08\x09\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x00\x01";
+        let value = BigUint::from_decimal_digits(digits);
+        assert_eq!(value.into_decimal_digits(), digits);
+    }
+
+    #[test]
+    fn division_and_bits() {
+        let mut dividend = BigUint::from_u128(1);
+        dividend.multiply_pow10(40);
+        let mut divisor = BigUint::from_u128(1);
+        divisor.multiply_pow10(20);
+        divisor.add_small(1);
+        let (quotient, inexact) = dividend.clone().divide(&divisor);
+        assert_eq!(quotient, 99_999_999_999_999_999_999);
+        assert!(inexact);
+
+        assert_eq!(dividend.bit_length(), 133);
+        assert_eq!(dividend.bits(5, 128), 10u128.pow(35) * 3125);
+        assert!(!dividend.any_bits_below(40));
+        assert!(dividend.any_bits_below(41));
+    }
+}
@@ -1,4 +1,9 @@
+mod bignum;
 pub mod printf;
+pub(crate) mod scanf;
+pub(crate) mod strtod;
+#[cfg(test)]
+mod test_random;
 
 use core::ffi::{c_char, c_int, c_void};
 use std::ptr;
@@ -34,25 +39,25 @@ unsafe fn first_byte_difference(
         })
 }
 
-#[cfg_attr(not(test), no_mangle)]
-pub unsafe extern "C" fn strlen(start_character: *mut i8) -> usize {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+pub unsafe extern "C" fn strlen(start_character: *mut c_char) -> usize {
     signature_matches_libc!(libc::strlen(start_character));
     c_string_bytes(start_character).count()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strcmp(left: *const c_char, right: *const c_char) -> c_int {
     signature_matches_libc!(libc::strcmp(left, right));
     first_byte_difference(left, right, 0..).unwrap_unchecked()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strncmp(left: *const c_char, right: *const c_char, length: usize) -> c_int {
     signature_matches_libc!(libc::strncmp(left, right, length));
     first_byte_difference(left, right, 0..length).unwrap_or(0)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strcpy(destination: *mut c_char, source: *const c_char) -> *mut c_char {
     signature_matches_libc!(libc::strcpy(destination, source));
     c_string_bytes(source)
@@ -62,7 +67,7 @@ unsafe extern "C" fn strcpy(destination: *mut c_char, source: *const c_char) ->
     destination
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strncpy(
     destination: *mut c_char,
     source: *const c_char,
@@ -78,7 +83,7 @@ unsafe extern "C" fn strncpy(
     destination
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strchr(string: *const c_char, character: c_int) -> *mut c_char {
     signature_matches_libc!(libc::strchr(string, character));
     let needle = character as u8;
@@ -91,7 +96,7 @@ unsafe extern "C" fn strchr(string: *const c_char, character: c_int) -> *mut c_c
         .unwrap_unchecked()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strrchr(string: *const c_char, character: c_int) -> *mut c_char {
     signature_matches_libc!(libc::strrchr(string, character));
     let needle = character as u8;
@@ -104,7 +109,7 @@ unsafe extern "C" fn strrchr(string: *const c_char, character: c_int) -> *mut c_
         .unwrap_or(ptr::null_mut())
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strspn(string: *const c_char, accept: *const c_char) -> usize {
     signature_matches_libc!(libc::strspn(string, accept));
     let accepted = byte_membership(accept);
@@ -113,7 +118,7 @@ unsafe extern "C" fn strspn(string: *const c_char, accept: *const c_char) -> usi
         .count()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn strcspn(string: *const c_char, reject: *const c_char) -> usize {
     signature_matches_libc!(libc::strcspn(string, reject));
     let rejected = byte_membership(reject);
@@ -122,7 +127,7 @@ unsafe extern "C" fn strcspn(string: *const c_char, reject: *const c_char) -> us
         .count()
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn memchr(memory: *const c_void, character: c_int, length: usize) -> *mut c_void {
     signature_matches_libc!(libc::memchr(memory, character, length));
     let needle = character as u8;
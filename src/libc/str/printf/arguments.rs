@@ -0,0 +1,249 @@
+//! Where conversions take their arguments from. Most formats consume the `va_list` front to back; a format that
+//! numbers its arguments (`%2$s`, `*1$`) may use them in any order, so they are read out first, by position, into a
+//! table. A `va_list` can only be walked in order and only with the right types, so the types come from a pass over
+//! the format. scanf's numbered formats share the table: all they take are pointers.
+
+use std::ffi::{c_char, c_void, VaArgSafe, VaList};
+
+use super::{
+    float::{self, FloatValue},
+    parse::{PrintfItem, PrintfParser},
+    specifier::{Conversion, DimensionSpecifier, LengthModifier, PrintfSpecifier},
+};
+use crate::libc::errno::Errno;
+
+/// glibc's `NL_ARGMAX`: the highest argument number a format may use.
+const MAX_POSITION: usize = 4096;
+
+pub trait ArgumentSource {
+    unsafe fn next_arg<T: Argument>(&mut self) -> T;
+
+    unsafe fn next_long_double(&mut self) -> FloatValue;
+
+    /// Points the next read at argument `position` (1-based). Only numbered formats select; a `va_list` never is one.
+    fn select(&mut self, _position: usize) {}
+}
+
+impl ArgumentSource for VaList<'_> {
+    unsafe fn next_arg<T: Argument>(&mut self) -> T {
+        VaList::next_arg::<T>(self)
+    }
+
+    unsafe fn next_long_double(&mut self) -> FloatValue {
+        float::next_long_double(self)
+    }
+}
+
+/// A type printf reads, and how it comes back out of a table slot. A slot of another kind reads as zero: the format
+/// used one position as two types, which C leaves undefined.
+pub trait Argument: VaArgSafe {
+    fn from_slot(slot: Slot) -> Self;
+}
+
+macro_rules! integer_argument {
+    ($($type:ty),*) => {
+        $(
+            impl Argument for $type {
+                fn from_slot(slot: Slot) -> Self {
+                    match slot {
+                        Slot::Integer(bits) => bits as Self,
+                        _ => 0,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_argument!(i32, u32, i64, u64, isize, usize);
+
+impl Argument for f64 {
+    fn from_slot(slot: Slot) -> Self {
+        match slot {
+            Slot::Double(value) => value,
+            _ => 0.0,
+        }
+    }
+}
+
+impl<T> Argument for *const T {
+    fn from_slot(slot: Slot) -> Self {
+        <*mut T>::from_slot(slot).cast_const()
+    }
+}
+
+impl<T> Argument for *mut T {
+    fn from_slot(slot: Slot) -> Self {
+        match slot {
+            Slot::Pointer(pointer) => pointer.cast(),
+            _ => std::ptr::null_mut(),
+        }
+    }
+}
+
+/// How a conversion's argument is passed, which is all reading it out of a `va_list` needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgumentType {
+    /// `int`, and everything promoted to it.
+    Int,
+    /// `long`, `long long`, `intmax_t`, `size_t`, `ptrdiff_t`: all 64 bits here.
+    Long,
+    Double,
+    LongDouble,
+    Pointer,
+}
+
+impl ArgumentType {
+    /// The type `spec`'s value is read as; `None` for `%m`, which reads nothing.
+    fn of(spec: &PrintfSpecifier) -> Option<Self> {
+        Some(match spec.conversion {
+            Conversion::SignedInt
+            | Conversion::UnsignedInt
+            | Conversion::Octal
+            | Conversion::Hex { .. } => match spec.length {
+                LengthModifier::None
+                | LengthModifier::HalfHalf
+                | LengthModifier::Half
+                | LengthModifier::LongDouble => Self::Int,
+                LengthModifier::Long
+                | LengthModifier::LongLong
+                | LengthModifier::Size
+                | LengthModifier::Ptrdiff
+                | LengthModifier::IntMax => Self::Long,
+            },
+            Conversion::Float(_) if spec.length == LengthModifier::LongDouble => Self::LongDouble,
+            Conversion::Float(_) => Self::Double,
+            // `wint_t` for `%lc` is as wide as `int`.
+            Conversion::Char => Self::Int,
+            Conversion::String | Conversion::Pointer | Conversion::CharCount => Self::Pointer,
+            Conversion::ErrorMessage => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Slot {
+    Integer(u64),
+    Double(f64),
+    LongDouble(FloatValue),
+    Pointer(*mut c_void),
+}
+
+/// The arguments of a numbered format, in position order.
+pub struct NumberedArguments {
+    slots: Vec<Slot>,
+    next: usize,
+}
+
+impl NumberedArguments {
+    /// Reads out every argument `format` uses, if it numbers them; `Ok(None)` if it doesn't. A format that mixes
+    /// numbered and unnumbered arguments, skips a position or goes past `NL_ARGMAX` is `EINVAL`, as in musl: the
+    /// types of skipped arguments are unknown, so nothing after them can be read.
+    pub unsafe fn collect(
+        format: *const c_char,
+        args: &mut VaList<'_>,
+    ) -> Result<Option<Self>, Errno> {
+        let mut types: Vec<Option<ArgumentType>> = Vec::new();
+        let mut numbered = None;
+        for item in PrintfParser::new(format) {
+            let PrintfItem::Specifier(spec) = item else {
+                continue;
+            };
+            let dimensions = [spec.width, spec.precision]
+                .into_iter()
+                .filter_map(|dimension| match dimension {
+                    DimensionSpecifier::Unspecified | DimensionSpecifier::Fixed(_) => None,
+                    DimensionSpecifier::FromNextArg => Some((None, ArgumentType::Int)),
+                    DimensionSpecifier::FromNumberedArg(position) => {
+                        Some((Some(position), ArgumentType::Int))
+                    }
+                });
+            let value = ArgumentType::of(&spec).map(|kind| (spec.argument, kind));
+
+            for (position, kind) in dimensions.chain(value) {
+                if *numbered.get_or_insert(position.is_some()) != position.is_some() {
+                    return Err(Errno::INVAL);
+                }
+                let Some(position) = position else {
+                    continue;
+                };
+                if !(1..=MAX_POSITION).contains(&position) {
+                    return Err(Errno::INVAL);
+                }
+                if types.len() < position {
+                    types.resize(position, None);
+                }
+                types[position - 1] = Some(kind);
+            }
+        }
+        if numbered != Some(true) {
+            return Ok(None);
+        }
+
+        let mut slots = Vec::with_capacity(types.len());
+        for kind in types {
+            slots.push(match kind.ok_or(Errno::INVAL)? {
+                ArgumentType::Int => Slot::Integer(args.next_arg::<i32>() as u64),
+                ArgumentType::Long => Slot::Integer(args.next_arg::<u64>()),
+                ArgumentType::Double => Slot::Double(args.next_arg::<f64>()),
+                ArgumentType::LongDouble => Slot::LongDouble(float::next_long_double(args)),
+                ArgumentType::Pointer => Slot::Pointer(args.next_arg::<*mut c_void>()),
+            });
+        }
+        Ok(Some(Self { slots, next: 0 }))
+    }
+
+    /// Reads out the pointers of a scanf format, given the argument number of each conversion that stores; `Ok(None)`
+    /// if none is numbered. Mixing and out-of-range numbers are `EINVAL` as in [`collect`](Self::collect), but every
+    /// argument is a pointer, so a skipped position is still read past.
+    pub unsafe fn collect_pointers(
+        positions: impl IntoIterator<Item = Option<usize>>,
+        args: &mut VaList<'_>,
+    ) -> Result<Option<Self>, Errno> {
+        let mut numbered = None;
+        let mut count = 0;
+        for position in positions {
+            if *numbered.get_or_insert(position.is_some()) != position.is_some() {
+                return Err(Errno::INVAL);
+            }
+            let Some(position) = position else {
+                continue;
+            };
+            if !(1..=MAX_POSITION).contains(&position) {
+                return Err(Errno::INVAL);
+            }
+            count = count.max(position);
+        }
+        if numbered != Some(true) {
+            return Ok(None);
+        }
+
+        let slots = (0..count)
+            .map(|_| Slot::Pointer(args.next_arg::<*mut c_void>()))
+            .collect();
+        Ok(Some(Self { slots, next: 0 }))
+    }
+
+    fn take(&mut self) -> Slot {
+        let slot = self.slots[self.next];
+        self.next += 1;
+        slot
+    }
+}
+
+impl ArgumentSource for NumberedArguments {
+    unsafe fn next_arg<T: Argument>(&mut self) -> T {
+        T::from_slot(self.take())
+    }
+
+    unsafe fn next_long_double(&mut self) -> FloatValue {
+        match self.take() {
+            Slot::LongDouble(value) => value,
+            _ => FloatValue::from_f64(0.0),
+        }
+    }
+
+    fn select(&mut self, position: usize) {
+        self.next = position - 1;
+    }
+}
@@ -0,0 +1,660 @@
+//! `%f %e %g %a` for `double` and `long double`. Decimal conversions are exact: the binary value is expanded with
+//! arbitrary precision and rounded once, to nearest with ties to even, which is glibc's output in the default rounding
+//! mode.
+
+use std::ffi::VaList;
+
+use super::{
+    arguments::ArgumentSource,
+    specifier::{FloatFormat, LengthModifier, ResolvedSpecifier},
+};
+use crate::libc::str::bignum::{BigUint, CHUNK};
+
+/// glibc's precision when none is given, for every conversion but `%a`.
+const DEFAULT_PRECISION: usize = 6;
+
+/// A `double` or `long double` argument, decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatValue {
+    Finite { negative: bool, parts: FloatParts },
+    Infinite { negative: bool },
+    Nan { negative: bool },
+}
+
+/// A finite value as `mantissa × 2^exponent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatParts {
+    mantissa: u128,
+    exponent: i32,
+    /// Mantissa bits `%a` shows after its leading hex digit: 52 for `double`, 60 for x87 (whose leading digit is the
+    /// top nibble of the explicit mantissa, as glibc prints it) and 112 for binary128.
+    fraction_bits: u32,
+}
+
+impl FloatValue {
+    pub fn from_f64(value: f64) -> Self {
+        let bits = value.to_bits();
+        let negative = bits >> 63 != 0;
+        let biased = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = (bits & ((1 << 52) - 1)) as u128;
+        match biased {
+            0x7ff if fraction == 0 => Self::Infinite { negative },
+            0x7ff => Self::Nan { negative },
+            0 => Self::finite(negative, fraction, -1074, 52),
+            _ => Self::finite(negative, fraction | 1 << 52, biased - 1075, 52),
+        }
+    }
+
+    /// The x87 80-bit format: a 64-bit mantissa with an explicit integer bit, then sign and 15-bit exponent.
+    pub fn from_x87(bytes: [u8; 16]) -> Self {
+        let mantissa = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let sign_exponent = u16::from_le_bytes([bytes[8], bytes[9]]);
+        let negative = sign_exponent >> 15 != 0;
+        let biased = (sign_exponent & 0x7fff) as i32;
+        match biased {
+            0x7fff if mantissa << 1 == 0 => Self::Infinite { negative },
+            0x7fff => Self::Nan { negative },
+            _ => Self::finite(negative, mantissa as u128, biased.max(1) - 16383 - 63, 60),
+        }
+    }
+
+    /// IEEE binary128, AArch64's `long double`.
+    pub fn from_binary128(bits: u128) -> Self {
+        let negative = bits >> 127 != 0;
+        let biased = ((bits >> 112) & 0x7fff) as i32;
+        let fraction = bits & ((1 << 112) - 1);
+        match biased {
+            0x7fff if fraction == 0 => Self::Infinite { negative },
+            0x7fff => Self::Nan { negative },
+            0 => Self::finite(negative, fraction, -16494, 112),
+            _ => Self::finite(negative, fraction | 1 << 112, biased - 16495, 112),
+        }
+    }
+
+    fn finite(negative: bool, mantissa: u128, exponent: i32, fraction_bits: u32) -> Self {
+        Self::Finite {
+            negative,
+            parts: FloatParts {
+                mantissa,
+                exponent,
+                fraction_bits,
+            },
+        }
+    }
+
+    /// Takes the next argument: a `long double` for `L`, otherwise a `double` (which a `float` was promoted to).
+    pub unsafe fn from_args(length: LengthModifier, args: &mut impl ArgumentSource) -> Self {
+        if length == LengthModifier::LongDouble {
+            args.next_long_double()
+        } else {
+            Self::from_f64(args.next_arg::<f64>())
+        }
+    }
+
+    fn is_negative(self) -> bool {
+        match self {
+            Self::Finite { negative, .. }
+            | Self::Infinite { negative }
+            | Self::Nan { negative } => negative,
+        }
+    }
+}
+
+/// `long double` is class X87 in the System V ABI: never in registers, always in the overflow area, 16-byte aligned.
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn next_long_double(args: &mut VaList<'_>) -> FloatValue {
+    /// The `__va_list_tag` a `VaList` is.
+    #[repr(C)]
+    struct VaListTag {
+        gp_offset: u32,
+        fp_offset: u32,
+        overflow_arg_area: *mut u8,
+        reg_save_area: *mut u8,
+    }
+
+    let tag = &mut *(args as *mut VaList<'_>).cast::<VaListTag>();
+    let address = tag
+        .overflow_arg_area
+        .map_addr(|address| address.next_multiple_of(16));
+    tag.overflow_arg_area = address.add(16);
+    FloatValue::from_x87(address.cast::<[u8; 16]>().read())
+}
+
+/// AAPCS64 passes binary128 in a vector register while any remain (16-byte slots below `vr_top`), then on the stack,
+/// 16-byte aligned.
+#[cfg(target_arch = "aarch64")]
+pub unsafe fn next_long_double(args: &mut VaList<'_>) -> FloatValue {
+    /// The AAPCS64 `va_list` a `VaList` is.
+    #[repr(C)]
+    struct VaListTag {
+        stack: *mut u8,
+        gr_top: *mut u8,
+        vr_top: *mut u8,
+        gr_offs: i32,
+        vr_offs: i32,
+    }
+
+    let tag = &mut *(args as *mut VaList<'_>).cast::<VaListTag>();
+    let address = if tag.vr_offs < 0 {
+        let address = tag.vr_top.offset(tag.vr_offs as isize);
+        tag.vr_offs += 16;
+        address
+    } else {
+        let address = tag.stack.map_addr(|address| address.next_multiple_of(16));
+        tag.stack = address.add(16);
+        address
+    };
+    FloatValue::from_binary128(address.cast::<u128>().read_unaligned())
+}
+
+/// A conversion's output, for the formatter to pad: zeros go between `prefix` and `body`, and only for finite values.
+pub struct RenderedFloat {
+    pub sign: Option<u8>,
+    pub prefix: &'static [u8],
+    pub body: Vec<u8>,
+    pub finite: bool,
+}
+
+pub fn render(spec: &ResolvedSpecifier, format: FloatFormat, value: FloatValue) -> RenderedFloat {
+    let sign = spec.sign_mode.sign_byte(value.is_negative());
+    let uppercase = match format {
+        FloatFormat::Fixed { uppercase }
+        | FloatFormat::Scientific { uppercase }
+        | FloatFormat::General { uppercase }
+        | FloatFormat::Hex { uppercase } => uppercase,
+    };
+
+    let parts = match value {
+        FloatValue::Finite { parts, .. } => parts,
+        FloatValue::Infinite { .. } | FloatValue::Nan { .. } => {
+            let word: &[u8] = match (value, uppercase) {
+                (FloatValue::Infinite { .. }, false) => b"inf",
+                (FloatValue::Infinite { .. }, true) => b"INF",
+                (_, false) => b"nan",
+                (_, true) => b"NAN",
+            };
+            return RenderedFloat {
+                sign,
+                prefix: b"",
+                body: word.to_vec(),
+                finite: false,
+            };
+        }
+    };
+
+    let precision = spec.precision.unwrap_or(DEFAULT_PRECISION);
+    let (prefix, mut body): (&'static [u8], _) = match format {
+        FloatFormat::Fixed { .. } => (b"", render_fixed(parts, precision, spec.alternate)),
+        FloatFormat::Scientific { .. } => {
+            (b"", render_scientific(parts, precision, spec.alternate))
+        }
+        FloatFormat::General { .. } => (b"", render_general(parts, precision, spec.alternate)),
+        FloatFormat::Hex { uppercase } => (
+            if uppercase { b"0X" } else { b"0x" },
+            render_hex(parts, spec.precision, spec.alternate),
+        ),
+    };
+    if uppercase {
+        body.make_ascii_uppercase();
+    }
+    RenderedFloat {
+        sign,
+        prefix,
+        body,
+        finite: true,
+    }
+}
+
+fn render_fixed(parts: FloatParts, precision: usize, alternate: bool) -> Vec<u8> {
+    let mut decimal = Decimal::expand(parts, DigitLimit::Fraction(precision));
+    decimal.round(decimal.point as i64 + precision as i64);
+    decimal.write_fixed(precision, alternate)
+}
+
+fn render_scientific(parts: FloatParts, precision: usize, alternate: bool) -> Vec<u8> {
+    let mut decimal = Decimal::expand(parts, DigitLimit::Significant(precision + 1));
+    decimal.round(precision as i64 + 1);
+    decimal.write_scientific(precision, alternate)
+}
+
+/// `%g`: `P` significant digits, in `%e` style if the exponent `X` is below -4 or at least `P`, else `%f` style; without
+/// `#`, trailing zeros (and a bare point) go.
+fn render_general(parts: FloatParts, precision: usize, alternate: bool) -> Vec<u8> {
+    let significant = precision.max(1);
+    let mut decimal = Decimal::expand(parts, DigitLimit::Significant(significant));
+    decimal.round(significant as i64);
+
+    let exponent = decimal.exponent() as i64;
+    let (mut body, fraction_digits) = if exponent < -4 || exponent >= significant as i64 {
+        let precision = significant - 1;
+        (decimal.write_scientific(precision, alternate), precision)
+    } else {
+        let precision = (significant as i64 - 1 - exponent) as usize;
+        (decimal.write_fixed(precision, alternate), precision)
+    };
+    if !alternate && fraction_digits > 0 {
+        strip_fraction_zeros(&mut body);
+    }
+    body
+}
+
+/// Drops trailing zeros after the point, and the point itself if nothing is left after it, keeping any exponent.
+fn strip_fraction_zeros(body: &mut Vec<u8>) {
+    let Some(point) = body.iter().position(|&byte| byte == b'.') else {
+        return;
+    };
+    let mantissa_end = body
+        .iter()
+        .position(|&byte| byte == b'e')
+        .unwrap_or(body.len());
+    let mut end = mantissa_end;
+    while end > point + 1 && body[end - 1] == b'0' {
+        end -= 1;
+    }
+    if end == point + 1 {
+        end = point;
+    }
+    body.drain(end..mantissa_end);
+}
+
+/// `%a`: the leading hex digit, the fraction's nibbles (all of them, less trailing zeros, unless a precision rounds or
+/// pads them) and a binary exponent. Zero is `0x0p+0`.
+fn render_hex(parts: FloatParts, precision: Option<usize>, alternate: bool) -> Vec<u8> {
+    let fraction_digits = (parts.fraction_bits / 4) as usize;
+    let mut exponent = if parts.mantissa == 0 {
+        0
+    } else {
+        parts.exponent + parts.fraction_bits as i32
+    };
+
+    let (mut mantissa, digits) = match precision {
+        Some(precision) if precision < fraction_digits => {
+            let dropped = (4 * (fraction_digits - precision)) as u32;
+            (round_shift(parts.mantissa, dropped), precision)
+        }
+        Some(precision) => (parts.mantissa, precision),
+        None => {
+            let mut digits = fraction_digits;
+            let mut mantissa = parts.mantissa;
+            while digits > 0 && mantissa & 0xf == 0 {
+                mantissa >>= 4;
+                digits -= 1;
+            }
+            (mantissa, digits)
+        }
+    };
+    let shown_bits = 4 * digits.min(fraction_digits) as u32;
+    let mut leading = mantissa >> shown_bits;
+    // x87's leading nibble can carry out to 0x10: glibc renormalizes it to 0x1 with a larger exponent.
+    if leading == 0x10 {
+        leading = 1;
+        mantissa = 0;
+        exponent += 4;
+    }
+
+    let mut body = Vec::with_capacity(digits + 16);
+    body.push(hex_digit(leading as u8));
+    if digits > 0 || alternate {
+        body.push(b'.');
+    }
+    for index in (0..digits.min(fraction_digits)).rev() {
+        body.push(hex_digit((mantissa >> (4 * index) & 0xf) as u8));
+    }
+    body.resize(body.len() + digits.saturating_sub(fraction_digits), b'0');
+    body.push(b'p');
+    body.push(if exponent < 0 { b'-' } else { b'+' });
+    body.extend_from_slice(exponent.unsigned_abs().to_string().as_bytes());
+    body
+}
+
+/// `value >> shift`, rounded to nearest with ties to even.
+fn round_shift(value: u128, shift: u32) -> u128 {
+    let kept = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if remainder > half || (remainder == half && kept & 1 == 1) {
+        kept + 1
+    } else {
+        kept
+    }
+}
+
+fn hex_digit(value: u8) -> u8 {
+    b"0123456789abcdef"[value as usize]
+}
+
+/// How many digits `Decimal::expand` must produce: one more than are kept, so rounding sees the first dropped digit.
+#[derive(Clone, Copy)]
+enum DigitLimit {
+    Significant(usize),
+    Fraction(usize),
+}
+
+/// A decimal value `0.d₁d₂d₃… × 10^point`, with no leading or trailing zero digits (zero has none), plus whether
+/// nonzero digits follow the ones expanded.
+struct Decimal {
+    digits: Vec<u8>,
+    point: i32,
+    inexact: bool,
+}
+
+impl Decimal {
+    /// Expands `parts` exactly, as far as `limit` needs.
+    fn expand(parts: FloatParts, limit: DigitLimit) -> Self {
+        let mut decimal = Self {
+            digits: Vec::new(),
+            point: 0,
+            inexact: false,
+        };
+        if parts.mantissa == 0 {
+            return decimal;
+        }
+
+        let fraction_bits = (-parts.exponent).max(0) as u32;
+        let integer = if fraction_bits == 0 {
+            BigUint::from_u128(parts.mantissa).shifted_left(parts.exponent as u32)
+        } else if fraction_bits < 128 {
+            BigUint::from_u128(parts.mantissa >> fraction_bits)
+        } else {
+            BigUint::from_u128(0)
+        };
+        decimal.digits = integer.into_decimal_digits();
+        decimal.point = decimal.digits.len() as i32;
+        if fraction_bits == 0 {
+            decimal.trim();
+            return decimal;
+        }
+
+        // The fraction is `remainder / 2^fraction_bits`; scaling it by 10⁹ moves the next nine digits above the point.
+        let mut remainder = BigUint::from_u128(parts.mantissa).low_bits(fraction_bits);
+        let mut fraction_digits = 0;
+        while !remainder.is_zero() {
+            let enough = match limit {
+                DigitLimit::Significant(count) => decimal.digits.len() > count,
+                DigitLimit::Fraction(count) => fraction_digits > count,
+            };
+            if enough {
+                decimal.inexact = true;
+                break;
+            }
+            remainder.multiply_small(CHUNK);
+            let chunk = remainder.take_bits_above(fraction_bits);
+            for position in (0..9).rev() {
+                let digit = (chunk / 10u32.pow(position) % 10) as u8;
+                if decimal.digits.is_empty() && digit == 0 {
+                    decimal.point -= 1;
+                } else {
+                    decimal.digits.push(digit);
+                }
+            }
+            fraction_digits += 9;
+        }
+        decimal.trim();
+        decimal
+    }
+
+    fn trim(&mut self) {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+
+    /// The `%e` exponent of the leading digit; 0 for zero.
+    fn exponent(&self) -> i32 {
+        if self.digits.is_empty() {
+            0
+        } else {
+            self.point - 1
+        }
+    }
+
+    /// Keeps the first `keep` digits (none if negative), rounding to nearest with ties to even.
+    fn round(&mut self, keep: i64) {
+        if keep >= self.digits.len() as i64 {
+            return;
+        }
+        if keep < 0 {
+            self.digits.clear();
+            return;
+        }
+        let keep = keep as usize;
+        let first_dropped = self.digits[keep];
+        let beyond_half = self.inexact || self.digits[keep + 1..].iter().any(|&digit| digit != 0);
+        let last_kept_odd = keep > 0 && self.digits[keep - 1] % 2 == 1;
+        let round_up = first_dropped > 5 || (first_dropped == 5 && (beyond_half || last_kept_odd));
+
+        self.digits.truncate(keep);
+        self.inexact = false;
+        if round_up {
+            // Nines carry; if every digit was a nine, the value gains a digit at the front.
+            while self.digits.last() == Some(&9) {
+                self.digits.pop();
+            }
+            match self.digits.last_mut() {
+                Some(digit) => *digit += 1,
+                None => {
+                    self.digits.push(1);
+                    self.point += 1;
+                }
+            }
+        }
+        self.trim();
+    }
+
+    fn digit_at(&self, index: i64) -> u8 {
+        if index < 0 || index >= self.digits.len() as i64 {
+            b'0'
+        } else {
+            b'0' + self.digits[index as usize]
+        }
+    }
+
+    fn write_fixed(&self, precision: usize, alternate: bool) -> Vec<u8> {
+        let integer_digits = self.point.max(1) as i64;
+        let mut body = Vec::with_capacity(integer_digits as usize + precision + 1);
+        if self.point <= 0 {
+            body.push(b'0');
+        } else {
+            body.extend((0..integer_digits).map(|index| self.digit_at(index)));
+        }
+        if precision > 0 || alternate {
+            body.push(b'.');
+        }
+        let point = self.point as i64;
+        body.extend((0..precision as i64).map(|index| self.digit_at(point + index)));
+        body
+    }
+
+    fn write_scientific(&self, precision: usize, alternate: bool) -> Vec<u8> {
+        let mut body = Vec::with_capacity(precision + 8);
+        body.push(self.digit_at(0));
+        if precision > 0 || alternate {
+            body.push(b'.');
+        }
+        body.extend((1..=precision as i64).map(|index| self.digit_at(index)));
+
+        let exponent = self.exponent();
+        body.push(b'e');
+        body.push(if exponent < 0 { b'-' } else { b'+' });
+        let magnitude = exponent.unsigned_abs();
+        if magnitude < 10 {
+            body.push(b'0');
+        }
+        body.extend_from_slice(magnitude.to_string().as_bytes());
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use crate::libc::str::test_random::Random;
+
+    /// A format string with random flags, width, precision and conversion.
+    fn random_format(random: &mut Random, length: &str) -> CString {
+        let mut format = String::from("%");
+        for flag in ['-', '+', ' ', '#', '0'] {
+            if random.below(4) == 0 {
+                format.push(flag);
+            }
+        }
+        if random.below(3) == 0 {
+            format += &random.below(40).to_string();
+        }
+        if random.below(4) != 0 {
+            format += &format!(".{}", random.below(40));
+        }
+        format += length;
+        format.push(b"fFeEgGaA"[random.below(8) as usize] as char);
+        CString::new(format).unwrap()
+    }
+
+    /// Doubles from raw bits, short decimals and exact binary ties, so every rounding path is exercised.
+    fn random_double(random: &mut Random) -> f64 {
+        match random.below(4) {
+            0 => f64::from_bits(random.next()),
+            1 => random.below(1_000_000) as f64 / 10f64.powi(random.below(8) as i32),
+            2 => (random.below(1 << 20) as f64 + 0.5) * 2f64.powi(random.below(40) as i32 - 20),
+            _ => f64::from_bits(random.next()) % 1e6,
+        }
+    }
+
+    unsafe fn miros_output(format: &CString, value: f64) -> Vec<u8> {
+        let mut buffer = vec![0u8; 8192];
+        let length = super::super::sprintf(buffer.as_mut_ptr().cast(), format.as_ptr(), value);
+        buffer.truncate(length as usize);
+        buffer
+    }
+
+    unsafe fn glibc_output(format: &CString, value: f64) -> Vec<u8> {
+        let mut buffer = vec![0u8; 8192];
+        let length = libc::snprintf(
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+            format.as_ptr(),
+            value,
+        );
+        buffer.truncate(length as usize);
+        buffer
+    }
+
+    #[test]
+    fn doubles_match_glibc() {
+        let mut random = Random(0x5eed);
+        for _ in 0..100_000 {
+            let format = random_format(&mut random, "");
+            let value = random_double(&mut random);
+            let (ours, theirs) =
+                unsafe { (miros_output(&format, value), glibc_output(&format, value)) };
+            assert_eq!(
+                String::from_utf8_lossy(&ours),
+                String::from_utf8_lossy(&theirs),
+                "{format:?} of {value:e} ({:#x})",
+                value.to_bits()
+            );
+        }
+    }
+
+    /// A `long double` can't be passed to a variadic function from Rust, so both sides get a hand-built `va_list`
+    /// whose register areas are exhausted, leaving the value in the overflow area where the ABI puts it.
+    #[cfg(target_arch = "x86_64")]
+    mod long_double {
+        use std::ffi::{c_char, VaList};
+
+        use super::*;
+
+        #[repr(C)]
+        struct VaListTag {
+            gp_offset: u32,
+            fp_offset: u32,
+            overflow_arg_area: *mut u8,
+            reg_save_area: *mut u8,
+        }
+
+        #[repr(C, align(16))]
+        struct Slot([u8; 16]);
+
+        extern "C" {
+            #[link_name = "vsnprintf"]
+            fn glibc_vsnprintf(
+                destination: *mut c_char,
+                size: usize,
+                format: *const c_char,
+                args: *mut VaListTag,
+            ) -> i32;
+        }
+
+        unsafe fn format_with(
+            value: [u8; 16],
+            print: impl FnOnce(*mut c_char, *mut VaListTag) -> i32,
+        ) -> Vec<u8> {
+            let mut slot = Slot(value);
+            let mut tag = VaListTag {
+                gp_offset: 48,
+                fp_offset: 304,
+                overflow_arg_area: slot.0.as_mut_ptr(),
+                reg_save_area: std::ptr::null_mut(),
+            };
+            let mut buffer = vec![0u8; 8192];
+            let length = print(buffer.as_mut_ptr().cast(), &mut tag);
+            buffer.truncate(length as usize);
+            buffer
+        }
+
+        unsafe fn miros_output(format: &CString, value: [u8; 16]) -> Vec<u8> {
+            let vsprintf: unsafe extern "C" fn(*mut c_char, *const c_char, *mut VaListTag) -> i32 =
+                std::mem::transmute(
+                    super::super::super::vsprintf
+                        as unsafe extern "C" fn(*mut c_char, *const c_char, VaList<'_>) -> i32,
+                );
+            format_with(value, |buffer, tag| vsprintf(buffer, format.as_ptr(), tag))
+        }
+
+        unsafe fn glibc_output(format: &CString, value: [u8; 16]) -> Vec<u8> {
+            format_with(value, |buffer, tag| {
+                glibc_vsnprintf(buffer, 8192, format.as_ptr(), tag)
+            })
+        }
+
+        /// Valid x87 encodings only: the explicit integer bit is set exactly when the exponent is nonzero.
+        fn random_x87(random: &mut Random) -> [u8; 16] {
+            let sign = random.below(2) as u16;
+            let biased = match random.below(8) {
+                0 => random.below(0x8000) as u16,
+                1 => 0,
+                2 => 0x7fff,
+                _ => (16383 + random.below(200)) as u16 - 100,
+            };
+            let mut mantissa = match random.below(3) {
+                0 => random.next() & 0xffff_ff00_0000_0000,
+                _ => random.next(),
+            };
+            if biased == 0 {
+                mantissa &= !(1 << 63);
+            } else {
+                mantissa |= 1 << 63;
+            }
+            let mut bytes = [0; 16];
+            bytes[..8].copy_from_slice(&mantissa.to_le_bytes());
+            bytes[8..10].copy_from_slice(&(sign << 15 | biased).to_le_bytes());
+            bytes
+        }
+
+        #[test]
+        fn long_doubles_match_glibc() {
+            let mut random = Random(0x1d0b);
+            for _ in 0..30_000 {
+                let format = random_format(&mut random, "L");
+                let value = random_x87(&mut random);
+                let (ours, theirs) =
+                    unsafe { (miros_output(&format, value), glibc_output(&format, value)) };
+                assert_eq!(
+                    String::from_utf8_lossy(&ours),
+                    String::from_utf8_lossy(&theirs),
+                    "{format:?} of {value:02x?}"
+                );
+            }
+        }
+    }
+}
@@ -1,14 +1,21 @@
 use std::{
-    ffi::{CStr, VaList},
+    ffi::{c_char, CStr},
     io::Write,
 };
 
-use super::specifier::{Conversion, LengthModifier, PadMode, ResolvedSpecifier};
+use super::{
+    arguments::ArgumentSource,
+    float::{self, FloatValue},
+    specifier::{Conversion, FloatFormat, LengthModifier, PadMode, ResolvedSpecifier},
+};
+use crate::libc::errno::{errno, set_errno, Errno};
 
 pub struct Formatter<W: Write> {
     writer: W,
     bytes_written: usize,
     error: bool,
+    /// `errno` when formatting began, for `%m`: writing may change it before the conversion is reached.
+    saved_errno: Errno,
 }
 
 impl<W: Write> Formatter<W> {
@@ -17,7 +24,26 @@ impl<W: Write> Formatter<W> {
             writer,
             bytes_written: 0,
             error: false,
+            saved_errno: errno.get(),
+        }
+    }
+
+    /// Stops all further output; the call returns -1 with `errno` set to `error`.
+    fn fail(&mut self, error: Errno) {
+        set_errno(error);
+        self.error = true;
+    }
+
+    /// Whether `count` more bytes may be written: the total has to fit the `int` the call returns.
+    fn reserve(&mut self, count: usize) -> bool {
+        if self.error {
+            return false;
         }
+        if self.bytes_written + count > i32::MAX as usize {
+            self.fail(Errno::OVERFLOW);
+            return false;
+        }
+        true
     }
 
     pub fn finish(mut self) -> i32 {
@@ -31,7 +57,7 @@ impl<W: Write> Formatter<W> {
     }
 
     pub fn write_byte(&mut self, byte: u8) {
-        if self.error {
+        if !self.reserve(1) {
             return;
         }
         if self.writer.write_all(&[byte]).is_err() {
@@ -42,7 +68,7 @@ impl<W: Write> Formatter<W> {
     }
 
     pub fn write_bytes(&mut self, bytes: &[u8]) {
-        if self.error || bytes.is_empty() {
+        if bytes.is_empty() || !self.reserve(bytes.len()) {
             return;
         }
         if self.writer.write_all(bytes).is_err() {
@@ -53,7 +79,7 @@ impl<W: Write> Formatter<W> {
     }
 
     fn write_repeated(&mut self, byte: u8, mut count: usize) {
-        if self.error || count == 0 {
+        if count == 0 || !self.reserve(count) {
             return;
         }
         let chunk = [byte; 64];
@@ -68,7 +94,12 @@ impl<W: Write> Formatter<W> {
         }
     }
 
-    pub unsafe fn format(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
+    pub unsafe fn format(&mut self, spec: &ResolvedSpecifier, args: &mut impl ArgumentSource) {
+        // No field wider than `int` can be printed, whatever follows it.
+        if spec.width.max(spec.precision) > Some(i32::MAX as usize) {
+            self.fail(Errno::OVERFLOW);
+            return;
+        }
         match spec.conversion {
             Conversion::SignedInt => self.format_signed_integer(spec, args),
             Conversion::UnsignedInt | Conversion::Octal | Conversion::Hex { .. } => {
@@ -78,25 +109,26 @@ impl<W: Write> Formatter<W> {
             Conversion::Char => self.format_char(spec, args),
             Conversion::Pointer => self.format_pointer(spec, args),
             Conversion::CharCount => self.store_character_count(spec, args),
-            Conversion::Float(_) => {
-                // TODO: implement float formatting.
-                // Consume the double from the VaList to keep subsequent arguments aligned.
-                // C default argument promotion guarantees float → double in variadic calls.
-                // NOTE: `L` (long double) args are wider than f64 on x86-64 (80-bit in a
-                // 128-bit slot), so `%Lf` will misalign subsequent arguments until float
-                // formatting is fully implemented.
-                let _: f64 = args.next_arg();
-            }
+            Conversion::Float(format) => self.format_float(spec, format, args),
+            Conversion::ErrorMessage => self.format_error_message(spec),
         }
     }
 
-    unsafe fn format_signed_integer(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
+    unsafe fn format_signed_integer(
+        &mut self,
+        spec: &ResolvedSpecifier,
+        args: &mut impl ArgumentSource,
+    ) {
         let value = spec.length.extract_signed(args);
         let radix = Radix::from_conversion(spec.conversion);
         self.write_integer(spec, radix, value.unsigned_abs(), value < 0);
     }
 
-    unsafe fn format_unsigned_integer(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
+    unsafe fn format_unsigned_integer(
+        &mut self,
+        spec: &ResolvedSpecifier,
+        args: &mut impl ArgumentSource,
+    ) {
         let value = spec.length.extract_unsigned(args);
         let radix = Radix::from_conversion(spec.conversion);
         self.write_integer(spec, radix, value, false);
@@ -148,15 +180,83 @@ impl<W: Write> Formatter<W> {
         self.write_repeated(b' ', right_spaces);
     }
 
-    unsafe fn format_string(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
-        let pointer: *const i8 = args.next_arg();
+    unsafe fn format_float(
+        &mut self,
+        spec: &ResolvedSpecifier,
+        format: FloatFormat,
+        args: &mut impl ArgumentSource,
+    ) {
+        let value = FloatValue::from_args(spec.length, args);
+        let rendered = float::render(spec, format, value);
+
+        let sign_len = rendered.sign.is_some() as usize;
+        let content_width = sign_len + rendered.prefix.len() + rendered.body.len();
+        let padding = spec.width.unwrap_or(0).saturating_sub(content_width);
+
+        // inf and nan are never zero-padded.
+        let pad_mode = match spec.pad_mode {
+            PadMode::ZeroPad if !rendered.finite => PadMode::RightAlign,
+            pad_mode => pad_mode,
+        };
+        let (left_spaces, extra_zeros, right_spaces) = pad_mode.resolve_padding(padding);
+
+        self.write_repeated(b' ', left_spaces);
+        if let Some(sign_char) = rendered.sign {
+            self.write_byte(sign_char);
+        }
+        self.write_bytes(rendered.prefix);
+        self.write_repeated(b'0', extra_zeros);
+        self.write_bytes(&rendered.body);
+        self.write_repeated(b' ', right_spaces);
+    }
+
+    unsafe fn format_string(&mut self, spec: &ResolvedSpecifier, args: &mut impl ArgumentSource) {
+        if spec.length == LengthModifier::Long {
+            return self.format_wide_string(spec, args);
+        }
+        let pointer: *const c_char = args.next_arg();
 
         let bytes: &[u8] = if pointer.is_null() {
             b"(null)"
         } else {
             CStr::from_ptr(pointer).to_bytes()
         };
+        self.write_truncated(spec, bytes);
+    }
+
+    /// `%ls`: the wide string converted to multibyte characters, at most `precision` bytes of them.
+    unsafe fn format_wide_string(
+        &mut self,
+        spec: &ResolvedSpecifier,
+        args: &mut impl ArgumentSource,
+    ) {
+        let mut wide: *const u32 = args.next_arg();
+        if wide.is_null() {
+            return self.write_truncated(spec, b"(null)");
+        }
+
+        let mut bytes = Vec::new();
+        while *wide != 0
+            && spec
+                .precision
+                .is_none_or(|precision| bytes.len() < precision)
+        {
+            let Some(byte) = narrow(*wide) else {
+                return self.fail(Errno::ILSEQ);
+            };
+            bytes.push(byte);
+            wide = wide.add(1);
+        }
+        self.write_padded(spec, bytes.len(), |formatter| formatter.write_bytes(&bytes));
+    }
 
+    fn format_error_message(&mut self, spec: &ResolvedSpecifier) {
+        let message = self.saved_errno.to_string();
+        self.write_truncated(spec, message.as_bytes());
+    }
+
+    /// `bytes`, cut to `precision` and padded to `width`, as `%s` prints them.
+    fn write_truncated(&mut self, spec: &ResolvedSpecifier, bytes: &[u8]) {
         let effective_length = match spec.precision {
             Some(max) => bytes.len().min(max),
             None => bytes.len(),
@@ -167,15 +267,22 @@ impl<W: Write> Formatter<W> {
         });
     }
 
-    unsafe fn format_char(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
-        let character = args.next_arg::<i32>() as u8;
+    unsafe fn format_char(&mut self, spec: &ResolvedSpecifier, args: &mut impl ArgumentSource) {
+        let character = if spec.length == LengthModifier::Long {
+            let Some(byte) = narrow(args.next_arg::<u32>()) else {
+                return self.fail(Errno::ILSEQ);
+            };
+            byte
+        } else {
+            args.next_arg::<i32>() as u8
+        };
 
         self.write_padded(spec, 1, |formatter| {
             formatter.write_byte(character);
         });
     }
 
-    unsafe fn format_pointer(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
+    unsafe fn format_pointer(&mut self, spec: &ResolvedSpecifier, args: &mut impl ArgumentSource) {
         let address = args.next_arg::<*const ()>().addr();
 
         if address == 0 {
@@ -196,7 +303,11 @@ impl<W: Write> Formatter<W> {
         }
     }
 
-    unsafe fn store_character_count(&mut self, spec: &ResolvedSpecifier, args: &mut VaList<'_>) {
+    unsafe fn store_character_count(
+        &mut self,
+        spec: &ResolvedSpecifier,
+        args: &mut impl ArgumentSource,
+    ) {
         let count = self.bytes_written as i64;
 
         macro_rules! store_count {
@@ -239,6 +350,11 @@ impl<W: Write> Formatter<W> {
     }
 }
 
+/// A wide character in the C locale's multibyte encoding, which is ASCII: glibc's `wcrtomb` fails on the rest.
+fn narrow(wide: u32) -> Option<u8> {
+    u8::try_from(wide).ok().filter(u8::is_ascii)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Radix {
     Decimal,
@@ -505,7 +621,7 @@ mod tests {
             (c"hello".as_ptr()), "hello";
         null_string_pointer_emits_null_sentinel,
             { conversion: Conversion::String },
-            (core::ptr::null::<i8>()), "(null)";
+            (core::ptr::null::<c_char>()), "(null)";
         precision_truncates_string,
             { conversion: Conversion::String, precision: Some(3) },
             (c"hello".as_ptr()), "hel";
@@ -1,21 +1,28 @@
+mod arguments;
+mod float;
 mod format;
 mod parse;
 mod specifier;
 
 use std::{
-    ffi::VaList,
+    ffi::{c_char, VaList},
     fs::File,
     io::{self, BufWriter, Write},
     mem::ManuallyDrop,
     os::fd::FromRawFd,
 };
 
+pub(crate) use arguments::{ArgumentSource, NumberedArguments};
 use format::Formatter;
 use parse::{PrintfItem, PrintfParser};
+pub(crate) use specifier::LengthModifier;
 use specifier::ResolvedSpecifier;
 
 use crate::{
-    libc::stdio::{stdout_ptr, vfprintf},
+    libc::{
+        errno::{set_errno, Errno},
+        stdio::{stdout_ptr, vfprintf},
+    },
     signature_matches_libc,
 };
 
@@ -26,7 +33,7 @@ struct UncheckedBufWriter {
 }
 
 impl UncheckedBufWriter {
-    fn new(destination: *mut i8) -> Self {
+    fn new(destination: *mut c_char) -> Self {
         Self {
             cursor: destination as *mut u8,
         }
@@ -47,46 +54,158 @@ impl Write for UncheckedBufWriter {
     }
 }
 
+/// Fills a buffer of `size` bytes, keeping the last for the terminator, and drops the rest while the formatter goes
+/// on counting: `snprintf` returns the length it would have written.
+struct TruncatingWriter {
+    cursor: *mut u8,
+    remaining: usize,
+}
+
+impl Write for TruncatingWriter {
+    fn write(&mut self, source: &[u8]) -> io::Result<usize> {
+        let copied = source.len().min(self.remaining);
+        unsafe {
+            core::ptr::copy_nonoverlapping(source.as_ptr(), self.cursor, copied);
+            self.cursor = self.cursor.add(copied);
+        }
+        self.remaining -= copied;
+        Ok(source.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// The shared `v*printf` engine: parse `format`, drive `writer`, return `finish`'s byte count.
 pub(crate) unsafe fn format_into<W: Write>(
     writer: W,
-    format: *const i8,
+    format: *const c_char,
     mut args: VaList<'_>,
 ) -> i32 {
     let mut formatter = Formatter::new(writer);
+    match NumberedArguments::collect(format, &mut args) {
+        Ok(None) => format_items(&mut formatter, format, &mut args),
+        Ok(Some(mut numbered)) => format_items(&mut formatter, format, &mut numbered),
+        Err(error) => {
+            set_errno(error);
+            return -1;
+        }
+    }
+    formatter.finish()
+}
+
+unsafe fn format_items<W: Write>(
+    formatter: &mut Formatter<W>,
+    format: *const c_char,
+    args: &mut impl ArgumentSource,
+) {
     for item in PrintfParser::new(format) {
         match item {
             PrintfItem::Literal(bytes) => formatter.write_bytes(bytes),
             PrintfItem::Specifier(spec) => {
-                let resolved = ResolvedSpecifier::from_parsed(spec, &mut args);
-                formatter.format(&resolved, &mut args);
+                let resolved = ResolvedSpecifier::from_parsed(spec, args);
+                if let Some(position) = spec.argument {
+                    args.select(position);
+                }
+                formatter.format(&resolved, args);
             }
         }
     }
-    formatter.finish()
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn printf(format: *const i8, args: ...) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn printf(format: *const c_char, args: ...) -> i32 {
     signature_matches_libc!(libc::printf(format, args));
     vfprintf(stdout_ptr(), format, args)
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn sprintf(destination: *mut i8, format: *const i8, args: ...) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn sprintf(destination: *mut c_char, format: *const c_char, args: ...) -> i32 {
     vsprintf(destination, format, args)
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn vsprintf(destination: *mut i8, format: *const i8, args: VaList<'_>) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vsprintf(
+    destination: *mut c_char,
+    format: *const c_char,
+    args: VaList<'_>,
+) -> i32 {
     let bytes_written = format_into(UncheckedBufWriter::new(destination), format, args);
     // WARN: Null-terminate even on error — glibc does this, and callers may read the buffer regardless of the return value.
     *destination.add(bytes_written.max(0) as usize) = 0;
     bytes_written
 }
 
-#[cfg_attr(not(test), no_mangle)]
-unsafe extern "C" fn vdprintf(file_descriptor: i32, format: *const i8, args: VaList<'_>) -> i32 {
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn snprintf(
+    destination: *mut c_char,
+    size: usize,
+    format: *const c_char,
+    args: ...
+) -> i32 {
+    signature_matches_libc!(libc::snprintf(destination, size, format, args));
+    vsnprintf(destination, size, format, args)
+}
+
+/// At most `size - 1` bytes and a terminator; with `size` 0, `destination` may be null and only the length comes back.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vsnprintf(
+    destination: *mut c_char,
+    size: usize,
+    format: *const c_char,
+    args: VaList<'_>,
+) -> i32 {
+    let mut writer = TruncatingWriter {
+        cursor: destination as *mut u8,
+        remaining: size.saturating_sub(1),
+    };
+    let bytes_written = format_into(&mut writer, format, args);
+    if size > 0 {
+        *writer.cursor = 0;
+    }
+    bytes_written
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn asprintf(result: *mut *mut c_char, format: *const c_char, args: ...) -> i32 {
+    vasprintf(result, format, args)
+}
+
+/// Formats into a fresh `malloc` buffer for the program to `free`. On failure `*result` is left alone, as in glibc.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vasprintf(
+    result: *mut *mut c_char,
+    format: *const c_char,
+    args: VaList<'_>,
+) -> i32 {
+    let mut bytes = Vec::new();
+    let bytes_written = format_into(&mut bytes, format, args);
+    if bytes_written < 0 {
+        return -1;
+    }
+    let string = libc::malloc(bytes.len() + 1).cast::<u8>();
+    if string.is_null() {
+        set_errno(Errno::NOMEM);
+        return -1;
+    }
+    core::ptr::copy_nonoverlapping(bytes.as_ptr(), string, bytes.len());
+    *string.add(bytes.len()) = 0;
+    *result = string.cast();
+    bytes_written
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn dprintf(file_descriptor: i32, format: *const c_char, args: ...) -> i32 {
+    vdprintf(file_descriptor, format, args)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vdprintf(
+    file_descriptor: i32,
+    format: *const c_char,
+    args: VaList<'_>,
+) -> i32 {
     let file = ManuallyDrop::new(File::from_raw_fd(file_descriptor));
     format_into(BufWriter::new(&*file), format, args)
 }
@@ -98,9 +217,9 @@ mod tests {
 
     use super::*;
 
-    unsafe extern "C" fn test_sprintf(format: *const i8, args: ...) -> Vec<u8> {
+    unsafe extern "C" fn test_sprintf(format: *const c_char, args: ...) -> Vec<u8> {
         let mut buffer = [0u8; 4096];
-        let bytes_written = vsprintf(buffer.as_mut_ptr() as *mut i8, format, args);
+        let bytes_written = vsprintf(buffer.as_mut_ptr() as *mut c_char, format, args);
         buffer[..bytes_written as usize].to_vec()
     }
 
@@ -140,4 +259,109 @@ mod tests {
         forced_sign,         "%+d",                      (42i32),                                "+42";
         literal_and_specs,   "val=%d hex=%#x",           (42i32, 255u32),                        "val=42 hex=0xff";
     });
+
+    sprintf_tests!(mod floats {
+        fixed_default,       "%f",                       (1.5f64),                               "1.500000";
+        fixed_ties_to_even,  "%.0f %.0f %.0f",           (0.5f64, 1.5f64, 2.5f64),               "0 2 2";
+        fixed_exact,         "%.30f",                    (0.1f64),                               "0.100000000000000005551115123126";
+        scientific,          "%.2e",                     (12345.0f64),                           "1.23e+04";
+        scientific_carry,    "%.3e",                     (9.9996f64),                            "1.000e+01";
+        general_fixed,       "%g",                       (100000.0f64),                          "100000";
+        general_scientific,  "%g",                       (1e-5f64),                              "1e-05";
+        general_alternate,   "%#g",                      (1.0f64),                               "1.00000";
+        hex,                 "%a",                       (-0.1f64),                              "-0x1.999999999999ap-4";
+        hex_subnormal,       "%a",                       (f64::MIN_POSITIVE / 4.0),              "0x0.4p-1022";
+        hex_rounded_carry,   "%.0a",                     (1.5f64),                               "0x2p+0";
+        hex_zero_padded,     "%010a",                    (1.0f64),                               "0x00001p+0";
+        negative_zero,       "%g",                       (-0.0f64),                              "-0";
+        infinity_unpadded,   "[%010f]",                  (f64::NEG_INFINITY),                    "[      -inf]";
+        nan_uppercase,       "%+F",                      (f64::NAN),                             "+NAN";
+        then_integer,        "%e %d",                    (2.0f64, 7i32),                         "2.000000e+00 7";
+    });
+
+    sprintf_tests!(mod numbered_arguments {
+        reordered,           "%2$s %1$s",                (c"world".as_ptr(), c"hello".as_ptr()), "hello world";
+        reused,              "%1$d %1$x",                (255i32),                               "255 ff";
+        star_width,          "%1$*2$d|",                 (42i32, 5i32),                          "   42|";
+        star_precision,      "%2$.*1$f",                 (2i32, 2.50684f64),                     "2.51";
+        mixed_types,         "%3$g %1$d %2$s",           (7i32, c"x".as_ptr(), 1.5f64),          "1.5 7 x";
+        long_after_int,      "%2$ld %1$d",               (1i32, 1i64 << 40),                     "1099511627776 1";
+    });
+
+    sprintf_tests!(mod gnu_extensions {
+        thousands_in_c_locale, "%'d",                    (1234567i32),                           "1234567";
+        wide_string,         "[%ls|%.1ls]",              (WIDE_HI.as_ptr(), WIDE_HI.as_ptr()),   "[hi|h]";
+        wide_null,           "%ls",                      (core::ptr::null::<u32>()),             "(null)";
+        wide_char,           "%5lc",                     (65u32),                                "    A";
+    });
+
+    const WIDE_HI: [u32; 3] = [b'h' as u32, b'i' as u32, 0];
+
+    unsafe fn errno_after(result: i32) -> (i32, Errno) {
+        (result, crate::libc::errno::errno.get())
+    }
+
+    #[test]
+    fn error_message_uses_errno_at_entry() {
+        set_errno(Errno::NOENT);
+        let output = unsafe { test_sprintf(c"%m|%.6m".as_ptr()) };
+        assert_eq!(output, b"No such file or directory|No suc");
+    }
+
+    #[test]
+    fn numbered_formats_reject_mixing_and_gaps() {
+        let mut buffer = [0u8; 64];
+        let destination = buffer.as_mut_ptr().cast();
+        unsafe {
+            assert_eq!(
+                errno_after(sprintf(destination, c"%1$d %d".as_ptr(), 1i32, 2i32)),
+                (-1, Errno::INVAL)
+            );
+            assert_eq!(
+                errno_after(sprintf(destination, c"%2$d".as_ptr(), 1i32, 2i32)),
+                (-1, Errno::INVAL)
+            );
+            assert_eq!(
+                errno_after(sprintf(destination, c"%4097$d".as_ptr(), 1i32)),
+                (-1, Errno::INVAL)
+            );
+        }
+    }
+
+    #[test]
+    fn wide_characters_outside_ascii_fail() {
+        let mut buffer = [0u8; 64];
+        let wide = [0xe9u32, 0];
+        unsafe {
+            let result = sprintf(buffer.as_mut_ptr().cast(), c"%ls".as_ptr(), wide.as_ptr());
+            assert_eq!(errno_after(result), (-1, Errno::ILSEQ));
+        }
+    }
+
+    #[test]
+    fn snprintf_truncates_and_counts() {
+        let mut buffer = [0xffu8; 8];
+        let written = unsafe { snprintf(buffer.as_mut_ptr().cast(), 4, c"%d".as_ptr(), 123456i32) };
+        assert_eq!(written, 6);
+        assert_eq!(&buffer[..5], b"123\0\xff");
+
+        let counted =
+            unsafe { snprintf(core::ptr::null_mut(), 0, c"%s!".as_ptr(), c"hello".as_ptr()) };
+        assert_eq!(counted, 6);
+    }
+
+    #[test]
+    fn fields_wider_than_int_overflow() {
+        let result = unsafe { snprintf(core::ptr::null_mut(), 0, c"%2147483648d".as_ptr(), 1i32) };
+        assert_eq!(unsafe { errno_after(result) }, (-1, Errno::OVERFLOW));
+    }
+
+    #[test]
+    fn asprintf_allocates_the_string() {
+        let mut result = core::ptr::null_mut();
+        let written = unsafe { asprintf(&mut result, c"%s-%d".as_ptr(), c"id".as_ptr(), 7i32) };
+        assert_eq!(written, 4);
+        assert_eq!(unsafe { std::ffi::CStr::from_ptr(result) }, c"id-7");
+        unsafe { libc::free(result.cast()) };
+    }
 }
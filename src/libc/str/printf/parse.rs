@@ -1,4 +1,4 @@
-use std::ffi::CStr;
+use std::ffi::{c_char, CStr};
 
 use super::specifier::{Conversion, DimensionSpecifier, LengthModifier, PrintfSpecifier, RawFlags};
 
@@ -22,7 +22,7 @@ impl<'a> PrintfParser<'a> {
     /// # Safety
     ///
     /// `format` must point to a valid, null-terminated C string that outlives `'a`.
-    pub unsafe fn new(format: *const i8) -> Self {
+    pub unsafe fn new(format: *const c_char) -> Self {
         Self {
             bytes: CStr::from_ptr(format).to_bytes(),
             position: 0,
@@ -40,6 +40,7 @@ impl<'a> PrintfParser<'a> {
     }
 
     fn parse_specifier(&mut self) -> Option<PrintfSpecifier> {
+        let argument = self.parse_position();
         let flags = self.parse_raw_flags();
         let width = self.parse_dimension();
         let precision = self.parse_precision();
@@ -47,6 +48,7 @@ impl<'a> PrintfParser<'a> {
         let conversion = Conversion::from_byte(self.advance().unwrap_or(b'\0'))?;
 
         Some(PrintfSpecifier {
+            argument,
             flags,
             width,
             precision,
@@ -64,17 +66,41 @@ impl<'a> PrintfParser<'a> {
                 Some(b' ') => flags.space_sign = true,
                 Some(b'#') => flags.alternate = true,
                 Some(b'0') => flags.zero_pad = true,
+                // Thousands grouping: the C locale has no separator, so there's nothing to group with.
+                Some(b'\'') => {}
                 _ => return flags,
             }
             self.advance();
         }
     }
 
+    /// A POSIX argument number, `n$`; anything else is left for the rest of the specifier.
+    fn parse_position(&mut self) -> Option<usize> {
+        let start = self.position;
+        if let Some(b'1'..=b'9') = self.peek() {
+            let position = self.parse_decimal();
+            if self.peek() == Some(b'$') {
+                self.advance();
+                return Some(position);
+            }
+        }
+        self.position = start;
+        None
+    }
+
+    /// `*` or `*m$`, after the `*`.
+    fn parse_star(&mut self) -> DimensionSpecifier {
+        match self.parse_position() {
+            Some(position) => DimensionSpecifier::FromNumberedArg(position),
+            None => DimensionSpecifier::FromNextArg,
+        }
+    }
+
     fn parse_dimension(&mut self) -> DimensionSpecifier {
         match self.peek() {
             Some(b'*') => {
                 self.advance();
-                DimensionSpecifier::FromNextArg
+                self.parse_star()
             }
             Some(b'1'..=b'9') => DimensionSpecifier::Fixed(self.parse_decimal()),
             _ => DimensionSpecifier::Unspecified,
@@ -89,7 +115,7 @@ impl<'a> PrintfParser<'a> {
         match self.peek() {
             Some(b'*') => {
                 self.advance();
-                DimensionSpecifier::FromNextArg
+                self.parse_star()
             }
             Some(b'0'..=b'9') => DimensionSpecifier::Fixed(self.parse_decimal()),
             _ => DimensionSpecifier::Fixed(0),
@@ -305,7 +331,17 @@ mod tests {
         dot_alone_means_zero,    "%.d",  { precision == DimensionSpecifier::Fixed(0) };
         dot_with_digits,         "%.6d", { precision == DimensionSpecifier::Fixed(6) };
         dot_with_zero,           "%.0d", { precision == DimensionSpecifier::Fixed(0) };
-        dot_with_star,           "%.*d", { precision == DimensionSpecifier::FromNextArg }
+        dot_with_star,           "%.*d", { precision == DimensionSpecifier::FromNextArg };
+        dot_with_numbered_star,  "%.*2$d", { precision == DimensionSpecifier::FromNumberedArg(2) }
+    });
+
+    specifier_tests!(mod positions {
+        unnumbered,           "%d",      { argument == None };
+        numbered,             "%3$d",    { argument == Some(3), conversion == Conversion::SignedInt };
+        numbered_with_flags,  "%1$-5d",  { argument == Some(1), flags.left_justify == true, width == DimensionSpecifier::Fixed(5) };
+        width_is_not_number,  "%12d",    { argument == None, width == DimensionSpecifier::Fixed(12) };
+        numbered_star_width,  "%2$*1$d", { argument == Some(2), width == DimensionSpecifier::FromNumberedArg(1) };
+        thousands_is_ignored, "%'d",     { conversion == Conversion::SignedInt }
     });
 
     specifier_tests!(mod length_modifiers {
@@ -338,7 +374,8 @@ mod tests {
         string,                 "%s", { conversion == Conversion::String };
         char,                   "%c", { conversion == Conversion::Char };
         pointer,                "%p", { conversion == Conversion::Pointer };
-        char_count,             "%n", { conversion == Conversion::CharCount }
+        char_count,             "%n", { conversion == Conversion::CharCount };
+        error_message,          "%m", { conversion == Conversion::ErrorMessage }
     });
 
     specifier_tests!(mod combined_specifiers {
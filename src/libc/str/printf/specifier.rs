@@ -1,4 +1,4 @@
-use std::ffi::VaList;
+use super::arguments::ArgumentSource;
 
 /// Raw flags parsed from a printf format specifier.
 /// No interpretation is applied — flag validity and interactions are resolved later by `ResolvedSpecifier`.
@@ -72,7 +72,7 @@ pub enum LengthModifier {
 }
 
 impl LengthModifier {
-    pub unsafe fn extract_signed(self, args: &mut VaList<'_>) -> i64 {
+    pub unsafe fn extract_signed(self, args: &mut impl ArgumentSource) -> i64 {
         match self {
             Self::HalfHalf => (args.next_arg::<i32>() as i8) as i64,
             Self::Half => (args.next_arg::<i32>() as i16) as i64,
@@ -82,7 +82,7 @@ impl LengthModifier {
         }
     }
 
-    pub unsafe fn extract_unsigned(self, args: &mut VaList<'_>) -> u64 {
+    pub unsafe fn extract_unsigned(self, args: &mut impl ArgumentSource) -> u64 {
         match self {
             Self::HalfHalf => (args.next_arg::<u32>() as u8) as u64,
             Self::Half => (args.next_arg::<u32>() as u16) as u64,
@@ -99,6 +99,18 @@ pub enum DimensionSpecifier {
     Unspecified,
     Fixed(usize),
     FromNextArg,
+    /// `*m$`: argument `m` of a numbered format.
+    FromNumberedArg(usize),
+}
+
+impl DimensionSpecifier {
+    /// The `int` a `*` or `*m$` dimension takes.
+    unsafe fn read_argument(self, args: &mut impl ArgumentSource) -> i32 {
+        if let Self::FromNumberedArg(position) = self {
+            args.select(position);
+        }
+        args.next_arg::<i32>()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -106,12 +118,16 @@ pub enum Conversion {
     SignedInt,
     UnsignedInt,
     Octal,
-    Hex { uppercase: bool },
+    Hex {
+        uppercase: bool,
+    },
     Float(FloatFormat),
     String,
     Char,
     Pointer,
     CharCount,
+    /// `%m`: glibc's `strerror(errno)`, taking no argument.
+    ErrorMessage,
 }
 
 impl Conversion {
@@ -134,6 +150,7 @@ impl Conversion {
             b'c' => Some(Self::Char),
             b'p' => Some(Self::Pointer),
             b'n' => Some(Self::CharCount),
+            b'm' => Some(Self::ErrorMessage),
             _ => None,
         }
     }
@@ -169,6 +186,8 @@ pub enum FloatFormat {
 /// A parsed format specifier — everything between `%` and the conversion character.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PrintfSpecifier {
+    /// `n$`: which argument a numbered format converts.
+    pub argument: Option<usize>,
     pub flags: RawFlags,
     pub width: DimensionSpecifier,
     pub precision: DimensionSpecifier,
@@ -192,13 +211,14 @@ pub struct ResolvedSpecifier {
 
 impl ResolvedSpecifier {
     /// Resolve `*` width/precision from the argument list and apply all flag-interaction rules (C11 §7.21.6.1p6).
-    pub unsafe fn from_parsed(spec: PrintfSpecifier, args: &mut VaList<'_>) -> Self {
+    pub unsafe fn from_parsed(spec: PrintfSpecifier, args: &mut impl ArgumentSource) -> Self {
         let mut left_align = spec.flags.left_justify;
 
         let width = match spec.width {
             DimensionSpecifier::Unspecified => None,
             DimensionSpecifier::Fixed(value) => Some(value),
-            DimensionSpecifier::FromNextArg => match args.next_arg::<i32>() {
+            dimension @ (DimensionSpecifier::FromNextArg
+            | DimensionSpecifier::FromNumberedArg(_)) => match dimension.read_argument(args) {
                 raw if raw < 0 => {
                     left_align = true;
                     Some(raw.unsigned_abs() as usize)
@@ -210,9 +230,10 @@ impl ResolvedSpecifier {
         let precision = match spec.precision {
             DimensionSpecifier::Unspecified => None,
             DimensionSpecifier::Fixed(value) => Some(value),
-            DimensionSpecifier::FromNextArg => {
-                let raw = args.next_arg::<i32>();
-                (raw >= 0).then(|| raw as usize)
+            dimension @ (DimensionSpecifier::FromNextArg
+            | DimensionSpecifier::FromNumberedArg(_)) => {
+                let raw = dimension.read_argument(args);
+                (raw >= 0).then_some(raw as usize)
             }
         };
 
@@ -247,9 +268,11 @@ impl ResolvedSpecifier {
         };
 
         // `L` is only meaningful for float conversions; collapse to default otherwise.
-        let length = (spec.length == LengthModifier::LongDouble && !spec.conversion.is_float())
-            .then_some(LengthModifier::None)
-            .unwrap_or(spec.length);
+        let length = if spec.length == LengthModifier::LongDouble && !spec.conversion.is_float() {
+            LengthModifier::None
+        } else {
+            spec.length
+        };
 
         Self {
             sign_mode,
@@ -280,6 +303,7 @@ mod tests {
     impl Default for PrintfSpecifier {
         fn default() -> Self {
             Self {
+                argument: None,
                 flags: RawFlags::default(),
                 width: DimensionSpecifier::default(),
                 precision: DimensionSpecifier::default(),
@@ -393,7 +417,8 @@ mod tests {
         string,       Conversion::String,                 (false, false, false, false);
         char_conv,    Conversion::Char,                   (false, false, false, false);
         pointer,      Conversion::Pointer,                (false, false, false, false);
-        char_count,   Conversion::CharCount,              (false, false, false, false)
+        char_count,   Conversion::CharCount,              (false, false, false, false);
+        error_message, Conversion::ErrorMessage,          (false, false, false, false)
     });
 
     eq_tests!(mod from_byte {
@@ -415,6 +440,7 @@ mod tests {
         c_is_char,            Conversion::from_byte(b'c'), Some(Conversion::Char);
         p_is_pointer,         Conversion::from_byte(b'p'), Some(Conversion::Pointer);
         n_is_char_count,      Conversion::from_byte(b'n'), Some(Conversion::CharCount);
+        m_is_error_message,   Conversion::from_byte(b'm'), Some(Conversion::ErrorMessage);
         invalid_returns_none, Conversion::from_byte(b'Q'), None
     });
 
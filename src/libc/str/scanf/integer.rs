@@ -0,0 +1,76 @@
+use crate::libc::str::strtod::{ByteSource, Scan};
+
+/// An integer as read, before it is narrowed to the type it is stored as.
+pub struct ScannedInteger {
+    negative: bool,
+    magnitude: u64,
+    /// The digits went past `u64::MAX`.
+    overflowed: bool,
+}
+
+impl ScannedInteger {
+    /// The value as `strtol` returns it: clamped to the range of `long`.
+    pub fn signed(&self) -> i64 {
+        let limit = i64::MAX as u64 + self.negative as u64;
+        if self.overflowed || self.magnitude > limit {
+            return if self.negative { i64::MIN } else { i64::MAX };
+        }
+        let value = self.magnitude as i64;
+        if self.negative {
+            value.wrapping_neg()
+        } else {
+            value
+        }
+    }
+
+    /// The value as `strtoul` returns it: `ULONG_MAX` past the range, and a minus sign negating modulo 2⁶⁴.
+    pub fn unsigned(&self) -> u64 {
+        if self.overflowed {
+            u64::MAX
+        } else if self.negative {
+            self.magnitude.wrapping_neg()
+        } else {
+            self.magnitude
+        }
+    }
+}
+
+/// Reads an integer in `base` (0 for C's prefixes: `0x` hex, `0` octal, otherwise decimal; 16 also takes a `0x`),
+/// with an optional sign. `None` if there was no digit, though a sign may have been taken; a `0x` with no hex digit
+/// after it is the number 0, taken whole, as glibc does.
+pub fn scan_integer(source: &mut impl ByteSource, base: u32) -> Option<ScannedInteger> {
+    let mut scan = Scan::new(source);
+    let negative = scan.take_sign();
+    let mut base = base;
+    let mut seen_digit = false;
+    if matches!(base, 0 | 16) && scan.take_if(|byte| byte == b'0').is_some() {
+        seen_digit = true;
+        if scan.take_if(|byte| byte | 0x20 == b'x').is_some() {
+            base = 16;
+        } else if base == 0 {
+            base = 8;
+        }
+    }
+    if base == 0 {
+        base = 10;
+    }
+
+    let mut scanned = ScannedInteger {
+        negative,
+        magnitude: 0,
+        overflowed: false,
+    };
+    while let Some(digit) = scan.take_if(|byte| (byte as char).is_digit(base)) {
+        seen_digit = true;
+        let digit = (digit as char).to_digit(base).unwrap_or(0) as u64;
+        match scanned
+            .magnitude
+            .checked_mul(base as u64)
+            .and_then(|magnitude| magnitude.checked_add(digit))
+        {
+            Some(magnitude) => scanned.magnitude = magnitude,
+            None => scanned.overflowed = true,
+        }
+    }
+    seen_digit.then_some(scanned)
+}
@@ -0,0 +1,597 @@
+//! The scanf family's engine, shared by `sscanf` here and the `FILE` variants in `stdio`: each directive of the
+//! format reads from a [`ByteSource`] and stores through the next pointer in the `va_list`, or with `%n$`, the `n`th. Numbers are read the way
+//! `strtol`, `strtoul` and `strtod` read them, floats through `strtod`'s own grammar and rounding.
+
+mod integer;
+mod parse;
+
+use std::{
+    ffi::{c_char, c_int, c_void, VaList},
+    ptr,
+};
+
+use integer::scan_integer;
+use parse::{ScanfConversion, ScanfItem, ScanfParser, ScanfSpecifier};
+
+use crate::{
+    libc::{
+        errno::{set_errno, Errno},
+        str::{
+            printf::{ArgumentSource, LengthModifier, NumberedArguments},
+            strtod::{is_space, scan_float, BinaryFormat, ByteSource, CStringSource},
+        },
+    },
+    signature_matches_libc,
+};
+
+const EOF: c_int = -1;
+
+/// Why a scan stopped before the end of its format.
+enum Failure {
+    /// The input ran out, or couldn't be read, before a directive could match.
+    Input,
+    /// The input doesn't match the directive; the byte that didn't is left unread.
+    Matching,
+    /// `%m` couldn't allocate its buffer.
+    Allocation,
+}
+
+/// Counts the bytes a scan takes, for `%n`.
+struct Scanner<'s, S: ByteSource> {
+    source: &'s mut S,
+    consumed: usize,
+}
+
+/// At most `remaining` bytes of the scanner's input: a conversion's field.
+struct Field<'f, 's, S: ByteSource> {
+    scanner: &'f mut Scanner<'s, S>,
+    remaining: usize,
+}
+
+impl<S: ByteSource> ByteSource for Field<'_, '_, S> {
+    fn peek(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.scanner.source.peek()
+        }
+    }
+
+    fn advance(&mut self) {
+        self.remaining -= 1;
+        self.scanner.advance();
+    }
+}
+
+impl<S: ByteSource> Field<'_, '_, S> {
+    /// Takes bytes while `accept` holds for them.
+    fn take_while(&mut self, accept: impl Fn(u8) -> bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        while let Some(byte) = self.peek().filter(|&byte| accept(byte)) {
+            bytes.push(byte);
+            self.advance();
+        }
+        bytes
+    }
+}
+
+impl<'s, S: ByteSource> Scanner<'s, S> {
+    fn advance(&mut self) {
+        self.source.advance();
+        self.consumed += 1;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.source.peek().is_some_and(is_space) {
+            self.advance();
+        }
+    }
+
+    /// Skips whitespace to the start of an item, which must have a byte.
+    fn skip_to_item(&mut self) -> Result<(), Failure> {
+        self.skip_whitespace();
+        self.source.peek().map(|_| ()).ok_or(Failure::Input)
+    }
+
+    fn match_byte(&mut self, expected: u8) -> Result<(), Failure> {
+        match self.source.peek() {
+            None => Err(Failure::Input),
+            Some(byte) if byte != expected => Err(Failure::Matching),
+            Some(_) => {
+                self.advance();
+                Ok(())
+            }
+        }
+    }
+
+    fn field(&mut self, width: Option<usize>) -> Field<'_, 's, S> {
+        Field {
+            scanner: self,
+            remaining: width.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Runs one conversion, returning whether it stored a value (which `%n` and suppressed ones don't).
+    unsafe fn convert(
+        &mut self,
+        spec: &ScanfSpecifier<'_>,
+        args: &mut impl ArgumentSource,
+    ) -> Result<bool, Failure> {
+        // Everything but `%c`, `%[` and `%n` skips leading whitespace.
+        match spec.conversion {
+            ScanfConversion::Percent => {
+                self.skip_to_item()?;
+                return self.match_byte(b'%').map(|()| false);
+            }
+            ScanfConversion::CharCount => {
+                if !spec.suppress {
+                    store_integer(destination(spec, args), spec.length, self.consumed as u64);
+                }
+                return Ok(false);
+            }
+            ScanfConversion::Chars | ScanfConversion::Set(_) => {
+                self.source.peek().ok_or(Failure::Input)?;
+            }
+            _ => self.skip_to_item()?,
+        }
+        let destination = if spec.suppress {
+            ptr::null_mut()
+        } else {
+            destination(spec, args)
+        };
+
+        let width = match spec.conversion {
+            ScanfConversion::Chars => Some(spec.width.unwrap_or(1)),
+            _ => spec.width,
+        };
+        let mut field = self.field(width);
+        match spec.conversion {
+            ScanfConversion::SignedInt { base } => {
+                let scanned = scan_integer(&mut field, base).ok_or(Failure::Matching)?;
+                if !spec.suppress {
+                    store_integer(destination, spec.length, scanned.signed() as u64);
+                }
+            }
+            ScanfConversion::UnsignedInt { base } => {
+                let scanned = scan_integer(&mut field, base).ok_or(Failure::Matching)?;
+                if !spec.suppress {
+                    store_integer(destination, spec.length, scanned.unsigned());
+                }
+            }
+            ScanfConversion::Pointer => {
+                let scanned = scan_integer(&mut field, 16).ok_or(Failure::Matching)?;
+                if !spec.suppress {
+                    destination
+                        .cast::<usize>()
+                        .write(scanned.unsigned() as usize);
+                }
+            }
+            ScanfConversion::Float => {
+                let scanned = scan_float(&mut field, false);
+                if !scanned.scanf_match() {
+                    return Err(Failure::Matching);
+                }
+                let format = match spec.length {
+                    LengthModifier::Long => BinaryFormat::Double,
+                    LengthModifier::LongDouble | LengthModifier::LongLong => {
+                        BinaryFormat::LONG_DOUBLE
+                    }
+                    _ => BinaryFormat::Single,
+                };
+                let converted = scanned.convert(format);
+                if converted.range_error {
+                    set_errno(Errno::RANGE);
+                }
+                if !spec.suppress {
+                    let bytes = converted.bits.to_le_bytes();
+                    ptr::copy_nonoverlapping(bytes.as_ptr(), destination.cast(), format.size());
+                }
+            }
+            ScanfConversion::String => {
+                let bytes = field.take_while(|byte| !is_space(byte));
+                store_string(destination, spec, &bytes, true)?;
+            }
+            ScanfConversion::Chars => {
+                // A short read at the end of the input still stores what it got, as in glibc.
+                let bytes = field.take_while(|_| true);
+                store_string(destination, spec, &bytes, false)?;
+            }
+            ScanfConversion::Set(set) => {
+                let membership = set.membership();
+                let bytes = field.take_while(|byte| membership[byte as usize]);
+                if bytes.is_empty() {
+                    return Err(Failure::Matching);
+                }
+                store_string(destination, spec, &bytes, true)?;
+            }
+            ScanfConversion::CharCount | ScanfConversion::Percent => unreachable!(),
+        }
+        Ok(!spec.suppress)
+    }
+}
+
+/// The pointer `spec` stores through: its numbered argument, or the next one.
+unsafe fn destination(spec: &ScanfSpecifier<'_>, args: &mut impl ArgumentSource) -> *mut c_void {
+    if let Some(position) = spec.argument {
+        args.select(position);
+    }
+    args.next_arg()
+}
+
+/// Stores `value` truncated to the integer type `length` names: `int` without one, 64 bits for `L` as for `ll`.
+unsafe fn store_integer(destination: *mut c_void, length: LengthModifier, value: u64) {
+    match length {
+        LengthModifier::HalfHalf => destination.cast::<u8>().write(value as u8),
+        LengthModifier::Half => destination.cast::<u16>().write(value as u16),
+        LengthModifier::None => destination.cast::<u32>().write(value as u32),
+        LengthModifier::Long
+        | LengthModifier::LongLong
+        | LengthModifier::LongDouble
+        | LengthModifier::IntMax
+        | LengthModifier::Size
+        | LengthModifier::Ptrdiff => destination.cast::<u64>().write(value),
+    }
+}
+
+/// Stores what `%s`, `%c` or `%[` read, null-terminated unless it was `%c`: as `wchar_t`s with `l` (ASCII only,
+/// as in the C locale; anything else is `EILSEQ`), and into a fresh `malloc` buffer with `m`.
+unsafe fn store_string(
+    destination: *mut c_void,
+    spec: &ScanfSpecifier<'_>,
+    bytes: &[u8],
+    terminate: bool,
+) -> Result<(), Failure> {
+    let wide = spec.length == LengthModifier::Long;
+    if wide && !bytes.is_ascii() {
+        set_errno(Errno::ILSEQ);
+        return Err(Failure::Matching);
+    }
+    if spec.suppress {
+        return Ok(());
+    }
+
+    let unit = if wide { size_of::<u32>() } else { 1 };
+    let size = (bytes.len() + terminate as usize) * unit;
+    let buffer = if spec.allocate {
+        let buffer = libc::malloc(size);
+        if buffer.is_null() {
+            set_errno(Errno::NOMEM);
+            return Err(Failure::Allocation);
+        }
+        destination.cast::<*mut c_void>().write(buffer);
+        buffer
+    } else {
+        destination
+    };
+
+    let terminator = terminate.then_some(0);
+    for (index, byte) in bytes.iter().copied().chain(terminator).enumerate() {
+        if wide {
+            buffer.cast::<u32>().add(index).write(byte as u32);
+        } else {
+            buffer.cast::<u8>().add(index).write(byte);
+        }
+    }
+    Ok(())
+}
+
+/// The shared `v*scanf` engine: runs `format` against `source`, returning the number of values stored, or `EOF` if
+/// the input ran out before the first one. A format that numbers only some of its arguments is `EOF` with `EINVAL`,
+/// before anything is read.
+pub(crate) unsafe fn scan_from(
+    source: &mut impl ByteSource,
+    format: *const c_char,
+    mut args: VaList<'_>,
+) -> c_int {
+    let positions = ScanfParser::new(format).filter_map(|item| match item {
+        ScanfItem::Specifier(spec)
+            if !spec.suppress && spec.conversion != ScanfConversion::Percent =>
+        {
+            Some(spec.argument)
+        }
+        _ => None,
+    });
+    match NumberedArguments::collect_pointers(positions, &mut args) {
+        Ok(None) => scan_items(source, format, &mut args),
+        Ok(Some(mut numbered)) => scan_items(source, format, &mut numbered),
+        Err(error) => {
+            set_errno(error);
+            EOF
+        }
+    }
+}
+
+unsafe fn scan_items(
+    source: &mut impl ByteSource,
+    format: *const c_char,
+    args: &mut impl ArgumentSource,
+) -> c_int {
+    let mut scanner = Scanner {
+        source,
+        consumed: 0,
+    };
+    let mut stored = 0;
+    for item in ScanfParser::new(format) {
+        let result = match item {
+            ScanfItem::Whitespace => {
+                scanner.skip_whitespace();
+                Ok(())
+            }
+            ScanfItem::Literal(byte) => scanner.match_byte(byte),
+            ScanfItem::Specifier(spec) => scanner
+                .convert(&spec, args)
+                .map(|assigned| stored += assigned as c_int),
+        };
+        match result {
+            Ok(()) => {}
+            Err(Failure::Matching) => break,
+            Err(Failure::Input) if stored > 0 => break,
+            Err(Failure::Input | Failure::Allocation) => return EOF,
+        }
+    }
+    stored
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn sscanf(string: *const c_char, format: *const c_char, args: ...) -> c_int {
+    signature_matches_libc!(libc::sscanf(string, format, args));
+    vsscanf(string, format, args)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn vsscanf(
+    string: *const c_char,
+    format: *const c_char,
+    args: VaList<'_>,
+) -> c_int {
+    scan_from(&mut CStringSource::new(string), format, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, c_int, CString};
+
+    use super::sscanf;
+    use crate::libc::{
+        errno::{errno, Errno},
+        str::test_random::Random,
+    };
+
+    /// What one `sscanf` call did: its result and the bytes of every destination, which start out as `0xaa`.
+    type Outcome = (c_int, [[u8; 16]; 3]);
+
+    /// Runs `input` through `format` with three 16-byte destinations, under miros's `sscanf` and glibc's.
+    fn both(input: &[u8], format: &str) -> (Outcome, Outcome) {
+        let input = CString::new(input).unwrap();
+        let format = CString::new(format).unwrap();
+        let run = |scan: unsafe extern "C" fn(*const c_char, *const c_char, ...) -> c_int| {
+            let mut destinations = [[0xaa_u8; 16]; 3];
+            let [first, second, third] = &mut destinations;
+            let result = unsafe {
+                scan(
+                    input.as_ptr(),
+                    format.as_ptr(),
+                    first.as_mut_ptr(),
+                    second.as_mut_ptr(),
+                    third.as_mut_ptr(),
+                )
+            };
+            (result, destinations)
+        };
+        (run(sscanf), run(libc::sscanf))
+    }
+
+    fn assert_matches_glibc(input: &[u8], format: &str) {
+        let (miros, glibc) = both(input, format);
+        assert_eq!(
+            miros,
+            glibc,
+            "sscanf({:?}, {format:?})",
+            String::from_utf8_lossy(input)
+        );
+    }
+
+    #[test]
+    fn integers() {
+        for (input, format) in [
+            ("", "%d"),
+            ("", "%n"),
+            ("  ", " %n"),
+            ("abc", "%d"),
+            ("0x", "%x%n"),
+            ("0xg", "%x%n"),
+            ("-", "%d%n"),
+            ("+", "%d"),
+            ("12abc", "%d%n"),
+            ("99999999999", "%d"),
+            ("-1", "%u"),
+            ("0x1f 017 08", "%i %i %i"),
+            ("0x", "%i%n"),
+            ("0", "%x%n"),
+            ("1", "%d %d"),
+            ("1;2", "%d,%d"),
+            ("300 -300", "%hhd %hd%n"),
+            ("-0x10", "%x"),
+            ("-2147483649", "%d"),
+            ("18446744073709551617 -18446744073709551617", "%d %lu"),
+            ("-9223372036854775808 -9223372036854775809", "%lld %lld"),
+            ("12345", "%2d%3d%n"),
+            ("0x7fffffff", "%p%n"),
+            ("777 ff", "%o %X"),
+            ("42", "%*d%n"),
+            ("x", "%*d"),
+            ("", "%*d"),
+            ("5", "%*d %d"),
+            ("  42", "%n%d"),
+        ] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn literals_and_whitespace() {
+        for (input, format) in [
+            ("", "abc"),
+            ("a", "a%n"),
+            ("b", "a%n"),
+            ("", "%%"),
+            (" %", "%%%n"),
+            ("1 ", "%d%n"),
+            ("1 x", "%d %n"),
+            ("1\t\n\x0b\x0c\r2", "%d %d%n"),
+            ("a  b", "a b%n"),
+            ("ab", "a b%n"),
+            ("1-2", "%d - %d"),
+        ] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn floats() {
+        for (input, format) in [
+            ("1e", "%lf%n"),
+            ("1e+x", "%lf%n"),
+            ("infinx", "%lf%n"),
+            ("infx", "%lf%n"),
+            ("-INFINITY", "%lf%n"),
+            ("0x1p3", "%lf%n"),
+            ("nan(123)", "%lf%n"),
+            ("1.2345", "%3lf%n"),
+            (".", "%lf"),
+            (".5 5.", "%lf %lf"),
+            ("0x", "%lf"),
+            ("0x.p1", "%lf%n"),
+            ("-0x", "%lf%n"),
+            ("1e500 1e-500", "%lf %lf"),
+            ("+.e1", "%lf"),
+            ("0xp", "%lf"),
+            ("3.4028235e38 1e39 1e-46", "%f %e %g"),
+            ("0.1 1e4000 0x1.fffffffffffffffep16383", "%Lf %Le %La"),
+            ("2.5 7", "%llf %*f%n"),
+        ] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn strings_and_sets() {
+        for (input, format) in [
+            ("abcd", "%[a-c]%n"),
+            ("]a]b", "%[]a]%n"),
+            ("ab]c", "%[^]]"),
+            ("abc", "%5c%n"),
+            ("abcdef", "%2s%n"),
+            ("  x y", "%s%n"),
+            ("", "%s"),
+            ("-", "%[a-]"),
+            ("b", "%[a-c-]"),
+            ("zc", "%[c-a]"),
+            ("abc", "%c"),
+            ("  abc", "%c%n"),
+            ("xyz", "%[^a-x]"),
+            ("", "%c"),
+            ("abc", "%0s"),
+            ("a b", "%c%c%c"),
+            ("hello world", "%*s %s%n"),
+            ("key=value;", "%[^=]=%[^;]%n"),
+            ("ab", "%3c"),
+        ] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn numbered_arguments() {
+        for (input, format) in [
+            ("2 3", "%2$d %1$d"),
+            ("7 x 9", "%3$d %2$s %1$d"),
+            ("5 abc", "%1$d %*s%1$n"),
+            ("12345", "%2$2d%1$3d"),
+            ("1 2", "%3$d %1$d"),
+            ("1 %", "%1$d %%"),
+        ] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn mixing_numbered_and_unnumbered_is_invalid() {
+        let (mut first, mut second) = (0 as c_int, 0 as c_int);
+        let result = unsafe {
+            sscanf(
+                c"2 3".as_ptr(),
+                c"%2$d %d".as_ptr(),
+                &mut first,
+                &mut second,
+            )
+        };
+        assert_eq!(result, -1);
+        assert_eq!(errno.get(), Errno::INVAL);
+        assert_eq!((first, second), (0, 0));
+    }
+
+    #[test]
+    fn wide_strings() {
+        for (input, format) in [("ab cd", "%ls %2lc"), ("xyz", "%l[xy]%n")] {
+            assert_matches_glibc(input.as_bytes(), format);
+        }
+    }
+
+    #[test]
+    fn allocated_strings() {
+        let mut word: *mut c_char = std::ptr::null_mut();
+        let mut set: *mut c_char = std::ptr::null_mut();
+        let mut chars: *mut c_char = std::ptr::null_mut();
+        let result = unsafe {
+            sscanf(
+                c"hello 123abc xy".as_ptr(),
+                c"%ms %m[0-9]%*s %2mc".as_ptr(),
+                &mut word,
+                &mut set,
+                &mut chars,
+            )
+        };
+        assert_eq!(result, 3);
+        unsafe {
+            assert_eq!(std::ffi::CStr::from_ptr(word), c"hello");
+            assert_eq!(std::ffi::CStr::from_ptr(set), c"123");
+            assert_eq!(std::slice::from_raw_parts(chars.cast::<u8>(), 2), b"xy");
+            libc::free(word.cast());
+            libc::free(set.cast());
+            libc::free(chars.cast());
+        }
+    }
+
+    #[test]
+    fn matches_glibc_on_random_input() {
+        const ALPHABET: &[u8] = b"0123456789+-.eExXpPaAfFiInNtTyY( )";
+        const FORMATS: &[&str] = &[
+            "%d%n",
+            "%i%n",
+            "%x%n",
+            "%o%n",
+            "%u%n",
+            "%2d%n",
+            "%lf%n",
+            "%f%n",
+            "%Lf%n",
+            "%3lf%n",
+            "%5lg%n",
+            "%s%n",
+            "%3c%n",
+            "%[0-9a-f]%n",
+            "%d %lf%n",
+            "%x.%2i%n",
+        ];
+        let mut random = Random(0x5ca9f);
+        for _ in 0..50_000 {
+            let length = random.below(12);
+            let input: Vec<u8> = (0..length)
+                .map(|_| ALPHABET[random.below(ALPHABET.len() as u64) as usize])
+                .collect();
+            let format = FORMATS[random.below(FORMATS.len() as u64) as usize];
+            assert_matches_glibc(&input, format);
+        }
+    }
+}
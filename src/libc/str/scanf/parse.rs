@@ -0,0 +1,312 @@
+use std::ffi::{c_char, CStr};
+
+use crate::libc::str::{printf::LengthModifier, strtod::is_space};
+
+/// An item yielded by [`ScanfParser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanfItem<'a> {
+    /// A run of whitespace, which matches any amount of input whitespace, none included.
+    Whitespace,
+    Literal(u8),
+    Specifier(ScanfSpecifier<'a>),
+}
+
+/// `%[n$][*][width][m][length]conversion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanfSpecifier<'a> {
+    /// `n$`: the 1-based argument this conversion stores through, instead of the next one.
+    pub argument: Option<usize>,
+    /// `*`: the item is read but not stored, and takes no argument.
+    pub suppress: bool,
+    /// The most bytes the item may take; `%c` takes exactly one without it.
+    pub width: Option<usize>,
+    /// `m`: the string is stored in a `malloc`ed buffer, through a `char **`.
+    pub allocate: bool,
+    pub length: LengthModifier,
+    pub conversion: ScanfConversion<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanfConversion<'a> {
+    /// `%d` (base 10) and `%i` (base 0: whatever the prefix says), read as `strtol` does.
+    SignedInt { base: u32 },
+    /// `%u`, `%o`, `%x` and `%X`, read as `strtoul` does.
+    UnsignedInt { base: u32 },
+    /// `%p`, in hex.
+    Pointer,
+    /// `%a %e %f %g`, in either case.
+    Float,
+    /// `%s`: a run of non-whitespace.
+    String,
+    /// `%c`: `width` bytes, whitespace included, unterminated.
+    Chars,
+    /// `%[...]`: a run of bytes in (or with `^`, out of) the set.
+    Set(ByteSet<'a>),
+    /// `%n`: the number of bytes read so far.
+    CharCount,
+    /// `%%`
+    Percent,
+}
+
+/// The bytes between `%[` and `]`, with `^` already taken off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSet<'a> {
+    negated: bool,
+    members: &'a [u8],
+}
+
+impl ByteSet<'_> {
+    /// A 256-entry membership table. `a-z` is a range when it runs upwards; a `-` first, last or before a lower
+    /// byte is itself a member, as in glibc.
+    pub fn membership(&self) -> [bool; 256] {
+        let mut table = [false; 256];
+        let mut index = 0;
+        while index < self.members.len() {
+            let byte = self.members[index];
+            if let Some(&[b'-', end]) = self.members.get(index + 1..index + 3) {
+                if (index > 0 || byte != b'-') && byte <= end {
+                    table[byte as usize..=end as usize].fill(true);
+                    index += 3;
+                    continue;
+                }
+            }
+            table[byte as usize] = true;
+            index += 1;
+        }
+        if self.negated {
+            table = table.map(|member| !member);
+        }
+        table
+    }
+}
+
+/// Iterator that parses a C scanf format string into [`ScanfItem`]s. An invalid conversion ends the format, as it
+/// ends glibc's scan.
+pub struct ScanfParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ScanfParser<'a> {
+    /// # Safety
+    ///
+    /// `format` must point to a valid, null-terminated C string that outlives `'a`.
+    pub unsafe fn new(format: *const c_char) -> Self {
+        Self {
+            bytes: CStr::from_ptr(format).to_bytes(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn advance(&mut self) -> Option<u8> {
+        let byte = self.bytes.get(self.position).copied()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    /// Takes `byte` if it is next.
+    fn take(&mut self, byte: u8) -> bool {
+        let next = self.peek() == Some(byte);
+        if next {
+            self.position += 1;
+        }
+        next
+    }
+
+    fn parse_specifier(&mut self) -> Option<ScanfSpecifier<'a>> {
+        let argument = self.parse_argument_number();
+        let suppress = self.take(b'*');
+        // A zero width is no width at all, as in glibc.
+        let width = Some(self.parse_decimal()).filter(|&width| width > 0);
+        let allocate = self.take(b'm');
+        let length = self.parse_length_modifier();
+        let conversion = match self.advance()? {
+            b'd' => ScanfConversion::SignedInt { base: 10 },
+            b'i' => ScanfConversion::SignedInt { base: 0 },
+            b'u' => ScanfConversion::UnsignedInt { base: 10 },
+            b'o' => ScanfConversion::UnsignedInt { base: 8 },
+            b'x' | b'X' => ScanfConversion::UnsignedInt { base: 16 },
+            b'p' => ScanfConversion::Pointer,
+            b'a' | b'A' | b'e' | b'E' | b'f' | b'F' | b'g' | b'G' => ScanfConversion::Float,
+            b's' => ScanfConversion::String,
+            b'c' => ScanfConversion::Chars,
+            b'[' => ScanfConversion::Set(self.parse_set()?),
+            b'n' => ScanfConversion::CharCount,
+            b'%' => ScanfConversion::Percent,
+            _ => return None,
+        };
+        Some(ScanfSpecifier {
+            argument,
+            suppress,
+            width,
+            allocate,
+            length,
+            conversion,
+        })
+    }
+
+    /// The set after `%[`, through its `]`. A `]` right at the start is a member, not the end.
+    fn parse_set(&mut self) -> Option<ByteSet<'a>> {
+        let negated = self.take(b'^');
+        let start = self.position;
+        self.take(b']');
+        while self.advance()? != b']' {}
+        Some(ByteSet {
+            negated,
+            members: &self.bytes[start..self.position - 1],
+        })
+    }
+
+    /// `n$`, if the digits ahead end in `$`; otherwise they are the width, and are left for it.
+    fn parse_argument_number(&mut self) -> Option<usize> {
+        let start = self.position;
+        let position = self.parse_decimal();
+        if self.position > start && self.take(b'$') {
+            return Some(position);
+        }
+        self.position = start;
+        None
+    }
+
+    fn parse_length_modifier(&mut self) -> LengthModifier {
+        let (length, size) = match (self.peek(), self.bytes.get(self.position + 1)) {
+            (Some(b'h'), Some(b'h')) => (LengthModifier::HalfHalf, 2),
+            (Some(b'h'), _) => (LengthModifier::Half, 1),
+            (Some(b'l'), Some(b'l')) => (LengthModifier::LongLong, 2),
+            (Some(b'l'), _) => (LengthModifier::Long, 1),
+            (Some(b'q'), _) => (LengthModifier::LongLong, 1),
+            (Some(b'L'), _) => (LengthModifier::LongDouble, 1),
+            (Some(b'j'), _) => (LengthModifier::IntMax, 1),
+            (Some(b'z'), _) => (LengthModifier::Size, 1),
+            (Some(b't'), _) => (LengthModifier::Ptrdiff, 1),
+            _ => return LengthModifier::None,
+        };
+        self.position += size;
+        length
+    }
+
+    fn parse_decimal(&mut self) -> usize {
+        let mut value = 0_usize;
+        while let Some(digit @ b'0'..=b'9') = self.peek() {
+            value = value
+                .saturating_mul(10)
+                .saturating_add((digit - b'0') as usize);
+            self.advance();
+        }
+        value
+    }
+}
+
+impl<'a> Iterator for ScanfParser<'a> {
+    type Item = ScanfItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.advance()?;
+        if is_space(current) {
+            while self.peek().is_some_and(is_space) {
+                self.advance();
+            }
+            return Some(ScanfItem::Whitespace);
+        }
+        if current != b'%' {
+            return Some(ScanfItem::Literal(current));
+        }
+        let specifier = self.parse_specifier();
+        if specifier.is_none() {
+            self.position = self.bytes.len();
+        }
+        specifier.map(ScanfItem::Specifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    fn parse(format: &str) -> Vec<ScanfItem<'static>> {
+        let format = CString::new(format).unwrap().into_bytes_with_nul().leak();
+        unsafe { ScanfParser::new(format.as_ptr().cast()) }.collect()
+    }
+
+    fn set_members(set: &str) -> String {
+        let items = parse(&format!("%[{set}"));
+        let [ScanfItem::Specifier(ScanfSpecifier {
+            conversion: ScanfConversion::Set(set),
+            ..
+        })] = items[..]
+        else {
+            panic!("expected one set, got {items:?}");
+        };
+        let table = set.membership();
+        (0..=255u8)
+            .filter(|&byte| table[byte as usize])
+            .map(char::from)
+            .collect()
+    }
+
+    #[test]
+    fn directives() {
+        assert_eq!(parse(" a\t\n%%"), [
+            ScanfItem::Whitespace,
+            ScanfItem::Literal(b'a'),
+            ScanfItem::Whitespace,
+            ScanfItem::Specifier(ScanfSpecifier {
+                argument: None,
+                suppress: false,
+                width: None,
+                allocate: false,
+                length: LengthModifier::None,
+                conversion: ScanfConversion::Percent,
+            }),
+        ]);
+    }
+
+    #[test]
+    fn specifier_fields() {
+        let [ScanfItem::Specifier(specifier)] = parse("%*12mlld")[..] else {
+            panic!("expected one specifier");
+        };
+        assert!(specifier.suppress && specifier.allocate);
+        assert_eq!(specifier.width, Some(12));
+        assert_eq!(specifier.length, LengthModifier::LongLong);
+        assert_eq!(specifier.conversion, ScanfConversion::SignedInt {
+            base: 10
+        });
+
+        let [ScanfItem::Specifier(specifier)] = parse("%0hhx")[..] else {
+            panic!("expected one specifier");
+        };
+        assert_eq!(specifier.width, None);
+        assert_eq!(specifier.length, LengthModifier::HalfHalf);
+
+        let [ScanfItem::Specifier(specifier)] = parse("%2$12s")[..] else {
+            panic!("expected one specifier");
+        };
+        assert_eq!(specifier.argument, Some(2));
+        assert_eq!(specifier.width, Some(12));
+        assert_eq!(specifier.conversion, ScanfConversion::String);
+    }
+
+    #[test]
+    fn invalid_conversion_ends_the_format() {
+        assert_eq!(parse("a%yb%d"), [ScanfItem::Literal(b'a')]);
+        assert_eq!(parse("%[abc"), []);
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(set_members("a-d]"), "abcd");
+        assert_eq!(set_members("]a]"), "]a");
+        assert_eq!(set_members("-a]"), "-a");
+        assert_eq!(set_members("a-]"), "-a");
+        assert_eq!(set_members("c-a]"), "-ac");
+        assert_eq!(set_members("a-c-e]"), "-abce");
+        assert_eq!(set_members("^\x01-\u{7f}]").chars().count(), 129);
+    }
+}
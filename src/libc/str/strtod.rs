@@ -0,0 +1,795 @@
+//! `strtod`, `strtof` and `strtold`, and the floating-point reading scanf shares with them. Conversion is exact: the
+//! digits are read into a big integer, scaled by their power of ten and rounded once, to nearest with ties to even.
+
+use std::{arch::naked_asm, ffi::c_char};
+
+use super::bignum::BigUint;
+use crate::{
+    libc::errno::{set_errno, Errno},
+    signature_matches_libc,
+};
+
+/// Significant decimal digits kept. Past them, all that matters is whether a nonzero digit follows, which a final
+/// sticky `1` records: a point halfway between two `long double`s has at most ~11,500 significant digits.
+const MAX_DIGITS: usize = 12_000;
+
+/// Where exponents saturate while being read; any exponent past it is infinity or zero in every format.
+const MAX_EXPONENT: i64 = 1_000_000_000;
+
+/// Decimal magnitudes (the power of ten just above the value) past which every format overflows or underflows.
+const DECIMAL_RANGE: i64 = 5000;
+
+/// Where the scanners take bytes from: a string for `strtod` and `sscanf`, a stream for `fscanf`. Looking at a byte
+/// doesn't take it, so a scanner leaves the byte that ended its item for whatever reads next.
+pub(crate) trait ByteSource {
+    fn peek(&mut self) -> Option<u8>;
+
+    fn advance(&mut self);
+}
+
+/// The bytes of a null-terminated string.
+pub(crate) struct CStringSource {
+    cursor: *const u8,
+}
+
+impl CStringSource {
+    pub unsafe fn new(string: *const c_char) -> Self {
+        Self {
+            cursor: string.cast(),
+        }
+    }
+
+    pub fn position(&self) -> *const u8 {
+        self.cursor
+    }
+}
+
+impl ByteSource for CStringSource {
+    fn peek(&mut self) -> Option<u8> {
+        let byte = unsafe { *self.cursor };
+        (byte != 0).then_some(byte)
+    }
+
+    fn advance(&mut self) {
+        self.cursor = unsafe { self.cursor.add(1) };
+    }
+}
+
+/// `isspace` in the C locale.
+pub(crate) fn is_space(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t'..=b'\r')
+}
+
+/// A `ByteSource` that counts what it hands out.
+pub(crate) struct Scan<'s, S: ByteSource> {
+    source: &'s mut S,
+    pub consumed: usize,
+}
+
+impl<'s, S: ByteSource> Scan<'s, S> {
+    pub fn new(source: &'s mut S) -> Self {
+        Self {
+            source,
+            consumed: 0,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<u8> {
+        self.source.peek()
+    }
+
+    /// Takes the next byte if `accept` holds for it.
+    pub fn take_if(&mut self, accept: impl FnOnce(u8) -> bool) -> Option<u8> {
+        let byte = self.source.peek().filter(|&byte| accept(byte))?;
+        self.source.advance();
+        self.consumed += 1;
+        Some(byte)
+    }
+
+    /// Takes `+` or `-`, returning whether it was a minus.
+    pub fn take_sign(&mut self) -> bool {
+        self.take_if(|byte| matches!(byte, b'+' | b'-')) == Some(b'-')
+    }
+
+    /// Takes as much of `word` as follows, ignoring case; whether all of it did.
+    fn take_word(&mut self, word: &[u8]) -> bool {
+        word.iter().all(|&expected| {
+            self.take_if(|byte| byte.to_ascii_lowercase() == expected)
+                .is_some()
+        })
+    }
+}
+
+/// A floating-point number as read, before it is rounded to a format.
+pub(crate) struct ScannedFloat {
+    /// Bytes taken: the longest prefix of something that could still have become a number.
+    pub consumed: usize,
+    /// Bytes of that which form a number (`"1e+"` takes three but only `"1"` is one); 0 if none do.
+    pub valid: usize,
+    negative: bool,
+    magnitude: Magnitude,
+    /// The item ended right after `0x`, which is only ever the number 0.
+    bare_hex_prefix: bool,
+}
+
+enum Magnitude {
+    /// `digits × 10^exponent`, without leading zeros.
+    Decimal {
+        digits: Vec<u8>,
+        exponent: i64,
+    },
+    /// `(mantissa + tail) × 2^exponent`, the tail nonzero just when `sticky`.
+    Hex {
+        mantissa: u128,
+        exponent: i64,
+        sticky: bool,
+    },
+    Infinity,
+    /// A quiet NaN, with the low significand bits `nan(chars)` may set.
+    Nan {
+        payload: u64,
+    },
+}
+
+impl ScannedFloat {
+    /// Whether scanf takes this as a match, as glibc decides: some prefix is a number, the item didn't stop inside
+    /// `inf`, `infinity` or `nan`, and it doesn't end right after `0x` (though `strtod` reads a 0 out of that).
+    pub fn scanf_match(&self) -> bool {
+        let whole_word = match self.magnitude {
+            Magnitude::Infinity | Magnitude::Nan { .. } => self.valid == self.consumed,
+            _ => true,
+        };
+        self.valid > 0 && whole_word && !self.bare_hex_prefix
+    }
+
+    /// The value in `format`.
+    pub fn convert(&self, format: BinaryFormat) -> Converted {
+        match self.magnitude {
+            Magnitude::Decimal {
+                ref digits,
+                exponent,
+            } => format.round_decimal(self.negative, digits, exponent),
+            Magnitude::Hex {
+                mantissa,
+                exponent,
+                sticky,
+            } => format.round(self.negative, mantissa, exponent, sticky),
+            Magnitude::Infinity => Converted {
+                bits: format.infinity(self.negative),
+                range_error: false,
+            },
+            Magnitude::Nan { payload } => Converted {
+                bits: format.nan(self.negative, payload),
+                range_error: false,
+            },
+        }
+    }
+}
+
+/// Reads the longest prefix of `source` that is, or could still become, a floating-point number: decimal or hex,
+/// `inf`, `infinity` or `nan`, and with `nan_payload` the `nan(chars)` form `strtod` reads and scanf doesn't.
+pub(crate) fn scan_float(source: &mut impl ByteSource, nan_payload: bool) -> ScannedFloat {
+    let mut scan = Scan::new(source);
+    let mut scanned = ScannedFloat {
+        consumed: 0,
+        valid: 0,
+        negative: scan.take_sign(),
+        magnitude: Magnitude::Decimal {
+            digits: Vec::new(),
+            exponent: 0,
+        },
+        bare_hex_prefix: false,
+    };
+    match scan.peek().map(|byte| byte.to_ascii_lowercase()) {
+        Some(b'i') => {
+            if scan.take_word(b"inf") {
+                scanned.magnitude = Magnitude::Infinity;
+                scanned.valid = scan.consumed;
+                if scan.take_word(b"inity") {
+                    scanned.valid = scan.consumed;
+                }
+            }
+        }
+        Some(b'n') => {
+            if scan.take_word(b"nan") {
+                scanned.magnitude = Magnitude::Nan { payload: 0 };
+                scanned.valid = scan.consumed;
+                if nan_payload && scan.take_if(|byte| byte == b'(').is_some() {
+                    let mut sequence = Vec::new();
+                    while let Some(byte) =
+                        scan.take_if(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+                    {
+                        sequence.push(byte);
+                    }
+                    if scan.take_if(|byte| byte == b')').is_some() {
+                        scanned.magnitude = Magnitude::Nan {
+                            payload: parse_payload(&sequence),
+                        };
+                        scanned.valid = scan.consumed;
+                    }
+                }
+            }
+        }
+        Some(b'0') => {
+            scan.take_if(|_| true);
+            scanned.valid = scan.consumed;
+            if scan.take_if(|byte| byte | 0x20 == b'x').is_some() {
+                scan_hex(&mut scan, &mut scanned);
+            } else {
+                scan_decimal(&mut scan, &mut scanned, true);
+            }
+        }
+        _ => scan_decimal(&mut scan, &mut scanned, false),
+    }
+    scanned.consumed = scan.consumed;
+    scanned
+}
+
+/// glibc's reading of `nan(chars)`: `chars` as an unsigned number in C notation (decimal, `0x` hex or `0` octal),
+/// ignored unless all of it is one.
+fn parse_payload(sequence: &[u8]) -> u64 {
+    let (digits, radix) = match sequence {
+        [b'0', x, rest @ ..] if x | 0x20 == b'x' => (rest, 16),
+        [b'0', rest @ ..] => (rest, 8),
+        _ => (sequence, 10),
+    };
+    digits
+        .iter()
+        .try_fold(0u64, |value, &byte| {
+            let digit = (byte as char).to_digit(radix)?;
+            Some(
+                value
+                    .saturating_mul(radix as u64)
+                    .saturating_add(digit as u64),
+            )
+        })
+        .unwrap_or(0)
+}
+
+/// Reads an exponent's digits, after its `e` or `p`, returning it if it has any.
+fn scan_exponent<S: ByteSource>(scan: &mut Scan<'_, S>, scanned: &mut ScannedFloat) -> Option<i64> {
+    let negative = scan.take_sign();
+    let mut exponent = None;
+    while let Some(digit) = scan.take_if(|byte| byte.is_ascii_digit()) {
+        let value: i64 = exponent.unwrap_or(0);
+        exponent = Some((value * 10 + (digit - b'0') as i64).min(MAX_EXPONENT));
+        scanned.valid = scan.consumed;
+    }
+    exponent.map(|exponent| if negative { -exponent } else { exponent })
+}
+
+fn scan_decimal<S: ByteSource>(
+    scan: &mut Scan<'_, S>,
+    scanned: &mut ScannedFloat,
+    mut seen_digit: bool,
+) {
+    let mut digits = Vec::new();
+    let mut exponent = 0i64;
+    let mut dropped_nonzero = false;
+    let mut point = false;
+    loop {
+        if let Some(digit) = scan.take_if(|byte| byte.is_ascii_digit()) {
+            let digit = digit - b'0';
+            seen_digit = true;
+            if digits.is_empty() && digit == 0 {
+                exponent -= point as i64;
+            } else if digits.len() < MAX_DIGITS {
+                digits.push(digit);
+                exponent -= point as i64;
+            } else {
+                dropped_nonzero |= digit != 0;
+                exponent += !point as i64;
+            }
+            scanned.valid = scan.consumed;
+        } else if !point && scan.take_if(|byte| byte == b'.').is_some() {
+            point = true;
+            if seen_digit {
+                scanned.valid = scan.consumed;
+            }
+        } else {
+            break;
+        }
+    }
+    if dropped_nonzero {
+        digits.push(1);
+        exponent -= 1;
+    }
+    if seen_digit && scan.take_if(|byte| byte | 0x20 == b'e').is_some() {
+        exponent += scan_exponent(scan, scanned).unwrap_or(0);
+    }
+    scanned.magnitude = Magnitude::Decimal { digits, exponent };
+}
+
+fn scan_hex<S: ByteSource>(scan: &mut Scan<'_, S>, scanned: &mut ScannedFloat) {
+    let mut mantissa = 0u128;
+    let mut exponent = 0i64;
+    let mut sticky = false;
+    let mut point = false;
+    let mut seen_digit = false;
+    loop {
+        if let Some(digit) = scan.take_if(|byte| byte.is_ascii_hexdigit()) {
+            let digit = (digit as char).to_digit(16).unwrap_or(0) as u128;
+            seen_digit = true;
+            // Keep 124 bits, more than any format rounds from.
+            if mantissa >> 116 == 0 {
+                mantissa = mantissa << 4 | digit;
+                exponent -= 4 * point as i64;
+            } else {
+                sticky |= digit != 0;
+                exponent += 4 * !point as i64;
+            }
+            scanned.valid = scan.consumed;
+        } else if !point && scan.take_if(|byte| byte == b'.').is_some() {
+            point = true;
+            if seen_digit {
+                scanned.valid = scan.consumed;
+            }
+        } else {
+            break;
+        }
+    }
+    scanned.bare_hex_prefix = !seen_digit && !point;
+    if seen_digit && scan.take_if(|byte| byte | 0x20 == b'p').is_some() {
+        exponent += scan_exponent(scan, scanned).unwrap_or(0);
+    }
+    scanned.magnitude = Magnitude::Hex {
+        mantissa,
+        exponent,
+        sticky,
+    };
+}
+
+/// The binary formats numbers are read into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryFormat {
+    Single,
+    Double,
+    /// The x87 80-bit format, with an explicit integer bit: `long double` on x86_64.
+    Extended,
+    /// IEEE binary128: `long double` on aarch64.
+    Quad,
+}
+
+/// A value's bits in the low end of a `u128`, and whether it overflowed or came out tiny and inexact (`ERANGE`).
+pub(crate) struct Converted {
+    pub bits: u128,
+    pub range_error: bool,
+}
+
+impl BinaryFormat {
+    #[cfg(target_arch = "x86_64")]
+    pub const LONG_DOUBLE: Self = Self::Extended;
+    #[cfg(target_arch = "aarch64")]
+    pub const LONG_DOUBLE: Self = Self::Quad;
+
+    /// Significand bits, counting the leading one.
+    fn precision(self) -> i64 {
+        match self {
+            Self::Single => 24,
+            Self::Double => 53,
+            Self::Extended => 64,
+            Self::Quad => 113,
+        }
+    }
+
+    /// The largest exponent, which is also the bias.
+    fn max_exponent(self) -> i64 {
+        match self {
+            Self::Single => 127,
+            Self::Double => 1023,
+            Self::Extended | Self::Quad => 16383,
+        }
+    }
+
+    /// The bytes a value occupies; x87's 10, without the padding that follows it in memory.
+    pub fn size(self) -> usize {
+        match self {
+            Self::Single => 4,
+            Self::Double => 8,
+            Self::Extended => 10,
+            Self::Quad => 16,
+        }
+    }
+
+    fn exponent_bits(self) -> u32 {
+        match self {
+            Self::Single => 8,
+            Self::Double => 11,
+            Self::Extended | Self::Quad => 15,
+        }
+    }
+
+    /// Where the biased exponent starts; the sign bit sits just above it.
+    fn exponent_shift(self) -> u32 {
+        match self {
+            Self::Extended => 64,
+            _ => self.precision() as u32 - 1,
+        }
+    }
+
+    /// `significand` as stored: the explicit format keeps its leading one, the others drop it.
+    fn encode(self, negative: bool, biased_exponent: u128, significand: u128) -> u128 {
+        let stored = match self {
+            Self::Extended => significand,
+            _ => significand & ((1 << (self.precision() - 1)) - 1),
+        };
+        let sign_shift = self.exponent_shift() + self.exponent_bits();
+        (negative as u128) << sign_shift | biased_exponent << self.exponent_shift() | stored
+    }
+
+    fn infinity(self, negative: bool) -> u128 {
+        let integer_bit = match self {
+            Self::Extended => 1 << 63,
+            _ => 0,
+        };
+        self.encode(negative, (1 << self.exponent_bits()) - 1, integer_bit)
+    }
+
+    /// A quiet NaN: the top fraction bit (and x87's integer bit) set, and as much of `payload` as fits below it.
+    fn nan(self, negative: bool, payload: u64) -> u128 {
+        let quiet_bit = 1 << (self.precision() - 2);
+        let integer_bit = match self {
+            Self::Extended => 1 << 63,
+            _ => 0,
+        };
+        let significand = integer_bit | quiet_bit | (payload as u128 & (quiet_bit - 1));
+        self.encode(negative, (1 << self.exponent_bits()) - 1, significand)
+    }
+
+    fn overflow(self, negative: bool) -> Converted {
+        Converted {
+            bits: self.infinity(negative),
+            range_error: true,
+        }
+    }
+
+    /// Rounds `(top + tail) × 2^exponent`, the tail in [0, 1) and nonzero just when `sticky`, to the nearest value
+    /// of this format, ties to even.
+    fn round(self, negative: bool, top: u128, exponent: i64, sticky: bool) -> Converted {
+        if top == 0 {
+            return Converted {
+                bits: self.encode(negative, 0, 0),
+                range_error: sticky,
+            };
+        }
+        let precision = self.precision();
+        let max_exponent = self.max_exponent();
+        let leading = exponent + 127 - top.leading_zeros() as i64;
+        if leading > max_exponent {
+            return self.overflow(negative);
+        }
+
+        // The exponent of the result's last significand bit; subnormals all share the smallest.
+        let mut quantum = leading.max(1 - max_exponent) - (precision - 1);
+        let shift = quantum - exponent;
+        let (mut significand, round_bit, rest) = if shift <= 0 {
+            (top << -shift, false, sticky)
+        } else if shift > 128 {
+            (0, false, true)
+        } else {
+            let below = top & ((1 << (shift - 1)) - 1) != 0;
+            (
+                top.checked_shr(shift as u32).unwrap_or(0),
+                top >> (shift - 1) & 1 == 1,
+                below || sticky,
+            )
+        };
+        if round_bit && (rest || significand & 1 == 1) {
+            significand += 1;
+            if significand >> precision != 0 {
+                significand >>= 1;
+                quantum += 1;
+            }
+        }
+        if quantum + precision - 1 > max_exponent {
+            return self.overflow(negative);
+        }
+
+        let normal = significand >> (precision - 1) != 0;
+        let biased_exponent = if normal {
+            quantum + precision - 1 + max_exponent
+        } else {
+            0
+        };
+        Converted {
+            bits: self.encode(negative, biased_exponent as u128, significand),
+            range_error: !normal && (round_bit || rest),
+        }
+    }
+
+    /// Rounds `digits × 10^exponent`. A positive power multiplies out exactly; a negative one divides, keeping a few
+    /// quotient bits more than the precision and whether the division left a remainder.
+    fn round_decimal(self, negative: bool, digits: &[u8], exponent: i64) -> Converted {
+        if digits.is_empty() {
+            return self.round(negative, 0, 0, false);
+        }
+        let magnitude = digits.len() as i64 + exponent;
+        if magnitude > DECIMAL_RANGE {
+            return self.overflow(negative);
+        }
+        if magnitude < -DECIMAL_RANGE {
+            return self.round(negative, 0, 0, true);
+        }
+
+        let mut value = BigUint::from_decimal_digits(digits);
+        if exponent >= 0 {
+            value.multiply_pow10(exponent as u32);
+            let shift = value.bit_length().saturating_sub(128);
+            let top = value.bits(shift, 128);
+            return self.round(negative, top, shift as i64, value.any_bits_below(shift));
+        }
+
+        let mut divisor = BigUint::from_u128(1);
+        divisor.multiply_pow10(-exponent as u32);
+        let scale = divisor.bit_length() as i64 - value.bit_length() as i64 + self.precision() + 3;
+        let (numerator, denominator) = if scale >= 0 {
+            (value.shifted_left(scale as u32), divisor)
+        } else {
+            (value, divisor.shifted_left(-scale as u32))
+        };
+        let (quotient, remainder) = numerator.divide(&denominator);
+        self.round(negative, quotient, -scale, remainder)
+    }
+}
+
+/// Converts the number at the start of `string`, after any whitespace, storing where it ends in `end`: `string`
+/// itself if there is no number.
+unsafe fn convert_prefix(
+    string: *const c_char,
+    end: *mut *mut c_char,
+    format: BinaryFormat,
+) -> u128 {
+    let mut source = CStringSource::new(string);
+    while source.peek().is_some_and(is_space) {
+        source.advance();
+    }
+    let start = source.position();
+    let scanned = scan_float(&mut source, true);
+    if !end.is_null() {
+        *end = if scanned.valid == 0 {
+            string.cast_mut()
+        } else {
+            start.add(scanned.valid).cast_mut().cast()
+        };
+    }
+    if scanned.valid == 0 {
+        return 0;
+    }
+    let converted = scanned.convert(format);
+    if converted.range_error {
+        set_errno(Errno::RANGE);
+    }
+    converted.bits
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn strtod(string: *const c_char, end: *mut *mut c_char) -> f64 {
+    signature_matches_libc!(libc::strtod(string, end));
+    f64::from_bits(convert_prefix(string, end, BinaryFormat::Double) as u64)
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn strtof(string: *const c_char, end: *mut *mut c_char) -> f32 {
+    signature_matches_libc!(libc::strtof(string, end));
+    f32::from_bits(convert_prefix(string, end, BinaryFormat::Single) as u32)
+}
+
+/// `strtold`'s conversion, leaving the value's 16 bytes at `value` for the wrapper to load.
+unsafe extern "C" fn strtold_into(string: *const c_char, end: *mut *mut c_char, value: *mut u128) {
+    value.write_unaligned(convert_prefix(string, end, BinaryFormat::LONG_DOUBLE));
+}
+
+// Rust has no `long double` to return, so the conversion stores the value on the stack and these load it into the
+// return register: `st(0)` on x86_64, `q0` on aarch64.
+#[cfg(target_arch = "x86_64")]
+#[unsafe(naked)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn strtold(string: *const c_char, end: *mut *mut c_char) {
+    naked_asm!(
+        "sub rsp, 24",
+        "mov rdx, rsp",
+        "call {}",
+        "fld tbyte ptr [rsp]",
+        "add rsp, 24",
+        "ret",
+        sym strtold_into,
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[unsafe(naked)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn strtold(string: *const c_char, end: *mut *mut c_char) {
+    naked_asm!(
+        "stp x29, x30, [sp, #-32]!",
+        "mov x29, sp",
+        "add x2, sp, #16",
+        "bl {}",
+        "ldr q0, [sp, #16]",
+        "ldp x29, x30, [sp], #32",
+        "ret",
+        sym strtold_into,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CString};
+
+    use super::{convert_prefix, scan_float, BinaryFormat, CStringSource};
+    use crate::libc::{
+        errno::{errno, Errno},
+        str::test_random::Random,
+    };
+
+    /// miros's `strtod` bits and end offset, next to glibc's.
+    fn both_strtod(input: &str) -> ((u64, isize), (u64, isize)) {
+        let string = CString::new(input).unwrap();
+        let mut miros_end = std::ptr::null_mut();
+        let mut glibc_end = std::ptr::null_mut();
+        unsafe {
+            let miros =
+                convert_prefix(string.as_ptr(), &mut miros_end, BinaryFormat::Double) as u64;
+            let glibc = libc::strtod(string.as_ptr(), &mut glibc_end).to_bits();
+            (
+                (miros, miros_end.offset_from(string.as_ptr())),
+                (glibc, glibc_end.offset_from(string.as_ptr())),
+            )
+        }
+    }
+
+    #[test]
+    fn strtod_matches_glibc_on_edge_cases() {
+        for input in [
+            "0",
+            "-0",
+            "1",
+            "  +1.5e3x",
+            "1e",
+            "1e+",
+            "1e+x",
+            ".",
+            ".5",
+            "5.",
+            "-.e1",
+            "0x",
+            "0x.",
+            "0x.p1",
+            "0x1p3",
+            "0X1.8P-1",
+            "0x1.fffffffffffff8p0",
+            "0x1.fffffffffffff7ffp1023",
+            "0x1p-1074",
+            "0x1p-1075",
+            "0x1.0000000000001p-1075",
+            "inf",
+            "-Infinity",
+            "infin",
+            "nan",
+            "-nan",
+            "nan(123)",
+            "nan(12",
+            "nan(0x1f)",
+            "nan(017)",
+            "nan(12z)",
+            "nan()",
+            "1e308",
+            "1.7976931348623157e308",
+            "1.7976931348623158e308",
+            "1.7976931348623159e308",
+            "1e309",
+            "4.9406564584124654e-324",
+            "2.4703282292062327e-324",
+            "2.4703282292062328e-324",
+            "1e-400",
+            "2.2250738585072011e-308",
+            "2.2250738585072014e-308",
+            "9007199254740993",
+            "9007199254740993.0000001",
+            "123456789012345678901234567890e-30",
+            "0.000000000000000000000000000000000001e36",
+            "1e-99999999999",
+            "1e99999999999",
+            "0e99999999999",
+            "-",
+            "+",
+            "e5",
+            "x",
+        ] {
+            let (miros, glibc) = both_strtod(input);
+            assert_eq!(miros, glibc, "strtod({input:?})");
+        }
+    }
+
+    #[test]
+    fn strtod_matches_glibc_on_random_numbers() {
+        let mut random = Random(0x5ca7_7e12);
+        for _ in 0..20_000 {
+            let bits = random.next();
+            let value = f64::from_bits(bits);
+            // Shortest round-trip, a few digits off either way, and halfway points between neighbours.
+            let precision = random.below(25);
+            let scientific = format!("{value:.*e}", precision as usize);
+            let halfway = format!(
+                "{:.40e}",
+                value.abs() / 2.0 + f64::from_bits(bits.wrapping_add(1)).abs() / 2.0
+            );
+            for input in [value.to_string(), format!("{value:e}"), scientific, halfway] {
+                let (miros, glibc) = both_strtod(&input);
+                assert_eq!(miros, glibc, "strtod({input:?})");
+            }
+        }
+    }
+
+    #[test]
+    fn strtof_and_long_double_match_glibc_scanf() {
+        let mut random = Random(0xf10a7);
+        for _ in 0..10_000 {
+            let digits = random.below(40) + 1;
+            let mantissa: String = (0..digits)
+                .map(|_| char::from(b'0' + random.below(10) as u8))
+                .collect();
+            let exponent = random.below(9000) as i64 - 4500;
+            let input = CString::new(format!("{mantissa}e{exponent}")).unwrap();
+
+            let mut glibc_single = 0f32;
+            let mut glibc_long_double = [0u8; 16];
+            unsafe {
+                libc::sscanf(input.as_ptr(), c"%f".as_ptr(), &mut glibc_single);
+                libc::sscanf(
+                    input.as_ptr(),
+                    c"%Lf".as_ptr(),
+                    glibc_long_double.as_mut_ptr(),
+                );
+                let null = std::ptr::null_mut();
+                let single = convert_prefix(input.as_ptr(), null, BinaryFormat::Single) as u32;
+                assert_eq!(single, glibc_single.to_bits(), "{input:?} as float");
+                let format = BinaryFormat::LONG_DOUBLE;
+                let long_double = convert_prefix(input.as_ptr(), null, format).to_le_bytes();
+                assert_eq!(
+                    long_double[..format.size()],
+                    glibc_long_double[..format.size()],
+                    "{input:?} as long double"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn range_errors() {
+        for (input, range_error) in [
+            ("1e309", true),
+            ("1e-400", true),
+            ("1e-310", true),
+            ("1e-300", false),
+            ("0", false),
+        ] {
+            let string = CString::new(input).unwrap();
+            errno.set(Errno::INVAL);
+            unsafe { convert_prefix(string.as_ptr(), std::ptr::null_mut(), BinaryFormat::Double) };
+            assert_eq!(errno.get() == Errno::RANGE, range_error, "{input}");
+        }
+    }
+
+    #[test]
+    fn scanf_takes_more_than_the_number() {
+        for (input, consumed, valid, matched) in [
+            ("1e+x", 3, 1, true),
+            ("infinx", 5, 3, false),
+            ("infx", 3, 3, true),
+            ("nan(1)", 3, 3, true),
+            ("0x", 2, 1, false),
+            ("0x.p1", 3, 1, true),
+            ("+.e1", 2, 0, false),
+        ] {
+            let string = CString::new(input).unwrap();
+            let mut source = unsafe { CStringSource::new(string.as_ptr() as *const c_char) };
+            let scanned = scan_float(&mut source, false);
+            assert_eq!(
+                (scanned.consumed, scanned.valid, scanned.scanf_match()),
+                (consumed, valid, matched),
+                "{input}"
+            );
+        }
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
 // genuine result (including valid negative offsets and pointers).
 const ERROR_RANGE: std::ops::RangeInclusive<isize> = -4095..=-1;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn syscall(number: c_long, mut args: ...) -> c_long {
     signature_matches_libc!(libc::syscall(number, args));
 
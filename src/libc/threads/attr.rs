@@ -1,10 +1,11 @@
 use core::{
     ffi::{c_int, c_void},
+    mem::offset_of,
     ptr::NonNull,
 };
 
 use crate::{
-    libc::threads::PthreadT,
+    libc::threads::{initialize_object, PthreadT},
     page_size, signature_matches_libc,
     tls::thread_control_block::{DetachState, ThreadControlBlock},
 };
@@ -12,6 +13,12 @@ use crate::{
 const PTHREAD_CREATE_JOINABLE: i32 = 0;
 const PTHREAD_CREATE_DETACHED: i32 = 1;
 
+// glibc pads `pthread_attr_t` to 56 bytes on x86-64 and 64 on arm64.
+#[cfg(target_arch = "x86_64")]
+const RESERVED_SIZE: usize = 28;
+#[cfg(target_arch = "aarch64")]
+const RESERVED_SIZE: usize = 36;
+
 /// Caller-opaque blob matching glibc's `pthread_attr_t` footprint; zero-valued `stack_size`/`guard_size` resolve to the runtime defaults.
 #[repr(C, align(8))]
 pub struct PthreadAttr {
@@ -19,10 +26,13 @@ pub struct PthreadAttr {
     stack_size: usize,
     guard_size: usize,
     detach_state: i32,
-    _reserved: [u8; 28],
+    _reserved: [u8; RESERVED_SIZE],
 }
 
 const _: () = assert!(size_of::<PthreadAttr>() == size_of::<libc::pthread_attr_t>());
+/// musl's `pthread_attr_t` on every 64-bit target: arm64 glibc's is 8 bytes longer.
+const MUSL_ATTR_SIZE: usize = 56;
+const _: () = assert!(offset_of!(PthreadAttr, _reserved) <= MUSL_ATTR_SIZE);
 const _: () = assert!(align_of::<PthreadAttr>() == align_of::<libc::pthread_attr_t>());
 
 /// Thread-creation parameters resolved from a (possibly null) attr against the runtime defaults.
@@ -59,26 +69,27 @@ pub unsafe fn resolve(
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_init(attr: &mut PthreadAttr) -> c_int {
     signature_matches_libc!(libc::pthread_attr_init(std::mem::transmute(attr)));
-    *attr = PthreadAttr {
+    let attributes = PthreadAttr {
         stack_base: core::ptr::null_mut(),
         stack_size: 0,
         guard_size: page_size::get_page_size(),
         detach_state: PTHREAD_CREATE_JOINABLE,
-        _reserved: [0; 28],
+        _reserved: [0; RESERVED_SIZE],
     };
+    initialize_object(attr, attributes, MUSL_ATTR_SIZE);
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_destroy(attr: &PthreadAttr) -> c_int {
     signature_matches_libc!(libc::pthread_attr_destroy(std::mem::transmute(attr)));
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_setdetachstate(
     attr: &mut PthreadAttr,
     detach_state: c_int,
@@ -94,7 +105,7 @@ unsafe extern "C" fn pthread_attr_setdetachstate(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_setstacksize(attr: &mut PthreadAttr, stack_size: usize) -> c_int {
     signature_matches_libc!(libc::pthread_attr_setstacksize(
         std::mem::transmute(attr),
@@ -104,7 +115,7 @@ unsafe extern "C" fn pthread_attr_setstacksize(attr: &mut PthreadAttr, stack_siz
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_getstack(
     attr: &PthreadAttr,
     stack_base_out: &mut *mut c_void,
@@ -120,7 +131,7 @@ unsafe extern "C" fn pthread_attr_getstack(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_attr_getguardsize(
     attr: &PthreadAttr,
     guard_size_out: &mut usize,
@@ -133,9 +144,9 @@ unsafe extern "C" fn pthread_attr_getguardsize(
     0
 }
 
-/// Reports a running thread's stack from its TCB region. The handle is the thread pointer (= TCB address),
-/// and a worker's region is `[guard][stack][TLS_RESERVE][TCB][miros tls]`, so the stack is the slice below it.
-#[cfg_attr(not(test), no_mangle)]
+/// Reports a running thread's stack from its TCB region. The handle is the TCB address, and a worker's region is
+/// `[guard][stack][static TLS]`, so the stack is the slice below its TLS.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_getattr_np(thread: PthreadT, attr: &mut PthreadAttr) -> c_int {
     signature_matches_libc!(libc::pthread_getattr_np(
         thread as _,
@@ -148,12 +159,13 @@ unsafe extern "C" fn pthread_getattr_np(thread: PthreadT, attr: &mut PthreadAttr
         DetachState::Detached => PTHREAD_CREATE_DETACHED,
         _ => PTHREAD_CREATE_JOINABLE,
     };
-    *attr = PthreadAttr {
+    let attributes = PthreadAttr {
         stack_base: stack_base as *mut c_void,
         stack_size,
         guard_size: page_size::get_page_size(),
         detach_state,
-        _reserved: [0; 28],
+        _reserved: [0; RESERVED_SIZE],
     };
+    initialize_object(attr, attributes, MUSL_ATTR_SIZE);
     0
 }
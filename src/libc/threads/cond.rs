@@ -222,7 +222,7 @@ impl PthreadCond {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_cond_wait(cond: &PthreadCond, mutex: &PthreadMutex) -> c_int {
     signature_matches_libc!(libc::pthread_cond_wait(
         std::mem::transmute(cond),
@@ -274,7 +274,7 @@ unsafe extern "C" fn pthread_cond_wait(cond: &PthreadCond, mutex: &PthreadMutex)
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_cond_signal(cond: &PthreadCond) -> c_int {
     signature_matches_libc!(libc::pthread_cond_signal(std::mem::transmute(cond)));
     if cond.waiter_references.load(Ordering::Relaxed).waiters() == u29::new(0) {
@@ -302,7 +302,7 @@ unsafe extern "C" fn pthread_cond_signal(cond: &PthreadCond) -> c_int {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_cond_broadcast(cond: &PthreadCond) -> c_int {
     signature_matches_libc!(libc::pthread_cond_broadcast(std::mem::transmute(cond)));
     if cond.waiter_references.load(Ordering::Relaxed).waiters() == u29::new(0) {
@@ -339,7 +339,7 @@ unsafe extern "C" fn pthread_cond_broadcast(cond: &PthreadCond) -> c_int {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_cond_init(
     cond: &mut PthreadCond,
     attr: Option<NonNull<libc::pthread_condattr_t>>,
@@ -359,7 +359,7 @@ unsafe extern "C" fn pthread_cond_init(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_cond_destroy(cond: &PthreadCond) -> c_int {
     signature_matches_libc!(libc::pthread_cond_destroy(std::mem::transmute(cond)));
     let (mut references, _) =
@@ -376,7 +376,7 @@ unsafe extern "C" fn pthread_cond_destroy(cond: &PthreadCond) -> c_int {
     0
 }
 
-/// glibc's `struct pthread_condattr { int value; }`.
+/// glibc's `struct pthread_condattr { int value; }`, padded to 8 bytes in arm64's public `pthread_condattr_t`.
 #[bitfield(u32)]
 struct PthreadCondAttr {
     #[bit(0, rw)]
@@ -385,22 +385,23 @@ struct PthreadCondAttr {
     monotonic_clock: bool,
 }
 
-const _: () = assert!(size_of::<PthreadCondAttr>() == size_of::<libc::pthread_condattr_t>());
+const _: () = assert!(size_of::<PthreadCondAttr>() == size_of::<c_int>());
+const _: () = assert!(size_of::<PthreadCondAttr>() <= size_of::<libc::pthread_condattr_t>());
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_init(attr: &mut PthreadCondAttr) -> c_int {
     signature_matches_libc!(libc::pthread_condattr_init(std::mem::transmute(attr)));
     *attr = PthreadCondAttr::new_with_raw_value(0);
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_destroy(attr: &PthreadCondAttr) -> c_int {
     signature_matches_libc!(libc::pthread_condattr_destroy(std::mem::transmute(attr)));
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_setpshared(
     attr: &mut PthreadCondAttr,
     pshared: c_int,
@@ -417,7 +418,7 @@ unsafe extern "C" fn pthread_condattr_setpshared(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_getpshared(
     attr: &PthreadCondAttr,
     pshared: &mut c_int,
@@ -434,7 +435,7 @@ unsafe extern "C" fn pthread_condattr_getpshared(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_setclock(
     attr: &mut PthreadCondAttr,
     clock_id: libc::clockid_t,
@@ -451,7 +452,7 @@ unsafe extern "C" fn pthread_condattr_setclock(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_condattr_getclock(
     attr: &PthreadCondAttr,
     clock_id: &mut libc::clockid_t,
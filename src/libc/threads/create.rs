@@ -1,7 +1,6 @@
 use std::{
     ffi::c_void,
-    mem::size_of,
-    ptr::{self, null_mut, NonNull},
+    ptr::{self, NonNull},
 };
 
 use super::{attr::PthreadAttr, PthreadT};
@@ -11,13 +10,10 @@ use crate::{
         process::clone::{clone3, Clone3Args, Clone3Flags},
     },
     page_size, signature_matches_libc,
-    syscall::thread_pointer::get_thread_pointer,
     tls::{
         get_tls_allocator,
-        thread_control_block::{
-            AtomicDetachState, DetachState, DynamicThreadVector, ThreadControlBlock,
-        },
-        TLS_RESERVE_SIZE,
+        thread_control_block::{DetachState, ThreadControlBlock},
+        thread_region_extent,
     },
 };
 
@@ -27,16 +23,25 @@ const DEFAULT_STACK_SIZE: usize = 8 * 1024 * 1024;
 pub unsafe fn thread_stack_bounds(
     thread_control_block: *const ThreadControlBlock,
 ) -> (usize, usize) {
-    // The TCB resides one `TLS_RESERVE_SIZE` above the stack top, and the stack starts one guard page into the region.
-    // `[guard][stack][TLS_RESERVE][TCB][miros tls]`
+    // `[guard][stack][static TLS]`: the stack starts one guard page into the region, and ends where the part of the
+    // static TLS below the thread pointer begins.
     let (region_base, _) = (*thread_control_block).region.to_raw_parts();
     let stack_base = region_base.addr() + page_size::get_page_size();
-    let stack_size = (thread_control_block as usize)
-        .saturating_sub(TLS_RESERVE_SIZE)
+    let (below_thread_pointer, _) = thread_region_extent(miros_tls_size());
+    let stack_size = (ThreadControlBlock::thread_pointer(thread_control_block.cast_mut()) as usize)
+        .saturating_sub(below_thread_pointer)
         .saturating_sub(stack_base);
     (stack_base, stack_size)
 }
 
+fn miros_tls_size() -> usize {
+    let allocator = unsafe { get_tls_allocator().lock().unwrap_unchecked() };
+    allocator
+        .miros_template()
+        .map(|t| t.block_size)
+        .unwrap_or(0)
+}
+
 unsafe extern "C" fn pthread_entry(context: *mut c_void) -> ! {
     let context = &*(context as *const PthreadContext);
     crate::allocator::install_heap();
@@ -44,13 +49,13 @@ unsafe extern "C" fn pthread_entry(context: *mut c_void) -> ! {
     // Userland:
     let return_value = (context.entry_function)(context.entry_argument);
 
-    let thread_pointer = get_thread_pointer() as *mut ThreadControlBlock;
-    (*thread_pointer).return_value = return_value;
+    let thread_control_block = ThreadControlBlock::current();
+    (*thread_control_block).return_value = return_value;
 
     // Destructors run before `abandon_heap` because they may malloc/free.
     super::run_at_thread_exit_destructors();
     crate::allocator::abandon_heap();
-    super::self_detach::on_thread_exit(thread_pointer);
+    super::self_detach::on_thread_exit(thread_control_block);
 }
 
 #[repr(C)]
@@ -59,7 +64,7 @@ struct PthreadContext {
     entry_argument: *mut c_void,
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_create(
     thread_addr_out: &mut PthreadT,
     attr: Option<NonNull<PthreadAttr>>,
@@ -77,20 +82,8 @@ unsafe extern "C" fn pthread_create(
     let guard_size = resolved_attr.guard_size;
     let stack_size = resolved_attr.stack_size;
 
-    let miros_tls_size;
-    {
-        let allocator = get_tls_allocator().lock().unwrap_unchecked();
-        miros_tls_size = allocator
-            .miros_template()
-            .map(|t| t.block_size)
-            .unwrap_or(0);
-    }
-
-    let total_size = guard_size
-        + stack_size
-        + TLS_RESERVE_SIZE
-        + size_of::<ThreadControlBlock>()
-        + miros_tls_size;
+    let (below_thread_pointer, above_thread_pointer) = thread_region_extent(miros_tls_size());
+    let total_size = guard_size + stack_size + below_thread_pointer + above_thread_pointer;
 
     let region = mmap(
         ptr::null_mut(),
@@ -105,25 +98,21 @@ unsafe extern "C" fn pthread_create(
 
     mprotect(region, guard_size, ProtectionFlags::ZERO);
 
-    let thread_pointer = region.add(guard_size + stack_size + TLS_RESERVE_SIZE) as *mut c_void;
-    let thread_control_block = thread_pointer as *mut ThreadControlBlock;
+    let thread_pointer = region.add(guard_size + stack_size + below_thread_pointer) as *mut c_void;
+    let thread_control_block = ThreadControlBlock::from_thread_pointer(thread_pointer);
 
     let initial_detach_state = if resolved_attr.detached {
         DetachState::Detached
     } else {
         DetachState::Joinable
     };
-    let current_tcb = get_thread_pointer() as *const ThreadControlBlock;
-    *thread_control_block = ThreadControlBlock {
-        thread_pointee: [],
-        thread_pointer_register: thread_pointer,
-        tid: 0,
-        detach_state: AtomicDetachState::new(initial_detach_state),
-        return_value: null_mut(),
-        region: ptr::slice_from_raw_parts_mut(region, total_size),
-        canary: (*current_tcb).canary,
-        dynamic_thread_vector: DynamicThreadVector::new(),
-    };
+    ThreadControlBlock::initialize(
+        thread_control_block,
+        0,
+        initial_detach_state,
+        ptr::slice_from_raw_parts_mut(region, total_size),
+        (*ThreadControlBlock::current()).canary,
+    );
 
     get_tls_allocator()
         .lock()
@@ -168,6 +157,6 @@ unsafe extern "C" fn pthread_create(
         return libc::EAGAIN;
     }
 
-    *thread_addr_out = thread_pointer as PthreadT;
+    *thread_addr_out = thread_control_block as PthreadT;
     0
 }
@@ -31,7 +31,7 @@ pub unsafe fn wait_until_exited(tid_pointer: *const i32) {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_join(
     thread_addr: PthreadT,
     return_value: Option<NonNull<*mut c_void>>,
@@ -7,7 +7,7 @@ use std::{cell::Cell, ffi::c_void, ptr::null_mut, sync::RwLock};
 
 use crate::signature_matches_libc;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 static PTHREAD_KEYS_MAX: usize = 128;
 
 pub(super) const PTHREAD_DESTRUCTOR_ITERATIONS: usize = 4;
@@ -48,7 +48,7 @@ static THREAD_LOCAL_ENTRIES: Cell<[ThreadLocalEntry; 128]> = Cell::new(
     }; PTHREAD_KEYS_MAX],
 );
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_key_create(
     mut_key_index: *mut u32,
     destructor: Option<unsafe extern "C" fn(*mut c_void)>,
@@ -69,7 +69,7 @@ unsafe extern "C" fn pthread_key_create(
         .unwrap_or(libc::EAGAIN)
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_key_delete(key_index: u32) -> i32 {
     signature_matches_libc!(libc::pthread_key_delete(key_index));
 
@@ -131,7 +131,7 @@ pub fn run_key_destructor_round() -> bool {
     )
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_getspecific(key_index: u32) -> *mut c_void {
     signature_matches_libc!(libc::pthread_getspecific(key_index));
 
@@ -154,7 +154,7 @@ unsafe extern "C" fn pthread_getspecific(key_index: u32) -> *mut c_void {
         .unwrap_or(null_mut())
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_setspecific(key_index: u32, value: *const c_void) -> i32 {
     signature_matches_libc!(libc::pthread_setspecific(key_index, value));
 
@@ -4,8 +4,10 @@ use atomic::Atomic;
 use bytemuck::NoUninit;
 
 use crate::{
-    signature_matches_libc, syscall,
-    syscall::{futex::FutexOperation, thread_pointer::get_thread_pointer, Syscall},
+    signature_matches_libc,
+    start::personality::{personality, Personality},
+    syscall,
+    syscall::{futex::FutexOperation, Syscall},
     tls::thread_control_block::ThreadControlBlock,
 };
 
@@ -21,11 +23,26 @@ mod self_detach;
 
 use key::{run_key_destructor_round, PTHREAD_DESTRUCTOR_ITERATIONS};
 
-/// A thread handle, thread pointer (= TCB address), matching glibc's `pthread_t` width.
+/// A thread handle, the thread's TCB address (also its thread pointer on x86-64), matching glibc's `pthread_t` width.
 pub type PthreadT = usize;
 
 pub unsafe fn current_tid() -> u32 {
-    (*get_thread_pointer().cast::<ThreadControlBlock>()).tid as u32
+    (*ThreadControlBlock::current()).tid as u32
+}
+
+/// Stores `value` into a caller-allocated pthread object, stopping at `musl_size` for musl programs: on arm64 musl's
+/// `pthread_mutex_t` and `pthread_attr_t` end 8 bytes before glibc's, whose tail miros never reads.
+unsafe fn initialize_object<T>(destination: *mut T, value: T, musl_size: usize) {
+    let value = std::mem::ManuallyDrop::new(value);
+    let size = match personality() {
+        Personality::Glibc => size_of::<T>(),
+        Personality::Musl => musl_size,
+    };
+    std::ptr::copy_nonoverlapping(
+        (&raw const value).cast::<u8>(),
+        destination.cast::<u8>(),
+        size,
+    );
 }
 
 /// A word a futex can park on: the kernel's 32-bit compare unit. Blanket-implemented, so a wrong-sized `T` fails only when a caller evaluates `ASSERT`.
@@ -93,7 +110,7 @@ struct TlsDestructor {
 #[thread_local]
 static TLS_DESTRUCTORS: RefCell<Vec<TlsDestructor>> = RefCell::new(Vec::new());
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn __cxa_thread_atexit_impl(
     destructor: unsafe extern "C" fn(*mut c_void),
     object: *mut c_void,
@@ -133,7 +150,7 @@ pub unsafe fn run_at_thread_exit_destructors() {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn gettid() -> i32 {
     signature_matches_libc!(std::mem::transmute(libc::gettid()));
     let result = syscall!(Syscall::GetTid);
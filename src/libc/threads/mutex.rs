@@ -11,7 +11,7 @@ use bitbybit::{bitenum, bitfield};
 use crate::{
     libc::{
         errno::Errno,
-        threads::{current_tid, futex_wait, futex_wake},
+        threads::{current_tid, futex_wait, futex_wake, initialize_object},
     },
     signature_matches_libc,
 };
@@ -67,9 +67,16 @@ pub struct PthreadMutex {
     _elision: u16,
     // TODO: Implement the robust list.
     _robust_list: RobustList,
+    // arm64's `pthread_mutex_t` is 48 bytes; the generic `__pthread_mutex_s` only fills the first 40.
+    #[cfg(target_arch = "aarch64")]
+    _padding: u64,
 }
 
 const _: () = assert!(size_of::<PthreadMutex>() == size_of::<libc::pthread_mutex_t>());
+/// musl's `pthread_mutex_t` on every 64-bit target; arm64's glibc padding lies past it.
+const MUSL_MUTEX_SIZE: usize = 40;
+const _: () =
+    assert!(offset_of!(PthreadMutex, _robust_list) + size_of::<RobustList>() <= MUSL_MUTEX_SIZE);
 const _: () = assert!(align_of::<PthreadMutex>() == align_of::<libc::pthread_mutex_t>());
 // glibc x86_64 `__pthread_mutex_s`: __lock @0, __count @4, __owner @8, __nusers @12, __kind @16, __spins @20, __elision @22, __list @24. The static initializers write only `kind`.
 const _: () = {
@@ -100,6 +107,8 @@ impl PthreadMutex {
                 prev: ptr::null_mut(),
                 next: ptr::null_mut(),
             },
+            #[cfg(target_arch = "aarch64")]
+            _padding: 0,
         }
     }
 
@@ -171,7 +180,7 @@ impl PthreadMutex {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutex_lock(mutex: &PthreadMutex) -> c_int {
     signature_matches_libc!(libc::pthread_mutex_lock(std::mem::transmute(mutex)));
     if mutex.has_unsupported_flags() {
@@ -213,7 +222,7 @@ unsafe extern "C" fn pthread_mutex_lock(mutex: &PthreadMutex) -> c_int {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutex_trylock(mutex: &PthreadMutex) -> c_int {
     signature_matches_libc!(libc::pthread_mutex_trylock(std::mem::transmute(mutex)));
     if mutex.has_unsupported_flags() {
@@ -236,7 +245,7 @@ unsafe extern "C" fn pthread_mutex_trylock(mutex: &PthreadMutex) -> c_int {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutex_unlock(mutex: &PthreadMutex) -> c_int {
     signature_matches_libc!(libc::pthread_mutex_unlock(std::mem::transmute(mutex)));
     if mutex.has_unsupported_flags() {
@@ -258,7 +267,7 @@ unsafe extern "C" fn pthread_mutex_unlock(mutex: &PthreadMutex) -> c_int {
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutex_init(
     mutex: &mut PthreadMutex,
     attr: Option<NonNull<PthreadMutexAttr>>,
@@ -275,39 +284,41 @@ unsafe extern "C" fn pthread_mutex_init(
     } else {
         0
     };
-    *mutex = PthreadMutex::new(kind, spins);
+    initialize_object(mutex, PthreadMutex::new(kind, spins), MUSL_MUTEX_SIZE);
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutex_destroy(mutex: &PthreadMutex) -> c_int {
     signature_matches_libc!(libc::pthread_mutex_destroy(std::mem::transmute(mutex)));
     0
 }
 
-/// glibc's `pthread_mutexattr_t` is a 4-byte blob; we keep only the kind in it.
+/// glibc's `struct pthread_mutexattr { int mutexkind; }`; we keep only the kind in it. The public
+/// `pthread_mutexattr_t` is that int on x86-64, padded to 8 bytes on arm64.
 #[bitfield(u32)]
 struct PthreadMutexAttr {
     #[bits(0..=1, rw)]
     kind: MutexKind,
 }
 
-const _: () = assert!(size_of::<PthreadMutexAttr>() == size_of::<libc::pthread_mutexattr_t>());
+const _: () = assert!(size_of::<PthreadMutexAttr>() == size_of::<c_int>());
+const _: () = assert!(size_of::<PthreadMutexAttr>() <= size_of::<libc::pthread_mutexattr_t>());
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutexattr_init(attr: &mut PthreadMutexAttr) -> c_int {
     signature_matches_libc!(libc::pthread_mutexattr_init(std::mem::transmute(attr)));
     *attr = PthreadMutexAttr::ZERO.with_kind(MutexKind::Normal);
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutexattr_destroy(attr: &PthreadMutexAttr) -> c_int {
     signature_matches_libc!(libc::pthread_mutexattr_destroy(std::mem::transmute(attr)));
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_mutexattr_settype(attr: &mut PthreadMutexAttr, kind: c_int) -> c_int {
     signature_matches_libc!(libc::pthread_mutexattr_settype(
         std::mem::transmute(attr),
@@ -56,7 +56,7 @@ impl PthreadOnce {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_once(once: &PthreadOnce, init_routine: extern "C" fn()) -> c_int {
     signature_matches_libc!(libc::pthread_once(
         std::mem::transmute(once),
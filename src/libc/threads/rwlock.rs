@@ -597,7 +597,7 @@ impl PthreadRwlock {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_rdlock(std::mem::transmute(rwlock)));
     match rwlock.read_lock() {
@@ -606,7 +606,7 @@ unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: &PthreadRwlock) -> c_int {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_tryrdlock(std::mem::transmute(rwlock)));
     match rwlock.try_read_lock() {
@@ -615,7 +615,7 @@ unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: &PthreadRwlock) -> c_int {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_wrlock(std::mem::transmute(rwlock)));
     match rwlock.write_lock() {
@@ -624,7 +624,7 @@ unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: &PthreadRwlock) -> c_int {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_trywrlock(std::mem::transmute(rwlock)));
     match rwlock.try_write_lock() {
@@ -633,14 +633,14 @@ unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: &PthreadRwlock) -> c_int {
     }
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_unlock(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_unlock(std::mem::transmute(rwlock)));
     rwlock.unlock();
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_init(
     rwlock: &mut PthreadRwlock,
     attr: Option<NonNull<PthreadRwlockAttr>>,
@@ -660,7 +660,7 @@ unsafe extern "C" fn pthread_rwlock_init(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlock_destroy(rwlock: &PthreadRwlock) -> c_int {
     signature_matches_libc!(libc::pthread_rwlock_destroy(std::mem::transmute(rwlock)));
     0
@@ -675,7 +675,7 @@ struct PthreadRwlockAttr {
 
 const _: () = assert!(size_of::<PthreadRwlockAttr>() == size_of::<libc::pthread_rwlockattr_t>());
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_init(attr: &mut PthreadRwlockAttr) -> c_int {
     signature_matches_libc!(libc::pthread_rwlockattr_init(std::mem::transmute(attr)));
     *attr = PthreadRwlockAttr {
@@ -685,13 +685,13 @@ unsafe extern "C" fn pthread_rwlockattr_init(attr: &mut PthreadRwlockAttr) -> c_
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_destroy(attr: &PthreadRwlockAttr) -> c_int {
     signature_matches_libc!(libc::pthread_rwlockattr_destroy(std::mem::transmute(attr)));
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_setpshared(
     attr: &mut PthreadRwlockAttr,
     pshared: c_int,
@@ -707,7 +707,7 @@ unsafe extern "C" fn pthread_rwlockattr_setpshared(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_getpshared(
     attr: &PthreadRwlockAttr,
     pshared: &mut c_int,
@@ -720,7 +720,7 @@ unsafe extern "C" fn pthread_rwlockattr_getpshared(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_setkind_np(
     attr: &mut PthreadRwlockAttr,
     preference: c_int,
@@ -736,7 +736,7 @@ unsafe extern "C" fn pthread_rwlockattr_setkind_np(
     0
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_rwlockattr_getkind_np(
     attr: &PthreadRwlockAttr,
     preference: &mut c_int,
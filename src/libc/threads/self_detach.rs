@@ -10,20 +10,20 @@ use crate::{
         threads::{join::wait_until_exited, PthreadT},
     },
     signature_matches_libc, syscall,
-    syscall::{exit, thread_pointer::get_thread_pointer, Syscall},
+    syscall::{exit, Syscall},
     tls::thread_control_block::{DetachState, ThreadControlBlock},
 };
 
 const PR_SET_NAME: usize = 15;
 const TASK_COMM_LEN: usize = 16;
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_self() -> PthreadT {
     signature_matches_libc!(std::mem::transmute(libc::pthread_self()));
-    get_thread_pointer() as PthreadT
+    ThreadControlBlock::current() as PthreadT
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_detach(thread: PthreadT) -> c_int {
     signature_matches_libc!(libc::pthread_detach(thread as _));
     let thread_control_block = thread as *mut ThreadControlBlock;
@@ -58,7 +58,7 @@ pub unsafe fn on_thread_exit(thread_control_block: *mut ThreadControlBlock) -> !
     exit::exit(0);
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn pthread_setname_np(thread: PthreadT, name: *const c_char) -> c_int {
     signature_matches_libc!(libc::pthread_setname_np(thread as _, name));
 
@@ -67,7 +67,7 @@ unsafe extern "C" fn pthread_setname_np(thread: PthreadT, name: *const c_char) -
         return libc::ERANGE;
     }
 
-    if thread == get_thread_pointer() as PthreadT {
+    if thread == ThreadControlBlock::current() as PthreadT {
         let result = syscall!(Syscall::PrCtl, PR_SET_NAME, name, 0usize, 0usize, 0usize);
         return if result < 0 { (-result) as c_int } else { 0 };
     }
@@ -1,32 +1,124 @@
-use core::ffi::{c_int, c_void};
+use core::{
+    ffi::{c_int, c_uint, c_void},
+    ptr,
+};
 
 use crate::{
-    libc::errno::{set_errno, Errno},
+    libc::{translate_syscall_result, vdso::vdso},
     signature_matches_libc, syscall,
     syscall::Syscall,
 };
 
-// glibc routes these through the vDSO; the raw syscall is correct, just without the vDSO fast path.
-#[cfg_attr(not(test), no_mangle)]
+// Each of these takes the vDSO fast path when the kernel provides one, and the raw syscall otherwise.
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn clock_gettime(clock_id: c_int, time: *mut libc::timespec) -> c_int {
     signature_matches_libc!(libc::clock_gettime(clock_id, time));
-    let result = syscall!(Syscall::ClockGetTime, clock_id, time);
-    if result < 0 {
-        set_errno(Errno(result.unsigned_abs() as u32));
-        -1
-    } else {
-        0
-    }
+    let result = match vdso().clock_gettime {
+        Some(function) => function(clock_id, time) as isize,
+        None => syscall!(Syscall::ClockGetTime, clock_id, time),
+    };
+    translate_syscall_result(result) as c_int
 }
 
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 unsafe extern "C" fn gettimeofday(time: *mut libc::timeval, timezone: *mut c_void) -> c_int {
     signature_matches_libc!(libc::gettimeofday(time, timezone.cast()));
-    let result = syscall!(Syscall::GetTimeOfDay, time, timezone);
-    if result < 0 {
-        set_errno(Errno(result.unsigned_abs() as u32));
-        -1
-    } else {
-        0
+    let result = match vdso().gettimeofday {
+        Some(function) => function(time, timezone) as isize,
+        None => syscall!(Syscall::GetTimeOfDay, time, timezone),
+    };
+    translate_syscall_result(result) as c_int
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn time(time: *mut libc::time_t) -> libc::time_t {
+    signature_matches_libc!(libc::time(time));
+    // Cannot fail; the only error is EFAULT for a bad `time` pointer, which the vDSO doesn't check either.
+    match vdso().time {
+        Some(function) => function(time),
+        None => time_syscall(time),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn time_syscall(time: *mut libc::time_t) -> libc::time_t {
+    syscall!(Syscall::Time, time) as libc::time_t
+}
+
+/// arm64 has no `time` syscall, so this reads the realtime clock's seconds.
+#[cfg(target_arch = "aarch64")]
+unsafe fn time_syscall(time: *mut libc::time_t) -> libc::time_t {
+    let mut now: libc::timespec = core::mem::zeroed();
+    clock_gettime(libc::CLOCK_REALTIME, &mut now);
+    if !time.is_null() {
+        *time = now.tv_sec;
+    }
+    now.tv_sec
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn clock_getres(clock_id: c_int, resolution: *mut libc::timespec) -> c_int {
+    signature_matches_libc!(libc::clock_getres(clock_id, resolution));
+    let result = match vdso().clock_getres {
+        Some(function) => function(clock_id, resolution) as isize,
+        None => syscall!(Syscall::ClockGetRes, clock_id, resolution),
+    };
+    translate_syscall_result(result) as c_int
+}
+
+// NOTE: Not in the `libc` crate, so there is no signature to check against; glibc declares it in <sched.h>.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn getcpu(cpu: *mut c_uint, node: *mut c_uint) -> c_int {
+    let result = match vdso().getcpu {
+        Some(function) => function(cpu, node, ptr::null_mut()) as isize,
+        None => syscall!(Syscall::GetCpu, cpu, node, ptr::null_mut::<c_void>()),
+    };
+    translate_syscall_result(result) as c_int
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+unsafe extern "C" fn sched_getcpu() -> c_int {
+    signature_matches_libc!(libc::sched_getcpu());
+    let mut cpu: c_uint = 0;
+    match getcpu(&mut cpu, ptr::null_mut()) {
+        0 => cpu as c_int,
+        error => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Without `install_vdso` these exercise the syscall fallback.
+
+    #[test]
+    fn time_matches_clock_gettime_realtime() {
+        let mut now: libc::timespec = unsafe { core::mem::zeroed() };
+        let mut stored: libc::time_t = 0;
+        unsafe {
+            assert_eq!(clock_gettime(libc::CLOCK_REALTIME, &mut now), 0);
+            let returned = time(&mut stored);
+            assert_eq!(returned, stored);
+            assert!(returned - now.tv_sec <= 1);
+        }
+    }
+
+    #[test]
+    fn clock_getres_reports_a_nonzero_resolution() {
+        let mut resolution: libc::timespec = unsafe { core::mem::zeroed() };
+        unsafe {
+            assert_eq!(clock_getres(libc::CLOCK_MONOTONIC, &mut resolution), 0);
+            assert_eq!(clock_getres(-1, &mut resolution), -1);
+        }
+        assert!(resolution.tv_sec > 0 || resolution.tv_nsec > 0);
+    }
+
+    #[test]
+    fn sched_getcpu_names_an_online_cpu() {
+        let cpu = unsafe { sched_getcpu() };
+        assert!(cpu >= 0);
+        assert!((cpu as i64) < unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) });
     }
 }
@@ -0,0 +1,169 @@
+use core::ffi::{c_int, c_uint, c_void};
+
+use crate::{
+    error::MirosError,
+    objects::object_data::ObjectData,
+    start::auxiliary_vector::{get_auxiliary_value, AuxiliaryVectorType},
+};
+
+/// The names one architecture's vDSO exports its entry points under; `None` where it has no fast path.
+struct EntryPoints {
+    clock_gettime: &'static str,
+    gettimeofday: &'static str,
+    time: Option<&'static str>,
+    clock_getres: &'static str,
+    getcpu: Option<&'static str>,
+}
+
+// NOTE: Names and version from the kernel's arch/x86/entry/vdso/vdso.lds.S.
+#[cfg(target_arch = "x86_64")]
+const VDSO_VERSION: &str = "LINUX_2.6";
+#[cfg(target_arch = "x86_64")]
+const ENTRY_POINTS: EntryPoints = EntryPoints {
+    clock_gettime: "__vdso_clock_gettime",
+    gettimeofday: "__vdso_gettimeofday",
+    time: Some("__vdso_time"),
+    clock_getres: "__vdso_clock_getres",
+    getcpu: Some("__vdso_getcpu"),
+};
+
+// NOTE: From arch/arm64/kernel/vdso/vdso.lds.S, which has no `time` or `getcpu`.
+#[cfg(target_arch = "aarch64")]
+const VDSO_VERSION: &str = "LINUX_2.6.39";
+#[cfg(target_arch = "aarch64")]
+const ENTRY_POINTS: EntryPoints = EntryPoints {
+    clock_gettime: "__kernel_clock_gettime",
+    gettimeofday: "__kernel_gettimeofday",
+    time: None,
+    clock_getres: "__kernel_clock_getres",
+    getcpu: None,
+};
+
+pub type ClockGetTime = unsafe extern "C" fn(c_int, *mut libc::timespec) -> c_int;
+pub type GetTimeOfDay = unsafe extern "C" fn(*mut libc::timeval, *mut c_void) -> c_int;
+pub type Time = unsafe extern "C" fn(*mut libc::time_t) -> libc::time_t;
+pub type ClockGetRes = unsafe extern "C" fn(c_int, *mut libc::timespec) -> c_int;
+pub type GetCpu = unsafe extern "C" fn(*mut c_uint, *mut c_uint, *mut c_void) -> c_int;
+
+/// The kernel-provided fast paths; each is `None` when the vDSO is absent or lacks it, and callers fall back to the syscall.
+///
+/// The entry points return the raw syscall result (`-errno` on failure), not the C convention.
+#[derive(Clone, Copy)]
+pub struct VdsoFunctions {
+    pub clock_gettime: Option<ClockGetTime>,
+    pub gettimeofday: Option<GetTimeOfDay>,
+    pub time: Option<Time>,
+    pub clock_getres: Option<ClockGetRes>,
+    pub getcpu: Option<GetCpu>,
+}
+
+// Written once during startup, before any thread but the main one exists.
+static mut VDSO: VdsoFunctions = VdsoFunctions::NONE;
+
+impl VdsoFunctions {
+    const NONE: Self = Self {
+        clock_gettime: None,
+        gettimeofday: None,
+        time: None,
+        clock_getres: None,
+        getcpu: None,
+    };
+
+    /// Parses the vDSO image mapped at `base` like any other loaded object.
+    pub unsafe fn from_base(base: *const c_void) -> Result<Self, MirosError> {
+        let vdso = ObjectData::from_base(base)?;
+        let function = |name: &str| vdso.resolve_versioned_symbol(name, VDSO_VERSION);
+
+        Ok(Self {
+            clock_gettime: function(ENTRY_POINTS.clock_gettime)
+                .map(|address| core::mem::transmute(address)),
+            gettimeofday: function(ENTRY_POINTS.gettimeofday)
+                .map(|address| core::mem::transmute(address)),
+            time: ENTRY_POINTS
+                .time
+                .and_then(function)
+                .map(|address| core::mem::transmute(address)),
+            clock_getres: function(ENTRY_POINTS.clock_getres)
+                .map(|address| core::mem::transmute(address)),
+            getcpu: ENTRY_POINTS
+                .getcpu
+                .and_then(function)
+                .map(|address| core::mem::transmute(address)),
+        })
+    }
+}
+
+/// Looks up the vDSO named by `AT_SYSINFO_EHDR`; without one (or if it won't parse) every call keeps using syscalls.
+pub unsafe fn install_vdso() {
+    let Some(base) = get_auxiliary_value(AuxiliaryVectorType::SysinfoEhdr as usize) else {
+        return;
+    };
+    if let Ok(functions) = VdsoFunctions::from_base(base as *const c_void) {
+        VDSO = functions;
+    }
+}
+
+pub(crate) fn vdso() -> VdsoFunctions {
+    unsafe { VDSO }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system_vdso() -> VdsoFunctions {
+        unsafe {
+            crate::page_size::set_page_size(libc::sysconf(libc::_SC_PAGESIZE) as usize);
+            let base = libc::getauxval(libc::AT_SYSINFO_EHDR) as *const c_void;
+            VdsoFunctions::from_base(base).unwrap()
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn finds_every_x86_64_entry_point() {
+        let vdso = system_vdso();
+        assert!(vdso.clock_gettime.is_some());
+        assert!(vdso.gettimeofday.is_some());
+        assert!(vdso.time.is_some());
+        assert!(vdso.clock_getres.is_some());
+        assert!(vdso.getcpu.is_some());
+    }
+
+    #[test]
+    fn clock_gettime_agrees_with_the_syscall() {
+        let clock_gettime = system_vdso().clock_gettime.unwrap();
+        let mut from_vdso: libc::timespec = unsafe { core::mem::zeroed() };
+        let mut from_syscall: libc::timespec = unsafe { core::mem::zeroed() };
+        unsafe {
+            assert_eq!(clock_gettime(libc::CLOCK_MONOTONIC, &mut from_vdso), 0);
+            assert_eq!(
+                libc::syscall(
+                    libc::SYS_clock_gettime,
+                    libc::CLOCK_MONOTONIC,
+                    &mut from_syscall
+                ),
+                0
+            );
+        }
+        // Same clock, read later: the vDSO value must not run ahead of the syscall's.
+        assert!(
+            (from_vdso.tv_sec, from_vdso.tv_nsec) <= (from_syscall.tv_sec, from_syscall.tv_nsec)
+        );
+    }
+
+    #[test]
+    fn unknown_versions_are_not_bound() {
+        unsafe {
+            crate::page_size::set_page_size(libc::sysconf(libc::_SC_PAGESIZE) as usize);
+            let base = libc::getauxval(libc::AT_SYSINFO_EHDR) as *const c_void;
+            let vdso = ObjectData::from_base(base).unwrap();
+            assert!(vdso
+                .resolve_versioned_symbol("__vdso_clock_gettime", "LINUX_2.6")
+                .is_some());
+            assert!(vdso
+                .resolve_versioned_symbol("__vdso_clock_gettime", "LINUX_9.9")
+                .is_none());
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ffi::{c_char, c_uint, c_void, CStr, CString, OsStr},
+    mem,
+    os::unix::ffi::OsStrExt,
+    path::PathBuf,
+};
+
+use crate::{
+    elf::symbol::Symbol,
+    objects::{
+        object_data::{ObjectData, SearchOrigin},
+        object_data_graph::{Definition, ObjectDataGraph},
+        rendezvous::LinkMap,
+    },
+};
+
+// NOTE: Values from glibc's <link.h>; audit modules are compiled against them.
+pub const LAV_CURRENT: c_uint = 2;
+
+pub const LA_FLG_BINDTO: c_uint = 0x01;
+pub const LA_FLG_BINDFROM: c_uint = 0x02;
+
+pub const LA_ACT_CONSISTENT: c_uint = 0;
+pub const LA_ACT_ADD: c_uint = 1;
+
+pub const LA_SER_ORIG: c_uint = 0x01;
+pub const LA_SER_LIBPATH: c_uint = 0x02;
+pub const LA_SER_RUNPATH: c_uint = 0x04;
+pub const LA_SER_DEFAULT: c_uint = 0x40;
+
+pub const LA_SYMB_NOPLTENTER: c_uint = 0x01;
+pub const LA_SYMB_NOPLTEXIT: c_uint = 0x02;
+
+const LM_ID_BASE: isize = 0;
+
+type VersionHook = unsafe extern "C" fn(c_uint) -> c_uint;
+type ObjectSearchHook = unsafe extern "C" fn(*const c_char, *mut usize, c_uint) -> *mut c_char;
+type ObjectOpenHook = unsafe extern "C" fn(*mut LinkMap, isize, *mut usize) -> c_uint;
+type ActivityHook = unsafe extern "C" fn(*mut usize, c_uint);
+type PreinitHook = unsafe extern "C" fn(*mut usize);
+type SymbolBindHook = unsafe extern "C" fn(
+    *mut Symbol,
+    c_uint,
+    *mut usize,
+    *mut usize,
+    *mut c_uint,
+    *const c_char,
+) -> usize;
+
+/// An auditor's view of one object: the cookie it may overwrite in `la_objopen`, and the flags it returned there.
+struct ObjectAudit {
+    cookie: usize,
+    flags: c_uint,
+}
+
+/// One `LD_AUDIT` module, loaded and relocated in a graph of its own so its bindings never leak into the program's.
+///
+/// `la_pltenter`/`la_pltexit` are never called: miros binds every PLT slot at load time, so there is no lazy trampoline to hook.
+pub struct Auditor {
+    // Keeps the module's objects alive for the life of the process.
+    _graph: ObjectDataGraph,
+    object_search: Option<ObjectSearchHook>,
+    object_open: Option<ObjectOpenHook>,
+    activity: Option<ActivityHook>,
+    preinit: Option<PreinitHook>,
+    symbol_bind: Option<SymbolBindHook>,
+    // Keyed by link-map address; boxed so a cookie pointer handed to the module stays put as objects are added.
+    objects: RefCell<BTreeMap<usize, Box<ObjectAudit>>>,
+}
+
+impl Auditor {
+    /// Wraps a fully initialized audit module, or returns `None` if `la_version` rejects this interface version.
+    pub unsafe fn new(graph: ObjectDataGraph) -> Option<Self> {
+        let hook = |name: &str| {
+            graph
+                .program
+                .resolve_symbol_and_address(name)
+                .map(|(_, address)| address)
+        };
+
+        let version: VersionHook = mem::transmute(hook("la_version")?);
+        match version(LAV_CURRENT) {
+            0 => return None,
+            version if version > LAV_CURRENT => return None,
+            _ => (),
+        }
+
+        Some(Self {
+            object_search: hook("la_objsearch").map(|address| mem::transmute(address)),
+            object_open: hook("la_objopen").map(|address| mem::transmute(address)),
+            activity: hook("la_activity").map(|address| mem::transmute(address)),
+            preinit: hook("la_preinit").map(|address| mem::transmute(address)),
+            symbol_bind: hook("la_symbind64").map(|address| mem::transmute(address)),
+            objects: RefCell::new(BTreeMap::new()),
+            _graph: graph,
+        })
+    }
+
+    /// This auditor's record for `object`; its cookie defaults to the link-map address until `la_objopen` replaces it.
+    fn audit(&self, object: &ObjectData) -> *mut ObjectAudit {
+        let mut objects = self.objects.borrow_mut();
+        let audit = objects.entry(object.link_map.addr()).or_insert_with(|| {
+            Box::new(ObjectAudit {
+                cookie: object.link_map.addr(),
+                flags: LA_FLG_BINDTO | LA_FLG_BINDFROM,
+            })
+        });
+        // SAFETY: Boxed entries are never removed, so the pointer outlives the borrow.
+        &raw mut **audit
+    }
+
+    fn cookie(&self, object: &ObjectData) -> *mut usize {
+        unsafe { &raw mut (*self.audit(object)).cookie }
+    }
+
+    fn flags(&self, object: &ObjectData) -> c_uint {
+        unsafe { (*self.audit(object)).flags }
+    }
+}
+
+/// Every auditor named by `LD_AUDIT`, in the order given; each hook is called on all of them in turn.
+#[derive(Default)]
+pub struct Auditors(Vec<Auditor>);
+
+impl Auditors {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, auditor: Auditor) {
+        self.0.push(auditor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn object_open(&self, object: &ObjectData) {
+        self.0.iter().for_each(|auditor| {
+            let Some(hook) = auditor.object_open else {
+                return;
+            };
+            let audit = auditor.audit(object);
+            unsafe { (*audit).flags = hook(object.link_map, LM_ID_BASE, &raw mut (*audit).cookie) };
+        });
+    }
+
+    pub fn activity(&self, program: &ObjectData, flag: c_uint) {
+        self.0.iter().for_each(|auditor| {
+            if let Some(hook) = auditor.activity {
+                unsafe { hook(auditor.cookie(program), flag) };
+            }
+        });
+    }
+
+    pub fn preinit(&self, program: &ObjectData) {
+        self.0.iter().for_each(|auditor| {
+            if let Some(hook) = auditor.preinit {
+                unsafe { hook(auditor.cookie(program)) };
+            }
+        });
+    }
+
+    /// Passes `name` through each auditor's `la_objsearch`; `None` means an auditor vetoed the search.
+    pub fn object_search(
+        &self,
+        requester: &ObjectData,
+        name: &OsStr,
+        flag: c_uint,
+    ) -> Option<PathBuf> {
+        let mut name = CString::new(name.as_bytes()).ok()?;
+        for auditor in &self.0 {
+            let Some(hook) = auditor.object_search else {
+                continue;
+            };
+            let replacement = unsafe { hook(name.as_ptr(), auditor.cookie(requester), flag) };
+            if replacement.is_null() {
+                return None;
+            }
+            name = unsafe { CStr::from_ptr(replacement) }.to_owned();
+        }
+        Some(PathBuf::from(OsStr::from_bytes(name.as_bytes())))
+    }
+
+    /// A `PathResolver::resolve_with` filter: lets the auditors rewrite or veto each directory probe.
+    pub fn search_candidate(
+        &self,
+        requester: &ObjectData,
+        candidate: &mut PathBuf,
+        origin: SearchOrigin,
+    ) -> bool {
+        let flag = match origin {
+            SearchOrigin::LibraryPath => LA_SER_LIBPATH,
+            SearchOrigin::Runpath => LA_SER_RUNPATH,
+            SearchOrigin::Default => LA_SER_DEFAULT,
+        };
+        match self.object_search(requester, candidate.as_os_str(), flag) {
+            Some(replacement) => {
+                *candidate = replacement;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Offers a PLT binding to each auditor's `la_symbind64`, returning the (possibly rewritten) address to store.
+    ///
+    /// The flags always carry `LA_SYMB_NOPLTENTER | LA_SYMB_NOPLTEXIT`, since miros never hooks PLT calls.
+    pub fn bind_symbol(&self, referrer: &ObjectData, definition: &Definition) -> *const c_void {
+        let mut address = definition.address;
+        let symbol_name = unsafe {
+            definition
+                .object
+                .dynamic_fields
+                .string_table
+                .get(definition.symbol.st_name as usize)
+        };
+        let Ok(symbol_name) = CString::new(symbol_name) else {
+            return address;
+        };
+
+        for auditor in &self.0 {
+            let Some(hook) = auditor.symbol_bind else {
+                continue;
+            };
+            if auditor.flags(referrer) & LA_FLG_BINDFROM == 0
+                || auditor.flags(definition.object) & LA_FLG_BINDTO == 0
+            {
+                continue;
+            }
+
+            // Each auditor sees the address as left by the one before it.
+            let mut symbol = definition.symbol;
+            symbol.st_value = address.addr();
+            let mut flags = LA_SYMB_NOPLTENTER | LA_SYMB_NOPLTEXIT;
+            let rebound = unsafe {
+                hook(
+                    &mut symbol,
+                    definition.symbol_index as c_uint,
+                    auditor.cookie(referrer),
+                    auditor.cookie(definition.object),
+                    &mut flags,
+                    symbol_name.as_ptr(),
+                )
+            };
+            address = rebound as *const c_void;
+        }
+
+        address
+    }
+}
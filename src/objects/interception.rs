@@ -0,0 +1,244 @@
+use std::{env, fs, io::ErrorKind, path::PathBuf};
+
+use crate::{error::MirosError, libc::errno::Errno, start::secure_execution::is_secure_execution};
+
+/// Comma-separated `pattern=target` rules, applied after the config file.
+const RULES_VARIABLE: &str = "MIROS_INTERCEPT";
+/// Overrides [`DEFAULT_CONFIG_PATH`]; unlike the default, a file named here must exist.
+pub(crate) const CONFIG_PATH_VARIABLE: &str = "MIROS_INTERCEPT_CONFIG";
+pub(crate) const DEFAULT_CONFIG_PATH: &str = "/etc/miros/intercept.conf";
+
+/// The libraries miros replaces when nothing says otherwise.
+const DEFAULT_INTERCEPTED: &[&str] = &[
+    "libc.so.6",
+    "libm.so.6",
+    "libgcc_s.so.1",
+    "libpthread.so.0",
+    "libdl.so.2",
+    "ld-linux-x86-64.so.2",
+    // aarch64 programs name the loader for `__stack_chk_guard` and `_r_debug`; a real one mapped beside miros would
+    // shadow both.
+    "ld-linux-aarch64.so.1",
+    // musl ships its loader and libc as one file under both names; Alpine's soname, then upstream's, which `musl-gcc`
+    // links against.
+    "libc.musl-x86_64.so.1",
+    "ld-musl-x86_64.so.1",
+    "libc.musl-aarch64.so.1",
+    "ld-musl-aarch64.so.1",
+    "libc.so",
+];
+
+/// Where a `DT_NEEDED` entry is satisfied from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Redirect {
+    /// Not loaded: miros provides its symbols (or, in fallback mode, the system copy sits behind miros).
+    Miros,
+    /// Searched for and loaded like any other dependency.
+    System,
+    /// This file is loaded in its place, still under the requested soname.
+    Path(PathBuf),
+}
+
+/// Maps sonames to a [`Redirect`]; the last matching rule wins, so later sources override earlier ones.
+///
+/// Rules come from, in order: the built-in defaults, the config file, then `MIROS_INTERCEPT`. Both sources use
+/// `pattern=target`, where the pattern is a soname with optional `*`/`?` wildcards and the target is `miros`, `system` or
+/// a path. The file takes one rule per line, with `#` comments; the variable separates rules with commas.
+#[derive(Clone)]
+pub struct InterceptionPolicy {
+    rules: Vec<(String, Redirect)>,
+}
+
+impl Default for InterceptionPolicy {
+    fn default() -> Self {
+        Self {
+            rules: DEFAULT_INTERCEPTED
+                .iter()
+                .map(|soname| (soname.to_string(), Redirect::Miros))
+                .collect(),
+        }
+    }
+}
+
+impl InterceptionPolicy {
+    /// The defaults with the config file and `MIROS_INTERCEPT` applied; in secure mode only the system-wide file counts.
+    pub fn from_environment() -> Result<Self, MirosError> {
+        let mut policy = Self::default();
+
+        let config_path = env::var_os(CONFIG_PATH_VARIABLE).filter(|_| !is_secure_execution());
+        let required = config_path.is_some();
+        let config_path =
+            config_path.map_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH), PathBuf::from);
+        match fs::read_to_string(&config_path) {
+            Ok(contents) => policy.extend(&config_path.display().to_string(), contents.lines())?,
+            Err(error) if error.kind() == ErrorKind::NotFound && !required => (),
+            Err(error) => {
+                return Err(MirosError::Io {
+                    action: "read interception rules",
+                    errno: Errno::from(&error),
+                }
+                .in_object(config_path))
+            }
+        }
+
+        if let Ok(rules) = env::var(RULES_VARIABLE) {
+            if !is_secure_execution() {
+                policy.extend(RULES_VARIABLE, rules.split(','))?;
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Appends rules from `source`, skipping blank entries and `#` comments.
+    fn extend<'a>(
+        &mut self,
+        source: &str,
+        rules: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), MirosError> {
+        for rule in rules {
+            let rule = rule.split('#').next().unwrap_or_default().trim();
+            if rule.is_empty() {
+                continue;
+            }
+            let invalid = || MirosError::InvalidInterceptionRule {
+                source: source.to_string(),
+                rule: rule.to_string(),
+            };
+
+            let (pattern, target) = rule.split_once('=').ok_or_else(invalid)?;
+            let (pattern, target) = (pattern.trim(), target.trim());
+            let redirect = match target {
+                "miros" => Redirect::Miros,
+                "system" => Redirect::System,
+                "" => return Err(invalid()),
+                path => Redirect::Path(PathBuf::from(path)),
+            };
+            if pattern.is_empty() {
+                return Err(invalid());
+            }
+            self.rules.push((pattern.to_string(), redirect));
+        }
+        Ok(())
+    }
+
+    pub fn redirect(&self, soname: &str) -> Redirect {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches_pattern(pattern.as_bytes(), soname.as_bytes()))
+            .map_or(Redirect::System, |(_, redirect)| redirect.clone())
+    }
+
+    pub fn is_intercepted(&self, soname: &str) -> bool {
+        self.redirect(soname) == Redirect::Miros
+    }
+}
+
+/// Shell-style matching: `*` spans any run of bytes, `?` exactly one; everything else is literal.
+///
+/// Only the latest `*` is ever retried, which bounds the work by `pattern.len() * name.len()`: a mismatch after a star
+/// lets that star swallow one more byte, and an earlier star can never do better.
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut pattern_index, mut name_index) = (0, 0);
+    // The position just after the latest `*`, and the name byte it would swallow next.
+    let mut star = None;
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some(b'*') => {
+                pattern_index += 1;
+                star = Some((pattern_index, name_index));
+            }
+            Some(&expected) if expected == b'?' || expected == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => match star {
+                Some((after_star, swallowed)) => {
+                    pattern_index = after_star;
+                    name_index = swallowed + 1;
+                    star = Some((after_star, swallowed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..].iter().all(|&byte| byte == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_intercept_the_libc_families_only() {
+        let policy = InterceptionPolicy::default();
+        assert!(policy.is_intercepted("libc.so.6"));
+        assert!(policy.is_intercepted("ld-linux-x86-64.so.2"));
+        assert!(policy.is_intercepted("ld-linux-aarch64.so.1"));
+        assert!(policy.is_intercepted("libdl.so.2"));
+        assert!(policy.is_intercepted("libc.musl-x86_64.so.1"));
+        assert!(policy.is_intercepted("libc.so"));
+        assert_eq!(policy.redirect("libz.so.1"), Redirect::System);
+    }
+
+    #[test]
+    fn later_rules_override_earlier_ones() {
+        let mut policy = InterceptionPolicy::default();
+        policy
+            .extend("file", [
+                "# redirect the glibc helpers",
+                "libdl.so.*=system",
+                "libdl.so.? = miros",
+                "",
+                "librt.so.1=miros  # trailing comment",
+            ])
+            .unwrap();
+        policy
+            .extend(
+                RULES_VARIABLE,
+                "libgcc_s.so.1=system,libfoo.so*=/opt/foo.so".split(','),
+            )
+            .unwrap();
+
+        assert!(policy.is_intercepted("libdl.so.2"));
+        assert!(policy.is_intercepted("librt.so.1"));
+        assert_eq!(policy.redirect("libgcc_s.so.1"), Redirect::System);
+        assert_eq!(
+            policy.redirect("libfoo.so.3"),
+            Redirect::Path(PathBuf::from("/opt/foo.so"))
+        );
+        assert!(policy.is_intercepted("libc.so.6"));
+    }
+
+    #[test]
+    fn malformed_rules_name_their_source() {
+        let mut policy = InterceptionPolicy::default();
+        for rule in ["libdl.so.2", "=miros", "libdl.so.2="] {
+            let error = policy.extend(RULES_VARIABLE, [rule]).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("fatal error: invalid interception rule `{rule}` in MIROS_INTERCEPT")
+            );
+        }
+    }
+
+    #[test]
+    fn wildcards_match_whole_names() {
+        assert!(matches_pattern(b"lib*.so.?", b"libutil.so.1"));
+        assert!(matches_pattern(b"*", b""));
+        assert!(!matches_pattern(b"lib*.so.?", b"libutil.so.10"));
+        assert!(!matches_pattern(b"libc.so", b"libc.so.6"));
+        assert!(matches_pattern(b"*a*b", b"xaab"));
+        assert!(!matches_pattern(b"a*b?", b"ab"));
+    }
+
+    #[test]
+    fn many_stars_do_not_backtrack_exponentially() {
+        let pattern = "*a".repeat(30) + "b";
+        assert!(!matches_pattern(
+            pattern.as_bytes(),
+            "a".repeat(60).as_bytes()
+        ));
+    }
+}
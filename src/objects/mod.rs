@@ -1,4 +1,12 @@
+pub mod audit;
+pub mod interception;
+pub mod namespace;
 pub mod object_data;
 pub mod object_data_graph;
 pub mod object_pipeline;
+pub mod relocation_cache;
+pub mod rendezvous;
+pub mod stage_statistics;
 pub mod strategies;
+pub mod symbol_lookup_cache;
+pub mod unique_symbols;
@@ -0,0 +1,244 @@
+//! Link-map namespaces: isolated symbol scopes, each one [`ObjectDataGraph`], all sharing the one copy of miros.
+//!
+//! The startup graph becomes [`LM_ID_BASE`] once the program is running; `dlmopen(LM_ID_NEWLM, ...)` builds another
+//! graph around the opened object, runs its own pipeline over it, and keeps it here. Nothing is ever unloaded.
+//!
+//! What namespaces share, and what they do not:
+//! - Symbols: a namespace's objects only see its own graph (the opened object, its dependencies, then miros), so two
+//!   namespaces can each hold their own copy of a library under the same soname. `STB_GNU_UNIQUE` definitions are
+//!   unique per namespace, as in glibc.
+//! - TLS: every namespace's static blocks come from the same reserve as the program's, so the thread pointer reaches all
+//!   of them. A new namespace's blocks are initialized on the thread calling `dlmopen` and on every thread created later;
+//!   threads already running at that point find them zeroed instead.
+//! - Interposable cells: miros's data exports (`environ`, `stdout`, `_r_debug`, ...) are bound once, by the base
+//!   namespace, possibly to its program's COPY. Other namespaces never rebind them, and their references to those names
+//!   resolve to wherever the base namespace bound them, so the whole process shares one `environ` and one `stdout`.
+//! - Debuggers see only the base namespace's objects, and auditors only the base namespace's events.
+
+use std::{
+    ffi::{c_long, c_void},
+    ops::Range,
+    os::fd::BorrowedFd,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crate::{
+    error::MirosError,
+    objects::{
+        object_data::{ObjectData, PathResolver},
+        object_data_graph::ObjectDataGraph,
+        object_pipeline::ObjectPipeline,
+        rendezvous::LinkMap,
+        stage_statistics::StageStatistics,
+        strategies::{
+            init_array::InitArray, load_dependencies::LoadDependencies, relocate::Relocate,
+            relocate_thread_locals::RelocateThreadLocals, report_statistics::ReportStatistics,
+            thread_local_storage::ThreadLocalStorage,
+        },
+    },
+};
+
+/// A namespace id, glibc's `Lmid_t`.
+pub type Lmid = c_long;
+
+/// The namespace the program and its dependencies were loaded into.
+pub const LM_ID_BASE: Lmid = 0;
+/// Asks `dlmopen` for a new namespace.
+pub const LM_ID_NEWLM: Lmid = -1;
+
+struct Registry {
+    namespaces: Vec<ObjectDataGraph>,
+    /// The arguments the program's constructors got, passed on to constructors in new namespaces.
+    init_array: Option<InitArray>,
+}
+
+// SAFETY: The graphs hold raw pointers and `RefCell`s, but are only ever reached through `REGISTRY`'s lock.
+unsafe impl Send for Registry {}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    namespaces: Vec::new(),
+    init_array: None,
+});
+
+static NEXT_NAMESPACE: AtomicI64 = AtomicI64::new(LM_ID_BASE + 1);
+
+fn registry() -> MutexGuard<'static, Registry> {
+    // A panic mid-lookup leaves nothing half-written: graphs are only pushed whole.
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Keeps the startup graph as [`LM_ID_BASE`], and `init_array`'s arguments for constructors in later namespaces.
+pub fn register_base(graph: ObjectDataGraph, init_array: InitArray) {
+    let mut registry = registry();
+    registry.namespaces.push(graph);
+    registry.init_array = Some(init_array);
+}
+
+/// Where [`open_new`] maps a namespace's root object from.
+pub enum Source<'a> {
+    /// A path, or a bare name searched for as the object holding `caller` (a return address) would search for a
+    /// `DT_NEEDED` entry: its RPATH or RUNPATH, and `LD_LIBRARY_PATH`. An address outside every object searches as the
+    /// program does.
+    Search { name: &'a str, caller: usize },
+    /// An open descriptor, borrowed for the load, with the name the object goes by.
+    Fd(BorrowedFd<'a>, &'a str),
+    /// The object's bytes, mapped through a memfd and named `memfd:<name>`.
+    Memory(&'a [u8], &'a str),
+}
+
+/// Loads a root object and its dependencies into a namespace of their own, returning its id and the root's handle (link
+/// map). The root's dependencies are always searched for on disk.
+///
+/// The pipeline runs without the registry locked, so constructors may call back into `dl*`; until they return, the new
+/// namespace is not visible to them. A load that fails partway leaves what it mapped in place.
+pub unsafe fn open_new(source: Source) -> Result<(Lmid, *mut LinkMap), MirosError> {
+    let (miros_base, interception, init_array) = {
+        let registry = registry();
+        match (registry.namespaces.first(), registry.init_array) {
+            (Some(base), Some(init_array)) => {
+                (base.miros.base, base.interception.clone(), init_array)
+            }
+            _ => return Err(MirosError::InvalidNamespace(LM_ID_BASE)),
+        }
+    };
+
+    let root = match source {
+        Source::Search { name, caller } => {
+            let (path, file) = path_resolver_at(caller).resolve(name)?;
+            ObjectData::from_file(file, path.clone()).map_err(|error| error.in_object(path))?
+        }
+        Source::Fd(fd, name) => ObjectData::from_fd(fd, PathBuf::from(name))?,
+        Source::Memory(bytes, name) => ObjectData::from_memory(bytes, name)?,
+    };
+    let mut graph = ObjectDataGraph::new(root, ObjectData::from_base(miros_base)?);
+    graph.namespace = NEXT_NAMESPACE.fetch_add(1, Ordering::Relaxed);
+    if ReportStatistics::is_enabled() {
+        graph.observers.push(Box::new(StageStatistics::default()));
+    }
+
+    // The rules read at startup hold for every namespace.
+    let load_dependencies = LoadDependencies::with_policy(interception);
+    ObjectPipeline::new(&[
+        &load_dependencies,
+        &Relocate,
+        &ThreadLocalStorage,
+        &RelocateThreadLocals,
+        &init_array,
+    ])
+    .run_pipeline(&mut graph)?;
+
+    let opened = (graph.namespace, graph.program.link_map);
+    registry().namespaces.push(graph);
+    Ok(opened)
+}
+
+/// The search order of the object mapped at `address`, in any namespace, or else of the base namespace's program.
+fn path_resolver_at(address: usize) -> PathResolver {
+    let registry = registry();
+    let mut objects = registry
+        .namespaces
+        .iter()
+        .flat_map(|graph| graph.iter_indexed());
+    objects
+        .find(|object| object.mapped_range().contains(&address))
+        .or_else(|| registry.namespaces.first().map(|base| &base.program))
+        .map_or(PathResolver::None, |object| {
+            object.dynamic_fields.path_resolver
+        })
+}
+
+/// The handle of `name` in namespace `namespace`, which must already hold it: only [`open_new`] loads from disk.
+pub fn find_loaded(namespace: Lmid, name: &str) -> Result<*mut LinkMap, MirosError> {
+    let registry = registry();
+    let graph = registry
+        .namespaces
+        .iter()
+        .find(|graph| graph.namespace == namespace)
+        .ok_or(MirosError::InvalidNamespace(namespace))?;
+    graph
+        .find_loaded(name)
+        .map(|object| object.link_map)
+        .ok_or_else(|| MirosError::NotInNamespace {
+            name: name.to_string(),
+            namespace,
+        })
+}
+
+/// The namespace holding the object behind `handle`, if any does.
+pub fn namespace_of(handle: *mut LinkMap) -> Option<Lmid> {
+    registry()
+        .namespaces
+        .iter()
+        .find(|graph| owner(graph, handle).is_some())
+        .map(|graph| graph.namespace)
+}
+
+/// Looks `symbol_name` up in the namespace holding `handle`, or the base namespace for `None` (`RTLD_DEFAULT`).
+///
+/// Searches the whole namespace in its global order, not only `handle`'s dependencies as glibc does; for the object a
+/// namespace was opened with the two agree. `None` if no namespace holds `handle`.
+pub fn symbol(
+    handle: Option<*mut LinkMap>,
+    symbol_name: &str,
+) -> Option<Result<*const c_void, MirosError>> {
+    let registry = registry();
+    let (graph, requester) = match handle {
+        None => (registry.namespaces.first()?, None),
+        Some(handle) => registry
+            .namespaces
+            .iter()
+            .find_map(|graph| owner(graph, handle).map(|object| (graph, Some(object))))?,
+    };
+    Some(
+        graph
+            .resolve_symbol_by_name(symbol_name)
+            .map_err(|error| match error {
+                MirosError::UndefinedSymbol {
+                    name,
+                    version,
+                    relocation,
+                    ..
+                } => MirosError::UndefinedSymbol {
+                    name,
+                    version,
+                    requester: requester.and_then(|object| object.path.clone()),
+                    relocation,
+                },
+                error => error,
+            }),
+    )
+}
+
+/// What `_dl_find_object` reports about the object mapped at an address.
+pub struct FoundObject {
+    pub mapped_range: Range<usize>,
+    pub link_map: *mut LinkMap,
+    pub eh_frame_header: Option<*const c_void>,
+}
+
+/// The object, in any namespace, whose mapping holds `address`.
+pub fn find_object(address: usize) -> Option<FoundObject> {
+    registry()
+        .namespaces
+        .iter()
+        .flat_map(|graph| graph.iter_indexed())
+        .map(|object| (object, object.mapped_range()))
+        .find(|(_, mapped_range)| mapped_range.contains(&address))
+        .map(|(object, mapped_range)| FoundObject {
+            mapped_range,
+            link_map: object.link_map,
+            eh_frame_header: object.eh_frame_header(),
+        })
+}
+
+fn owner(graph: &ObjectDataGraph, handle: *mut LinkMap) -> Option<&ObjectData> {
+    graph
+        .iter_indexed()
+        .find(|object| std::ptr::eq(object.link_map, handle))
+}
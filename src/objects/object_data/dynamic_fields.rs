@@ -1,17 +1,16 @@
 use std::{ffi::c_void, ptr};
 
 use super::{hash_tables::HashTable, path_resolver::PathResolver};
-#[cfg(debug_assertions)]
-use crate::io_macros::syscall_assert;
 use crate::{
     elf::{
         dynamic_array::{DynamicArrayItem, DynamicArrayIter, DynamicFlags, DynamicTag},
         relocate::Rela,
         string_table::StringTable,
         symbol::{Symbol, SymbolTable},
+        version::SymbolVersions,
     },
     error::MirosError,
-    objects::strategies::init_array::InitArrayFunction,
+    objects::strategies::init_array::{FiniFunction, InitArrayFunction},
 };
 
 pub struct DynamicFields {
@@ -20,39 +19,65 @@ pub struct DynamicFields {
     pub symbol_table: SymbolTable,
     rela_slice: Option<*const [Rela]>,
     plt_rela_slice: Option<*const [Rela]>,
+    relr_slice: Option<*const [usize]>,
     preinit_array: Option<*const [InitArrayFunction]>,
     init_array: Option<*const [InitArrayFunction]>,
+    /// The legacy `DT_INIT` constructor, run before `init_array`.
+    init: Option<InitArrayFunction>,
+    fini_array: Option<*const [FiniFunction]>,
+    /// The legacy `DT_FINI` destructor, run after `fini_array`.
+    fini: Option<FiniFunction>,
     pub hash_table: Option<HashTable>,
-    symbol_count: Option<usize>,
+    symbol_versions: Option<SymbolVersions>,
     pub path_resolver: PathResolver,
     dependencies: Vec<*const str>,
     pub static_tls: bool,
 }
 
 impl DynamicFields {
-    pub(super) unsafe fn from_dynamic_array(
+    /// Parses the `dynamic_len` items at `dynamic_array`, checking every table they name fits in the `image_size` bytes mapped at `base`.
+    pub(crate) unsafe fn from_dynamic_array(
         base: *const c_void,
         dynamic_array: *const DynamicArrayItem,
+        dynamic_len: usize,
+        image_size: usize,
     ) -> Result<Self, MirosError> {
-        let mut global_offset_table: Option<*const usize> = None;
-        let mut string_table_pointer: Result<*const u8, MirosError> =
-            Err(MirosError::MissingDynamicEntry(DynamicTag::StrTab));
-        let mut symbol_table_pointer: Result<*const Symbol, MirosError> =
-            Err(MirosError::MissingDynamicEntry(DynamicTag::SymTab));
+        // Offsets from `base`, validated against the image once every size tag has been seen.
+        let mut global_offset_table: Option<usize> = None;
+        let mut string_table_offset: Option<usize> = None;
+        let mut string_table_size: Option<usize> = None;
+        let mut symbol_table_offset: Option<usize> = None;
 
-        let mut rela_pointer: Option<*const Rela> = None;
-        let mut rela_count = 0;
+        let mut rela_offset: Option<usize> = None;
+        let mut rela_size = 0;
 
-        let mut plt_rela_pointer: Option<*const Rela> = None;
-        let mut plt_rela_count = 0;
+        let mut plt_rela_offset: Option<usize> = None;
+        let mut plt_rela_size = 0;
 
-        let mut preinit_array_pointer: Option<*const InitArrayFunction> = None;
+        let mut relr_offset: Option<usize> = None;
+        let mut relr_size = 0;
+
+        let mut preinit_array_offset: Option<usize> = None;
         let mut preinit_array_size = 0;
 
-        let mut init_array_pointer: Option<*const InitArrayFunction> = None;
+        let mut init_array_offset: Option<usize> = None;
         let mut init_array_size = 0;
 
-        let mut hash_table: Option<HashTable> = None;
+        let mut init_offset: Option<usize> = None;
+
+        let mut fini_array_offset: Option<usize> = None;
+        let mut fini_array_size = 0;
+
+        let mut fini_offset: Option<usize> = None;
+
+        let mut sysv_hash_offset: Option<usize> = None;
+        let mut gnu_hash_offset: Option<usize> = None;
+
+        let mut versym_offset: Option<usize> = None;
+        let mut verneed_offset: Option<usize> = None;
+        let mut verneed_count = 0;
+        let mut verdef_offset: Option<usize> = None;
+        let mut verdef_count = 0;
 
         let mut rpath_string_table_index: Option<usize> = None;
         let mut runpath_string_table_index: Option<usize> = None;
@@ -61,109 +86,193 @@ impl DynamicFields {
 
         let mut static_tls = false;
 
-        DynamicArrayIter::new(dynamic_array).for_each(|item| match item.d_tag() {
-            Ok(DynamicTag::PltGot) => {
-                global_offset_table = Some(base.byte_add(item.d_un.d_ptr.addr()) as *const usize)
-            }
-            Ok(DynamicTag::StrTab) => {
-                string_table_pointer = Ok(base.byte_add(item.d_un.d_ptr.addr()) as *const u8)
-            }
-            Ok(DynamicTag::SymTab) => {
-                symbol_table_pointer = Ok(base.byte_add(item.d_un.d_ptr.addr()) as *const Symbol)
-            }
-            #[cfg(debug_assertions)]
-            Ok(DynamicTag::SymEnt) => syscall_assert!(item.d_un.d_val == size_of::<Symbol>()),
-
-            Ok(DynamicTag::Rela) => {
-                rela_pointer = Some(base.byte_add(item.d_un.d_ptr.addr()) as *const Rela);
-            }
-            Ok(DynamicTag::RelaSz) => {
-                rela_count = item.d_un.d_val / size_of::<Rela>();
-            }
-            #[cfg(debug_assertions)]
-            Ok(DynamicTag::RelaEnt) => {
-                syscall_assert!(item.d_un.d_val == size_of::<Rela>())
-            }
-
-            Ok(DynamicTag::JmpRel) => {
-                plt_rela_pointer = Some(base.byte_add(item.d_un.d_ptr.addr()) as *const Rela);
-            }
-            Ok(DynamicTag::PltRelSz) => {
-                plt_rela_count = item.d_un.d_val / size_of::<Rela>();
-            }
-            #[cfg(debug_assertions)]
-            Ok(DynamicTag::PltRel) => {
-                syscall_assert!(item.d_un.d_val == DynamicTag::Rela as usize)
-            }
-
-            Ok(DynamicTag::PreInitArray) => {
-                preinit_array_pointer =
-                    Some(base.byte_add(item.d_un.d_ptr.addr()) as *const InitArrayFunction);
-            }
-            Ok(DynamicTag::PreInitArraySz) => {
-                preinit_array_size = item.d_un.d_val / size_of::<InitArrayFunction>();
+        // Entry sizes and the PLT relocation format are fixed on this target; anything else is a file we can't read.
+        let mut unexpected_value: Option<DynamicTag> = None;
+
+        DynamicArrayIter::with_len(dynamic_array, dynamic_len).for_each(|item| {
+            match item.d_tag() {
+                Ok(DynamicTag::PltGot) => global_offset_table = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::StrTab) => string_table_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::StrSz) => string_table_size = Some(item.d_un.d_val),
+                Ok(DynamicTag::SymTab) => symbol_table_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::SymEnt) if item.d_un.d_val != size_of::<Symbol>() => {
+                    unexpected_value.get_or_insert(DynamicTag::SymEnt);
+                }
+
+                Ok(DynamicTag::Rela) => rela_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::RelaSz) => rela_size = item.d_un.d_val,
+                Ok(DynamicTag::RelaEnt) if item.d_un.d_val != size_of::<Rela>() => {
+                    unexpected_value.get_or_insert(DynamicTag::RelaEnt);
+                }
+
+                Ok(DynamicTag::JmpRel) => plt_rela_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::PltRelSz) => plt_rela_size = item.d_un.d_val,
+                Ok(DynamicTag::PltRel) if item.d_un.d_val != DynamicTag::Rela as usize => {
+                    unexpected_value.get_or_insert(DynamicTag::PltRel);
+                }
+
+                Ok(DynamicTag::Relr) => relr_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::RelrSz) => relr_size = item.d_un.d_val,
+                Ok(DynamicTag::RelrEnt) if item.d_un.d_val != size_of::<usize>() => {
+                    unexpected_value.get_or_insert(DynamicTag::RelrEnt);
+                }
+
+                Ok(DynamicTag::PreInitArray) => preinit_array_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::PreInitArraySz) => preinit_array_size = item.d_un.d_val,
+
+                Ok(DynamicTag::InitArray) => init_array_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::InitArraySz) => init_array_size = item.d_un.d_val,
+                Ok(DynamicTag::Init) => init_offset = Some(item.d_un.d_ptr.addr()),
+
+                Ok(DynamicTag::FiniArray) => fini_array_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::FiniArraySz) => fini_array_size = item.d_un.d_val,
+                Ok(DynamicTag::Fini) => fini_offset = Some(item.d_un.d_ptr.addr()),
+
+                Ok(DynamicTag::Hash) => sysv_hash_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::GnuHash) => gnu_hash_offset = Some(item.d_un.d_ptr.addr()),
+
+                Ok(DynamicTag::VerSym) => versym_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::VerNeed) => verneed_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::VerNeedNum) => verneed_count = item.d_un.d_val,
+                Ok(DynamicTag::VerDef) => verdef_offset = Some(item.d_un.d_ptr.addr()),
+                Ok(DynamicTag::VerDefNum) => verdef_count = item.d_un.d_val,
+
+                Ok(DynamicTag::Rpath) => rpath_string_table_index = Some(item.d_un.d_val),
+                Ok(DynamicTag::Runpath) => runpath_string_table_index = Some(item.d_un.d_val),
+
+                Ok(DynamicTag::Needed) => {
+                    needed_libraries_string_table_offsets.push(item.d_un.d_val);
+                }
+
+                Ok(DynamicTag::Flags) => {
+                    let flags = DynamicFlags::new_with_raw_value(item.d_un.d_val as u64);
+                    static_tls |= flags.static_tls();
+                }
+
+                _ => (),
             }
+        });
 
-            Ok(DynamicTag::InitArray) => {
-                init_array_pointer =
-                    Some(base.byte_add(item.d_un.d_ptr.addr()) as *const InitArrayFunction);
-            }
-            Ok(DynamicTag::InitArraySz) => {
-                init_array_size = item.d_un.d_val / size_of::<InitArrayFunction>();
-            }
+        if let Some(tag) = unexpected_value {
+            return Err(MirosError::UnexpectedDynamicValue(tag));
+        }
 
-            Ok(DynamicTag::Hash) => {
-                hash_table.get_or_insert(HashTable::from_sysv(base, item.d_un.d_ptr));
-            }
-            Ok(DynamicTag::GnuHash) => {
-                hash_table = Some(HashTable::from_gnu(base, item.d_un.d_ptr))
-            }
+        // Turns an offset + byte size into a pointer, or names the tag whose table leaves the image.
+        let checked = |tag: DynamicTag, offset: usize, size: usize| {
+            offset
+                .checked_add(size)
+                .filter(|&end| end <= image_size)
+                .map(|_| base.byte_add(offset))
+                .ok_or(MirosError::DynamicEntryOutOfBounds(tag))
+        };
+        let checked_slice = |tag: DynamicTag, offset: Option<usize>, size: usize| {
+            offset.map(|offset| checked(tag, offset, size)).transpose()
+        };
+
+        // GNU hash wins when both are present; it is the faster lookup.
+        let hash_table = match (gnu_hash_offset, sysv_hash_offset) {
+            (Some(offset), _) => Some(HashTable::from_gnu(base, offset, image_size)?),
+            (None, Some(offset)) => Some(HashTable::from_sysv(base, offset, image_size)?),
+            (None, None) => None,
+        };
+
+        let string_table_offset =
+            string_table_offset.ok_or(MirosError::MissingDynamicEntry(DynamicTag::StrTab))?;
+        let symbol_table_offset =
+            symbol_table_offset.ok_or(MirosError::MissingDynamicEntry(DynamicTag::SymTab))?;
+
+        // Without DT_STRSZ the table may run to the end of the image, but no further.
+        let string_table_size =
+            string_table_size.unwrap_or_else(|| image_size.saturating_sub(string_table_offset));
+        let string_table_pointer =
+            checked(DynamicTag::StrTab, string_table_offset, string_table_size)? as *const u8;
 
-            Ok(DynamicTag::Rpath) => rpath_string_table_index = Some(item.d_un.d_val),
-            Ok(DynamicTag::Runpath) => runpath_string_table_index = Some(item.d_un.d_val),
+        // .dynstr immediately follows .dynsym in real linker output, so the address difference is the exact entry count; the hash table count is the fallback.
+        let symbol_count = (string_table_offset > symbol_table_offset)
+            .then(|| (string_table_offset - symbol_table_offset) / size_of::<Symbol>())
+            .or_else(|| hash_table.as_ref().and_then(|table| table.symbol_count()));
+        let symbol_table_size = symbol_count
+            .and_then(|count| count.checked_mul(size_of::<Symbol>()))
+            .unwrap_or(size_of::<Symbol>());
+        let symbol_table_pointer =
+            checked(DynamicTag::SymTab, symbol_table_offset, symbol_table_size)? as *const Symbol;
 
-            Ok(DynamicTag::Needed) => {
-                needed_libraries_string_table_offsets.push(item.d_un.d_val);
-            }
+        let string_table = StringTable::with_size(string_table_pointer, string_table_size);
+        let symbol_table = SymbolTable::with_count(symbol_table_pointer, symbol_count);
 
-            Ok(DynamicTag::Flags) => {
-                let flags = DynamicFlags::new_with_raw_value(item.d_un.d_val as u64);
-                static_tls |= flags.static_tls();
-            }
+        let global_offset_table =
+            checked_slice(DynamicTag::PltGot, global_offset_table, size_of::<usize>())?
+                .map(|pointer| pointer as *const usize);
 
-            _ => (),
+        let rela_slice = checked_slice(DynamicTag::Rela, rela_offset, rela_size)?.map(|pointer| {
+            ptr::slice_from_raw_parts(pointer as *const Rela, rela_size / size_of::<Rela>())
         });
+        let plt_rela_slice = checked_slice(DynamicTag::JmpRel, plt_rela_offset, plt_rela_size)?
+            .map(|pointer| {
+                ptr::slice_from_raw_parts(pointer as *const Rela, plt_rela_size / size_of::<Rela>())
+            });
 
-        let string_table_pointer = string_table_pointer?;
-        let symbol_table_pointer = symbol_table_pointer?;
-        let string_table = StringTable::new(string_table_pointer);
-        let symbol_table = SymbolTable::new(symbol_table_pointer);
-        // .dynstr immediately follows .dynsym in real linker output, so the address difference is the exact entry count; the hash table count is the fallback.
-        let symbol_count = (string_table_pointer > symbol_table_pointer.cast())
-            .then(|| {
-                (string_table_pointer as usize - symbol_table_pointer as usize)
-                    / size_of::<Symbol>()
-            })
-            .or_else(|| hash_table.as_ref().and_then(|table| table.symbol_count()));
-
-        let rela_slice = rela_pointer.map(|pointer| ptr::slice_from_raw_parts(pointer, rela_count));
-        let plt_rela_slice =
-            plt_rela_pointer.map(|pointer| ptr::slice_from_raw_parts(pointer, plt_rela_count));
+        let relr_slice = checked_slice(DynamicTag::Relr, relr_offset, relr_size)?.map(|pointer| {
+            ptr::slice_from_raw_parts(pointer as *const usize, relr_size / size_of::<usize>())
+        });
 
-        let preinit_array = preinit_array_pointer
-            .map(|pointer| ptr::slice_from_raw_parts(pointer, preinit_array_size));
-        let init_array =
-            init_array_pointer.map(|pointer| ptr::slice_from_raw_parts(pointer, init_array_size));
+        let preinit_array = checked_slice(
+            DynamicTag::PreInitArray,
+            preinit_array_offset,
+            preinit_array_size,
+        )?
+        .map(|pointer| {
+            ptr::slice_from_raw_parts(
+                pointer as *const InitArrayFunction,
+                preinit_array_size / size_of::<InitArrayFunction>(),
+            )
+        });
+        let init_array = checked_slice(DynamicTag::InitArray, init_array_offset, init_array_size)?
+            .map(|pointer| {
+                ptr::slice_from_raw_parts(
+                    pointer as *const InitArrayFunction,
+                    init_array_size / size_of::<InitArrayFunction>(),
+                )
+            });
+
+        // A zero DT_INIT/DT_FINI means "none", as glibc reads it.
+        let init = checked_slice(
+            DynamicTag::Init,
+            init_offset.filter(|&offset| offset != 0),
+            1,
+        )?
+        .map(|pointer| std::mem::transmute::<*const c_void, InitArrayFunction>(pointer));
+        let fini_array = checked_slice(DynamicTag::FiniArray, fini_array_offset, fini_array_size)?
+            .map(|pointer| {
+                ptr::slice_from_raw_parts(
+                    pointer as *const FiniFunction,
+                    fini_array_size / size_of::<FiniFunction>(),
+                )
+            });
+        let fini = checked_slice(
+            DynamicTag::Fini,
+            fini_offset.filter(|&offset| offset != 0),
+            1,
+        )?
+        .map(|pointer| std::mem::transmute::<*const c_void, FiniFunction>(pointer));
+
+        // Read rarely (diagnostics, versioned lookups), so each record is bounds-checked lazily rather than walked here.
+        let symbol_versions = versym_offset.map(|offset| {
+            SymbolVersions::new(base, image_size, offset)
+                .with_requirements(verneed_offset, verneed_count)
+                .with_definitions(verdef_offset, verdef_count)
+        });
 
-        let path_resolver = runpath_string_table_index
-            .map(|index| PathResolver::Runpath(string_table.get(index)))
-            .or(rpath_string_table_index.map(|index| PathResolver::Rpath(string_table.get(index))))
-            .unwrap_or(PathResolver::None);
+        let path_resolver = match (runpath_string_table_index, rpath_string_table_index) {
+            (Some(index), _) => PathResolver::Runpath(string_table.try_get(index)?),
+            (None, Some(index)) => PathResolver::Rpath(string_table.try_get(index)?),
+            (None, None) => PathResolver::None,
+        };
 
         let dependencies = needed_libraries_string_table_offsets
             .iter()
-            .map(|index| string_table.get_wide_pointer(*index))
-            .collect();
+            .map(|index| string_table.try_get(*index).map(|name| name as *const str))
+            .collect::<Result<_, _>>()?;
 
         Ok(Self {
             global_offset_table,
@@ -171,10 +280,14 @@ impl DynamicFields {
             symbol_table,
             rela_slice,
             plt_rela_slice,
+            relr_slice,
             preinit_array,
             init_array,
+            init,
+            fini_array,
+            fini,
             hash_table,
-            symbol_count,
+            symbol_versions,
             path_resolver,
             dependencies,
             static_tls,
@@ -183,10 +296,25 @@ impl DynamicFields {
 
     // Relocation indices come straight from the file; reject any past the dynsym before dereferencing.
     pub fn checked_symbol(&self, index: usize) -> Result<Symbol, MirosError> {
-        if self.symbol_count.is_some_and(|count| index >= count) {
-            return Err(MirosError::SymbolIndexOutOfBounds(index));
+        unsafe { self.symbol_table.try_get(index) }.ok_or(MirosError::SymbolIndexOutOfBounds(index))
+    }
+
+    /// The version name (`DT_VERNEED`) the symbol at `index` was linked against, e.g. `GLIBC_2.34`.
+    pub fn required_version(&self, index: usize) -> Option<&str> {
+        let versions = self.symbol_versions.as_ref()?;
+        unsafe {
+            let name = versions.required_name(index)?;
+            self.string_table.try_get(name).ok()
+        }
+    }
+
+    /// The version name (`DT_VERDEF`) the symbol at `index` is defined under, e.g. `LINUX_2.6`.
+    pub fn defined_version(&self, index: usize) -> Option<&str> {
+        let versions = self.symbol_versions.as_ref()?;
+        unsafe {
+            let name = versions.defined_name(index)?;
+            self.string_table.try_get(name).ok()
         }
-        Ok(unsafe { self.symbol_table.get(index) })
     }
 
     pub fn dependencies(&self) -> &[&str] {
@@ -209,6 +337,11 @@ impl DynamicFields {
         unsafe { self.plt_rela_slice.map(|pointer| &*pointer) }
     }
 
+    /// The packed `DT_RELR` relative relocations; decode with `RelrIter`.
+    pub fn relr_slice(&self) -> Option<&[usize]> {
+        unsafe { self.relr_slice.map(|pointer| &*pointer) }
+    }
+
     pub fn preinit_functions(&self) -> Option<&[InitArrayFunction]> {
         unsafe { self.preinit_array.map(|pointer| &*pointer) }
     }
@@ -216,4 +349,17 @@ impl DynamicFields {
     pub fn init_functions(&self) -> Option<&[InitArrayFunction]> {
         unsafe { self.init_array.map(|pointer| &*pointer) }
     }
+
+    pub fn init_function(&self) -> Option<InitArrayFunction> {
+        self.init
+    }
+
+    /// In array order; they run last to first.
+    pub fn fini_functions(&self) -> Option<&[FiniFunction]> {
+        unsafe { self.fini_array.map(|pointer| &*pointer) }
+    }
+
+    pub fn fini_function(&self) -> Option<FiniFunction> {
+        self.fini
+    }
 }
@@ -1,8 +1,12 @@
 use std::{ffi::c_void, ptr};
 
-use crate::elf::{
-    string_table::StringTable,
-    symbol::{Symbol, SymbolTable},
+use crate::{
+    elf::{
+        dynamic_array::DynamicTag,
+        string_table::StringTable,
+        symbol::{Symbol, SymbolTable},
+    },
+    error::MirosError,
 };
 
 pub enum HashTable {
@@ -15,12 +19,18 @@ pub enum HashTable {
         bloom_shift: u32,
         bloom: *const [u64],
         buckets: *const [u32],
-        chain: *const u32,
+        /// Runs to the end of the mapped image; the format stores no length, stop bits end each chain.
+        chain: *const [u32],
     },
 }
 
 impl HashTable {
-    pub unsafe fn from_sysv(base: *const c_void, hash_table_pointer: *const c_void) -> Self {
+    /// Parses the `DT_HASH` table at `offset` from `base`, checking it fits in the `image_size` bytes mapped there.
+    pub unsafe fn from_sysv(
+        base: *const c_void,
+        offset: usize,
+        image_size: usize,
+    ) -> Result<Self, MirosError> {
         // ┌────────────────────┐
         // │ bucket_count (u32) │
         // │ chain_count  (u32) │
@@ -29,20 +39,42 @@ impl HashTable {
         // ├────────────────────┤
         // │ chain     [u32; …] │
         // └────────────────────┘
-        let header = base.byte_add(hash_table_pointer.addr()) as *const u32;
+        let out_of_bounds = MirosError::DynamicEntryOutOfBounds(DynamicTag::Hash);
+        let header_size = 2 * size_of::<u32>();
+        if !fits(offset, header_size, image_size) || !offset.is_multiple_of(align_of::<u32>()) {
+            return Err(out_of_bounds);
+        }
+
+        let header = base.byte_add(offset) as *const u32;
         let bucket_count = *header as usize;
         let chain_count = *header.add(1) as usize;
+        if bucket_count == 0 {
+            return Err(MirosError::MalformedHashTable(DynamicTag::Hash));
+        }
+
+        let table_size = bucket_count
+            .checked_add(chain_count)
+            .and_then(|count| count.checked_mul(size_of::<u32>()))
+            .ok_or(MirosError::DynamicEntryOutOfBounds(DynamicTag::Hash))?;
+        if !fits(offset + header_size, table_size, image_size) {
+            return Err(out_of_bounds);
+        }
 
         let buckets_start = header.add(2);
         let chain_start = buckets_start.add(bucket_count);
 
-        Self::SysV {
+        Ok(Self::SysV {
             buckets: ptr::slice_from_raw_parts(buckets_start, bucket_count),
             chain: ptr::slice_from_raw_parts(chain_start, chain_count),
-        }
+        })
     }
 
-    pub unsafe fn from_gnu(base: *const c_void, hash_table_pointer: *const c_void) -> Self {
+    /// Parses the `DT_GNU_HASH` table at `offset` from `base`, checking it fits in the `image_size` bytes mapped there.
+    pub unsafe fn from_gnu(
+        base: *const c_void,
+        offset: usize,
+        image_size: usize,
+    ) -> Result<Self, MirosError> {
         // ┌────────────────────┐
         // │ bucket_count (u32) │
         // │ symoffset    (u32) │
@@ -55,23 +87,41 @@ impl HashTable {
         // ├────────────────────┤
         // │ chain     [u32; …] │
         // └────────────────────┘
-        let header = base.byte_add(hash_table_pointer.addr()) as *const u32;
+        let out_of_bounds = MirosError::DynamicEntryOutOfBounds(DynamicTag::GnuHash);
+        let malformed = MirosError::MalformedHashTable(DynamicTag::GnuHash);
+        let header_size = 4 * size_of::<u32>();
+        if !fits(offset, header_size, image_size) || !offset.is_multiple_of(align_of::<u64>()) {
+            return Err(out_of_bounds);
+        }
+
+        let header = base.byte_add(offset) as *const u32;
         let bucket_count = *header as usize;
         let symbol_offset = *header.add(1);
         let bloom_count = *header.add(2) as usize;
         let bloom_shift = *header.add(3);
+        if bucket_count == 0 || bloom_count == 0 || bloom_shift >= u32::BITS {
+            return Err(malformed);
+        }
 
-        let bloom_start = header.add(4) as *const u64;
-        let buckets_start = bloom_start.add(bloom_count) as *const u32;
-        let chain_start = buckets_start.add(bucket_count);
+        let bloom_offset = offset + header_size;
+        let buckets_offset = bloom_count
+            .checked_mul(size_of::<u64>())
+            .and_then(|size| bloom_offset.checked_add(size))
+            .ok_or(out_of_bounds)?;
+        let chain_offset = bucket_count
+            .checked_mul(size_of::<u32>())
+            .and_then(|size| buckets_offset.checked_add(size))
+            .filter(|&chain_offset| chain_offset <= image_size)
+            .ok_or(MirosError::DynamicEntryOutOfBounds(DynamicTag::GnuHash))?;
+        let chain_count = (image_size - chain_offset) / size_of::<u32>();
 
-        Self::Gnu {
+        Ok(Self::Gnu {
             symbol_offset,
             bloom_shift,
-            bloom: ptr::slice_from_raw_parts(bloom_start, bloom_count),
-            buckets: ptr::slice_from_raw_parts(buckets_start, bucket_count),
-            chain: chain_start,
-        }
+            bloom: ptr::slice_from_raw_parts(base.byte_add(bloom_offset).cast(), bloom_count),
+            buckets: ptr::slice_from_raw_parts(base.byte_add(buckets_offset).cast(), bucket_count),
+            chain: ptr::slice_from_raw_parts(base.byte_add(chain_offset).cast(), chain_count),
+        })
     }
 
     // Dynsym entry count recovered from the hash table; None when the table can't provide it.
@@ -87,11 +137,12 @@ impl HashTable {
             } => {
                 let symbol_offset = *symbol_offset as usize;
                 let buckets = &**buckets;
+                let chain = &**chain;
                 // Empty buckets: nothing is hashed (import-only dynsym), so the count is unrecoverable.
                 let &highest = buckets.iter().filter(|&&index| index != 0).max()?;
                 // Walk the highest bucket's chain to its stop bit; the last index + 1 is the count.
                 let mut symbol_index = highest as usize;
-                while *chain.add(symbol_index - symbol_offset) & 1 == 0 {
+                while *chain.get(symbol_index.checked_sub(symbol_offset)?)? & 1 == 0 {
                     symbol_index += 1;
                 }
                 Some(symbol_index + 1)
@@ -99,12 +150,24 @@ impl HashTable {
         }
     }
 
+    /// Finds `name`, yielding its dynsym index alongside the symbol.
     pub unsafe fn lookup(
         &self,
         name: &str,
         symbol_table: &SymbolTable,
         string_table: &StringTable,
-    ) -> Option<Symbol> {
+    ) -> Option<(usize, Symbol)> {
+        self.lookup_hashed(name, gnu_hash(name), symbol_table, string_table)
+    }
+
+    /// Like `lookup`, with the name's [`gnu_hash`] already computed (SysV tables hash it themselves).
+    pub unsafe fn lookup_hashed(
+        &self,
+        name: &str,
+        hash: u32,
+        symbol_table: &SymbolTable,
+        string_table: &StringTable,
+    ) -> Option<(usize, Symbol)> {
         match self {
             Self::SysV { buckets, chain } => {
                 let buckets = &**buckets;
@@ -112,13 +175,17 @@ impl HashTable {
                 let hash = elf_hash(name);
 
                 let mut symbol_index = buckets[hash as usize % buckets.len()] as usize;
-                while symbol_index != 0 {
+                // A well-formed chain visits each symbol at most once; more steps than that means a cycle.
+                for _ in 0..chain.len() {
+                    if symbol_index == 0 {
+                        break;
+                    }
                     if let Some(symbol) =
                         resolve_symbol(symbol_index, name, symbol_table, string_table)
                     {
-                        return Some(symbol);
+                        return Some((symbol_index, symbol));
                     }
-                    symbol_index = chain[symbol_index] as usize;
+                    symbol_index = *chain.get(symbol_index)? as usize;
                 }
                 None
             }
@@ -131,7 +198,7 @@ impl HashTable {
             } => {
                 let bloom = &**bloom;
                 let buckets = &**buckets;
-                let hash = gnu_hash(name);
+                let chain = &**chain;
 
                 // Bloom filter rejection:
                 let word_bits = u64::BITS;
@@ -152,12 +219,12 @@ impl HashTable {
                 // Chain walk (stop bit in bit 0 marks end of chain):
                 let symbol_offset = *symbol_offset as usize;
                 loop {
-                    let chain_entry = *chain.add(symbol_index - symbol_offset);
+                    let chain_entry = *chain.get(symbol_index.checked_sub(symbol_offset)?)?;
                     if (chain_entry | 1) == (hash | 1) {
                         if let Some(symbol) =
                             resolve_symbol(symbol_index, name, symbol_table, string_table)
                         {
-                            return Some(symbol);
+                            return Some((symbol_index, symbol));
                         }
                     }
                     if chain_entry & 1 != 0 {
@@ -177,10 +244,16 @@ unsafe fn resolve_symbol(
     symbol_table: &SymbolTable,
     string_table: &StringTable,
 ) -> Option<Symbol> {
-    let symbol = symbol_table.get(symbol_index);
+    let symbol = symbol_table.try_get(symbol_index)?;
     (name == string_table.get(symbol.st_name as usize)).then_some(symbol)
 }
 
+fn fits(offset: usize, size: usize, image_size: usize) -> bool {
+    offset
+        .checked_add(size)
+        .is_some_and(|end| end <= image_size)
+}
+
 fn elf_hash(name: &str) -> u32 {
     name.bytes().fold(0u32, |hash, byte| {
         let shifted = (hash << 4).wrapping_add(byte as u32);
@@ -189,7 +262,7 @@ fn elf_hash(name: &str) -> u32 {
     })
 }
 
-fn gnu_hash(name: &str) -> u32 {
+pub fn gnu_hash(name: &str) -> u32 {
     const HASH_SEED: u32 = 5381;
     name.bytes().fold(HASH_SEED, |hash, byte| {
         hash.wrapping_mul(33).wrapping_add(byte as u32)
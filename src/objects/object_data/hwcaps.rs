@@ -0,0 +1,273 @@
+use std::sync::OnceLock;
+
+/// One bit per CPU feature the x86-64 psABI levels are defined by; names match glibc's `glibc.cpu.hwcaps` tunable.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CpuFeatures(u32);
+
+impl CpuFeatures {
+    pub const CMPXCHG16B: Self = Self(1 << 0);
+    pub const LAHF64_SAHF64: Self = Self(1 << 1);
+    pub const POPCNT: Self = Self(1 << 2);
+    pub const SSE3: Self = Self(1 << 3);
+    pub const SSSE3: Self = Self(1 << 4);
+    pub const SSE4_1: Self = Self(1 << 5);
+    pub const SSE4_2: Self = Self(1 << 6);
+    pub const AVX: Self = Self(1 << 7);
+    pub const AVX2: Self = Self(1 << 8);
+    pub const BMI1: Self = Self(1 << 9);
+    pub const BMI2: Self = Self(1 << 10);
+    pub const F16C: Self = Self(1 << 11);
+    pub const FMA: Self = Self(1 << 12);
+    pub const LZCNT: Self = Self(1 << 13);
+    pub const MOVBE: Self = Self(1 << 14);
+    pub const AVX512F: Self = Self(1 << 15);
+    pub const AVX512BW: Self = Self(1 << 16);
+    pub const AVX512CD: Self = Self(1 << 17);
+    pub const AVX512DQ: Self = Self(1 << 18);
+    pub const AVX512VL: Self = Self(1 << 19);
+
+    const NAMES: &[(&str, Self)] = &[
+        ("CMPXCHG16B", Self::CMPXCHG16B),
+        ("LAHF64_SAHF64", Self::LAHF64_SAHF64),
+        ("POPCNT", Self::POPCNT),
+        ("SSE3", Self::SSE3),
+        ("SSSE3", Self::SSSE3),
+        ("SSE4_1", Self::SSE4_1),
+        ("SSE4_2", Self::SSE4_2),
+        ("AVX", Self::AVX),
+        ("AVX2", Self::AVX2),
+        ("BMI1", Self::BMI1),
+        ("BMI2", Self::BMI2),
+        ("F16C", Self::F16C),
+        ("FMA", Self::FMA),
+        ("LZCNT", Self::LZCNT),
+        ("MOVBE", Self::MOVBE),
+        ("AVX512F", Self::AVX512F),
+        ("AVX512BW", Self::AVX512BW),
+        ("AVX512CD", Self::AVX512CD),
+        ("AVX512DQ", Self::AVX512DQ),
+        ("AVX512VL", Self::AVX512VL),
+    ];
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Reads the features from CPUID, counting AVX and AVX-512 only if the kernel saves their register state.
+    ///
+    /// `AT_HWCAP` on x86-64 is just CPUID leaf 1's EDX and `AT_HWCAP2` has none of these bits, so CPUID is the only source.
+    #[cfg(target_arch = "x86_64")]
+    pub fn detect() -> Self {
+        use std::arch::x86_64::{__cpuid, __cpuid_count};
+
+        let bit = |register: u32, index: u32, feature: Self| {
+            if register & (1 << index) != 0 {
+                feature
+            } else {
+                Self::default()
+            }
+        };
+
+        let leaf_1 = __cpuid(1);
+        let leaf_7 = __cpuid_count(7, 0);
+        let extended = __cpuid(0x8000_0000).eax;
+        let leaf_ext_1 = (extended >= 0x8000_0001).then(|| __cpuid(0x8000_0001));
+
+        // XCR0 bits 1-2 are SSE/AVX state, 5-7 the AVX-512 opmask and upper-register state.
+        let os_saves = if leaf_1.ecx & (1 << 27) != 0 {
+            let (low, _high): (u32, u32);
+            unsafe {
+                std::arch::asm!("xgetbv", in("ecx") 0, out("eax") low, out("edx") _high, options(nomem, nostack));
+            }
+            low
+        } else {
+            0
+        };
+        let avx_state = os_saves & 0b110 == 0b110;
+        let avx512_state = avx_state && os_saves & 0b1110_0000 == 0b1110_0000;
+
+        let mut features = bit(leaf_1.ecx, 13, Self::CMPXCHG16B)
+            .union(bit(leaf_1.ecx, 23, Self::POPCNT))
+            .union(bit(leaf_1.ecx, 0, Self::SSE3))
+            .union(bit(leaf_1.ecx, 9, Self::SSSE3))
+            .union(bit(leaf_1.ecx, 19, Self::SSE4_1))
+            .union(bit(leaf_1.ecx, 20, Self::SSE4_2))
+            .union(bit(leaf_1.ecx, 22, Self::MOVBE))
+            .union(bit(leaf_7.ebx, 3, Self::BMI1))
+            .union(bit(leaf_7.ebx, 8, Self::BMI2));
+        if let Some(leaf_ext_1) = leaf_ext_1 {
+            features = features
+                .union(bit(leaf_ext_1.ecx, 0, Self::LAHF64_SAHF64))
+                .union(bit(leaf_ext_1.ecx, 5, Self::LZCNT));
+        }
+        if avx_state {
+            features = features
+                .union(bit(leaf_1.ecx, 28, Self::AVX))
+                .union(bit(leaf_1.ecx, 29, Self::F16C))
+                .union(bit(leaf_1.ecx, 12, Self::FMA))
+                .union(bit(leaf_7.ebx, 5, Self::AVX2));
+        }
+        if avx512_state {
+            features = features
+                .union(bit(leaf_7.ebx, 16, Self::AVX512F))
+                .union(bit(leaf_7.ebx, 30, Self::AVX512BW))
+                .union(bit(leaf_7.ebx, 28, Self::AVX512CD))
+                .union(bit(leaf_7.ebx, 17, Self::AVX512DQ))
+                .union(bit(leaf_7.ebx, 31, Self::AVX512VL));
+        }
+        features
+    }
+
+    /// Applies a `GLIBC_TUNABLES` string: every `-NAME` in `glibc.cpu.hwcaps` clears that feature.
+    ///
+    /// Enabling (`NAME` without the dash) is ignored, as glibc ignores it for anything the CPU lacks.
+    pub fn masked_by_tunables(self, tunables: &str) -> Self {
+        let hwcaps = tunables
+            .split(':')
+            .filter_map(|tunable| tunable.strip_prefix("glibc.cpu.hwcaps="));
+        let disabled = hwcaps
+            .flat_map(|list| list.split(','))
+            .filter_map(|item| item.strip_prefix('-'))
+            .filter_map(|name| {
+                Self::NAMES
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|(_, feature)| *feature)
+            })
+            .fold(Self::default(), Self::union);
+        Self(self.0 & !disabled.0)
+    }
+}
+
+/// The x86-64 psABI micro-architecture levels, each a superset of the one before.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum IsaLevel {
+    Baseline,
+    V2,
+    V3,
+    V4,
+}
+
+impl IsaLevel {
+    const V2_FEATURES: CpuFeatures = CpuFeatures::CMPXCHG16B
+        .union(CpuFeatures::LAHF64_SAHF64)
+        .union(CpuFeatures::POPCNT)
+        .union(CpuFeatures::SSE3)
+        .union(CpuFeatures::SSSE3)
+        .union(CpuFeatures::SSE4_1)
+        .union(CpuFeatures::SSE4_2);
+    const V3_FEATURES: CpuFeatures = Self::V2_FEATURES
+        .union(CpuFeatures::AVX)
+        .union(CpuFeatures::AVX2)
+        .union(CpuFeatures::BMI1)
+        .union(CpuFeatures::BMI2)
+        .union(CpuFeatures::F16C)
+        .union(CpuFeatures::FMA)
+        .union(CpuFeatures::LZCNT)
+        .union(CpuFeatures::MOVBE);
+    const V4_FEATURES: CpuFeatures = Self::V3_FEATURES
+        .union(CpuFeatures::AVX512F)
+        .union(CpuFeatures::AVX512BW)
+        .union(CpuFeatures::AVX512CD)
+        .union(CpuFeatures::AVX512DQ)
+        .union(CpuFeatures::AVX512VL);
+
+    pub fn from_features(features: CpuFeatures) -> Self {
+        match features {
+            features if features.contains(Self::V4_FEATURES) => Self::V4,
+            features if features.contains(Self::V3_FEATURES) => Self::V3,
+            features if features.contains(Self::V2_FEATURES) => Self::V2,
+            _ => Self::Baseline,
+        }
+    }
+
+    /// The `glibc-hwcaps` subdirectories this level may load from, best first.
+    pub fn subdirectories(self) -> &'static [&'static str] {
+        const ALL: &[&str] = &[
+            "glibc-hwcaps/x86-64-v4",
+            "glibc-hwcaps/x86-64-v3",
+            "glibc-hwcaps/x86-64-v2",
+        ];
+        match self {
+            Self::V4 => ALL,
+            Self::V3 => &ALL[1..],
+            Self::V2 => &ALL[2..],
+            Self::Baseline => &[],
+        }
+    }
+}
+
+/// The subdirectories probed inside each search directory before the directory itself, detected once per process.
+pub fn hwcaps_subdirectories() -> &'static [&'static str] {
+    static SUBDIRECTORIES: OnceLock<&'static [&'static str]> = OnceLock::new();
+    SUBDIRECTORIES.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        {
+            let tunables = std::env::var("GLIBC_TUNABLES").unwrap_or_default();
+            let features = CpuFeatures::detect().masked_by_tunables(&tunables);
+            IsaLevel::from_features(features).subdirectories()
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            &[]
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_need_every_feature_they_name() {
+        assert_eq!(
+            IsaLevel::from_features(CpuFeatures::default()),
+            IsaLevel::Baseline
+        );
+        assert_eq!(IsaLevel::from_features(IsaLevel::V3_FEATURES), IsaLevel::V3);
+        // AVX2 without FMA is still only v2.
+        let almost_v3 = CpuFeatures(IsaLevel::V3_FEATURES.0 & !CpuFeatures::FMA.0);
+        assert_eq!(IsaLevel::from_features(almost_v3), IsaLevel::V2);
+        assert_eq!(IsaLevel::V3.subdirectories(), [
+            "glibc-hwcaps/x86-64-v3",
+            "glibc-hwcaps/x86-64-v2"
+        ]);
+    }
+
+    #[test]
+    fn tunables_mask_features_down_a_level() {
+        let tunables = "glibc.malloc.check=0:glibc.cpu.hwcaps=-AVX512F,-AVX2,SSE2,-BOGUS";
+        let masked = IsaLevel::V4_FEATURES.masked_by_tunables(tunables);
+        assert!(!masked.contains(CpuFeatures::AVX2));
+        assert!(masked.contains(CpuFeatures::AVX));
+        assert_eq!(IsaLevel::from_features(masked), IsaLevel::V2);
+        assert_eq!(
+            IsaLevel::from_features(
+                IsaLevel::V4_FEATURES.masked_by_tunables("glibc.cpu.hwcaps=-AVX512VL")
+            ),
+            IsaLevel::V3
+        );
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn detection_agrees_with_std() {
+        let features = CpuFeatures::detect();
+        assert_eq!(
+            features.contains(CpuFeatures::AVX2),
+            std::arch::is_x86_feature_detected!("avx2")
+        );
+        assert_eq!(
+            features.contains(CpuFeatures::SSE4_2),
+            std::arch::is_x86_feature_detected!("sse4.2")
+        );
+        assert_eq!(
+            features.contains(CpuFeatures::AVX512F),
+            std::arch::is_x86_feature_detected!("avx512f")
+        );
+    }
+}
@@ -1,37 +1,57 @@
 pub mod dynamic_fields;
-mod hash_tables;
+pub mod hash_tables;
+mod hwcaps;
 mod path_resolver;
 mod thread_local;
 
 use std::{
     cmp::{max, min},
-    ffi::c_void,
+    ffi::{c_void, CString},
     fs::File,
-    io::Read,
-    os::{fd::AsRawFd, unix::fs::FileExt},
+    mem::ManuallyDrop,
+    ops::Range,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+        unix::{ffi::OsStrExt, fs::FileExt},
+    },
+    path::PathBuf,
     ptr::{self, null, null_mut},
     slice,
 };
 
 pub use dynamic_fields::DynamicFields;
+use hash_tables::gnu_hash;
+pub use path_resolver::{PathResolver, SearchOrigin};
 pub use thread_local::{ThreadLocalAllocation, ThreadLocalData};
 
 use crate::{
     elf::{
         dynamic_array::DynamicArrayItem,
         header::ElfHeader,
-        program_header::{ProgramHeader, PT_DYNAMIC, PT_LOAD, PT_PHDR, PT_TLS},
+        note::gnu_build_id,
+        program_header::{
+            ProgramHeader, PT_DYNAMIC, PT_GNU_EH_FRAME, PT_LOAD, PT_NOTE, PT_PHDR, PT_TLS,
+        },
         section::SectionIndex,
         symbol::Symbol,
     },
     error::MirosError,
     io_macros::syscall_debug_assert,
-    libc::mem::{mmap, MapFlags, ProtectionFlags},
+    libc::{
+        errno::{self, Errno},
+        mem::{memfd_create, mmap, MapFlags, ProtectionFlags, MFD_CLOEXEC},
+    },
+    objects::rendezvous::LinkMap,
     page_size,
 };
 
 pub struct ObjectData {
     pub base: *const c_void,
+    /// The path this object was loaded from; `None` for the program, which glibc's link map names `""`.
+    pub path: Option<PathBuf>,
+    pub dynamic_array: *const DynamicArrayItem,
+    /// This object's entry in the debugger/audit link-map chain; unlinked until the rendezvous stage.
+    pub link_map: *mut LinkMap,
     pub dynamic_fields: DynamicFields,
     pub tls_data: Option<ThreadLocalData>,
 }
@@ -48,52 +68,77 @@ impl ObjectData {
             header.e_phnum as usize,
         );
 
-        let mut dynamic_program_header = null();
-        let mut tls_program_header = None;
-        for header in &*program_header_table {
-            match header.p_type {
-                PT_DYNAMIC => dynamic_program_header = header,
-                PT_TLS => tls_program_header = Some(header.to_owned()),
-                _ => (),
-            }
-        }
-
-        Self::build_internal(base, dynamic_program_header, tls_program_header)
+        Self::build_internal(base, &*program_header_table)
     }
 
     pub unsafe fn from_program_headers(
         program_header_table: *const [ProgramHeader],
     ) -> Result<Self, MirosError> {
-        let (mut base, mut dynamic_program_header) = (null(), null());
-        let mut tls_program_header = None;
-        for header in &*program_header_table {
-            match header.p_type {
-                PT_PHDR => {
-                    base =
-                        (*program_header_table).as_ptr().byte_sub(header.p_vaddr) as *const c_void;
-                }
-                PT_DYNAMIC => dynamic_program_header = header,
-                PT_TLS => tls_program_header = Some(header.to_owned()),
-                _ => (),
-            }
-        }
+        let base = (*program_header_table)
+            .iter()
+            .find(|header| header.p_type == PT_PHDR)
+            .map_or(null(), |header| {
+                (*program_header_table).as_ptr().byte_sub(header.p_vaddr) as *const c_void
+            });
 
-        Self::build_internal(base, dynamic_program_header, tls_program_header)
+        Self::build_internal(base, &*program_header_table)
     }
 
     unsafe fn build_internal(
         base: *const c_void,
-        dynamic_program_header: *const ProgramHeader,
-        tls_program_header: Option<ProgramHeader>,
+        program_header_table: &[ProgramHeader],
     ) -> Result<Self, MirosError> {
-        syscall_debug_assert!(dynamic_program_header != null());
+        let mut dynamic_program_header = None;
+        let mut tls_program_header = None;
+        for header in program_header_table {
+            match header.p_type {
+                PT_DYNAMIC => dynamic_program_header = Some(header),
+                PT_TLS => tls_program_header = Some(header.to_owned()),
+                _ => (),
+            }
+        }
+        let dynamic_program_header =
+            dynamic_program_header.ok_or(MirosError::MissingDynamicSegment)?;
+
+        // Every table the dynamic array names must lie inside the loaded image.
+        let (_, image_size) = calculate_virtual_address_bounds(program_header_table);
+        let in_image = |header: &ProgramHeader| {
+            header
+                .p_vaddr
+                .checked_add(header.p_memsz)
+                .is_some_and(|end| end <= image_size)
+        };
+        if !in_image(dynamic_program_header) {
+            return Err(MirosError::DynamicSegmentOutOfBounds);
+        }
+        if tls_program_header
+            .as_ref()
+            .is_some_and(|header| !in_image(header))
+        {
+            return Err(MirosError::SegmentOutOfBounds(
+                program_header_table
+                    .iter()
+                    .position(|header| header.p_type == PT_TLS)
+                    .unwrap_or_default(),
+            ));
+        }
 
         let dynamic_array =
-            base.byte_add((*dynamic_program_header).p_vaddr) as *const DynamicArrayItem;
+            base.byte_add(dynamic_program_header.p_vaddr) as *const DynamicArrayItem;
+        let dynamic_len = dynamic_program_header.p_memsz / size_of::<DynamicArrayItem>();
 
         Ok(Self {
             base,
-            dynamic_fields: DynamicFields::from_dynamic_array(base, dynamic_array)?,
+            path: None,
+            dynamic_array,
+            // Debuggers and auditors may hold on to link maps forever, so they are never freed.
+            link_map: Box::into_raw(Box::new(LinkMap::new(base, dynamic_array))),
+            dynamic_fields: DynamicFields::from_dynamic_array(
+                base,
+                dynamic_array,
+                dynamic_len,
+                image_size,
+            )?,
             tls_data: tls_program_header.map(|tls_program_header| ThreadLocalData {
                 tls_program_header,
                 thread_local_allocation: None,
@@ -101,47 +146,73 @@ impl ObjectData {
         })
     }
 
-    pub unsafe fn from_file(mut file: File) -> Result<Self, MirosError> {
+    pub unsafe fn from_file(file: File, path: PathBuf) -> Result<Self, MirosError> {
+        Self::from_fd(file.as_fd(), path)
+    }
+
+    /// Maps an object from an already-open descriptor; the descriptor is only borrowed, since the mappings outlive it.
+    pub unsafe fn from_fd(fd: BorrowedFd, path: PathBuf) -> Result<Self, MirosError> {
+        let mut object = Self::map_fd(fd).map_err(|error| error.in_object(&path))?;
+        object.set_path(path);
+        Ok(object)
+    }
+
+    unsafe fn map_fd(fd: BorrowedFd) -> Result<Self, MirosError> {
+        // SAFETY: Borrowed for `FileExt`'s positional reads only; never dropped, so never closed.
+        let file = ManuallyDrop::new(File::from_raw_fd(fd.as_raw_fd()));
+
+        let file_size = file
+            .metadata()
+            .map_err(|error| MirosError::Io {
+                action: "stat shared object",
+                errno: Errno::from(&error),
+            })?
+            .len() as usize;
+
         // Read the ELF header from file:
         let mut header_from_file: ElfHeader = std::mem::zeroed();
         let as_bytes = slice::from_raw_parts_mut(
             &mut header_from_file as *mut ElfHeader as *mut u8,
             size_of::<ElfHeader>(),
         );
-        file.read_exact(as_bytes)
-            .map_err(|_| MirosError::ElfReadError("failed to read ELF header".to_string()))?;
+        file.read_exact_at(as_bytes, 0)
+            .map_err(|error| MirosError::Io {
+                action: "read file data",
+                errno: Errno::from(&error),
+            })?;
+        header_from_file
+            .validate()
+            .map_err(MirosError::InvalidElfHeader)?;
 
         // Read the program header table from file:
+        let program_header_table_size =
+            size_of::<ProgramHeader>() * header_from_file.e_phnum as usize;
         let mut program_headers_from_file: Vec<ProgramHeader> =
             Vec::with_capacity(header_from_file.e_phnum as usize);
         let as_bytes = slice::from_raw_parts_mut(
             program_headers_from_file.as_mut_ptr() as *mut u8,
-            size_of::<ProgramHeader>() * header_from_file.e_phnum as usize,
+            program_header_table_size,
         );
         file.read_exact_at(as_bytes, header_from_file.e_phoff as u64)
-            .map_err(|_| {
-                MirosError::ElfReadError("failed to read program header table".to_string())
-            })?;
+            .map_err(|_| MirosError::ProgramHeaderTableOutOfBounds)?;
         program_headers_from_file.set_len(header_from_file.e_phnum as usize);
-        debug_assert!(program_headers_from_file
-            .iter()
-            .any(|header| header.p_type == PT_LOAD));
+
+        validate_program_headers(
+            &program_headers_from_file,
+            header_from_file.e_phoff,
+            program_header_table_size,
+            file_size,
+        )?;
 
         // Reserve a continuous region of memory:
-        let (min_addr, max_addr) = calculate_virtual_address_bounds(&program_headers_from_file);
+        // NOTE: Segments land at `base + p_vaddr` and the headers are read back from `base`, so the reservation starts at vaddr 0 even if the lowest segment doesn't.
+        let (_, max_addr) = calculate_virtual_address_bounds(&program_headers_from_file);
         let protection_flags = ProtectionFlags::ZERO
             .with_executable(true)
             .with_readable(true)
             .with_writable(true);
         let map_flags = MapFlags::ZERO.with_private(true).with_anonymous(true);
-        let base = mmap(
-            null_mut(),
-            max_addr - min_addr,
-            protection_flags,
-            map_flags,
-            -1,
-            0,
-        ) as *const c_void;
+        let base = mmap(null_mut(), max_addr, protection_flags, map_flags, -1, 0) as *const c_void;
 
         // Load all segments:
         program_headers_from_file
@@ -149,13 +220,65 @@ impl ObjectData {
             .filter(|program_header| program_header.p_type == PT_LOAD)
             .for_each(|program_header| load_segment(base, &file, program_header));
 
-        Self::from_base(base)
+        let object = Self::from_base(base)?;
+        // Only the program's preinit array ever runs; glibc silently skips these, rejecting says why they never run.
+        if object.dynamic_fields.preinit_functions().is_some() {
+            return Err(MirosError::PreinitArrayInSharedObject);
+        }
+        Ok(object)
+    }
+
+    /// Maps an object straight from its bytes through a memfd, never touching disk; it is named `memfd:<name>`.
+    pub unsafe fn from_memory(bytes: &[u8], name: &str) -> Result<Self, MirosError> {
+        let path = PathBuf::from(format!("memfd:{name}"));
+        let memfd_error = |errno| {
+            MirosError::Io {
+                action: "create memfd",
+                errno,
+            }
+            .in_object(&path)
+        };
+
+        let c_name = CString::new(name).map_err(|_| memfd_error(Errno::INVAL))?;
+        let descriptor = memfd_create(c_name.as_ptr(), MFD_CLOEXEC);
+        if descriptor < 0 {
+            return Err(memfd_error(errno::errno.get()));
+        }
+        let file = File::from(OwnedFd::from_raw_fd(descriptor));
+        file.write_all_at(bytes, 0)
+            .map_err(|error| memfd_error(Errno::from(&error)))?;
+
+        Self::from_fd(file.as_fd(), path)
+    }
+
+    /// Records where the object was loaded from, naming its link map after it.
+    pub fn set_path(&mut self, path: PathBuf) {
+        if let Ok(name) = CString::new(path.as_os_str().as_bytes()) {
+            unsafe { (*self.link_map).l_name = name.into_raw() };
+        }
+        self.path = Some(path);
     }
 
     pub fn resolve_symbol_and_address(&self, name: &str) -> Option<(Symbol, *const c_void)> {
-        let symbol = unsafe {
-            self.dynamic_fields.hash_table.as_ref()?.lookup(
+        self.resolve_indexed_symbol(name)
+            .map(|(_, symbol, address)| (symbol, address))
+    }
+
+    /// Like `resolve_symbol_and_address`, but also yields the symbol's dynsym index.
+    pub fn resolve_indexed_symbol(&self, name: &str) -> Option<(usize, Symbol, *const c_void)> {
+        self.resolve_hashed_symbol(name, gnu_hash(name))
+    }
+
+    /// Like `resolve_indexed_symbol`, for a caller that already has the name's GNU hash.
+    pub fn resolve_hashed_symbol(
+        &self,
+        name: &str,
+        hash: u32,
+    ) -> Option<(usize, Symbol, *const c_void)> {
+        let (symbol_index, symbol) = unsafe {
+            self.dynamic_fields.hash_table.as_ref()?.lookup_hashed(
                 name,
+                hash,
                 &self.dynamic_fields.symbol_table,
                 &self.dynamic_fields.string_table,
             )?
@@ -165,17 +288,110 @@ impl ObjectData {
             return None;
         }
 
-        let address = unsafe {
-            match symbol.section_index() {
-                Ok(SectionIndex::Absolute) => symbol.st_value as *const c_void,
-                _ => self.base.byte_add(symbol.st_value),
-            }
-        };
+        Some((symbol_index, symbol, self.symbol_address(&symbol)))
+    }
+
+    /// Where one of this object's defined symbols lives at run time.
+    pub fn symbol_address(&self, symbol: &Symbol) -> *const c_void {
+        match symbol.section_index() {
+            Ok(SectionIndex::Absolute) => symbol.st_value as *const c_void,
+            _ => self.base.wrapping_byte_add(symbol.st_value),
+        }
+    }
+
+    /// The mapped image's program headers, found through its ELF header.
+    fn program_headers(&self) -> &[ProgramHeader] {
+        unsafe {
+            let header = &*(self.base as *const ElfHeader);
+            slice::from_raw_parts(
+                self.base.byte_add(header.e_phoff).cast::<ProgramHeader>(),
+                header.e_phnum as usize,
+            )
+        }
+    }
+
+    /// The addresses the object's `PT_LOAD` segments span, page-aligned.
+    pub fn mapped_range(&self) -> Range<usize> {
+        let (start, end) = calculate_virtual_address_bounds(self.program_headers());
+        self.base.addr() + start..self.base.addr() + end
+    }
+
+    /// The `PT_GNU_EH_FRAME` segment (`.eh_frame_hdr`), which unwinders search for an address's frame description.
+    pub fn eh_frame_header(&self) -> Option<*const c_void> {
+        self.program_headers()
+            .iter()
+            .find(|program_header| program_header.p_type == PT_GNU_EH_FRAME)
+            .map(|program_header| self.base.wrapping_byte_add(program_header.p_vaddr))
+    }
 
-        Some((symbol, address))
+    /// The `NT_GNU_BUILD_ID` note, read from the mapped image's program headers.
+    pub fn build_id(&self) -> Option<&[u8]> {
+        self.program_headers()
+            .iter()
+            .filter(|program_header| program_header.p_type == PT_NOTE)
+            .find_map(|program_header| {
+                let notes = unsafe {
+                    slice::from_raw_parts(
+                        self.base.byte_add(program_header.p_vaddr).cast::<u8>(),
+                        program_header.p_filesz,
+                    )
+                };
+                gnu_build_id(notes, program_header.p_align)
+            })
+    }
+
+    /// Resolves `name@version`; an object without version definitions satisfies any version, as in glibc.
+    pub fn resolve_versioned_symbol(&self, name: &str, version: &str) -> Option<*const c_void> {
+        let (symbol_index, _, address) = self.resolve_indexed_symbol(name)?;
+        match self.dynamic_fields.defined_version(symbol_index) {
+            Some(defined) if defined != version => None,
+            _ => Some(address),
+        }
     }
 }
 
+/// Checks the program headers against the file before anything is mapped: every `PT_LOAD` must be backed by the file and mappable, and the table itself must land in mapped memory (`from_base` reads it back from there).
+fn validate_program_headers(
+    program_header_table: &[ProgramHeader],
+    table_offset: usize,
+    table_size: usize,
+    file_size: usize,
+) -> Result<(), MirosError> {
+    let mut loadable = program_header_table
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| header.p_type == PT_LOAD)
+        .peekable();
+    if loadable.peek().is_none() {
+        return Err(MirosError::NoLoadableSegments);
+    }
+
+    let page_size = page_size::get_page_size();
+    let mut table_mapped = false;
+    for (index, header) in loadable {
+        let file_end = header.p_offset.checked_add(header.p_filesz);
+        let memory_end = header.p_vaddr.checked_add(header.p_memsz);
+        if header.p_filesz > header.p_memsz
+            || file_end.is_none_or(|end| end > file_size)
+            || memory_end.is_none()
+        {
+            return Err(MirosError::SegmentOutOfBounds(index));
+        }
+        if header.p_offset % page_size != header.p_vaddr % page_size {
+            return Err(MirosError::MisalignedSegment(index));
+        }
+
+        table_mapped |= header.p_offset == header.p_vaddr
+            && header.p_offset <= table_offset
+            && file_end.is_some_and(|end| table_offset + table_size <= end);
+    }
+
+    if !table_mapped {
+        return Err(MirosError::ProgramHeaderTableOutOfBounds);
+    }
+    Ok(())
+}
+
 fn calculate_virtual_address_bounds(program_header_table: &[ProgramHeader]) -> (usize, usize) {
     let mut min_addr = usize::MAX;
     let mut max_addr = 0;
@@ -185,8 +401,8 @@ fn calculate_virtual_address_bounds(program_header_table: &[ProgramHeader]) -> (
             continue;
         }
 
-        let start = header.p_vaddr as usize;
-        let end = start + header.p_memsz as usize;
+        let start = header.p_vaddr;
+        let end = start + header.p_memsz;
 
         min_addr = min(min_addr, start);
         max_addr = max(max_addr, end);
@@ -230,8 +446,40 @@ unsafe fn load_segment(
             in_memory_base
                 .byte_add(segment_program_header.p_vaddr)
                 .byte_add(segment_program_header.p_filesz) as *mut u8,
-            segment_program_header.p_memsz - segment_program_header.p_filesz as usize,
+            segment_program_header.p_memsz - segment_program_header.p_filesz,
         )
         .fill(0);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[test]
+    fn from_memory_maps_the_same_object_as_from_file() {
+        unsafe { page_size::set_page_size(libc::sysconf(libc::_SC_PAGESIZE) as usize) };
+        let bytes = std::fs::read("/proc/self/exe").unwrap();
+        let from_memory = unsafe { ObjectData::from_memory(&bytes, "self").unwrap() };
+        let from_file = unsafe {
+            ObjectData::from_file(
+                File::open("/proc/self/exe").unwrap(),
+                PathBuf::from("/proc/self/exe"),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(from_memory.path, Some(PathBuf::from("memfd:self")));
+        assert_eq!(
+            unsafe { CStr::from_ptr((*from_memory.link_map).l_name) },
+            c"memfd:self"
+        );
+        assert_eq!(
+            from_memory.dynamic_fields.dependencies(),
+            from_file.dynamic_fields.dependencies()
+        );
+        assert_eq!(from_memory.tls_data.is_some(), from_file.tls_data.is_some());
+    }
+}
@@ -1,13 +1,27 @@
-use std::{cell::RefCell, env, fs::File, path::PathBuf};
+use std::{
+    cell::RefCell,
+    env,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
-use crate::error::MirosError;
+use super::hwcaps::hwcaps_subdirectories;
+use crate::{error::MirosError, libc::errno::Errno, start::secure_execution::is_secure_execution};
 
+#[cfg(target_arch = "x86_64")]
 const DEFAULT_SEARCH_PATHS: &[&str] = &[
     "/lib",
     "/usr/lib",
     "/lib/x86_64-linux-gnu",
     "/usr/lib/x86_64-linux-gnu",
 ];
+#[cfg(target_arch = "aarch64")]
+const DEFAULT_SEARCH_PATHS: &[&str] = &[
+    "/lib",
+    "/usr/lib",
+    "/lib/aarch64-linux-gnu",
+    "/usr/lib/aarch64-linux-gnu",
+];
 
 /// Resolves DT_NEEDED library names to open file handles by searching the standard ld.so directory order.
 ///
@@ -19,72 +33,172 @@ const DEFAULT_SEARCH_PATHS: &[&str] = &[
 /// | Runpath | LD_LIBRARY_PATH → RUNPATH → /etc/ld.so.cache → defaults   |
 /// | None    | LD_LIBRARY_PATH → /etc/ld.so.cache → defaults             |
 ///
+/// Within each directory, the `glibc-hwcaps/x86-64-v*` subdirectories this CPU can run are probed first, best first.
+///
+/// In secure-execution mode (`AT_SECURE`) LD_LIBRARY_PATH and relative RPATH/RUNPATH entries are skipped.
+#[derive(Clone, Copy)]
 pub enum PathResolver {
     Rpath(*const str),
     Runpath(*const str),
     None,
 }
 
+/// Which part of the search order produced a candidate path.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SearchOrigin {
+    LibraryPath,
+    /// Either DT_RPATH or DT_RUNPATH.
+    Runpath,
+    Default,
+}
+
 impl PathResolver {
     fn elf_search_dirs(&self) -> impl Iterator<Item = &str> {
         let path_string = match self {
             Self::Rpath(pointer) | Self::Runpath(pointer) => unsafe { &**pointer },
             Self::None => "",
         };
-        path_string.split(':').filter(|path| !path.is_empty())
+        // In secure mode a relative entry (including an unexpanded `$ORIGIN`) would resolve against the invoker's working directory.
+        let secure = is_secure_execution();
+        path_string
+            .split(':')
+            .filter(move |path| !path.is_empty() && (!secure || path.starts_with('/')))
+    }
+
+    /// Every directory to probe, in order; DT_RPATH goes before LD_LIBRARY_PATH, DT_RUNPATH after it.
+    fn search_directories<'a>(
+        &'a self,
+        ld_library_path: Option<&'a str>,
+    ) -> impl Iterator<Item = (&'a str, SearchOrigin)> {
+        let rpath = matches!(self, Self::Rpath(_));
+        let ld_library_path_dirs = ld_library_path
+            .into_iter()
+            .flat_map(|paths| paths.split(':'))
+            .filter(|path| !path.is_empty())
+            .map(|path| (path, SearchOrigin::LibraryPath));
+        let elf_search_dirs = || {
+            self.elf_search_dirs()
+                .map(|path| (path, SearchOrigin::Runpath))
+        };
+
+        // TODO: search /etc/ld.so.cache between LD_LIBRARY_PATH and defaults
+        let default_dirs = DEFAULT_SEARCH_PATHS
+            .iter()
+            .map(|path| (*path, SearchOrigin::Default));
+
+        elf_search_dirs()
+            .filter(move |_| rpath)
+            .chain(ld_library_path_dirs)
+            .chain(elf_search_dirs().filter(move |_| !rpath))
+            .chain(default_dirs)
     }
 
     fn open_first_match<'a>(
         &self,
-        mut search_directories: impl Iterator<Item = &'a str>,
+        mut search_directories: impl Iterator<Item = (&'a str, SearchOrigin)>,
         dependency_name: &str,
-    ) -> Option<File> {
+        filter: &mut dyn FnMut(&mut PathBuf, SearchOrigin) -> bool,
+        last_errno: &mut Errno,
+    ) -> Option<(PathBuf, File)> {
         // PERF: Reuse a single PathBuf across calls to avoid per-probe allocations.
         // LLVM can't hoist this — each iteration escapes into an opaque syscall with a different length.
         thread_local! {
-            static CANDIDATE_BUFFER: RefCell<PathBuf> = RefCell::new(PathBuf::new());
+            static CANDIDATE_BUFFER: RefCell<PathBuf> = const { RefCell::new(PathBuf::new()) };
         }
         CANDIDATE_BUFFER.with_borrow_mut(|candidate| {
-            search_directories.find_map(|directory| {
-                candidate.clear();
-                candidate.push(directory);
-                candidate.push(dependency_name);
-                File::open(&*candidate).ok()
+            search_directories.find_map(|(directory, origin)| {
+                hwcaps_subdirectories()
+                    .iter()
+                    .chain(std::iter::once(&""))
+                    .find_map(|subdirectory| {
+                        candidate.clear();
+                        candidate.push(directory);
+                        candidate.push(subdirectory);
+                        candidate.push(dependency_name);
+                        if !filter(candidate, origin) {
+                            return None;
+                        }
+                        match File::open(&*candidate) {
+                            Ok(file) => Some((candidate.clone(), file)),
+                            Err(error) => {
+                                *last_errno = Errno::from(&error);
+                                None
+                            }
+                        }
+                    })
             })
         })
     }
 
-    /// Resolves a dependency name to an open file handle by probing search directories. Names containing a slash are treated as literal paths.
-    pub fn resolve(&self, dependency_name: &str) -> Result<File, MirosError> {
+    /// Every directory a bare name is looked for in, `glibc-hwcaps` subdirectories included, in probe order.
+    ///
+    /// A library added to any of them can change what `resolve` finds, so their modification times stand in for a new search.
+    pub fn probed_directories(&self) -> Vec<PathBuf> {
+        let ld_library_path = env::var("LD_LIBRARY_PATH")
+            .ok()
+            .filter(|_| !is_secure_execution());
+        self.search_directories(ld_library_path.as_deref())
+            .flat_map(|(directory, _)| {
+                hwcaps_subdirectories()
+                    .iter()
+                    .chain(std::iter::once(&""))
+                    .map(move |subdirectory| Path::new(directory).join(subdirectory))
+            })
+            .collect()
+    }
+
+    /// Resolves a dependency name to its path and an open file handle by probing search directories. Names containing a slash are treated as literal paths.
+    pub fn resolve(&self, dependency_name: &str) -> Result<(PathBuf, File), MirosError> {
+        self.resolve_with(dependency_name, |_, _| true)
+    }
+
+    /// Like `resolve`, but shows every candidate path to `filter` first, which may rewrite it in place or return `false` to skip it.
+    pub fn resolve_with(
+        &self,
+        dependency_name: &str,
+        mut filter: impl FnMut(&mut PathBuf, SearchOrigin) -> bool,
+    ) -> Result<(PathBuf, File), MirosError> {
         if dependency_name.contains('/') {
             return File::open(dependency_name)
-                .map_err(|_| MirosError::DependencyNotFound(dependency_name.to_string()));
+                .map(|file| (PathBuf::from(dependency_name), file))
+                .map_err(|error| MirosError::DependencyNotFound {
+                    name: dependency_name.to_string(),
+                    requester: None,
+                    searched: Vec::new(),
+                    errno: Errno::from(&error),
+                });
         }
 
         // PERF: This allocates a string me thinks...
-        let ld_library_path = env::var("LD_LIBRARY_PATH").ok();
-        let ld_library_path_dirs = ld_library_path
-            .iter()
-            .flat_map(|paths| paths.split(':'))
-            .filter(|path| !path.is_empty());
-
-        // TODO: search /etc/ld.so.cache between LD_LIBRARY_PATH and defaults
-        let default_dirs = DEFAULT_SEARCH_PATHS.iter().copied();
-
-        match self {
-            Self::Rpath(_) => self.open_first_match(
-                self.elf_search_dirs()
-                    .chain(ld_library_path_dirs)
-                    .chain(default_dirs),
-                dependency_name,
-            ),
-            Self::None | Self::Runpath(_) => self.open_first_match(
-                ld_library_path_dirs
-                    .chain(self.elf_search_dirs())
-                    .chain(default_dirs),
-                dependency_name,
-            ),
-        }
-        .ok_or_else(|| MirosError::DependencyNotFound(dependency_name.to_string()))
+        let ld_library_path = env::var("LD_LIBRARY_PATH")
+            .ok()
+            .filter(|_| !is_secure_execution());
+        // Stays ENOENT if every candidate was filtered out before it could be opened.
+        let mut last_errno = Errno::NOENT;
+        self.open_first_match(
+            self.search_directories(ld_library_path.as_deref()),
+            dependency_name,
+            &mut filter,
+            &mut last_errno,
+        )
+        .ok_or_else(|| MirosError::DependencyNotFound {
+            name: dependency_name.to_string(),
+            requester: None,
+            // Rebuilt only on failure, so the successful path never allocates per probe.
+            searched: self
+                .search_directories(ld_library_path.as_deref())
+                .flat_map(|(directory, _)| {
+                    hwcaps_subdirectories()
+                        .iter()
+                        .chain(std::iter::once(&""))
+                        .map(move |subdirectory| {
+                            Path::new(directory)
+                                .join(subdirectory)
+                                .join(dependency_name)
+                        })
+                })
+                .collect(),
+            errno: last_errno,
+        })
     }
 }
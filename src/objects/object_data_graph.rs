@@ -3,15 +3,55 @@ use std::ffi::c_void;
 use indexmap::IndexMap;
 
 use crate::{
-    elf::symbol::{Symbol, SymbolVisibility},
+    elf::symbol::{Symbol, SymbolBinding, SymbolVisibility},
     error::MirosError,
-    objects::object_data::ObjectData,
+    libc::interposable::INTERPOSABLE_CELLS,
+    objects::{
+        audit::Auditors,
+        interception::InterceptionPolicy,
+        namespace::{Lmid, LM_ID_BASE},
+        object_data::ObjectData,
+        object_pipeline::Observers,
+        relocation_cache::RelocationCache,
+        symbol_lookup_cache::{CachedLookup, SymbolLookupCache, Winner},
+        unique_symbols::{self, UniqueDefinition},
+    },
 };
 
 pub struct ObjectDataGraph {
     pub(crate) program: ObjectData,
     pub(crate) miros: ObjectData,
     pub(crate) dependencies: IndexMap<String, ObjectData>,
+    /// System libraries miros normally stands in for, loaded only in fallback mode and searched after miros.
+    ///
+    /// They are relocated and given TLS, but their constructors never run: they expect glibc's own ld.so to have initialized them.
+    pub(crate) fallback: IndexMap<String, ObjectData>,
+    pub(crate) auditors: Auditors,
+    /// Present while this start may replay or record resolution, see [`RelocationCache`].
+    pub(crate) relocation_cache: Option<RelocationCache>,
+    /// The rules dependencies were loaded by, kept so later lookups by name agree with them; set by
+    /// [`LoadDependencies`](crate::objects::strategies::load_dependencies::LoadDependencies).
+    pub(crate) interception: InterceptionPolicy,
+    pub(crate) symbol_lookups: SymbolLookupCache,
+    /// Watch the pipeline run over this graph, see [`PipelineObserver`](crate::objects::object_pipeline::PipelineObserver).
+    pub(crate) observers: Observers,
+    /// The link-map namespace this graph is the scope of, see [`namespace`](crate::objects::namespace).
+    pub(crate) namespace: Lmid,
+}
+
+/// A `DT_NEEDED` edge inside a cycle that initialization order could not honour: `dependent` runs its constructors
+/// before `dependency`'s.
+pub struct BrokenDependency<'a> {
+    pub dependent: &'a ObjectData,
+    pub dependency: &'a ObjectData,
+}
+
+/// A resolved symbol reference: the object defining it, its dynsym index there, and its run-time address.
+pub struct Definition<'a> {
+    pub object: &'a ObjectData,
+    pub symbol_index: usize,
+    pub symbol: Symbol,
+    pub address: *const c_void,
 }
 
 impl ObjectDataGraph {
@@ -20,6 +60,13 @@ impl ObjectDataGraph {
             program,
             miros,
             dependencies: IndexMap::new(),
+            fallback: IndexMap::new(),
+            auditors: Auditors::new(),
+            relocation_cache: None,
+            interception: InterceptionPolicy::default(),
+            symbol_lookups: SymbolLookupCache::default(),
+            observers: Observers::default(),
+            namespace: LM_ID_BASE,
         }
     }
 
@@ -27,66 +74,112 @@ impl ObjectDataGraph {
         std::iter::once(&self.program).chain(self.dependencies.values())
     }
 
-    pub fn iter_objects_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut ObjectData> {
-        std::iter::once(&mut self.program).chain(self.dependencies.values_mut())
+    pub fn iter_fallback(&self) -> impl DoubleEndedIterator<Item = &ObjectData> {
+        self.fallback.values()
     }
 
-    // DFS post-order topological sort — dependencies before dependents, cycles skipped
-    pub fn iter_objects_topological(&self) -> impl DoubleEndedIterator<Item = &ObjectData> {
-        enum Event<'a> {
-            Discover(usize),
-            Emit(&'a ObjectData),
-        }
-
-        let objects: Vec<&ObjectData> = self.dependencies.values().collect();
-        let mut visited = vec![false; objects.len()];
-        let mut order: Vec<&ObjectData> = Vec::with_capacity(objects.len() + 1);
+    pub fn is_fallback(&self, object: &ObjectData) -> bool {
+        self.fallback
+            .values()
+            .any(|fallback| std::ptr::eq(fallback, object))
+    }
 
-        let mut stack: Vec<Event> = (0..objects.len()).rev().map(Event::Discover).collect();
+    /// Every object in a stable order, the one the relocation cache numbers them by: the program, its dependencies, miros,
+    /// then the fallback scope.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = &ObjectData> {
+        self.iter_objects()
+            .chain(std::iter::once(&self.miros))
+            .chain(self.fallback.values())
+    }
 
-        while let Some(event) = stack.pop() {
-            match event {
-                Event::Discover(index) => {
-                    if visited[index] {
-                        continue;
-                    }
-                    visited[index] = true;
+    pub fn object_index(&self, object: &ObjectData) -> Option<usize> {
+        self.iter_indexed()
+            .position(|candidate| std::ptr::eq(candidate, object))
+    }
 
-                    let object = objects[index];
-                    stack.push(Event::Emit(object));
+    pub fn object_at(&self, index: usize) -> Option<&ObjectData> {
+        self.iter_indexed().nth(index)
+    }
 
-                    for needed in object.dynamic_fields.dependencies() {
-                        if let Some(needed_index) = self.dependencies.get_index_of(*needed) {
-                            if !visited[needed_index] {
-                                stack.push(Event::Discover(needed_index));
-                            }
-                        }
-                    }
-                }
-                Event::Emit(object) => order.push(object),
-            }
+    /// The object loaded as `name`, be it a `DT_NEEDED` name, a file name or a path; an intercepted soname is miros.
+    pub fn find_loaded(&self, name: &str) -> Option<&ObjectData> {
+        if let Some(object) = self.dependencies.get(name) {
+            return Some(object);
         }
+        let by_path = self.iter_indexed().find(|object| {
+            object.path.as_deref().is_some_and(|path| {
+                path.as_os_str() == name || path.file_name().is_some_and(|file| file == name)
+            })
+        });
+        by_path.or_else(|| {
+            self.interception
+                .is_intercepted(name)
+                .then_some(&self.miros)
+        })
+    }
+
+    pub fn iter_objects_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut ObjectData> {
+        std::iter::once(&mut self.program).chain(self.dependencies.values_mut())
+    }
 
+    /// Constructor order: every dependency in the order glibc sorts them (see [`dependency_order`]), then the program.
+    /// Also returns the `DT_NEEDED` edges that had to be ignored to break cycles.
+    pub fn initialization_order(&self) -> (Vec<&ObjectData>, Vec<BrokenDependency<'_>>) {
+        let objects: Vec<&ObjectData> = self.dependencies.values().collect();
+        let needed: Vec<Vec<usize>> = objects
+            .iter()
+            .map(|object| {
+                object
+                    .dynamic_fields
+                    .dependencies()
+                    .iter()
+                    .filter_map(|name| self.dependencies.get_index_of(*name))
+                    .collect()
+            })
+            .collect();
+
+        let (order, broken) = dependency_order(&needed);
+        let mut order: Vec<&ObjectData> = order.into_iter().map(|index| objects[index]).collect();
         order.push(&self.program);
-        order.into_iter()
+        let broken = broken
+            .into_iter()
+            .map(|(dependent, dependency)| BrokenDependency {
+                dependent: objects[dependent],
+                dependency: objects[dependency],
+            })
+            .collect();
+        (order, broken)
+    }
+
+    /// Dependencies before dependents, the program last; see [`Self::initialization_order`].
+    pub fn iter_objects_topological(&self) -> impl DoubleEndedIterator<Item = &ObjectData> {
+        self.initialization_order().0.into_iter()
     }
 
     // COPY lookup rule: the program's own definition is the copy destination, so the search skips it.
-    pub fn resolve_symbol_outside_program(
-        &self,
-        symbol_name: &str,
-    ) -> Option<(Symbol, *const c_void)> {
-        self.dependencies
-            .values()
-            .chain(std::iter::once(&self.miros))
-            .find_definition(symbol_name)
+    pub fn resolve_symbol_outside_program(&self, symbol_name: &str) -> Option<Definition<'_>> {
+        let lookup = self.lookup(symbol_name);
+        match lookup.winner {
+            // Same order as the global scope minus the program, so only a program win needs a fresh search.
+            Some(Winner { object: 0, .. }) => self
+                .dependencies
+                .values()
+                .chain(std::iter::once(&self.miros))
+                .chain(self.fallback.values())
+                .find_hashed_definition(symbol_name, lookup.hash),
+            _ => self.definition_at(lookup),
+        }
     }
 
     // Interposable-cell lookup: asks whether anything but miros owns the name (a program's COPY relocation); miros's own weak export would mask that, so the search skips it.
     pub fn resolve_symbol_outside_miros(&self, symbol_name: &str) -> Option<*const c_void> {
-        self.iter_objects()
-            .find_definition(symbol_name)
-            .map(|(_, address)| address)
+        // The program and its dependencies come first in the global scope, so its winner is theirs if anyone's.
+        let lookup = self.lookup(symbol_name);
+        lookup
+            .winner
+            .filter(|winner| winner.object <= self.dependencies.len())
+            .and_then(|_| self.definition_at(lookup))
+            .map(|definition| definition.address)
     }
 
     pub fn resolve_symbol_address(
@@ -94,6 +187,15 @@ impl ObjectDataGraph {
         symbol: Symbol,
         requesting_object: &ObjectData,
     ) -> Result<*const c_void, MirosError> {
+        self.resolve_symbol_definition(symbol, requesting_object)
+            .map(|definition| definition.address)
+    }
+
+    pub fn resolve_symbol_definition<'a>(
+        &'a self,
+        symbol: Symbol,
+        requesting_object: &'a ObjectData,
+    ) -> Result<Definition<'a>, MirosError> {
         let symbol_name = unsafe {
             requesting_object
                 .dynamic_fields
@@ -101,38 +203,218 @@ impl ObjectDataGraph {
                 .get(symbol.st_name as usize)
         };
 
+        let lookup = self.lookup(symbol_name);
+
         // NOTE: Protected symbols cannot be interposed - bind to the requesting object's own definition.
-        let protected_symbol = requesting_object
-            .resolve_symbol_and_address(symbol_name)
-            .filter(|(symbol, _)| {
-                symbol.st_other.symbol_visibility() == SymbolVisibility::Protected
-            })
-            .map(|(_, address)| address);
+        let protected_symbol = std::iter::once(requesting_object)
+            .find_hashed_definition(symbol_name, lookup.hash)
+            .filter(|definition| {
+                definition.symbol.st_other.symbol_visibility() == SymbolVisibility::Protected
+            });
 
-        if let Some(address) = protected_symbol {
-            return Ok(address);
+        if let Some(definition) = protected_symbol {
+            return Ok(definition);
         }
 
-        self.resolve_symbol_by_name(symbol_name)
+        self.global_definition(symbol_name, lookup)
+            .ok_or_else(|| MirosError::undefined_symbol(symbol_name))
     }
 
     pub fn resolve_symbol_by_name(&self, symbol_name: &str) -> Result<*const c_void, MirosError> {
-        self.iter_objects()
-            .chain(std::iter::once(&self.miros))
-            .find_definition(symbol_name)
-            .map(|(_, address)| address)
-            .ok_or_else(|| MirosError::UndefinedSymbol(symbol_name.to_string()))
+        self.resolve_definition_by_name(symbol_name)
+            .map(|definition| definition.address)
+    }
+
+    pub fn resolve_definition_by_name(
+        &self,
+        symbol_name: &str,
+    ) -> Result<Definition<'_>, MirosError> {
+        self.global_definition(symbol_name, self.lookup(symbol_name))
+            .ok_or_else(|| MirosError::undefined_symbol(symbol_name))
+    }
+
+    /// The global scope's definition, or for an `STB_GNU_UNIQUE` one, whichever definition of the name the namespace
+    /// settled on first (see [`unique_symbols`]).
+    fn global_definition(&self, symbol_name: &str, lookup: CachedLookup) -> Option<Definition<'_>> {
+        let mut definition = self.definition_at(lookup)?;
+        if self.namespace != LM_ID_BASE && std::ptr::eq(definition.object, &self.miros) {
+            // The base namespace bound miros's interposable cells, possibly to its program's COPY; every namespace
+            // shares that one storage rather than miros's own, now stale, cell.
+            if let Some(address) = INTERPOSABLE_CELLS
+                .iter()
+                .find_map(|cell| cell.bound_address(symbol_name))
+            {
+                definition.address = address;
+            }
+        }
+        if definition.symbol.binding() != Ok(SymbolBinding::GnuUnique) {
+            return Some(definition);
+        }
+
+        let found = UniqueDefinition {
+            base: definition.object.base.addr(),
+            symbol_index: definition.symbol_index,
+        };
+        let canonical = unique_symbols::canonical(self.namespace, symbol_name, found);
+        if canonical == found {
+            return Some(definition);
+        }
+        // NOTE: The table is per namespace and a namespace is one graph, so the canonical object is here; only an
+        // auditor's graph, which shares the base namespace's id, can miss it, and then the local one stands.
+        self.iter_indexed()
+            .enumerate()
+            .find(|(_, object)| object.base.addr() == canonical.base)
+            .and_then(|(object, _)| {
+                self.definition_at(CachedLookup {
+                    hash: lookup.hash,
+                    winner: Some(Winner {
+                        object,
+                        symbol_index: canonical.symbol_index,
+                    }),
+                })
+            })
+            .or(Some(definition))
+    }
+
+    /// The global-scope answer for `symbol_name`, memoized in [`SymbolLookupCache`].
+    fn lookup(&self, symbol_name: &str) -> CachedLookup {
+        let object_count = 2 + self.dependencies.len() + self.fallback.len();
+        self.symbol_lookups
+            .get_or_search(symbol_name, object_count, |hash| {
+                // miros before the fallback scope (`iter_indexed` order), so its implementations win and only what it
+                // lacks falls through.
+                self.iter_indexed()
+                    .enumerate()
+                    .find_map(|(object, candidate)| {
+                        candidate.resolve_hashed_symbol(symbol_name, hash).map(
+                            |(symbol_index, _, _)| Winner {
+                                object,
+                                symbol_index,
+                            },
+                        )
+                    })
+            })
+    }
+
+    fn definition_at(&self, lookup: CachedLookup) -> Option<Definition<'_>> {
+        let winner = lookup.winner?;
+        let object = self.object_at(winner.object)?;
+        let symbol = object
+            .dynamic_fields
+            .checked_symbol(winner.symbol_index)
+            .ok()?;
+        Some(Definition {
+            object,
+            symbol_index: winner.symbol_index,
+            symbol,
+            address: object.symbol_address(&symbol),
+        })
     }
 }
 
+/// glibc's constructor order (`_dl_sort_maps_dfs`): a depth-first post-order over `needed` (each object's `DT_NEEDED`
+/// indices, in order), started from every object in reverse load order.
+///
+/// A dependency still on the current path closes a cycle; that edge is dropped and returned as `(dependent, dependency)`.
+fn dependency_order(needed: &[Vec<usize>]) -> (Vec<usize>, Vec<(usize, usize)>) {
+    enum Event {
+        Discover {
+            index: usize,
+            dependent: Option<usize>,
+        },
+        Emit(usize),
+    }
+
+    let mut visited = vec![false; needed.len()];
+    let mut emitted = vec![false; needed.len()];
+    let mut order = Vec::with_capacity(needed.len());
+    let mut broken = Vec::new();
+
+    // Popped from the back, so the last-loaded object is the first root.
+    let mut stack: Vec<Event> = (0..needed.len())
+        .map(|index| Event::Discover {
+            index,
+            dependent: None,
+        })
+        .collect();
+
+    while let Some(event) = stack.pop() {
+        match event {
+            Event::Discover { index, dependent } => {
+                if visited[index] {
+                    if let Some(dependent) = dependent.filter(|_| !emitted[index]) {
+                        broken.push((dependent, index));
+                    }
+                    continue;
+                }
+                visited[index] = true;
+                stack.push(Event::Emit(index));
+                // Reversed, so the first DT_NEEDED is explored first.
+                stack.extend(needed[index].iter().rev().map(|&needed| Event::Discover {
+                    index: needed,
+                    dependent: Some(index),
+                }));
+            }
+            Event::Emit(index) => {
+                emitted[index] = true;
+                order.push(index);
+            }
+        }
+    }
+
+    (order, broken)
+}
+
 // ELF search order: the first object with an exported definition wins, weak or global alike
 // (`resolve_symbol_and_address` already filters out undefined/local/hidden symbols).
-trait FindDefinition: Sized {
-    fn find_definition(self, symbol_name: &str) -> Option<(Symbol, *const c_void)>;
+trait FindDefinition<'a>: Sized {
+    fn find_hashed_definition(self, symbol_name: &str, hash: u32) -> Option<Definition<'a>>;
+}
+
+impl<'a, I: Iterator<Item = &'a ObjectData>> FindDefinition<'a> for I {
+    fn find_hashed_definition(mut self, symbol_name: &str, hash: u32) -> Option<Definition<'a>> {
+        self.find_map(|object| {
+            object.resolve_hashed_symbol(symbol_name, hash).map(
+                |(symbol_index, symbol, address)| Definition {
+                    object,
+                    symbol_index,
+                    symbol,
+                    address,
+                },
+            )
+        })
+    }
 }
 
-impl<'a, I: Iterator<Item = &'a ObjectData>> FindDefinition for I {
-    fn find_definition(mut self, symbol_name: &str) -> Option<(Symbol, *const c_void)> {
-        self.find_map(|object| object.resolve_symbol_and_address(symbol_name))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dependency_order_matches_glibc() {
+        // 0 needs 2; 1 and 2 need nothing. Roots go last-loaded first, so 1 precedes 0 even though 0 loaded first.
+        assert_eq!(
+            dependency_order(&[vec![2], vec![], vec![]]),
+            (vec![2, 1, 0], vec![])
+        );
+        // 0 needs 1 and 3, 1 needs 2.
+        assert_eq!(
+            dependency_order(&[vec![1, 3], vec![2], vec![], vec![]]),
+            (vec![3, 2, 1, 0], vec![])
+        );
+    }
+
+    #[test]
+    fn cycles_break_at_the_edge_that_closes_them() {
+        // 0 and 1 need each other; 1 is the first root, so 0 (reached from it) goes first.
+        assert_eq!(
+            dependency_order(&[vec![1], vec![0]]),
+            (vec![0, 1], vec![(0, 1)])
+        );
+        // 0 -> 1 -> 2 -> 0, with 2 also needing 3.
+        assert_eq!(
+            dependency_order(&[vec![1], vec![2], vec![0, 3], vec![]]),
+            (vec![3, 1, 0, 2], vec![(1, 2)])
+        );
     }
 }
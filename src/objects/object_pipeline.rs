@@ -1,6 +1,10 @@
 use crate::{
     error::MirosError,
-    objects::{object_data_graph::ObjectDataGraph, strategies::Stratagem},
+    objects::{
+        object_data::ObjectData,
+        object_data_graph::{Definition, ObjectDataGraph},
+        strategies::Stratagem,
+    },
 };
 
 pub struct ObjectPipeline<'a> {
@@ -14,9 +18,156 @@ impl<'a> ObjectPipeline<'a> {
         }
     }
 
+    /// Runs each stage in order, stopping at the first error; the graph's [`Observers`] see every stage start and finish.
     pub fn run_pipeline(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
-        self.pipeline
-            .into_iter()
-            .try_for_each(|stratagem| stratagem.run(object_data))
+        let result = self.pipeline.iter().try_for_each(|stratagem| {
+            let stage = stratagem.name();
+            object_data.observers.before_stage(stage);
+            let result = stratagem.run(object_data);
+            object_data.observers.after_stage(stage, &result);
+            result
+        });
+        object_data.observers.after_pipeline(&result);
+        result
+    }
+}
+
+/// What a stage reports about individual objects as it works on them.
+pub enum StageEvent<'a> {
+    /// A dependency (or fallback library) was mapped from its file.
+    ObjectMapped(&'a ObjectData),
+    /// `count` relocations of type `r_type` were applied in `object`; `DT_RELR` words count as `R_RELATIVE`.
+    Relocated {
+        object: &'a ObjectData,
+        r_type: u32,
+        count: usize,
+    },
+    /// A symbolic reference from `requester` was bound to `definition`.
+    SymbolResolved {
+        requester: &'a ObjectData,
+        definition: &'a Definition<'a>,
+    },
+    /// `object`'s static TLS block was placed, taking `bytes` per thread.
+    TlsReserved {
+        object: &'a ObjectData,
+        bytes: usize,
+    },
+}
+
+/// Hooks around an [`ObjectPipeline`] run. Every method defaults to doing nothing.
+///
+/// Observers only watch: they get shared references and cannot fail a stage. Any bookkeeping needs interior mutability.
+pub trait PipelineObserver {
+    fn before_stage(&self, _stage: &'static str) {}
+
+    fn after_stage(&self, _stage: &'static str, _result: &Result<(), MirosError>) {}
+
+    /// Called while the current stage runs, between its `before_stage` and `after_stage`.
+    fn event(&self, _event: &StageEvent) {}
+
+    fn after_pipeline(&self, _result: &Result<(), MirosError>) {}
+}
+
+/// The observers attached to a graph, called in the order they were added.
+#[derive(Default)]
+pub struct Observers(Vec<Box<dyn PipelineObserver>>);
+
+impl Observers {
+    pub fn push(&mut self, observer: Box<dyn PipelineObserver>) {
+        self.0.push(observer);
+    }
+
+    pub fn before_stage(&self, stage: &'static str) {
+        self.0
+            .iter()
+            .for_each(|observer| observer.before_stage(stage));
+    }
+
+    pub fn after_stage(&self, stage: &'static str, result: &Result<(), MirosError>) {
+        self.0
+            .iter()
+            .for_each(|observer| observer.after_stage(stage, result));
+    }
+
+    pub fn event(&self, event: &StageEvent) {
+        self.0.iter().for_each(|observer| observer.event(event));
+    }
+
+    pub fn after_pipeline(&self, result: &Result<(), MirosError>) {
+        self.0
+            .iter()
+            .for_each(|observer| observer.after_pipeline(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fs::File, path::PathBuf, rc::Rc};
+
+    use super::*;
+    use crate::page_size;
+
+    fn graph() -> ObjectDataGraph {
+        unsafe { page_size::set_page_size(libc::sysconf(libc::_SC_PAGESIZE) as usize) };
+        let map = || unsafe {
+            ObjectData::from_file(
+                File::open("/proc/self/exe").unwrap(),
+                PathBuf::from("/proc/self/exe"),
+            )
+            .unwrap()
+        };
+        ObjectDataGraph::new(map(), map())
+    }
+
+    struct Fails;
+
+    impl Stratagem for Fails {
+        fn run(&self, _: &mut ObjectDataGraph) -> Result<(), MirosError> {
+            Err(MirosError::TlsAllocationFailed)
+        }
+    }
+
+    struct Succeeds;
+
+    impl Stratagem for Succeeds {
+        fn run(&self, _: &mut ObjectDataGraph) -> Result<(), MirosError> {
+            Ok(())
+        }
+    }
+
+    struct Log(Rc<RefCell<Vec<String>>>);
+
+    impl PipelineObserver for Log {
+        fn before_stage(&self, stage: &'static str) {
+            self.0.borrow_mut().push(format!("before {stage}"));
+        }
+
+        fn after_stage(&self, stage: &'static str, result: &Result<(), MirosError>) {
+            self.0
+                .borrow_mut()
+                .push(format!("after {stage} {}", result.is_ok()));
+        }
+
+        fn after_pipeline(&self, result: &Result<(), MirosError>) {
+            self.0.borrow_mut().push(format!("done {}", result.is_ok()));
+        }
+    }
+
+    #[test]
+    fn observers_see_each_stage_up_to_the_failing_one() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut graph = graph();
+        graph.observers.push(Box::new(Log(log.clone())));
+
+        let result = ObjectPipeline::new(&[&Succeeds, &Fails, &Succeeds]).run_pipeline(&mut graph);
+
+        assert!(result.is_err());
+        assert_eq!(*log.borrow(), [
+            "before Succeeds",
+            "after Succeeds true",
+            "before Fails",
+            "after Fails false",
+            "done false",
+        ]);
     }
 }
@@ -0,0 +1,566 @@
+use std::{
+    env,
+    ffi::OsStr,
+    fs::{self, File, Metadata},
+    io::Write,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    objects::{
+        interception::{CONFIG_PATH_VARIABLE, DEFAULT_CONFIG_PATH},
+        object_data::{ObjectData, PathResolver},
+        object_data_graph::ObjectDataGraph,
+    },
+    start::secure_execution::is_secure_execution,
+};
+
+/// The directory cache files are kept in; caching is off unless it is set.
+const CACHE_DIRECTORY_VARIABLE: &str = "MIROS_RELOCATION_CACHE";
+/// Everything besides the files themselves that can change what gets loaded or bound.
+const FINGERPRINT_VARIABLES: &[&str] = &[
+    "LD_LIBRARY_PATH",
+    "MIROS_INTERCEPT",
+    "MIROS_INTERCEPT_CONFIG",
+    "MIROS_SYSTEM_FALLBACK",
+];
+
+const MAGIC: &[u8; 8] = b"MIROSRC\0";
+const FORMAT_VERSION: u32 = 1;
+
+/// How an object was loaded, and so where replay puts it back.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scope {
+    Program,
+    Dependency,
+    Miros,
+    Fallback,
+}
+
+/// What a file looked like when it was cached: any difference means it was replaced or rebuilt.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ObjectIdentity {
+    pub path: PathBuf,
+    pub device: u64,
+    pub inode: u64,
+    pub size: u64,
+    pub modified: (i64, i64),
+    /// Empty for objects linked without `--build-id`.
+    pub build_id: Vec<u8>,
+}
+
+impl ObjectIdentity {
+    pub fn new(path: PathBuf, metadata: &Metadata, build_id: Option<&[u8]>) -> Self {
+        Self {
+            path,
+            device: metadata.dev(),
+            inode: metadata.ino(),
+            size: metadata.size(),
+            modified: (metadata.mtime(), metadata.mtime_nsec()),
+            build_id: build_id.unwrap_or_default().to_vec(),
+        }
+    }
+
+    /// Stats `path` now and takes the build ID from the mapped `object`.
+    fn of(object: &ObjectData, path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self::new(path.to_path_buf(), &metadata, object.build_id()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedObject {
+    pub scope: Scope,
+    /// The name it is keyed under in its scope: the `DT_NEEDED` string, empty for the program and miros.
+    pub key: String,
+    pub identity: ObjectIdentity,
+}
+
+/// A directory or file whose modification time (or absence) must not have changed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct WatchedPath {
+    path: PathBuf,
+    modified: Option<(i64, i64)>,
+}
+
+impl WatchedPath {
+    fn now(path: PathBuf) -> Self {
+        let modified = fs::metadata(&path)
+            .ok()
+            .map(|metadata| (metadata.mtime(), metadata.mtime_nsec()));
+        Self { path, modified }
+    }
+
+    fn is_unchanged(&self) -> bool {
+        *self == Self::now(self.path.clone())
+    }
+}
+
+/// Where one symbolic relocation resolved to: an object by cache index and a symbol by its index in that object's `.dynsym`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    /// An undefined weak reference, left null.
+    Undefined,
+    Defined {
+        object: u32,
+        symbol: u32,
+    },
+}
+
+/// The bindings of every symbolic relocation in one object, in `DT_RELA` then `DT_JMPREL` order.
+pub type ObjectBindings = (u32, Vec<Binding>);
+
+/// One start's worth of resolution, as written to disk.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    fingerprint: u64,
+    watched: Vec<WatchedPath>,
+    /// In cache index order (see [`ObjectDataGraph::iter_indexed`]).
+    pub objects: Vec<CachedObject>,
+    pub bindings: Vec<ObjectBindings>,
+}
+
+impl Snapshot {
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.bytes(MAGIC);
+        writer.u32(FORMAT_VERSION);
+        writer.u64(self.fingerprint);
+
+        writer.u32(self.watched.len() as u32);
+        for watched in &self.watched {
+            writer.path(&watched.path);
+            match watched.modified {
+                Some((seconds, nanoseconds)) => {
+                    writer.u32(1);
+                    writer.u64(seconds as u64);
+                    writer.u64(nanoseconds as u64);
+                }
+                None => writer.u32(0),
+            }
+        }
+
+        writer.u32(self.objects.len() as u32);
+        for object in &self.objects {
+            writer.u32(object.scope as u32);
+            writer.sized(object.key.as_bytes());
+            let identity = &object.identity;
+            writer.path(&identity.path);
+            writer.u64(identity.device);
+            writer.u64(identity.inode);
+            writer.u64(identity.size);
+            writer.u64(identity.modified.0 as u64);
+            writer.u64(identity.modified.1 as u64);
+            writer.sized(&identity.build_id);
+        }
+
+        writer.u32(self.bindings.len() as u32);
+        for (object, bindings) in &self.bindings {
+            writer.u32(*object);
+            writer.u32(bindings.len() as u32);
+            for binding in bindings {
+                let (object, symbol) = match binding {
+                    Binding::Undefined => (u32::MAX, 0),
+                    Binding::Defined { object, symbol } => (*object, *symbol),
+                };
+                writer.u32(object);
+                writer.u32(symbol);
+            }
+        }
+        writer.0
+    }
+
+    /// `None` for anything but a well-formed file of this format version.
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != FORMAT_VERSION {
+            return None;
+        }
+        let fingerprint = reader.u64()?;
+
+        let watched = (0..reader.u32()?)
+            .map(|_| {
+                let path = reader.path()?;
+                let modified = match reader.u32()? {
+                    0 => None,
+                    _ => Some((reader.u64()? as i64, reader.u64()? as i64)),
+                };
+                Some(WatchedPath { path, modified })
+            })
+            .collect::<Option<_>>()?;
+
+        let objects = (0..reader.u32()?)
+            .map(|_| {
+                let scope = match reader.u32()? {
+                    0 => Scope::Program,
+                    1 => Scope::Dependency,
+                    2 => Scope::Miros,
+                    3 => Scope::Fallback,
+                    _ => return None,
+                };
+                let key = String::from_utf8(reader.sized()?.to_vec()).ok()?;
+                let identity = ObjectIdentity {
+                    path: reader.path()?,
+                    device: reader.u64()?,
+                    inode: reader.u64()?,
+                    size: reader.u64()?,
+                    modified: (reader.u64()? as i64, reader.u64()? as i64),
+                    build_id: reader.sized()?.to_vec(),
+                };
+                Some(CachedObject {
+                    scope,
+                    key,
+                    identity,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        let bindings = (0..reader.u32()?)
+            .map(|_| {
+                let object = reader.u32()?;
+                let bindings = (0..reader.u32()?)
+                    .map(|_| match (reader.u32()?, reader.u32()?) {
+                        (u32::MAX, _) => Some(Binding::Undefined),
+                        (object, symbol) => Some(Binding::Defined { object, symbol }),
+                    })
+                    .collect::<Option<_>>()?;
+                Some((object, bindings))
+            })
+            .collect::<Option<_>>()?;
+
+        reader.0.is_empty().then_some(Self {
+            fingerprint,
+            watched,
+            objects,
+            bindings,
+        })
+    }
+
+    /// The bindings recorded for the object at `index`, if any were.
+    pub fn bindings(&self, index: usize) -> Option<&[Binding]> {
+        self.bindings
+            .iter()
+            .find(|(object, _)| *object as usize == index)
+            .map(|(_, bindings)| bindings.as_slice())
+    }
+}
+
+/// An opt-in, prelink-style record of how the last start resolved: which files were loaded and what every symbolic
+/// relocation bound to, replayed on the next start instead of searching.
+///
+/// Enabled by pointing `MIROS_RELOCATION_CACHE` at a directory. Every input is revalidated before replay: the program, miros
+/// and each dependency (device, inode, size, mtime, build ID), every search directory's mtime (so a newly shadowing library
+/// is noticed), and the variables that steer loading. Any difference, a missing or corrupt file included, silently falls back
+/// to normal resolution, which then rewrites the cache.
+pub struct RelocationCache {
+    file: PathBuf,
+    /// What to replay, once validated; taken away again if replay finds a mismatch part way.
+    snapshot: Option<Snapshot>,
+    /// Bindings resolved normally this start, to be written out.
+    recorded: Vec<ObjectBindings>,
+}
+
+impl RelocationCache {
+    /// `None` unless caching is enabled; in secure mode or under an auditor (whose hooks can rewrite any lookup) it never is.
+    pub fn from_environment(graph: &ObjectDataGraph) -> Option<Self> {
+        let directory = env::var_os(CACHE_DIRECTORY_VARIABLE)
+            .filter(|directory| !directory.is_empty())
+            .filter(|_| !is_secure_execution() && graph.auditors.is_empty())?;
+
+        let program = fs::read_link("/proc/self/exe").ok()?;
+        let file = Path::new(&directory).join(format!(
+            "{:016x}.relocs",
+            fnv1a(program.as_os_str().as_bytes())
+        ));
+
+        let snapshot = fs::read(&file)
+            .ok()
+            .and_then(|bytes| Snapshot::decode(&bytes))
+            .filter(|snapshot| Self::is_current(snapshot, graph));
+
+        Some(Self {
+            file,
+            snapshot,
+            recorded: Vec::new(),
+        })
+    }
+
+    /// Whether everything but the dependencies themselves (which replay checks as it opens them) is as it was.
+    fn is_current(snapshot: &Snapshot, graph: &ObjectDataGraph) -> bool {
+        let already_loaded = |scope: Scope, object: &ObjectData| {
+            snapshot
+                .objects
+                .iter()
+                .find(|cached| cached.scope == scope)
+                .is_some_and(|cached| {
+                    ObjectIdentity::of(object, &cached.identity.path).as_ref()
+                        == Some(&cached.identity)
+                })
+        };
+
+        snapshot.fingerprint == fingerprint()
+            && snapshot.watched.iter().all(WatchedPath::is_unchanged)
+            && already_loaded(Scope::Program, &graph.program)
+            && already_loaded(Scope::Miros, &graph.miros)
+    }
+
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Gives up on replay for the rest of this start; the next store overwrites the stale file.
+    pub fn discard_snapshot(&mut self) {
+        self.snapshot = None;
+    }
+
+    pub fn record(&mut self, bindings: Vec<ObjectBindings>) {
+        self.recorded = bindings;
+    }
+
+    /// Writes what this start resolved, unless it was itself a replay. Failures only cost the next start its cache.
+    pub fn store(&self, graph: &ObjectDataGraph) {
+        if self.snapshot.is_some() {
+            return;
+        }
+        let Some(snapshot) = Self::capture(graph, &self.recorded) else {
+            return;
+        };
+
+        // Written aside and renamed over, so a concurrent start never reads half a file.
+        let temporary = self
+            .file
+            .with_extension(format!("{}.tmp", std::process::id()));
+        let written = self
+            .file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| File::create(&temporary))
+            .and_then(|mut file| file.write_all(&snapshot.encode()))
+            .and_then(|_| fs::rename(&temporary, &self.file));
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+
+    fn capture(graph: &ObjectDataGraph, bindings: &[ObjectBindings]) -> Option<Snapshot> {
+        let program = fs::read_link("/proc/self/exe").ok()?;
+        // NOTE: An object without a real path (e.g. one mapped from memory) can't be revalidated, so nothing is cached.
+        let objects = std::iter::once((Scope::Program, "", &graph.program))
+            .chain(
+                graph
+                    .dependencies
+                    .iter()
+                    .map(|(key, object)| (Scope::Dependency, key.as_str(), object)),
+            )
+            .chain(std::iter::once((Scope::Miros, "", &graph.miros)))
+            .chain(
+                graph
+                    .fallback
+                    .iter()
+                    .map(|(key, object)| (Scope::Fallback, key.as_str(), object)),
+            )
+            .map(|(scope, key, object)| {
+                let path = match scope {
+                    Scope::Program => &program,
+                    _ => object.path.as_ref()?,
+                };
+                Some(CachedObject {
+                    scope,
+                    key: key.to_string(),
+                    identity: ObjectIdentity::of(object, path)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let config_path = env::var_os(CONFIG_PATH_VARIABLE)
+            .unwrap_or_else(|| OsStr::new(DEFAULT_CONFIG_PATH).to_os_string());
+        let mut watched: Vec<PathBuf> = std::iter::once(&graph.program)
+            .chain(graph.dependencies.values())
+            .chain(graph.fallback.values())
+            .flat_map(|object| object.dynamic_fields.path_resolver.probed_directories())
+            .chain(std::iter::once(PathBuf::from(config_path)))
+            .collect();
+        watched.sort();
+        watched.dedup();
+
+        Some(Snapshot {
+            fingerprint: fingerprint(),
+            watched: watched.into_iter().map(WatchedPath::now).collect(),
+            objects,
+            bindings: bindings.to_vec(),
+        })
+    }
+}
+
+/// Hashes the loader-relevant variables and the default search order (which folds in the CPU's `glibc-hwcaps` level).
+fn fingerprint() -> u64 {
+    let mut bytes = Vec::new();
+    for variable in FINGERPRINT_VARIABLES {
+        bytes.extend_from_slice(variable.as_bytes());
+        match env::var_os(variable) {
+            Some(value) => {
+                bytes.push(b'=');
+                bytes.extend_from_slice(value.as_bytes());
+            }
+            None => bytes.push(b'!'),
+        }
+        bytes.push(0);
+    }
+    for directory in PathResolver::None.probed_directories() {
+        bytes.extend_from_slice(directory.as_os_str().as_bytes());
+        bytes.push(0);
+    }
+    fnv1a(&bytes)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn sized(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.bytes(bytes);
+    }
+
+    fn path(&mut self, path: &Path) {
+        self.sized(path.as_os_str().as_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        if self.0.len() < length {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)?.try_into().ok().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+
+    fn sized(&mut self) -> Option<&'a [u8]> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+
+    fn path(&mut self) -> Option<PathBuf> {
+        self.sized()
+            .map(|bytes| PathBuf::from(OsStr::from_bytes(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        let identity = |path: &str, build_id: &[u8]| ObjectIdentity {
+            path: PathBuf::from(path),
+            device: 2049,
+            inode: 42,
+            size: 4096,
+            modified: (1_700_000_000, 123),
+            build_id: build_id.to_vec(),
+        };
+        Snapshot {
+            fingerprint: 0xfeed,
+            watched: vec![
+                WatchedPath {
+                    path: PathBuf::from("/usr/lib"),
+                    modified: Some((1, 2)),
+                },
+                WatchedPath {
+                    path: PathBuf::from("/etc/miros/intercept.conf"),
+                    modified: None,
+                },
+            ],
+            objects: vec![
+                CachedObject {
+                    scope: Scope::Program,
+                    key: String::new(),
+                    identity: identity("/bin/prog", &[1, 2, 3]),
+                },
+                CachedObject {
+                    scope: Scope::Dependency,
+                    key: "libfoo.so.1".to_string(),
+                    identity: identity("/usr/lib/libfoo.so.1", &[]),
+                },
+            ],
+            bindings: vec![(0, vec![
+                Binding::Defined {
+                    object: 1,
+                    symbol: 7,
+                },
+                Binding::Undefined,
+            ])],
+        }
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let original = snapshot();
+        let decoded = Snapshot::decode(&original.encode()).unwrap();
+        assert_eq!(decoded.fingerprint, original.fingerprint);
+        assert_eq!(decoded.watched, original.watched);
+        assert_eq!(decoded.objects.len(), 2);
+        assert_eq!(decoded.objects[1].key, "libfoo.so.1");
+        assert_eq!(decoded.objects[0].identity, original.objects[0].identity);
+        assert_eq!(decoded.bindings(0), original.bindings(0));
+        assert_eq!(decoded.bindings(1), None);
+    }
+
+    #[test]
+    fn damaged_files_are_rejected() {
+        let encoded = snapshot().encode();
+        assert!(Snapshot::decode(&encoded[..encoded.len() - 1]).is_none());
+
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(Snapshot::decode(&trailing).is_none());
+
+        let mut other_version = encoded;
+        other_version[MAGIC.len()] ^= 0xff;
+        assert!(Snapshot::decode(&other_version).is_none());
+    }
+
+    #[test]
+    fn watched_paths_notice_changes() {
+        let directory = std::env::temp_dir().join(format!("miros-watch-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let watched = WatchedPath::now(directory.clone());
+        assert!(watched.is_unchanged());
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        File::create(directory.join("libnew.so")).unwrap();
+        assert!(!watched.is_unchanged());
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(WatchedPath::now(directory).modified.is_none());
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    arch::asm,
+    ffi::{c_char, c_void},
+    mem::offset_of,
+    ptr::{self, null_mut},
+};
+
+use linkme::distributed_slice;
+
+use crate::{
+    elf::dynamic_array::{DynamicArrayItem, DynamicTag},
+    libc::interposable::{Bindable, InterposableCell, INTERPOSABLE_CELLS},
+    objects::object_data::ObjectData,
+};
+
+// NOTE: gdb and lldb read these through `DT_DEBUG` (or the `_r_debug` symbol) with glibc's layout baked in, so the offsets are ABI.
+const _: () = {
+    assert!(offset_of!(RDebug, r_map) == 8);
+    assert!(offset_of!(RDebug, r_brk) == 16);
+    assert!(offset_of!(RDebug, r_state) == 24);
+    assert!(offset_of!(RDebug, r_ldbase) == 32);
+    assert!(offset_of!(RDebug, r_next) == 40);
+    assert!(offset_of!(LinkMap, l_prev) == 32);
+};
+
+/// glibc's `r_debug.r_state`: whether the link-map chain is safe for a debugger to walk.
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RendezvousState {
+    Consistent = 0,
+    Add = 1,
+    Delete = 2,
+}
+
+/// The public prefix of glibc's `struct link_map`; debuggers never look past `l_prev`.
+#[repr(C)]
+pub struct LinkMap {
+    /// The load bias: the difference between the object's link-time and run-time addresses.
+    pub l_addr: usize,
+    /// The path the object was loaded from; the main program is `""`.
+    pub l_name: *const c_char,
+    pub l_ld: *const DynamicArrayItem,
+    pub l_next: *mut LinkMap,
+    pub l_prev: *mut LinkMap,
+}
+
+impl LinkMap {
+    pub fn new(base: *const c_void, dynamic_array: *const DynamicArrayItem) -> Self {
+        Self {
+            l_addr: base.addr(),
+            l_name: c"".as_ptr(),
+            l_ld: dynamic_array,
+            l_next: null_mut(),
+            l_prev: null_mut(),
+        }
+    }
+}
+
+/// glibc's `struct r_debug_extended`; `r_next` chains per-namespace rendezvous once `r_version` is 2.
+#[repr(C)]
+pub struct RDebug {
+    pub r_version: i32,
+    pub r_map: *mut LinkMap,
+    /// The address a debugger breaks on to observe `r_state` transitions, always `_dl_debug_state`.
+    pub r_brk: usize,
+    pub r_state: RendezvousState,
+    pub r_ldbase: usize,
+    pub r_next: *mut RDebug,
+}
+
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+#[allow(non_upper_case_globals)]
+static mut _r_debug: RDebug = RDebug {
+    r_version: 0,
+    r_map: null_mut(),
+    r_brk: 0,
+    r_state: RendezvousState::Consistent,
+    r_ldbase: 0,
+    r_next: null_mut(),
+};
+
+// A program that names `_r_debug` (e.g. through <link.h>) COPY-relocates it, and the debugger then follows DT_DEBUG to that copy.
+static R_DEBUG: InterposableCell<RDebug> =
+    InterposableCell::new(&["_r_debug", "_r_debug_extended"], &raw mut _r_debug);
+
+#[distributed_slice(INTERPOSABLE_CELLS)]
+static R_DEBUG_CELL: &'static dyn Bindable = &R_DEBUG;
+
+/// The debugger's breakpoint hook; it must exist as a real, out-of-line function for `r_brk` to point at.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+#[inline(never)]
+pub extern "C" fn _dl_debug_state() {
+    // SAFETY: An empty asm block only keeps the body from being folded away.
+    unsafe { asm!("", options(nomem, nostack, preserves_flags)) };
+}
+
+pub fn r_debug() -> *mut RDebug {
+    R_DEBUG.as_ptr()
+}
+
+/// Publishes `state` and signals the debugger.
+pub unsafe fn transition(state: RendezvousState) {
+    (*r_debug()).r_state = state;
+    _dl_debug_state();
+}
+
+/// Links `object` onto the end of the chain; must be bracketed by `Add` → `Consistent` transitions.
+pub unsafe fn append(object: &ObjectData) {
+    let r_debug = r_debug();
+    let link_map = object.link_map;
+
+    let mut tail = (*r_debug).r_map;
+    while !tail.is_null() && !(*tail).l_next.is_null() {
+        tail = (*tail).l_next;
+    }
+
+    (*link_map).l_prev = tail;
+    if tail.is_null() {
+        (*r_debug).r_map = link_map;
+    } else {
+        (*tail).l_next = link_map;
+    }
+}
+
+/// Points the object's `DT_DEBUG` slot (if it reserved one) at the rendezvous structure.
+pub unsafe fn fill_dynamic_debug(object: &ObjectData) {
+    let mut item = object.dynamic_array.cast_mut();
+    loop {
+        match (*item).d_tag() {
+            Ok(DynamicTag::Null) => return,
+            Ok(DynamicTag::Debug) => {
+                ptr::write_volatile(&raw mut (*item).d_un.d_ptr, r_debug().cast());
+                return;
+            }
+            _ => item = item.add(1),
+        }
+    }
+}
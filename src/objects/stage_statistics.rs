@@ -0,0 +1,140 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    elf::relocate::relocation_name,
+    error::MirosError,
+    objects::object_pipeline::{PipelineObserver, StageEvent},
+};
+
+/// What one stage did and how long it took.
+struct StageRecord {
+    name: &'static str,
+    started: Instant,
+    elapsed: Duration,
+    objects_mapped: usize,
+    /// Applied relocations by `r_type`.
+    relocations: BTreeMap<u32, usize>,
+    symbols_resolved: usize,
+    tls_bytes: usize,
+}
+
+/// The built-in observer behind `LD_DEBUG=statistics`: wall time and counts per stage, printed once the pipeline ends.
+#[derive(Default)]
+pub struct StageStatistics {
+    stages: RefCell<Vec<StageRecord>>,
+}
+
+impl StageStatistics {
+    fn report(&self) -> String {
+        let stages = self.stages.borrow();
+        let mut report = String::from("\npipeline stages:\n");
+        for stage in stages.iter() {
+            let _ = write!(
+                report,
+                "  {:<24} {:>10.3} ms",
+                stage.name,
+                stage.elapsed.as_secs_f64() * 1000.0
+            );
+            let counts = [
+                (stage.objects_mapped, "objects mapped"),
+                (stage.relocations.values().sum(), "relocations"),
+                (stage.symbols_resolved, "symbols resolved"),
+                (stage.tls_bytes, "TLS bytes reserved"),
+            ];
+            for (count, label) in counts.into_iter().filter(|(count, _)| *count != 0) {
+                let _ = write!(report, ", {count} {label}");
+            }
+            report.push('\n');
+            for (&r_type, count) in &stage.relocations {
+                let _ = match relocation_name(r_type) {
+                    Some(name) => writeln!(report, "      {name:<24} {count:>8}"),
+                    None => writeln!(report, "      type {r_type:<19} {count:>8}"),
+                };
+            }
+        }
+        let total: Duration = stages.iter().map(|stage| stage.elapsed).sum();
+        let _ = writeln!(
+            report,
+            "  {:<24} {:>10.3} ms",
+            "total",
+            total.as_secs_f64() * 1000.0
+        );
+        report
+    }
+}
+
+impl PipelineObserver for StageStatistics {
+    fn before_stage(&self, stage: &'static str) {
+        self.stages.borrow_mut().push(StageRecord {
+            name: stage,
+            started: Instant::now(),
+            elapsed: Duration::ZERO,
+            objects_mapped: 0,
+            relocations: BTreeMap::new(),
+            symbols_resolved: 0,
+            tls_bytes: 0,
+        });
+    }
+
+    fn after_stage(&self, _stage: &'static str, _result: &Result<(), MirosError>) {
+        if let Some(stage) = self.stages.borrow_mut().last_mut() {
+            stage.elapsed = stage.started.elapsed();
+        }
+    }
+
+    fn event(&self, event: &StageEvent) {
+        let mut stages = self.stages.borrow_mut();
+        let Some(stage) = stages.last_mut() else {
+            return;
+        };
+        match *event {
+            StageEvent::ObjectMapped(_) => stage.objects_mapped += 1,
+            StageEvent::Relocated { r_type, count, .. } if count != 0 => {
+                *stage.relocations.entry(r_type).or_default() += count
+            }
+            StageEvent::Relocated { .. } => (),
+            StageEvent::SymbolResolved { .. } => stage.symbols_resolved += 1,
+            StageEvent::TlsReserved { bytes, .. } => stage.tls_bytes += bytes,
+        }
+    }
+
+    fn after_pipeline(&self, _result: &Result<(), MirosError>) {
+        // Same line prefix as glibc's `LD_DEBUG` output, so the two interleave readably.
+        let pid = std::process::id();
+        for line in self.report().lines() {
+            eprintln!("{pid:>5}:\t{line}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf::relocate::{R_GLOB_DAT, R_RELATIVE};
+
+    #[test]
+    fn report_lists_each_stage_with_its_counts() {
+        let statistics = StageStatistics::default();
+        statistics.before_stage("Load");
+        statistics.after_stage("Load", &Ok(()));
+        statistics.before_stage("Relocate");
+        statistics.stages.borrow_mut()[1].relocations =
+            BTreeMap::from([(R_RELATIVE, 1200), (R_GLOB_DAT, 34)]);
+        statistics.after_stage("Relocate", &Ok(()));
+
+        let report = statistics.report();
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[2].starts_with("  Load "));
+        assert!(!lines[2].contains(','));
+        assert!(lines[3].ends_with("ms, 1234 relocations"));
+        let name = |r_type| relocation_name(r_type).unwrap();
+        assert!(lines[4].contains(name(R_GLOB_DAT)) && lines[4].ends_with(" 34"));
+        assert!(lines[5].contains(name(R_RELATIVE)) && lines[5].ends_with(" 1200"));
+        assert!(lines[6].starts_with("  total "));
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{
+    error::MirosError,
+    objects::{object_data_graph::ObjectDataGraph, strategies::Stratagem},
+};
+
+/// Calls each auditor's `la_preinit` once everything is loaded and relocated, just before any initializer runs.
+pub struct AuditPreinit;
+
+impl Stratagem for AuditPreinit {
+    fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        object_data.auditors.preinit(&object_data.program);
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    error::MirosError,
+    objects::{
+        object_data_graph::ObjectDataGraph,
+        rendezvous::{self, _dl_debug_state, RendezvousState},
+        strategies::Stratagem,
+    },
+};
+
+// Runs after BindInterposableCells so `_r_debug` is written wherever a COPY relocation moved it, before InitArray so constructor breakpoints resolve.
+pub struct DebuggerRendezvous;
+
+impl Stratagem for DebuggerRendezvous {
+    fn run(&self, graph: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        unsafe {
+            let r_debug = rendezvous::r_debug();
+            (*r_debug).r_version = 1;
+            (*r_debug).r_brk = _dl_debug_state as *const () as usize;
+            (*r_debug).r_ldbase = graph.miros.base.addr();
+
+            rendezvous::transition(RendezvousState::Add);
+            // glibc's order: the program, its dependencies in load order, then the interpreter.
+            graph
+                .iter_objects()
+                .chain(graph.iter_fallback())
+                .chain(std::iter::once(&graph.miros))
+                .for_each(|object| rendezvous::append(object));
+            rendezvous::fill_dynamic_debug(&graph.program);
+            rendezvous::transition(RendezvousState::Consistent);
+        }
+
+        Ok(())
+    }
+}
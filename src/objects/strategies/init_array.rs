@@ -1,14 +1,54 @@
-use std::ffi::c_void;
+use std::{ffi::c_void, sync::Mutex};
 
 use crate::{
     error::MirosError,
-    objects::{object_data_graph::ObjectDataGraph, strategies::Stratagem},
-    start::auxiliary_vector::AuxiliaryVectorItem,
+    objects::{
+        object_data::ObjectData,
+        object_data_graph::ObjectDataGraph,
+        strategies::{report_statistics::is_debug_option_set, Stratagem},
+    },
+    start::{self, auxiliary_vector::AuxiliaryVectorItem},
 };
 
 pub type InitArrayFunction =
     extern "C" fn(usize, *const *const u8, *const *const u8, *const AuxiliaryVectorItem);
+pub type FiniFunction = extern "C" fn();
 
+/// One initialized object's destructors: its `DT_FINI_ARRAY`, then its `DT_FINI`.
+struct Finalizers {
+    fini_array: Option<*const [FiniFunction]>,
+    fini: Option<FiniFunction>,
+}
+
+// The array lives in the object's mapping, which is never unmapped.
+unsafe impl Send for Finalizers {}
+
+/// In initialization order, across every graph initialized so far (program, auditors, later namespaces).
+static FINALIZERS: Mutex<Vec<Finalizers>> = Mutex::new(Vec::new());
+
+/// Runs every initialized object's destructors, the last initialized first, as glibc's `_dl_fini` does.
+///
+/// Each object's entry is taken before its functions run, so a destructor that calls `exit` does not run it twice.
+pub unsafe fn run_finalizers() {
+    loop {
+        let Some(finalizers) = FINALIZERS.lock().ok().and_then(|mut list| list.pop()) else {
+            return;
+        };
+        if let Some(fini_array) = finalizers.fini_array {
+            #[allow(useless_ptr_null_checks)]
+            (*fini_array)
+                .iter()
+                .rev()
+                .filter(|fini_fn| !(**fini_fn as *const c_void).is_null())
+                .for_each(|fini_fn| fini_fn());
+        }
+        if let Some(fini) = finalizers.fini {
+            fini();
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct InitArray {
     arg_count: usize,
     arg_pointer: *const *const u8,
@@ -51,7 +91,27 @@ impl Stratagem for InitArray {
                 });
         }
 
-        object_data.iter_objects_topological().for_each(|object| {
+        let (order, broken) = object_data.initialization_order();
+        // A broken cycle is not an error, so it is only reported with the rest of the load order, under `LD_DEBUG=files`.
+        for cycle in broken.iter().filter(|_| is_debug_option_set("files")) {
+            eprintln!(
+                "miros: warning: dependency cycle: {} needs {}, but is initialized first",
+                object_name(cycle.dependent),
+                object_name(cycle.dependency),
+            );
+        }
+
+        order.into_iter().for_each(|object| {
+            // Legacy constructors (`_init`, `-Wl,-init=`) come first, as in glibc's `call_init`.
+            if let Some(init_fn) = object.dynamic_fields.init_function() {
+                init_fn(
+                    self.arg_count,
+                    self.arg_pointer,
+                    self.env_pointer,
+                    self.auxv_pointer,
+                );
+            }
+
             if let Some(init_functions) = object.dynamic_fields.init_functions() {
                 #[allow(useless_ptr_null_checks)]
                 init_functions
@@ -66,8 +126,27 @@ impl Stratagem for InitArray {
                         )
                     });
             }
+
+            let fini_array = object.dynamic_fields.fini_functions();
+            let fini = object.dynamic_fields.fini_function();
+            if fini_array.is_some() || fini.is_some() {
+                if let Ok(mut finalizers) = FINALIZERS.lock() {
+                    finalizers.push(Finalizers {
+                        fini_array: fini_array.map(|functions| functions as *const _),
+                        fini,
+                    });
+                }
+            }
         });
 
         Ok(())
     }
 }
+
+fn object_name(object: &ObjectData) -> std::path::Display<'_> {
+    object
+        .path
+        .as_deref()
+        .unwrap_or(start::program_name())
+        .display()
+}
@@ -0,0 +1,73 @@
+use std::env;
+
+use crate::{
+    error::MirosError,
+    objects::{
+        audit::Auditor,
+        object_data::{ObjectData, PathResolver},
+        object_data_graph::ObjectDataGraph,
+        object_pipeline::ObjectPipeline,
+        strategies::{
+            init_array::InitArray, load_dependencies::LoadDependencies, relocate::Relocate,
+            Stratagem,
+        },
+    },
+};
+
+/// Loads every `LD_AUDIT` module before anything else, so its hooks see the whole load.
+///
+/// A module that cannot be loaded is reported and skipped, as glibc does, rather than failing the program.
+pub struct LoadAuditors {
+    init_array: InitArray,
+}
+
+impl LoadAuditors {
+    pub fn new(init_array: InitArray) -> Self {
+        Self { init_array }
+    }
+
+    /// Loads, relocates and initializes one audit module against miros alone.
+    unsafe fn load(&self, name: &str, miros: &ObjectData) -> Result<Option<Auditor>, MirosError> {
+        let (path, file) = PathResolver::None.resolve(name)?;
+        let auditor = ObjectData::from_file(file, path)?;
+        let mut graph = ObjectDataGraph::new(auditor, ObjectData::from_base(miros.base)?);
+
+        ObjectPipeline::new(&[&LoadDependencies::new(), &Relocate]).run_pipeline(&mut graph)?;
+
+        // NOTE: The program's static TLS block must be allocated first, and auditors run before it is.
+        if graph.iter_objects().any(|object| object.tls_data.is_some()) {
+            eprintln!(
+                "miros: auditor {name} uses thread-local storage, which is unsupported; ignored"
+            );
+            return Ok(None);
+        }
+
+        self.init_array.run(&mut graph)?;
+
+        let auditor = Auditor::new(graph);
+        if auditor.is_none() {
+            eprintln!(
+                "miros: auditor {name} does not support this audit interface version; ignored"
+            );
+        }
+        Ok(auditor)
+    }
+}
+
+impl Stratagem for LoadAuditors {
+    fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        let Ok(audit_list) = env::var("LD_AUDIT") else {
+            return Ok(());
+        };
+
+        for name in audit_list.split(':').filter(|name| !name.is_empty()) {
+            match unsafe { self.load(name, &object_data.miros) } {
+                Ok(Some(auditor)) => object_data.auditors.push(auditor),
+                Ok(None) => (),
+                Err(error) => eprintln!("miros: cannot load auditor {name}: {error}; ignored"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,59 +1,248 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, env, ffi::OsStr, fs::File, path::PathBuf};
 
 use crate::{
     error::MirosError,
-    objects::{object_data::ObjectData, object_data_graph::ObjectDataGraph, strategies::Stratagem},
+    libc::errno::Errno,
+    objects::{
+        audit::{Auditors, LA_ACT_ADD, LA_ACT_CONSISTENT, LA_SER_ORIG},
+        interception::{InterceptionPolicy, Redirect},
+        object_data::ObjectData,
+        object_data_graph::ObjectDataGraph,
+        object_pipeline::StageEvent,
+        relocation_cache::{ObjectIdentity, Scope, Snapshot},
+        strategies::Stratagem,
+    },
 };
 
-const INTERCEPTED_LIBRARIES: &[&str] = &[
-    "libc.so.6",
-    "libm.so.6",
-    "libgcc_s.so.1",
-    "libpthread.so.0",
-    "ld-linux-x86-64.so.2",
-];
+/// Set to `1` to load the real system libraries behind miros, see [`LoadDependencies::from_environment`].
+const SYSTEM_FALLBACK_VARIABLE: &str = "MIROS_SYSTEM_FALLBACK";
 
-pub struct LoadDependencies;
+#[derive(Default)]
+pub struct LoadDependencies {
+    policy: InterceptionPolicy,
+    system_fallback: bool,
+}
+
+/// Whose `DT_NEEDED` entry a pending name came from.
+enum Requester {
+    Program,
+    Dependency(String),
+    Fallback(String),
+}
+
+impl LoadDependencies {
+    /// Uses the built-in interception list; every symbol an intercepted library would have provided must come from miros.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the [`InterceptionPolicy`] from its config file and variables. Unless `MIROS_SYSTEM_FALLBACK=1`, intercepted
+    /// libraries are dropped; with it, they (and whatever they need) are still loaded into the graph's fallback scope, searched
+    /// only after miros, so symbols miros lacks resolve to the system's.
+    pub fn from_environment() -> Result<Self, MirosError> {
+        Ok(Self::with_policy(InterceptionPolicy::from_environment()?))
+    }
+
+    /// As [`from_environment`](Self::from_environment), with rules already read, e.g. from another graph.
+    pub fn with_policy(policy: InterceptionPolicy) -> Self {
+        Self {
+            policy,
+            system_fallback: env::var(SYSTEM_FALLBACK_VARIABLE).is_ok_and(|value| value == "1"),
+        }
+    }
+
+    /// Opens the file a `Redirect::Path` rule substitutes for `dependency_name`.
+    fn open_redirect(
+        requester: &ObjectData,
+        dependency_name: &str,
+        path: PathBuf,
+    ) -> Result<(PathBuf, File), MirosError> {
+        match File::open(&path) {
+            Ok(file) => Ok((path, file)),
+            Err(error) => Err(MirosError::DependencyNotFound {
+                name: dependency_name.to_string(),
+                requester: requester.path.clone(),
+                searched: vec![path],
+                errno: Errno::from(&error),
+            }),
+        }
+    }
+
+    /// Reopens the dependencies a relocation cache recorded, in their recorded order, without searching.
+    ///
+    /// `None` as soon as one is not the file it was. The ones mapped by then are abandoned rather than unmapped.
+    unsafe fn replay(snapshot: &Snapshot) -> Option<Vec<(Scope, String, ObjectData)>> {
+        snapshot
+            .objects
+            .iter()
+            .filter(|cached| matches!(cached.scope, Scope::Dependency | Scope::Fallback))
+            .map(|cached| {
+                let expected = &cached.identity;
+                let file = File::open(&expected.path).ok()?;
+                let metadata = file.metadata().ok()?;
+                // The cheap checks first; the build ID needs the object mapped.
+                if ObjectIdentity::new(expected.path.clone(), &metadata, Some(&expected.build_id))
+                    != *expected
+                {
+                    return None;
+                }
+                let object = ObjectData::from_file(file, expected.path.clone()).ok()?;
+                (object.build_id().unwrap_or_default() == expected.build_id)
+                    .then(|| (cached.scope, cached.key.clone(), object))
+            })
+            .collect()
+    }
+
+    fn resolve(
+        auditors: &Auditors,
+        requester: &ObjectData,
+        dependency_name: &str,
+    ) -> Result<(PathBuf, File), MirosError> {
+        let path_resolver = &requester.dynamic_fields.path_resolver;
+        let resolved = if auditors.is_empty() {
+            path_resolver.resolve(dependency_name)
+        } else {
+            // Auditors see the bare DT_NEEDED name first, then every directory probe.
+            match auditors.object_search(requester, OsStr::new(dependency_name), LA_SER_ORIG) {
+                Some(name) => path_resolver
+                    .resolve_with(&name.to_string_lossy(), |candidate, origin| {
+                        auditors.search_candidate(requester, candidate, origin)
+                    }),
+                None => Err(MirosError::DependencyNotFound {
+                    name: dependency_name.to_string(),
+                    requester: None,
+                    searched: Vec::new(),
+                    errno: Errno::NOENT,
+                }),
+            }
+        };
+
+        resolved.map_err(|error| match error {
+            MirosError::DependencyNotFound {
+                name,
+                searched,
+                errno,
+                ..
+            } => MirosError::DependencyNotFound {
+                name,
+                requester: requester.path.clone(),
+                searched,
+                errno,
+            },
+            error => error,
+        })
+    }
+}
 
 impl Stratagem for LoadDependencies {
     fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
-        let mut pending: VecDeque<(String, Option<String>)> = object_data
+        object_data.interception = self.policy.clone();
+        object_data.auditors.object_open(&object_data.program);
+        object_data.auditors.object_open(&object_data.miros);
+        object_data
+            .auditors
+            .activity(&object_data.program, LA_ACT_ADD);
+
+        let replayed = object_data
+            .relocation_cache
+            .as_ref()
+            .and_then(|cache| cache.snapshot())
+            .map(|snapshot| unsafe { Self::replay(snapshot) });
+        match replayed {
+            Some(Some(objects)) => {
+                for (scope, key, object) in objects {
+                    object_data
+                        .observers
+                        .event(&StageEvent::ObjectMapped(&object));
+                    let scope = match scope {
+                        Scope::Fallback => &mut object_data.fallback,
+                        _ => &mut object_data.dependencies,
+                    };
+                    scope.insert(key, object);
+                }
+                // NOTE: No auditor events in between: the cache is never used with auditors loaded.
+                object_data
+                    .auditors
+                    .activity(&object_data.program, LA_ACT_CONSISTENT);
+                return Ok(());
+            }
+            Some(None) => {
+                if let Some(cache) = &mut object_data.relocation_cache {
+                    cache.discard_snapshot();
+                }
+            }
+            None => (),
+        }
+
+        let mut pending: VecDeque<(String, Requester)> = object_data
             .program
             .dynamic_fields
             .dependencies()
             .iter()
-            .map(|name| (name.to_string(), None))
+            .map(|name| (name.to_string(), Requester::Program))
             .collect();
 
-        while let Some((dependency_name, declarer_key)) = pending.pop_front() {
+        while let Some((dependency_name, requester)) = pending.pop_front() {
             if object_data.dependencies.contains_key(&dependency_name)
-                || INTERCEPTED_LIBRARIES.contains(&dependency_name.as_str())
+                || object_data.fallback.contains_key(&dependency_name)
             {
                 continue;
             }
+            // Everything a fallback library pulls in is part of the fallback scope too, whatever the policy says.
+            let redirect = match requester {
+                Requester::Fallback(_) => Redirect::System,
+                _ => self.policy.redirect(&dependency_name),
+            };
+            let into_fallback =
+                matches!(requester, Requester::Fallback(_)) || redirect == Redirect::Miros;
+            if into_fallback && !self.system_fallback {
+                continue;
+            }
 
-            let path_resolver = match &declarer_key {
-                None => &object_data.program.dynamic_fields.path_resolver,
-                Some(key) => &object_data.dependencies[key].dynamic_fields.path_resolver,
+            let requester = match &requester {
+                Requester::Program => &object_data.program,
+                Requester::Dependency(key) => &object_data.dependencies[key],
+                Requester::Fallback(key) => &object_data.fallback[key],
             };
 
-            let file = path_resolver.resolve(&dependency_name)?;
-            let loaded_object = unsafe { ObjectData::from_file(file)? };
+            let (path, file) = match redirect {
+                Redirect::Path(path) => Self::open_redirect(requester, &dependency_name, path)?,
+                Redirect::Miros | Redirect::System => {
+                    Self::resolve(&object_data.auditors, requester, &dependency_name)?
+                }
+            };
+            let loaded_object = unsafe { ObjectData::from_file(file, path)? };
+            object_data
+                .observers
+                .event(&StageEvent::ObjectMapped(&loaded_object));
+            object_data.auditors.object_open(&loaded_object);
 
-            let transitive_dependencies: Vec<(String, Option<String>)> = loaded_object
+            let transitive_dependencies: Vec<(String, Requester)> = loaded_object
                 .dynamic_fields
                 .dependencies()
                 .iter()
-                .map(|name| (name.to_string(), Some(dependency_name.clone())))
+                .map(|name| {
+                    let requester = match into_fallback {
+                        true => Requester::Fallback(dependency_name.clone()),
+                        false => Requester::Dependency(dependency_name.clone()),
+                    };
+                    (name.to_string(), requester)
+                })
                 .collect();
 
-            object_data
-                .dependencies
-                .insert(dependency_name, loaded_object);
+            let scope = match into_fallback {
+                true => &mut object_data.fallback,
+                false => &mut object_data.dependencies,
+            };
+            scope.insert(dependency_name, loaded_object);
 
             pending.extend(transitive_dependencies);
         }
 
+        object_data
+            .auditors
+            .activity(&object_data.program, LA_ACT_CONSISTENT);
+
         Ok(())
     }
 }
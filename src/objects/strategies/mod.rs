@@ -1,11 +1,23 @@
 use crate::{error::MirosError, objects::object_data_graph::ObjectDataGraph};
 
+pub mod audit_preinit;
 pub mod bind_interposable_cells;
+pub mod debugger_rendezvous;
 pub mod init_array;
+pub mod load_auditors;
 pub mod load_dependencies;
 pub mod relocate;
+pub mod relocate_thread_locals;
+pub mod relocation_cache;
+pub mod report_statistics;
 pub mod thread_local_storage;
 
 pub trait Stratagem {
     fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError>;
+
+    /// What pipeline observers call this stage: the type's name, without its module path.
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
 }
@@ -1,76 +1,322 @@
-use std::{arch::asm, ptr};
+use std::{
+    arch::asm,
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+    ptr, slice,
+};
 
 use crate::{
-    elf::{relocate::Rela, symbol::SymbolBinding},
+    elf::{
+        relocate::{
+            Rela, RelrIter, R_ABSOLUTE, R_COPY, R_GLOB_DAT, R_IRELATIVE, R_JUMP_SLOT, R_RELATIVE,
+        },
+        symbol::{Symbol, SymbolBinding},
+    },
     error::MirosError,
-    objects::{object_data::ObjectData, object_data_graph::ObjectDataGraph, strategies::Stratagem},
+    objects::{
+        object_data::ObjectData,
+        object_data_graph::{Definition, ObjectDataGraph},
+        object_pipeline::StageEvent,
+        relocation_cache::{Binding, ObjectBindings, Snapshot},
+        strategies::Stratagem,
+    },
 };
 
 pub struct Relocate;
 
-impl Relocate {
-    #[cfg(target_arch = "x86_64")]
-    unsafe fn rela(
-        &self,
-        rela: Rela,
+/// `(symbol, defining library)` for every reference from the program or its dependencies that only a fallback library could satisfy.
+type FallbackBindings = BTreeSet<(String, PathBuf)>;
+
+/// What one run learns about symbol bindings, and where it takes them from when a relocation cache is replaying.
+struct Bindings<'a> {
+    fallback: FallbackBindings,
+    /// Every object by cache index, see [`ObjectDataGraph::iter_indexed`].
+    indexed: Vec<&'a ObjectData>,
+    /// The inverse of `indexed`, keyed by address.
+    indices: HashMap<*const ObjectData, usize>,
+    /// The current object's cached bindings, consumed in relocation order; `None` when resolving normally.
+    replay: Option<slice::Iter<'a, Binding>>,
+    /// The current object's bindings as resolved, and every finished object's before it.
+    recording: Vec<Binding>,
+    /// What a rewritten cache will hold; `None` when no cache is being written, so nothing is recorded.
+    recorded: Option<Vec<ObjectBindings>>,
+}
+
+impl<'a> Bindings<'a> {
+    fn new(object_data_map: &'a ObjectDataGraph) -> Self {
+        let indexed: Vec<_> = object_data_map.iter_indexed().collect();
+        let indices = indexed
+            .iter()
+            .enumerate()
+            .map(|(index, &object)| (ptr::from_ref(object), index))
+            .collect();
+        Self {
+            fallback: FallbackBindings::new(),
+            indexed,
+            indices,
+            replay: None,
+            recording: Vec::new(),
+            recorded: None,
+        }
+    }
+
+    fn index_of(&self, object: &ObjectData) -> usize {
+        self.indices[&ptr::from_ref(object)]
+    }
+
+    /// Whether `snapshot` has a binding for exactly every symbolic relocation of every relocated object, each in range.
+    fn is_replayable(&self, snapshot: &Snapshot, object_data_map: &ObjectDataGraph) -> bool {
+        let in_range = |binding: &Binding| match *binding {
+            Binding::Undefined => true,
+            Binding::Defined { object, symbol } => {
+                self.indexed.get(object as usize).is_some_and(|object| {
+                    object
+                        .dynamic_fields
+                        .checked_symbol(symbol as usize)
+                        .is_ok()
+                })
+            }
+        };
+        object_data_map
+            .iter_fallback()
+            .chain(object_data_map.iter_objects())
+            .all(|object| {
+                let expected = Relocate::relocations(object)
+                    .filter(|rela| Relocate::is_symbolic(rela))
+                    .count();
+                match snapshot.bindings(self.index_of(object)) {
+                    Some(bindings) => bindings.len() == expected && bindings.iter().all(in_range),
+                    None => expected == 0,
+                }
+            })
+    }
+
+    /// Switches to `object`: the previous object's recording is filed, and replay (if any) moves to this one's bindings.
+    fn begin(&mut self, object: &ObjectData, snapshot: Option<&'a Snapshot>) {
+        self.finish();
+        let index = self.index_of(object);
+        self.replay = snapshot.map(|snapshot| snapshot.bindings(index).unwrap_or(&[]).iter());
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push((index as u32, Vec::new()));
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some((_, bindings)) = self
+            .recorded
+            .as_mut()
+            .and_then(|recorded| recorded.last_mut())
+        {
+            *bindings = std::mem::take(&mut self.recording);
+        }
+    }
+
+    /// Resolves one symbolic relocation: from the cache when replaying, otherwise with `resolve`, recording the outcome.
+    fn lookup(
+        &mut self,
         object_data: &ObjectData,
+        local_symbol: Symbol,
+        resolve: impl FnOnce() -> Result<Definition<'a>, MirosError>,
+    ) -> Result<Definition<'a>, MirosError> {
+        let undefined = || unsafe {
+            let name = object_data
+                .dynamic_fields
+                .string_table
+                .get(local_symbol.st_name as usize);
+            MirosError::undefined_symbol(name)
+        };
+
+        if let Some(replay) = &mut self.replay {
+            // `is_replayable` has checked there is one per relocation, each in range.
+            return match *replay.next().unwrap() {
+                Binding::Undefined => Err(undefined()),
+                Binding::Defined { object, symbol } => {
+                    let object = self.indexed[object as usize];
+                    let symbol_index = symbol as usize;
+                    let symbol = object.dynamic_fields.checked_symbol(symbol_index)?;
+                    Ok(Definition {
+                        object,
+                        symbol_index,
+                        symbol,
+                        address: object.symbol_address(&symbol),
+                    })
+                }
+            };
+        }
+
+        let definition = resolve();
+        if self.recorded.is_some() {
+            self.recording.push(match &definition {
+                Ok(definition) => Binding::Defined {
+                    object: self.index_of(definition.object) as u32,
+                    symbol: definition.symbol_index as u32,
+                },
+                Err(_) => Binding::Undefined,
+            });
+        }
+        definition
+    }
+
+    /// Notes `definition` if it crosses from the primary scope into the fallback one.
+    fn record_fallback(
+        &mut self,
+        object_data: &ObjectData,
+        definition: &Definition,
         object_data_map: &ObjectDataGraph,
-    ) -> Result<(), MirosError> {
-        let relocate_address = rela.r_offset.wrapping_add(object_data.base.addr());
+    ) {
+        if object_data_map.is_fallback(definition.object)
+            && !object_data_map.is_fallback(object_data)
+        {
+            let name = unsafe {
+                definition
+                    .object
+                    .dynamic_fields
+                    .string_table
+                    .get(definition.symbol.st_name as usize)
+            };
+            let path = definition.object.path.clone().unwrap_or_default();
+            self.fallback.insert((name.to_string(), path));
+        }
+    }
+}
 
+impl Relocate {
+    pub(crate) fn relocations(object_data: &ObjectData) -> impl Iterator<Item = &Rela> {
+        let rela_entries = object_data.dynamic_fields.rela_slice().unwrap_or(&[]);
+        let plt_rela_entries = object_data.dynamic_fields.plt_rela_slice().unwrap_or(&[]);
+        rela_entries.iter().chain(plt_rela_entries.iter())
+    }
+
+    /// The relocations that search for a symbol by name, and so are what a relocation cache records.
+    pub(crate) fn is_symbolic(rela: &Rela) -> bool {
+        match rela.r_type() {
+            R_GLOB_DAT | R_JUMP_SLOT | R_COPY => true,
+            R_ABSOLUTE => rela.r_sym() != 0,
+            _ => false,
+        }
+    }
+
+    /// Adds the load base to every word a `DT_RELR` table marks; these are the packed equivalent of `R_RELATIVE`.
+    ///
+    /// Returns how many words it relocated.
+    unsafe fn relr(object_data: &ObjectData) -> usize {
+        let base = object_data.base.addr();
+        let mut count = 0;
+        for offset in RelrIter::new(object_data.dynamic_fields.relr_slice().unwrap_or(&[])) {
+            let word = object_data.base.byte_add(offset).cast_mut().cast::<usize>();
+            *word = (*word).wrapping_add(base);
+            count += 1;
+        }
+        count
+    }
+
+    /// Fills in which object, relocation and symbol version an unresolved reference came from.
+    fn attribute(error: MirosError, rela: Rela, object_data: &ObjectData) -> MirosError {
+        match error {
+            MirosError::UndefinedSymbol { name, .. } => MirosError::UndefinedSymbol {
+                name,
+                version: object_data
+                    .dynamic_fields
+                    .required_version(rela.r_sym() as usize)
+                    .map(str::to_string),
+                requester: object_data.path.clone(),
+                relocation: Some(rela.r_type()),
+            },
+            error => error,
+        }
+    }
+
+    /// Writes one relocated word; assembly so the store is never reordered against the code it relocates.
+    #[inline(always)]
+    unsafe fn store(relocate_address: usize, relocate_value: usize) {
         // x86_64 assembly pointer widths:
         // byte  | 8 bits  (1 byte)
         // word  | 16 bits (2 bytes)
         // dword | 32 bits (4 bytes) | "double word"
         // qword | 64 bits (8 bytes) | "quad word"
-        use crate::elf::relocate::{
-            R_X86_64_COPY, R_X86_64_GLOB_DAT, R_X86_64_IRELATIVE, R_X86_64_JUMP_SLOT,
-            R_X86_64_RELATIVE,
-        };
+        #[cfg(target_arch = "x86_64")]
+        asm!(
+            "mov qword ptr [{}], {}",
+            in(reg) relocate_address,
+            in(reg) relocate_value,
+            options(nostack, preserves_flags),
+        );
+        #[cfg(target_arch = "aarch64")]
+        asm!(
+            "str {}, [{}]",
+            in(reg) relocate_value,
+            in(reg) relocate_address,
+            options(nostack, preserves_flags),
+        );
+    }
+
+    unsafe fn rela<'a>(
+        &self,
+        rela: Rela,
+        object_data: &'a ObjectData,
+        object_data_map: &'a ObjectDataGraph,
+        bindings: &mut Bindings<'a>,
+    ) -> Result<(), MirosError> {
+        let relocate_address = rela.r_offset.wrapping_add(object_data.base.addr());
+
         match rela.r_type() {
-            R_X86_64_RELATIVE => {
+            R_RELATIVE => {
                 let relocate_value = object_data.base.addr().wrapping_add_signed(rela.r_addend);
-                asm!(
-                    "mov qword ptr [{}], {}",
-                    in(reg) relocate_address,
-                    in(reg) relocate_value,
-                    options(nostack, preserves_flags),
-                );
+                Self::store(relocate_address, relocate_value);
             }
-            R_X86_64_IRELATIVE => {
+            R_IRELATIVE => {
                 let function_pointer = object_data.base.addr().wrapping_add_signed(rela.r_addend);
                 let function: extern "C" fn() -> usize = std::mem::transmute(function_pointer);
-                let relocate_value = function();
-                asm!(
-                    "mov qword ptr [{}], {}",
-                    in(reg) relocate_address,
-                    in(reg) relocate_value,
-                    options(nostack, preserves_flags),
-                );
+                Self::store(relocate_address, function());
+            }
+            // Symbol 0 leaves S + A as the bare addend: an absolute address.
+            R_ABSOLUTE if rela.r_sym() == 0 => {
+                Self::store(relocate_address, rela.r_addend as usize);
             }
-            R_X86_64_GLOB_DAT | R_X86_64_JUMP_SLOT => {
-                debug_assert_eq!(rela.r_addend, 0);
+            R_GLOB_DAT | R_JUMP_SLOT | R_ABSOLUTE => {
+                // On x86-64 GLOB_DAT and JUMP_SLOT are S, R_X86_64_64 is S + A; the former always carry a zero addend.
+                // On aarch64 all three are S + A.
+                debug_assert!(
+                    cfg!(not(target_arch = "x86_64"))
+                        || rela.r_type() == R_ABSOLUTE
+                        || rela.r_addend == 0
+                );
 
                 let local_symbol = object_data
                     .dynamic_fields
                     .checked_symbol(rela.r_sym() as usize)?;
 
-                let remote_address = object_data_map
-                    .resolve_symbol_address(local_symbol, object_data)
+                let remote_address = bindings
+                    .lookup(object_data, local_symbol, || {
+                        object_data_map.resolve_symbol_definition(local_symbol, object_data)
+                    })
+                    .map(|definition| {
+                        bindings.record_fallback(object_data, &definition, object_data_map);
+                        object_data_map
+                            .observers
+                            .event(&StageEvent::SymbolResolved {
+                                requester: object_data,
+                                definition: &definition,
+                            });
+                        // glibc audits PLT bindings only.
+                        if rela.r_type() == R_JUMP_SLOT {
+                            object_data_map
+                                .auditors
+                                .bind_symbol(object_data, &definition)
+                        } else {
+                            definition.address
+                        }
+                    })
                     .or_else(|err| match local_symbol.binding() {
                         Ok(SymbolBinding::Weak) => Ok(std::ptr::null()),
-                        _ => Err(err),
-                    })?;
-
-                asm!(
-                    "mov qword ptr [{}], {}",
-                    in(reg) relocate_address,
-                    in(reg) remote_address,
-                    options(nostack, preserves_flags),
-                );
+                        _ => Err(Self::attribute(err, rela, object_data)),
+                    })?
+                    .wrapping_byte_offset(rela.r_addend);
+
+                Self::store(relocate_address, remote_address as usize);
             }
 
-            R_X86_64_COPY => {
+            R_COPY => {
                 let local_symbol = object_data
                     .dynamic_fields
                     .checked_symbol(rela.r_sym() as usize)?;
@@ -79,44 +325,104 @@ impl Relocate {
                     .string_table
                     .get(local_symbol.st_name as usize);
 
-                let Some((source_symbol, source_address)) =
-                    object_data_map.resolve_symbol_outside_program(symbol_name)
-                else {
+                let Ok(source) = bindings.lookup(object_data, local_symbol, || {
+                    object_data_map
+                        .resolve_symbol_outside_program(symbol_name)
+                        .ok_or_else(|| MirosError::undefined_symbol(symbol_name))
+                }) else {
                     // Undefined weak leaves the destination zeroed, as glibc does; strong is fatal.
                     return match local_symbol.binding() {
                         Ok(SymbolBinding::Weak) => Ok(()),
-                        _ => Err(MirosError::UndefinedSymbol(symbol_name.to_string())),
+                        _ => Err(Self::attribute(
+                            MirosError::undefined_symbol(symbol_name),
+                            rela,
+                            object_data,
+                        )),
                     };
                 };
 
+                bindings.record_fallback(object_data, &source, object_data_map);
+                object_data_map
+                    .observers
+                    .event(&StageEvent::SymbolResolved {
+                        requester: object_data,
+                        definition: &source,
+                    });
+
                 // Sizes can disagree after a re-link; the destination's reservation caps the copy.
                 ptr::copy_nonoverlapping(
-                    source_address.cast::<u8>(),
+                    source.address.cast::<u8>(),
                     relocate_address as *mut u8,
-                    source_symbol.st_size.min(local_symbol.st_size),
+                    source.symbol.st_size.min(local_symbol.st_size),
                 );
             }
 
-            _ => (),
+            _ => return Ok(()),
         }
 
+        object_data_map.observers.event(&StageEvent::Relocated {
+            object: object_data,
+            r_type: rela.r_type(),
+            count: 1,
+        });
         Ok(())
     }
 }
 
 impl Stratagem for Relocate {
     fn run(&self, object_data_map: &mut ObjectDataGraph) -> Result<(), MirosError> {
-        // Dependencies before the program: a COPY reloc reads its source object's relocated bytes.
-        object_data_map
-            .iter_objects_topological()
+        let graph = &*object_data_map;
+        let mut bindings = Bindings::new(graph);
+        let snapshot = graph
+            .relocation_cache
+            .as_ref()
+            .and_then(|cache| cache.snapshot())
+            .filter(|snapshot| bindings.is_replayable(snapshot, graph));
+        // A cache that can't be replayed is rewritten from this run.
+        if graph.relocation_cache.is_some() && snapshot.is_none() {
+            bindings.recorded = Some(Vec::new());
+        }
+
+        // Fallback libraries first, last loaded first, as nothing in the primary scope copies from them before they're ready.
+        // Then dependencies before the program: a COPY reloc reads its source object's relocated bytes.
+        graph
+            .iter_fallback()
+            .rev()
+            .chain(graph.iter_objects_topological())
             .try_for_each(|object| {
-                let rela_entries = object.dynamic_fields.rela_slice().unwrap_or(&[]);
-                let plt_rela_entries = object.dynamic_fields.plt_rela_slice().unwrap_or(&[]);
+                bindings.begin(object, snapshot);
+                let relr_count = unsafe { Self::relr(object) };
+                graph.observers.event(&StageEvent::Relocated {
+                    object,
+                    r_type: R_RELATIVE,
+                    count: relr_count,
+                });
+                Self::relocations(object)
+                    .try_for_each(|rela| unsafe { self.rela(*rela, object, graph, &mut bindings) })
+                    .map_err(|error| match &object.path {
+                        Some(path) => error.in_object(path),
+                        None => error,
+                    })
+            })?;
+        bindings.finish();
 
-                rela_entries
-                    .iter()
-                    .chain(plt_rela_entries.iter())
-                    .try_for_each(|rela| unsafe { self.rela(*rela, object, object_data_map) })
-            })
+        if !bindings.fallback.is_empty() {
+            eprintln!(
+                "miros: warning: {} symbol(s) bound to system fallback libraries:",
+                bindings.fallback.len()
+            );
+            for (name, path) in &bindings.fallback {
+                eprintln!("miros: warning:   {name} from {}", path.display());
+            }
+        }
+
+        // Whatever was cached no longer fits; what was just resolved replaces it.
+        let recorded = bindings.recorded;
+        if let (Some(cache), Some(recorded)) = (&mut object_data_map.relocation_cache, recorded) {
+            cache.discard_snapshot();
+            cache.record(recorded);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,146 @@
+use crate::{
+    elf::{
+        relocate::{Rela, R_DTPMOD, R_DTPOFF, R_TPOFF},
+        symbol::SymbolBinding,
+    },
+    error::MirosError,
+    objects::{
+        object_data::ObjectData, object_data_graph::ObjectDataGraph, object_pipeline::StageEvent,
+        strategies::Stratagem,
+    },
+    tls::MIROS_BLOCK_OFFSET,
+};
+
+/// Applies the initial-exec `R_X86_64_TPOFF64` (`R_AARCH64_TLS_TPREL64`) relocations `Relocate` leaves alone, once
+/// `ThreadLocalStorage` has placed every module's block.
+///
+/// Programs use the local-exec model and never need these; they come from shared objects built with initial-exec TLS, such as
+/// the system libraries loaded in fallback mode. The general-dynamic `DTPMOD64`/`DTPOFF64` pairs shared objects use by
+/// default feed [`__tls_get_addr`](crate::tls::__tls_get_addr): every block is static, so the module "id" stored is the
+/// block's TP-relative offset, and the offset is the variable's within the block. On aarch64, where TLS descriptors are
+/// the default for shared objects, `R_AARCH64_TLSDESC` is applied here too: each descriptor gets
+/// [`static_tls_descriptor`](crate::tls::static_tls_descriptor) and the offset.
+pub struct RelocateThreadLocals;
+
+impl RelocateThreadLocals {
+    /// The TP-relative offset of the block defining `rela`'s symbol, and the symbol's offset within it, without the
+    /// addend; `None` for an undefined weak reference.
+    fn resolve(
+        rela: Rela,
+        object_data: &ObjectData,
+        object_data_map: &ObjectDataGraph,
+    ) -> Result<Option<(isize, usize)>, MirosError> {
+        // Symbol 0 refers to the object's own block.
+        let (defining_object, symbol_offset) = match rela.r_sym() {
+            0 => (object_data, 0),
+            index => {
+                let local_symbol = object_data.dynamic_fields.checked_symbol(index as usize)?;
+                match object_data_map.resolve_symbol_definition(local_symbol, object_data) {
+                    Ok(definition) => {
+                        object_data_map
+                            .observers
+                            .event(&StageEvent::SymbolResolved {
+                                requester: object_data,
+                                definition: &definition,
+                            });
+                        (definition.object, definition.symbol.st_value)
+                    }
+                    Err(_) if local_symbol.binding() == Ok(SymbolBinding::Weak) => return Ok(None),
+                    Err(error) => return Err(error),
+                }
+            }
+        };
+
+        // miros's own block is laid out by the bootstrap rather than the allocator.
+        let block_offset = if std::ptr::eq(defining_object, &object_data_map.miros) {
+            MIROS_BLOCK_OFFSET
+        } else {
+            defining_object
+                .tls_data
+                .as_ref()
+                .and_then(|tls_data| tls_data.thread_local_allocation.as_ref())
+                .ok_or(MirosError::TlsAllocationFailed)?
+                .block_offset
+        };
+        Ok(Some((block_offset, symbol_offset)))
+    }
+
+    /// The TP-relative offset of `rela`'s symbol plus its addend; 0 for an undefined weak reference, as glibc's is.
+    fn resolve_offset(
+        rela: Rela,
+        object_data: &ObjectData,
+        object_data_map: &ObjectDataGraph,
+    ) -> Result<isize, MirosError> {
+        Ok(
+            Self::resolve(rela, object_data, object_data_map)?.map_or(0, |(block, symbol)| {
+                block
+                    .wrapping_add_unsigned(symbol)
+                    .wrapping_add(rela.r_addend)
+            }),
+        )
+    }
+
+    unsafe fn rela(
+        rela: Rela,
+        object_data: &ObjectData,
+        object_data_map: &ObjectDataGraph,
+    ) -> Result<(), MirosError> {
+        let relocate_address = object_data
+            .base
+            .byte_add(rela.r_offset)
+            .cast_mut()
+            .cast::<usize>();
+
+        match rela.r_type() {
+            R_TPOFF => {
+                *relocate_address =
+                    Self::resolve_offset(rela, object_data, object_data_map)? as usize;
+            }
+            R_DTPMOD => {
+                *relocate_address = Self::resolve(rela, object_data, object_data_map)?
+                    .map_or(0, |(block, _)| block as usize);
+            }
+            R_DTPOFF => {
+                *relocate_address = Self::resolve(rela, object_data, object_data_map)?
+                    .map_or(0, |(_, symbol)| symbol)
+                    .wrapping_add_signed(rela.r_addend);
+            }
+            #[cfg(target_arch = "aarch64")]
+            crate::elf::relocate::R_AARCH64_TLSDESC => {
+                // An undefined weak one resolves to offset 0 too; taking its address is all a program may do.
+                let offset = Self::resolve_offset(rela, object_data, object_data_map)?;
+                *relocate_address = crate::tls::static_tls_descriptor as *const () as usize;
+                *relocate_address.add(1) = offset as usize;
+            }
+            _ => return Ok(()),
+        }
+
+        object_data_map.observers.event(&StageEvent::Relocated {
+            object: object_data,
+            r_type: rela.r_type(),
+            count: 1,
+        });
+        Ok(())
+    }
+}
+
+impl Stratagem for RelocateThreadLocals {
+    fn run(&self, object_data_map: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        object_data_map
+            .iter_objects()
+            .chain(object_data_map.iter_fallback())
+            .try_for_each(|object| {
+                let rela_entries = object.dynamic_fields.rela_slice().unwrap_or(&[]);
+                let plt_rela_entries = object.dynamic_fields.plt_rela_slice().unwrap_or(&[]);
+
+                rela_entries
+                    .iter()
+                    .chain(plt_rela_entries.iter())
+                    .try_for_each(|rela| unsafe { Self::rela(*rela, object, object_data_map) })
+                    .map_err(|error| match &object.path {
+                        Some(path) => error.in_object(path),
+                        None => error,
+                    })
+            })
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    error::MirosError,
+    objects::{
+        object_data_graph::ObjectDataGraph, relocation_cache::RelocationCache,
+        strategies::Stratagem,
+    },
+};
+
+/// Reads and validates the [`RelocationCache`] for this program, so `LoadDependencies` and `Relocate` can replay it.
+///
+/// Runs after `LoadAuditors`, since any auditor disables the cache.
+pub struct OpenRelocationCache;
+
+impl Stratagem for OpenRelocationCache {
+    fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        object_data.relocation_cache = RelocationCache::from_environment(object_data);
+        Ok(())
+    }
+}
+
+/// Writes out what `Relocate` resolved, when it didn't come from the cache in the first place.
+pub struct StoreRelocationCache;
+
+impl Stratagem for StoreRelocationCache {
+    fn run(&self, object_data: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        if let Some(cache) = &object_data.relocation_cache {
+            cache.store(object_data);
+        }
+        Ok(())
+    }
+}
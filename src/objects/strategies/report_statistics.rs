@@ -0,0 +1,65 @@
+use std::env;
+
+use crate::{
+    elf::relocate::{RelrIter, R_RELATIVE},
+    error::MirosError,
+    objects::{
+        object_data_graph::ObjectDataGraph,
+        strategies::{relocate::Relocate, Stratagem},
+    },
+};
+
+/// Prints glibc-style `runtime linker statistics` to stderr when `LD_DEBUG` names `statistics`, once relocation is done
+/// and before any initializer runs.
+pub struct ReportStatistics;
+
+/// Whether `LD_DEBUG` names `option` or `all`. It is a list of options separated by commas, colons or spaces, as in glibc.
+pub fn is_debug_option_set(option: &str) -> bool {
+    env::var("LD_DEBUG").is_ok_and(|options| {
+        options
+            .split([',', ':', ' '])
+            .any(|named| named == option || named == "all")
+    })
+}
+
+impl ReportStatistics {
+    pub fn is_enabled() -> bool {
+        is_debug_option_set("statistics")
+    }
+}
+
+impl Stratagem for ReportStatistics {
+    fn run(&self, graph: &mut ObjectDataGraph) -> Result<(), MirosError> {
+        if !Self::is_enabled() {
+            return Ok(());
+        }
+
+        let (mut symbolic, mut relative) = (0, 0);
+        for object in graph.iter_fallback().chain(graph.iter_objects()) {
+            for rela in Relocate::relocations(object) {
+                if Relocate::is_symbolic(rela) {
+                    symbolic += 1;
+                } else if rela.r_type() == R_RELATIVE {
+                    relative += 1;
+                }
+            }
+            relative += RelrIter::new(object.dynamic_fields.relr_slice().unwrap_or(&[])).count();
+        }
+
+        let lookups = &graph.symbol_lookups;
+        let pid = std::process::id();
+        eprintln!("{pid:>5}:\t");
+        eprintln!("{pid:>5}:\truntime linker statistics:");
+        eprintln!("{pid:>5}:\t                 number of relocations: {symbolic}");
+        eprintln!("{pid:>5}:\t        number of relative relocations: {relative}");
+        eprintln!(
+            "{pid:>5}:\t              symbol lookup cache hits: {}",
+            lookups.hits()
+        );
+        eprintln!(
+            "{pid:>5}:\t            symbol lookup cache misses: {}",
+            lookups.misses()
+        );
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     objects::{
         object_data::{ThreadLocalAllocation, ThreadLocalData},
         object_data_graph::ObjectDataGraph,
+        object_pipeline::StageEvent,
         strategies::Stratagem,
     },
     syscall::thread_pointer::get_thread_pointer,
@@ -14,13 +15,15 @@ use crate::{
 pub struct ThreadLocalStorage;
 
 impl ThreadLocalStorage {
+    /// Places one module's block, returning its size in bytes.
     unsafe fn allocate_tls_module(
         allocator: &mut TlsAllocator,
         tls_data: &mut ThreadLocalData,
         base: *const c_void,
         thread_pointer: *mut c_void,
-    ) -> Result<(), MirosError> {
+    ) -> Result<usize, MirosError> {
         let template = TlsTemplate::from_program_header(base, &tls_data.tls_program_header);
+        let block_size = template.block_size;
 
         let module_id = allocator
             .register_module(template, thread_pointer)
@@ -30,7 +33,7 @@ impl ThreadLocalStorage {
         tls_data.thread_local_allocation =
             Some(ThreadLocalAllocation::new(module_id, block_offset));
 
-        Ok(())
+        Ok(block_size)
     }
 }
 
@@ -40,14 +43,26 @@ impl Stratagem for ThreadLocalStorage {
         let mut allocator = mutex.lock().unwrap();
         let thread_pointer = unsafe { get_thread_pointer() };
 
-        object_data
-            .iter_objects_mut()
-            .filter_map(|object| {
+        let ObjectDataGraph {
+            program,
+            dependencies,
+            fallback,
+            observers,
+            ..
+        } = object_data;
+        std::iter::once(program)
+            .chain(dependencies.values_mut())
+            .chain(fallback.values_mut())
+            .try_for_each(|object| {
                 let base = object.base;
-                object.tls_data.as_mut().map(|tls_data| (base, tls_data))
-            })
-            .try_for_each(|(base, tls_data)| unsafe {
-                Self::allocate_tls_module(&mut allocator, tls_data, base, thread_pointer)
+                let Some(tls_data) = object.tls_data.as_mut() else {
+                    return Ok(());
+                };
+                let bytes = unsafe {
+                    Self::allocate_tls_module(&mut allocator, tls_data, base, thread_pointer)?
+                };
+                observers.event(&StageEvent::TlsReserved { object, bytes });
+                Ok(())
             })
     }
 }
@@ -0,0 +1,126 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use crate::objects::object_data::hash_tables::gnu_hash;
+
+/// Where a name resolved in the global scope: an object by cache index (see
+/// [`ObjectDataGraph::iter_indexed`](crate::objects::object_data_graph::ObjectDataGraph::iter_indexed)) and the symbol's
+/// dynsym index there.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Winner {
+    pub object: usize,
+    pub symbol_index: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CachedLookup {
+    /// The name's GNU hash, so lookups with other scopes (protected symbols, COPY sources) need not recompute it.
+    pub hash: u32,
+    /// `None` when nothing in the global scope defines the name.
+    pub winner: Option<Winner>,
+}
+
+/// Global-scope lookups by name, memoized for one run of the pipeline.
+///
+/// Every library asks for the same few hundred names (`malloc`, `free`, `memcpy`, ...) and each ask otherwise walks
+/// every object's hash table. Only the requester-independent answer is kept: protected symbols and COPY sources are
+/// exceptions the graph applies on top.
+#[derive(Default)]
+pub struct SymbolLookupCache {
+    entries: RefCell<HashMap<String, CachedLookup>>,
+    /// How many objects the graph held when `entries` were filled; loading another can change any answer.
+    object_count: Cell<usize>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+impl SymbolLookupCache {
+    /// The cached answer for `name`, or `search`'s (given the name's hash) if this is the first ask since the graph last
+    /// grew.
+    pub fn get_or_search(
+        &self,
+        name: &str,
+        object_count: usize,
+        search: impl FnOnce(u32) -> Option<Winner>,
+    ) -> CachedLookup {
+        if self.object_count.replace(object_count) != object_count {
+            self.entries.borrow_mut().clear();
+        }
+
+        if let Some(&lookup) = self.entries.borrow().get(name) {
+            self.hits.set(self.hits.get() + 1);
+            return lookup;
+        }
+
+        self.misses.set(self.misses.get() + 1);
+        let hash = gnu_hash(name);
+        let lookup = CachedLookup {
+            hash,
+            winner: search(hash),
+        };
+        self.entries.borrow_mut().insert(name.to_string(), lookup);
+        lookup
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_names_are_searched_once() {
+        let cache = SymbolLookupCache::default();
+        let winner = Winner {
+            object: 2,
+            symbol_index: 7,
+        };
+        let searches = Cell::new(0);
+        let search = |hash| {
+            assert_eq!(hash, gnu_hash("malloc"));
+            searches.set(searches.get() + 1);
+            Some(winner)
+        };
+
+        assert_eq!(
+            cache.get_or_search("malloc", 3, search).winner,
+            Some(winner)
+        );
+        assert_eq!(
+            cache.get_or_search("malloc", 3, search).winner,
+            Some(winner)
+        );
+        assert_eq!(cache.get_or_search("free", 3, |_| None).winner, None);
+        assert_eq!(
+            cache.get_or_search("free", 3, |_| unreachable!()).winner,
+            None
+        );
+
+        assert_eq!(searches.get(), 1);
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    }
+
+    #[test]
+    fn a_grown_graph_starts_over() {
+        let cache = SymbolLookupCache::default();
+        cache.get_or_search("memcpy", 3, |_| None);
+        let winner = Winner {
+            object: 3,
+            symbol_index: 1,
+        };
+        assert_eq!(
+            cache.get_or_search("memcpy", 4, |_| Some(winner)).winner,
+            Some(winner)
+        );
+        assert_eq!(cache.misses(), 2);
+    }
+}
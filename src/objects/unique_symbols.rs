@@ -0,0 +1,63 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::objects::namespace::Lmid;
+
+/// The definition every `STB_GNU_UNIQUE` lookup of one name binds to: the defining object (by load base) and the symbol's
+/// dynsym index there.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UniqueDefinition {
+    pub base: usize,
+    pub symbol_index: usize,
+}
+
+/// Outlives any one [`ObjectDataGraph`](crate::objects::object_data_graph::ObjectDataGraph): the first unique definition a
+/// lookup settles on stays canonical for every later lookup, whatever its scope. Keyed by namespace as glibc does, so two
+/// namespaces holding the same library keep separate copies of its unique objects.
+static UNIQUE_SYMBOLS: Mutex<Option<HashMap<(Lmid, String), UniqueDefinition>>> = Mutex::new(None);
+
+/// The canonical definition of `name` in `namespace`, registering `found` as it if `name` has none there yet.
+pub fn canonical(namespace: Lmid, name: &str, found: UniqueDefinition) -> UniqueDefinition {
+    // A poisoned table only means another thread panicked mid-insert; the map itself is still consistent.
+    let mut table = UNIQUE_SYMBOLS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *table
+        .get_or_insert_with(HashMap::new)
+        .entry((namespace, name.to_string()))
+        .or_insert(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::namespace::LM_ID_BASE;
+
+    #[test]
+    fn the_first_definition_stays_canonical() {
+        let first = UniqueDefinition {
+            base: 0x1000,
+            symbol_index: 3,
+        };
+        let second = UniqueDefinition {
+            base: 0x2000,
+            symbol_index: 5,
+        };
+        assert_eq!(canonical(LM_ID_BASE, "_ZN4test5firstE", first), first);
+        assert_eq!(canonical(LM_ID_BASE, "_ZN4test5firstE", second), first);
+        assert_eq!(canonical(LM_ID_BASE, "_ZN4test6secondE", second), second);
+    }
+
+    #[test]
+    fn each_namespace_has_its_own_canonical_definition() {
+        let base = UniqueDefinition {
+            base: 0x1000,
+            symbol_index: 3,
+        };
+        let other = UniqueDefinition {
+            base: 0x2000,
+            symbol_index: 3,
+        };
+        assert_eq!(canonical(LM_ID_BASE, "_ZN4test5thirdE", base), base);
+        assert_eq!(canonical(1, "_ZN4test5thirdE", other), other);
+    }
+}
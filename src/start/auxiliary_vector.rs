@@ -42,6 +42,8 @@ pub enum AuxiliaryVectorType {
     Entry = 9,
     Random = 25,
     ClkTck = 17,
+    Secure = 23,
+    SysinfoEhdr = 33,
 }
 
 /// A union resolved by the a_type field of the parent auxiliary vector item.
@@ -74,6 +76,8 @@ pub struct AuxiliaryVectorInfo {
     pub pseudorandom_bytes: *const [u8; 16],
     pub program_header_pointer: *const ProgramHeader,
     pub program_header_count: usize,
+    /// `AT_SECURE`; absent on ancient kernels, which means "not secure".
+    pub secure: bool,
 }
 
 impl AuxiliaryVectorInfo {
@@ -91,6 +95,7 @@ impl AuxiliaryVectorInfo {
             Err(MirosError::MissingAuxvEntry(AuxiliaryVectorType::Phdr));
         let mut program_header_count: Result<usize, MirosError> =
             Err(MirosError::MissingAuxvEntry(AuxiliaryVectorType::Phnum));
+        let mut secure = false;
 
         auxiliary_vector_items(auxv_pointer).for_each(|item| match item.a_type() {
             Ok(AuxiliaryVectorType::Base) => base = Ok(item.a_un.a_ptr.cast()),
@@ -99,6 +104,7 @@ impl AuxiliaryVectorInfo {
             Ok(AuxiliaryVectorType::Random) => pseudorandom_bytes = Ok(item.a_un.a_ptr.cast()),
             Ok(AuxiliaryVectorType::Phdr) => program_header_pointer = Ok(item.a_un.a_ptr.cast()),
             Ok(AuxiliaryVectorType::Phnum) => program_header_count = Ok(item.a_un.a_val),
+            Ok(AuxiliaryVectorType::Secure) => secure = item.a_un.a_val != 0,
             _ => (),
         });
 
@@ -109,6 +115,12 @@ impl AuxiliaryVectorInfo {
             pseudorandom_bytes: pseudorandom_bytes?,
             program_header_pointer: program_header_pointer?,
             program_header_count: program_header_count?,
+            secure,
         })
     }
+
+    /// The program's program header table, as `AT_PHDR` and `AT_PHNUM` describe it.
+    pub fn program_header_table(&self) -> *const [ProgramHeader] {
+        ptr::slice_from_raw_parts(self.program_header_pointer, self.program_header_count)
+    }
 }
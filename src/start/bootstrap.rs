@@ -27,10 +27,8 @@ use crate::{
     tls::{
         get_tls_allocator, set_tls_allocator,
         template::TlsTemplate,
-        thread_control_block::{
-            AtomicDetachState, DetachState, DynamicThreadVector, ThreadControlBlock,
-        },
-        TLS_RESERVE_SIZE,
+        thread_control_block::{DetachState, ThreadControlBlock},
+        thread_region_extent, MIROS_BLOCK_OFFSET,
     },
     utils::round_up_to_boundary,
 };
@@ -210,8 +208,7 @@ impl Bootstrap<Relocate> {
                     );
                 }
                 R_X86_64_TPOFF64 => {
-                    let tpoff_value =
-                        (size_of::<ThreadControlBlock>() as isize).wrapping_add(rela.r_addend);
+                    let tpoff_value = MIROS_BLOCK_OFFSET.wrapping_add(rela.r_addend);
                     asm!(
                         "mov qword ptr [{}], {}",
                         in(reg) relocate_address,
@@ -225,23 +222,91 @@ impl Bootstrap<Relocate> {
 
         self.transition()
     }
+
+    #[cfg(target_arch = "aarch64")]
+    pub unsafe fn relocate(self) -> Bootstrap<AllocateTls> {
+        use crate::{
+            elf::relocate::{
+                R_AARCH64_IRELATIVE, R_AARCH64_RELATIVE, R_AARCH64_TLSDESC, R_AARCH64_TLS_TPREL64,
+            },
+            tls::static_tls_descriptor,
+        };
+
+        let base_address = self.base.addr();
+        for rela in &*self.rela_slice {
+            let relocate_address = rela.r_offset.wrapping_add(base_address);
+
+            match rela.r_type() {
+                R_AARCH64_RELATIVE => {
+                    let relocate_value = base_address.wrapping_add_signed(rela.r_addend);
+                    asm!(
+                        "str {}, [{}]",
+                        in(reg) relocate_value,
+                        in(reg) relocate_address,
+                        options(nostack, preserves_flags),
+                    );
+                }
+                R_AARCH64_IRELATIVE => {
+                    let function_pointer = base_address.wrapping_add_signed(rela.r_addend);
+                    let function: extern "C" fn() -> usize = std::mem::transmute(function_pointer);
+                    let relocate_value = function();
+                    asm!(
+                        "str {}, [{}]",
+                        in(reg) relocate_value,
+                        in(reg) relocate_address,
+                        options(nostack, preserves_flags),
+                    );
+                }
+                R_AARCH64_TLS_TPREL64 => {
+                    let tpoff_value = MIROS_BLOCK_OFFSET.wrapping_add(rela.r_addend);
+                    asm!(
+                        "str {}, [{}]",
+                        in(reg) tpoff_value,
+                        in(reg) relocate_address,
+                        options(nostack, preserves_flags),
+                    );
+                }
+                // Our own block is static, so the descriptor resolves to its offset without a call into `__tls_get_addr`.
+                R_AARCH64_TLSDESC => {
+                    let tpoff_value = MIROS_BLOCK_OFFSET.wrapping_add(rela.r_addend);
+                    asm!(
+                        "stp {}, {}, [{}]",
+                        in(reg) static_tls_descriptor as *const () as usize,
+                        in(reg) tpoff_value,
+                        in(reg) relocate_address,
+                        options(nostack, preserves_flags),
+                    );
+                }
+                _ => (),
+            }
+        }
+
+        self.transition()
+    }
 }
 
 impl Bootstrap<AllocateTls> {
     pub unsafe fn allocate_tls(self, pseudorandom_bytes: *const [u8; 16]) -> Bootstrap<InitArray> {
-        let miros_tls_size = self
-            .tls_program_header
-            .map(|h| round_up_to_boundary(h.p_memsz, h.p_align))
-            .unwrap_or(0);
-
-        let miros_template = self
+        let template = self
             .tls_program_header
             .map(|tls_header| TlsTemplate::from_program_header(self.base, &tls_header));
+        // A static image's one TLS segment is the program's as far as the linker is concerned: its accesses were resolved
+        // to the first block the layout allocator hands out, not to a miros block past the TCB.
+        #[cfg(miros_static)]
+        let (miros_template, image_template) = (None::<TlsTemplate>, template);
+        #[cfg(not(miros_static))]
+        let (miros_template, image_template) = (template, None);
+
+        let miros_tls_size = miros_template.map_or(0, |template| {
+            round_up_to_boundary(template.block_size, template.alignment)
+        });
         set_tls_allocator(miros_template);
 
+        // See `thread_region_extent`; on x86-64:
         // [~8 MiB reserve (exe TLS allocated)][TCB][miros TLS]
         //                                     ^TP (fs:0)
-        let region_total_size = TLS_RESERVE_SIZE + size_of::<ThreadControlBlock>() + miros_tls_size;
+        let (below_thread_pointer, above_thread_pointer) = thread_region_extent(miros_tls_size);
+        let region_total_size = below_thread_pointer + above_thread_pointer;
 
         let protection_flags = ProtectionFlags::ZERO
             .with_readable(true)
@@ -257,29 +322,27 @@ impl Bootstrap<AllocateTls> {
             0,
         );
 
-        let thread_control_block =
-            region_pointer.byte_add(TLS_RESERVE_SIZE) as *mut ThreadControlBlock;
         let thread_pointer_register: *mut c_void =
-            (*thread_control_block).thread_pointee.as_mut_ptr().cast();
-
-        *thread_control_block = ThreadControlBlock {
-            thread_pointee: [],
-            thread_pointer_register,
-            tid: getpid(),
-            detach_state: AtomicDetachState::new(DetachState::Joinable),
-            return_value: null_mut(),
-            region: ptr::slice_from_raw_parts_mut(region_pointer, region_total_size),
-            canary: usize::from_ne_bytes(ptr::read(
-                pseudorandom_bytes.cast::<[u8; size_of::<usize>()]>(),
-            )),
-            dynamic_thread_vector: DynamicThreadVector::new(),
-        };
+            region_pointer.byte_add(below_thread_pointer).cast();
+        let canary = usize::from_ne_bytes(ptr::read(
+            pseudorandom_bytes.cast::<[u8; size_of::<usize>()]>(),
+        ));
+        ThreadControlBlock::initialize(
+            ThreadControlBlock::from_thread_pointer(thread_pointer_register),
+            getpid(),
+            DetachState::Joinable,
+            ptr::slice_from_raw_parts_mut(region_pointer, region_total_size),
+            canary,
+        );
+        #[cfg(target_arch = "aarch64")]
+        crate::libc::process::set_stack_guard(canary);
 
         set_thread_pointer(thread_pointer_register);
-        get_tls_allocator()
-            .lock()
-            .unwrap_unchecked()
-            .initialize_thread_tls(thread_pointer_register);
+        let mut tls_allocator = get_tls_allocator().lock().unwrap_unchecked();
+        tls_allocator.initialize_thread_tls(thread_pointer_register);
+        if let Some(image_template) = image_template {
+            tls_allocator.register_module(image_template, thread_pointer_register);
+        }
 
         self.transition()
     }
@@ -293,6 +356,13 @@ impl Bootstrap<InitArray> {
         env_pointer: *const *const u8,
         auxv_pointer: *const AuxiliaryVectorItem,
     ) {
+        // A static image's constructors are the program's too, so they wait for the heap and stdio; see `start_static`.
+        // Only the allocator's own initializer has to run now.
+        if cfg!(miros_static) {
+            (crate::allocator::INIT_ALLOCATOR)(arg_count, arg_pointer, env_pointer, auxv_pointer);
+            return;
+        }
+
         let call_array = |functions: *const [InitArrayFunction]| {
             // SAFETY: The compiler thinks function pointers can't be null in Rust's type system,
             // but these are unsafely read from raw ELF init_array data...
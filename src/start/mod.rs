@@ -1,33 +1,63 @@
 use std::{
     arch::naked_asm,
-    ptr::{self, null, null_mut},
+    ffi::{CStr, OsStr},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr::{null, null_mut},
+    sync::OnceLock,
 };
 
 use crate::{
+    elf::program_header::{ProgramHeader, PT_INTERP},
+    error::MirosError,
     io_macros::syscall_debug_assert,
     libc::environ::set_environ_pointer,
     objects::{
+        namespace,
         object_data::ObjectData,
         object_data_graph::ObjectDataGraph,
         object_pipeline::ObjectPipeline,
+        stage_statistics::StageStatistics,
         strategies::{
-            bind_interposable_cells::BindInterposableCells, init_array::InitArray,
-            load_dependencies::LoadDependencies, relocate::Relocate,
-            thread_local_storage::ThreadLocalStorage, Stratagem,
+            audit_preinit::AuditPreinit,
+            bind_interposable_cells::BindInterposableCells,
+            debugger_rendezvous::DebuggerRendezvous,
+            init_array::InitArray,
+            load_auditors::LoadAuditors,
+            load_dependencies::LoadDependencies,
+            relocate::Relocate,
+            relocate_thread_locals::RelocateThreadLocals,
+            relocation_cache::{OpenRelocationCache, StoreRelocationCache},
+            report_statistics::ReportStatistics,
+            thread_local_storage::ThreadLocalStorage,
+            Stratagem,
         },
     },
     start::{
         auxiliary_vector::{AuxiliaryVectorInfo, AuxiliaryVectorItem},
         bootstrap::Bootstrap,
+        personality::Personality,
     },
 };
 
 pub mod auxiliary_vector;
 pub mod bootstrap;
 pub mod environment_variables;
+pub mod personality;
+pub mod secure_execution;
 
+static PROGRAM_NAME: OnceLock<PathBuf> = OnceLock::new();
+
+/// What errors call the program, glibc's `_dl_argv[0]`; "main program" until `_start` records it, or with no `argv[0]`.
+pub fn program_name() -> &'static Path {
+    PROGRAM_NAME
+        .get()
+        .map_or("main program".as_ref(), PathBuf::as_path)
+}
+
+#[cfg(target_arch = "x86_64")]
 #[unsafe(naked)]
-#[cfg_attr(not(test), no_mangle)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
 pub unsafe extern "C" fn _start() -> ! {
     extern "C" {
         fn rtld_fini();
@@ -42,6 +72,27 @@ pub unsafe extern "C" fn _start() -> ! {
     );
 }
 
+// The stack pointer is always 16-byte aligned on arm64, and the program's entry point takes `rtld_fini` in `x0`.
+#[cfg(target_arch = "aarch64")]
+#[unsafe(naked)]
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+pub unsafe extern "C" fn _start() -> ! {
+    extern "C" {
+        fn rtld_fini();
+    }
+    naked_asm!("mov x0, sp",
+        "bl {}",
+        "mov x16, x0",
+        "adrp x0, {rtld_fini}",
+        "add x0, x0, :lo12:{rtld_fini}",
+        "mov x29, xzr",
+        "mov x30, xzr",
+        "br x16",
+        sym relocate_and_calculate_jump_address,
+        rtld_fini = sym rtld_fini,
+    );
+}
+
 pub unsafe extern "C" fn relocate_and_calculate_jump_address(stack_pointer: *mut usize) -> usize {
     // + Newly Pushed Values      Example:                ┌-----------------┐
     // ┌-------------------┐    ┌----------------┐  ┌---> | "/bin/git", 0x0 |
@@ -90,14 +141,13 @@ pub unsafe extern "C" fn relocate_and_calculate_jump_address(stack_pointer: *mut
     syscall_debug_assert!(auxv_info.page_size.is_power_of_two());
     syscall_debug_assert!(auxv_info.base.addr() & (auxv_info.page_size - 1) == 0);
 
-    let program_header_table = ptr::slice_from_raw_parts(
-        auxv_info.program_header_pointer,
-        auxv_info.program_header_count,
-    );
-
     // Relocate ourselves, initialize TLS, and call init functions:
+    // A static-pie has neither an interpreter base nor a PT_PHDR, but the image knows where its own header is.
+    #[cfg(miros_static)]
+    let bootstrap = Bootstrap::from_base(image_base()).unwrap();
+    #[cfg(not(miros_static))]
     let bootstrap = if auxv_info.base.is_null() {
-        Bootstrap::from_program_headers(program_header_table).unwrap()
+        Bootstrap::from_program_headers(auxv_info.program_header_table()).unwrap()
     } else {
         Bootstrap::from_base(auxv_info.base).unwrap()
     };
@@ -109,10 +159,35 @@ pub unsafe extern "C" fn relocate_and_calculate_jump_address(stack_pointer: *mut
         .init_array(arg_count, arg_pointer, env_pointer, auxv_pointer);
 
     crate::allocator::install_heap();
+    // Parsing the vDSO allocates its link map, so it has to wait for the heap.
+    crate::libc::vdso::install_vdso();
+
+    if arg_count > 0 {
+        let name = CStr::from_ptr((*arg_pointer).cast());
+        let _ = PROGRAM_NAME.set(PathBuf::from(OsStr::from_bytes(name.to_bytes())));
+    }
 
     set_environ_pointer(env_pointer as *mut *mut u8);
+    // Before any stage reads LD_* variables.
+    secure_execution::set_secure_execution(auxv_info.secure, env_pointer as *mut *mut u8);
 
-    let miros_object_data = if auxv_info.base.is_null() {
+    let init_array = InitArray::new(arg_count, arg_pointer, env_pointer, auxv_pointer);
+    #[cfg(miros_static)]
+    let entry = start_static(init_array, arg_count, arg_pointer);
+    #[cfg(not(miros_static))]
+    let entry = load_program(&auxv_info, init_array, arg_count, arg_pointer);
+    entry
+}
+
+/// Loads, links and initializes the program and its dependencies; returns the program's entry point.
+unsafe fn load_program(
+    auxv_info: &AuxiliaryVectorInfo,
+    init_array: InitArray,
+    arg_count: usize,
+    arg_pointer: *const *const u8,
+) -> usize {
+    let program_header_table = auxv_info.program_header_table();
+    let mut miros_object_data = if auxv_info.base.is_null() {
         ObjectData::from_program_headers(program_header_table).unwrap()
     } else {
         ObjectData::from_base(auxv_info.base).unwrap()
@@ -123,25 +198,126 @@ pub unsafe extern "C" fn relocate_and_calculate_jump_address(stack_pointer: *mut
     } else {
         ObjectData::from_program_headers(program_header_table).unwrap()
     };
+    let interpreter = interpreter_path(&executable, program_header_table);
+    // Before any constructor runs: those may already call into an ABI the personality decides.
+    personality::set_personality(Personality::detect(
+        interpreter.as_deref(),
+        executable.dynamic_fields.dependencies(),
+    ));
+    if let Some(path) = interpreter {
+        miros_object_data.set_path(path);
+    }
     let mut executable_and_dependencies = ObjectDataGraph::new(executable, miros_object_data);
+    if ReportStatistics::is_enabled() {
+        executable_and_dependencies
+            .observers
+            .push(Box::new(StageStatistics::default()));
+    }
 
-    let load_dependencies = LoadDependencies;
+    let load_auditors = LoadAuditors::new(init_array);
+    let load_dependencies = match LoadDependencies::from_environment() {
+        Ok(load_dependencies) => load_dependencies,
+        Err(error) => exit_with_error(arg_count, arg_pointer, error),
+    };
+    let open_relocation_cache = OpenRelocationCache;
     let relocate = Relocate;
+    let store_relocation_cache = StoreRelocationCache;
     let bind_interposable_cells = BindInterposableCells;
+    let debugger_rendezvous = DebuggerRendezvous;
     let thread_local_storage = ThreadLocalStorage;
-    let init_array = InitArray::new(arg_count, arg_pointer, env_pointer, auxv_pointer);
+    let relocate_thread_locals = RelocateThreadLocals;
+    let audit_preinit = AuditPreinit;
+    let report_statistics = ReportStatistics;
     let executable_stratagems: &[&dyn Stratagem] = &[
+        &load_auditors,
+        &open_relocation_cache,
         &load_dependencies,
         &relocate,
+        &store_relocation_cache,
         &bind_interposable_cells,
+        &debugger_rendezvous,
         &thread_local_storage,
+        &relocate_thread_locals,
+        &audit_preinit,
+        &report_statistics,
         &init_array,
     ];
     let executable_pipeline = ObjectPipeline::new(executable_stratagems);
     if let Err(error) = executable_pipeline.run_pipeline(&mut executable_and_dependencies) {
-        eprintln!("miros: {error:?}");
-        crate::syscall::exit::exit(1);
+        exit_with_error(arg_count, arg_pointer, error);
     }
+    namespace::register_base(executable_and_dependencies, init_array);
 
     auxv_info.entry.addr()
 }
+
+/// Initializes a static image, which is the program and miros both: the bootstrap relocated all of it and there is
+/// nothing to load, so what is left of the pipeline is running its constructors.
+///
+/// Returns `rcrt1.o`'s `__miros_start_main`, the kernel's entry point being this `_start`.
+#[cfg(miros_static)]
+unsafe fn start_static(
+    init_array: InitArray,
+    arg_count: usize,
+    arg_pointer: *const *const u8,
+) -> usize {
+    extern "C" {
+        fn __miros_start_main();
+    }
+
+    let base = image_base();
+    let mut image = ObjectDataGraph::new(
+        ObjectData::from_base(base).unwrap(),
+        ObjectData::from_base(base).unwrap(),
+    );
+    if let Err(error) = ObjectPipeline::new(&[&init_array]).run_pipeline(&mut image) {
+        exit_with_error(arg_count, arg_pointer, error);
+    }
+    namespace::register_base(image, init_array);
+
+    __miros_start_main as *const () as usize
+}
+
+/// The static image's load address, from the linker-defined `__ehdr_start`; assembly, as this runs before relocation.
+#[cfg(miros_static)]
+unsafe fn image_base() -> *const std::ffi::c_void {
+    let base;
+    #[cfg(target_arch = "x86_64")]
+    std::arch::asm!(
+        "lea {}, [rip + __ehdr_start]",
+        out(reg) base,
+        options(nostack, preserves_flags, nomem)
+    );
+    #[cfg(target_arch = "aarch64")]
+    std::arch::asm!(
+        "adrp {0}, __ehdr_start",
+        "add {0}, {0}, :lo12:__ehdr_start",
+        out(reg) base,
+        options(nostack, preserves_flags, nomem)
+    );
+    base
+}
+
+/// Same shape and status as glibc: `./prog: error while loading shared libraries: ...`, exit 127.
+unsafe fn exit_with_error(arg_count: usize, arg_pointer: *const *const u8, error: MirosError) -> ! {
+    let program_name = match arg_count {
+        0 => "miros".into(),
+        _ => CStr::from_ptr((*arg_pointer).cast()).to_string_lossy(),
+    };
+    eprintln!("{program_name}: {error}");
+    crate::syscall::exit::exit(MirosError::EXIT_CODE as usize)
+}
+
+/// The executable's `PT_INTERP` string: the path the kernel loaded miros from.
+unsafe fn interpreter_path(
+    executable: &ObjectData,
+    program_header_table: *const [ProgramHeader],
+) -> Option<PathBuf> {
+    (*program_header_table)
+        .iter()
+        .find(|header| header.p_type == PT_INTERP)
+        .map(|header| {
+            let path = CStr::from_ptr(executable.base.byte_add(header.p_vaddr).cast());
+            PathBuf::from(OsStr::from_bytes(path.to_bytes()))
+        })
+}
@@ -0,0 +1,77 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The C library a program was linked against. Where glibc's and musl's ABIs disagree, miros presents the program's.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Personality {
+    Glibc,
+    Musl,
+}
+
+static MUSL: AtomicBool = AtomicBool::new(false);
+
+/// The running program's personality; glibc unless loading the program said otherwise, as for a static image.
+pub fn personality() -> Personality {
+    if MUSL.load(Ordering::Relaxed) {
+        Personality::Musl
+    } else {
+        Personality::Glibc
+    }
+}
+
+pub fn set_personality(personality: Personality) {
+    MUSL.store(personality == Personality::Musl, Ordering::Relaxed);
+}
+
+impl Personality {
+    /// musl's loader is `ld-musl-<arch>.so.1` and its libc `libc.musl-<arch>.so.1` on Alpine, or the unversioned
+    /// `libc.so` upstream, which glibc never uses. A program whose `PT_INTERP` was re-pointed at miros no longer names
+    /// the former, so its `DT_NEEDED` entries are checked too.
+    pub fn detect(interpreter: Option<&Path>, dependencies: &[&str]) -> Self {
+        let musl_interpreter = interpreter
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("ld-musl-"));
+        if musl_interpreter
+            || dependencies
+                .iter()
+                .any(|&dependency| dependency.starts_with("libc.musl-") || dependency == "libc.so")
+        {
+            Personality::Musl
+        } else {
+            Personality::Glibc
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_reads_the_interpreter_then_the_dependencies() {
+        let musl_loader = Path::new("/lib/ld-musl-x86_64.so.1");
+        let miros = Path::new("/opt/miros/libmiros.so");
+        assert_eq!(
+            Personality::detect(Some(musl_loader), &[]),
+            Personality::Musl
+        );
+        assert_eq!(
+            Personality::detect(Some(miros), &["libz.so.1", "libc.musl-x86_64.so.1"]),
+            Personality::Musl
+        );
+        assert_eq!(
+            Personality::detect(Some(Path::new("/lib64/ld-linux-x86-64.so.2")), &[
+                "libc.so.6"
+            ]),
+            Personality::Glibc
+        );
+        assert_eq!(
+            Personality::detect(Some(miros), &["libc.so"]),
+            Personality::Musl
+        );
+        assert_eq!(Personality::detect(None, &[]), Personality::Glibc);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    ffi::CStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// glibc's `unsecure-envvars.h`, plus miros's own loader variables: those that would let the invoking user steer a setuid or
+/// file-capability program.
+///
+/// In secure mode they are removed before anything reads them, so `LD_AUDIT`, `GLIBC_TUNABLES` and friends have no effect, and the program never sees them either.
+const UNSECURE_ENVIRONMENT_VARIABLES: &[&[u8]] = &[
+    b"GCONV_PATH",
+    b"GETCONF_DIR",
+    b"GLIBC_TUNABLES",
+    b"HOSTALIASES",
+    b"LD_AUDIT",
+    b"LD_BIND_NOT",
+    b"LD_BIND_NOW",
+    b"LD_DEBUG",
+    b"LD_DEBUG_OUTPUT",
+    b"LD_DYNAMIC_WEAK",
+    b"LD_HWCAP_MASK",
+    b"LD_LIBRARY_PATH",
+    b"LD_ORIGIN_PATH",
+    b"LD_PRELOAD",
+    b"LD_PROFILE",
+    b"LD_SHOW_AUXV",
+    b"LOCALDOMAIN",
+    b"LOCPATH",
+    b"MALLOC_TRACE",
+    b"MIROS_INTERCEPT",
+    b"MIROS_INTERCEPT_CONFIG",
+    b"MIROS_RELOCATION_CACHE",
+    b"NIS_PATH",
+    b"NLSPATH",
+    b"RESOLV_HOST_CONF",
+    b"RES_OPTIONS",
+    b"TMPDIR",
+    b"TZDIR",
+];
+
+static SECURE_EXECUTION: AtomicBool = AtomicBool::new(false);
+
+/// Whether the kernel set `AT_SECURE`: the program runs with privileges its invoker lacks (setuid, setgid or file capabilities).
+pub fn is_secure_execution() -> bool {
+    SECURE_EXECUTION.load(Ordering::Relaxed)
+}
+
+/// Records `AT_SECURE` and, when it is set, strips the unsecure variables from the initial environment.
+pub unsafe fn set_secure_execution(secure: bool, environment_pointer: *mut *mut u8) {
+    SECURE_EXECUTION.store(secure, Ordering::Relaxed);
+    if secure {
+        strip_environment(environment_pointer, UNSECURE_ENVIRONMENT_VARIABLES);
+    }
+}
+
+/// Removes every `NAME=value` entry whose name is in `names`, shifting later entries down in place as glibc's `unsetenv` does.
+unsafe fn strip_environment(environment_pointer: *mut *mut u8, names: &[&[u8]]) {
+    let mut write = environment_pointer;
+    let mut read = environment_pointer;
+    while !(*read).is_null() {
+        let entry = CStr::from_ptr((*read).cast()).to_bytes();
+        // An entry without `=` is malformed; match it whole so it can't dodge the list.
+        let name = entry.split(|&byte| byte == b'=').next().unwrap_or(entry);
+        if !names.contains(&name) {
+            *write = *read;
+            write = write.add(1);
+        }
+        read = read.add(1);
+    }
+    // Everything from the old end down to the new one is cleared, so no stale pointer lingers past the terminator.
+    while write <= read {
+        *write = std::ptr::null_mut();
+        write = write.add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CString, ptr};
+
+    use super::*;
+
+    #[test]
+    fn strip_environment_keeps_order_and_terminates() {
+        let strings: Vec<CString> = [
+            "PATH=/bin",
+            "LD_LIBRARY_PATH=/tmp/evil",
+            "HOME=/root",
+            "LD_PRELOAD=/tmp/evil.so",
+            "LD_LIBRARY_PATH_NOT=kept",
+        ]
+        .into_iter()
+        .map(|entry| CString::new(entry).unwrap())
+        .collect();
+        let mut environment: Vec<*mut u8> = strings
+            .iter()
+            .map(|entry| entry.as_ptr() as *mut u8)
+            .chain([ptr::null_mut()])
+            .collect();
+
+        unsafe { strip_environment(environment.as_mut_ptr(), UNSECURE_ENVIRONMENT_VARIABLES) };
+
+        let remaining: Vec<&str> = environment
+            .iter()
+            .take_while(|entry| !entry.is_null())
+            .map(|&entry| unsafe { CStr::from_ptr(entry.cast()) }.to_str().unwrap())
+            .collect();
+        assert_eq!(remaining, [
+            "PATH=/bin",
+            "HOME=/root",
+            "LD_LIBRARY_PATH_NOT=kept"
+        ]);
+        assert!(environment[3..].iter().all(|entry| entry.is_null()));
+    }
+}
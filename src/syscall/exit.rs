@@ -8,6 +8,7 @@ pub const EXIT_UNKNOWN_RELOCATION: usize = CODE_ADDEND + 1;
 
 #[inline(always)]
 pub fn exit(code: usize) -> ! {
+    #[cfg(target_arch = "x86_64")]
     unsafe {
         asm!(
             "syscall",
@@ -16,9 +17,19 @@ pub fn exit(code: usize) -> ! {
             options(noreturn)
         )
     }
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        asm!(
+            "svc 0",
+            in("x8") Syscall::Exit as usize,
+            in("x0") code,
+            options(noreturn)
+        )
+    }
 }
 
 /// Unmap `[base, base + length)` then exit the thread, touching no stack between the two syscalls.
+#[cfg(target_arch = "x86_64")]
 pub unsafe fn munmap_and_exit(base: *mut u8, length: usize) -> ! {
     asm!(
         "syscall",
@@ -32,3 +43,19 @@ pub unsafe fn munmap_and_exit(base: *mut u8, length: usize) -> ! {
         options(noreturn, nostack),
     )
 }
+
+/// Unmap `[base, base + length)` then exit the thread, touching no stack between the two syscalls.
+#[cfg(target_arch = "aarch64")]
+pub unsafe fn munmap_and_exit(base: *mut u8, length: usize) -> ! {
+    asm!(
+        "svc 0",
+        "mov x8, #{exit_number}",
+        "mov x0, #0",
+        "svc 0",
+        in("x8") Syscall::MunMap as usize,
+        in("x0") base,
+        in("x1") length,
+        exit_number = const Syscall::Exit as usize,
+        options(noreturn, nostack),
+    )
+}
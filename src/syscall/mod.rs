@@ -3,10 +3,12 @@
 pub enum Syscall {
     Read = 0,
     PRead64 = 17,
+    PWrite64 = 18,
     LSeek = 8,
     Write = 1,
     Close = 3,
     FCntl = 72,
+    Dup3 = 292,
     Stat = 4,
     FStat = 5,
     Statx = 332,
@@ -23,11 +25,19 @@ pub enum Syscall {
     GetTid = 186,
     TgKill = 234,
     OpenAt = 257,
+    MkdirAt = 258,
+    UnlinkAt = 263,
+    RenameAt = 264,
+    ReadLinkAt = 267,
     GetDents64 = 217,
     GetRandom = 318,
+    MemfdCreate = 319,
     Clone3 = 435,
     GetTimeOfDay = 96,
     ClockGetTime = 228,
+    ClockGetRes = 229,
+    Time = 201,
+    GetCpu = 309,
     SchedGetAffinity = 204,
     PrLimit64 = 302,
     PrCtl = 157,
@@ -56,19 +66,94 @@ pub enum Syscall {
     EpollCreate1 = 291,
 }
 
+/// The generic syscall table arm64 shares with the newer ports: no `stat`, `poll`, `select`, `time`, `epoll_wait` or
+/// `arch_prctl`, whose callers use the `*at`/`p*` successors instead.
+#[repr(usize)]
+#[cfg(target_arch = "aarch64")]
+pub enum Syscall {
+    Read = 63,
+    PRead64 = 67,
+    PWrite64 = 68,
+    LSeek = 62,
+    Write = 64,
+    Close = 57,
+    FCntl = 25,
+    Dup3 = 24,
+    NewFStatAt = 79,
+    FStat = 80,
+    Statx = 291,
+    MMap = 222,
+    IoCtl = 29,
+    MProtect = 226,
+    MunMap = 215,
+    MreMap = 216,
+    GetPid = 172,
+    Clone = 220,
+    Exit = 93,
+    Futex = 98,
+    GetTid = 178,
+    TgKill = 131,
+    OpenAt = 56,
+    MkdirAt = 34,
+    UnlinkAt = 35,
+    RenameAt = 38,
+    ReadLinkAt = 78,
+    GetDents64 = 61,
+    GetRandom = 278,
+    MemfdCreate = 279,
+    Clone3 = 435,
+    GetTimeOfDay = 169,
+    ClockGetTime = 113,
+    ClockGetRes = 114,
+    GetCpu = 168,
+    SchedGetAffinity = 123,
+    PrLimit64 = 261,
+    PrCtl = 167,
+    PPoll = 73,
+    PSelect6 = 72,
+    Socket = 198,
+    SocketPair = 199,
+    Bind = 200,
+    Listen = 201,
+    Accept = 202,
+    Connect = 203,
+    GetSockName = 204,
+    GetPeerName = 205,
+    SendTo = 206,
+    RecvFrom = 207,
+    SetSockOpt = 208,
+    GetSockOpt = 209,
+    Shutdown = 210,
+    SendMsg = 211,
+    RecvMsg = 212,
+    Accept4 = 242,
+    EventFd2 = 19,
+    EpollCreate1 = 20,
+    EpollCtl = 21,
+    EpollPWait = 22,
+}
+
 // TT-muncher: peels one register constraint and one argument per recursion step,
 // accumulating `in("reg") value` operands into a single `asm!` block.
 #[macro_export]
 macro_rules! syscall {
-    ($syscall:expr $(, $args:expr)* $(,)?) => {
+    ($syscall:expr $(, $args:expr)* $(,)?) => {{
         #[cfg(target_arch = "x86_64")]
-        $crate::syscall!(
+        let result = $crate::syscall!(
             @build $syscall,
             [in("rdi"), in("rsi"), in("rdx"), in("r10"), in("r8"), in("r9"),],
             []
             $(, $args)*
-        )
-    };
+        );
+        #[cfg(target_arch = "aarch64")]
+        let result = $crate::syscall!(
+            @build $syscall,
+            [in("x0"), in("x1"), in("x2"), in("x3"), in("x4"), in("x5"),],
+            []
+            $(, $args)*
+        );
+        result
+    }};
     (@build $syscall:expr, [$($unused:tt)*], [$($operands:tt)*]) => {{
         let result: isize;
         #[cfg(target_arch = "x86_64")]
@@ -80,6 +165,15 @@ macro_rules! syscall {
             lateout("r11") _,
             options(nostack, preserves_flags),
         );
+        // The result comes back in the first argument's register.
+        #[cfg(target_arch = "aarch64")]
+        std::arch::asm!(
+            "svc 0",
+            in("x8") $syscall as usize,
+            lateout("x0") result,
+            $($operands)*
+            options(nostack, preserves_flags),
+        );
         result
     }};
     (@build $syscall:expr, [$constraint:tt $register:tt, $($rest:tt)*], [$($operands:tt)*], $arg:expr $(, $more:expr)*) => {
@@ -91,7 +185,7 @@ macro_rules! syscall {
         )
     };
     (@build $syscall:expr, [], [$($operands:tt)*], $($overflow:expr),+) => {
-        compile_error!("x86_64 syscall ABI supports at most 6 arguments")
+        compile_error!("the syscall ABI supports at most 6 arguments")
     };
 }
 
@@ -1,8 +1,10 @@
 use std::{arch::asm, ffi::c_void};
 
+#[cfg(target_arch = "x86_64")]
 use super::Syscall;
 use crate::io_macros::syscall_debug_assert;
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 pub unsafe fn set_thread_pointer(new_pointer: *mut c_void) {
     const ARCH_SET_FS: usize = 4098;
@@ -12,6 +14,7 @@ pub unsafe fn set_thread_pointer(new_pointer: *mut c_void) {
     syscall_debug_assert!(get_thread_pointer() == new_pointer);
 }
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 pub unsafe fn get_thread_pointer() -> *mut c_void {
     let pointer;
@@ -22,3 +25,27 @@ pub unsafe fn get_thread_pointer() -> *mut c_void {
     );
     pointer
 }
+
+// TPIDR_EL0 is writable from userspace, so arm64 needs no syscall.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub unsafe fn set_thread_pointer(new_pointer: *mut c_void) {
+    asm!(
+        "msr tpidr_el0, {}",
+        in(reg) new_pointer,
+        options(nostack, preserves_flags)
+    );
+    syscall_debug_assert!(get_thread_pointer() == new_pointer);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub unsafe fn get_thread_pointer() -> *mut c_void {
+    let pointer;
+    asm!(
+        "mrs {}, tpidr_el0",
+        out(reg) pointer,
+        options(nostack, preserves_flags, nomem)
+    );
+    pointer
+}
@@ -1,5 +1,10 @@
-use std::ptr::{self, NonNull};
+use std::{
+    ops::Range,
+    ptr::{self, NonNull},
+};
 
+#[cfg(target_arch = "aarch64")]
+use crate::tls::thread_control_block::TCB_HEADER_SIZE;
 use crate::{
     tls::TLS_RESERVE_SIZE,
     utils::{
@@ -20,9 +25,19 @@ impl FreeChunk {
     }
 }
 
+/// The reserve's bounds relative to the thread pointer: all of it below on x86-64, from the end of the TCB header up to
+/// miros's own block on arm64 (see [`thread_region_extent`](super::thread_region_extent)).
+#[cfg(target_arch = "x86_64")]
+const RESERVE: Range<isize> = -(TLS_RESERVE_SIZE as isize)..0;
+#[cfg(target_arch = "aarch64")]
+const RESERVE: Range<isize> = TCB_HEADER_SIZE as isize..TLS_RESERVE_SIZE as isize;
+
 /// Manages the TLS reserve region layout shared across all threads. Tracks
 /// block allocations as offsets from the thread pointer — individual threads
 /// apply these offsets to their own TP to derive addresses.
+///
+/// Blocks are placed from the thread pointer outwards, so the first one (the program's) lands where the linker assumed:
+/// right below the thread pointer on x86-64, right after the TCB header on arm64.
 pub struct TlsLayoutAllocator {
     free: LinkedList<FreeChunk>,
     metadata: MetadataAllocator<LinkedListNode<FreeChunk>>,
@@ -39,7 +54,7 @@ impl TlsLayoutAllocator {
                 initial_chunk.as_ptr(),
                 LinkedListNode::new(FreeChunk {
                     offset: 0,
-                    size: TLS_RESERVE_SIZE,
+                    size: RESERVE.end.abs_diff(RESERVE.start),
                 }),
             );
             free.push(initial_chunk);
@@ -48,6 +63,7 @@ impl TlsLayoutAllocator {
         Self { free, metadata }
     }
 
+    #[cfg(target_arch = "x86_64")]
     pub unsafe fn allocate_block(&mut self, block_size: usize, alignment: usize) -> Option<isize> {
         let aligned_size = round_up_to_boundary(block_size, alignment);
 
@@ -68,12 +84,46 @@ impl TlsLayoutAllocator {
             chunk.size = remaining;
         }
 
-        Some(aligned_start as isize - TLS_RESERVE_SIZE as isize)
+        Some(aligned_start as isize + RESERVE.start)
+    }
+
+    /// Takes the lowest chunk the block fits in once aligned; the padding in front of it is given up.
+    #[cfg(target_arch = "aarch64")]
+    pub unsafe fn allocate_block(&mut self, block_size: usize, alignment: usize) -> Option<isize> {
+        let aligned_size = round_up_to_boundary(block_size, alignment);
+        // Alignment is relative to the thread pointer, not to the reserve's start.
+        let aligned_start = |chunk: &FreeChunk| {
+            round_up_to_boundary(chunk.offset + RESERVE.start as usize, alignment)
+                - RESERVE.start as usize
+        };
+
+        let node = self
+            .free
+            .iter()
+            .filter(|node| {
+                let chunk = &node.as_ref().value;
+                aligned_start(chunk) + aligned_size <= chunk.top()
+            })
+            .min_by_key(|node| node.as_ref().value.offset)?;
+
+        let chunk = &mut (*node.as_ptr()).value;
+        let start = aligned_start(chunk);
+        let end = start + aligned_size;
+
+        if end == chunk.top() {
+            self.free.remove(node);
+            self.metadata.dealloc(node);
+        } else {
+            chunk.size = chunk.top() - end;
+            chunk.offset = end;
+        }
+
+        Some(start as isize + RESERVE.start)
     }
 
     pub unsafe fn deallocate_block(&mut self, offset: isize, block_size: usize, alignment: usize) {
         let size = round_up_to_boundary(block_size, alignment);
-        let region_offset = (offset + TLS_RESERVE_SIZE as isize) as usize;
+        let region_offset = (offset - RESERVE.start) as usize;
         self.release(region_offset, size);
     }
 
@@ -16,6 +16,66 @@ pub mod template;
 pub mod thread_control_block;
 
 pub const TLS_RESERVE_SIZE: usize = 8 * 1024 * 1024;
+
+/// How many bytes of a thread's region lie below and above its thread pointer, given miros's own TLS block size.
+///
+/// The reserve holds every other module's block, at the offsets [`TlsLayoutAllocator`] hands out.
+/// - x86-64 (TLS variant II): `[reserve][TCB][miros TLS]`, the thread pointer at the TCB.
+/// - arm64 (variant I): `[TCB][reserve][miros TLS]`, the thread pointer at the TCB's last 16 bytes and the reserve
+///   right after them. The TCB is padded out to a page below, keeping the thread pointer page-aligned as on x86-64.
+pub fn thread_region_extent(miros_tls_size: usize) -> (usize, usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        (
+            TLS_RESERVE_SIZE,
+            size_of::<ThreadControlBlock>() + miros_tls_size,
+        )
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        let below = size_of::<ThreadControlBlock>() - thread_control_block::TCB_HEADER_SIZE;
+        (
+            crate::page_size::round_up_to_page_size(below),
+            TLS_RESERVE_SIZE + miros_tls_size,
+        )
+    }
+}
+
+/// miros's own TLS block's offset from the thread pointer, see [`thread_region_extent`].
+#[cfg(target_arch = "x86_64")]
+pub const MIROS_BLOCK_OFFSET: isize = size_of::<ThreadControlBlock>() as isize;
+
+/// miros's own TLS block's offset from the thread pointer, see [`thread_region_extent`].
+#[cfg(target_arch = "aarch64")]
+pub const MIROS_BLOCK_OFFSET: isize = TLS_RESERVE_SIZE as isize;
+
+/// The resolver of an `R_AARCH64_TLSDESC` descriptor whose block is in static TLS, as every block miros places is.
+///
+/// Called with the descriptor's address in `x0` and must return the variable's TP-relative offset, which the descriptor's
+/// second word already holds. The TLSDESC calling convention lets it clobber nothing but `x0`.
+#[cfg(target_arch = "aarch64")]
+#[unsafe(naked)]
+pub unsafe extern "C" fn static_tls_descriptor() {
+    std::arch::naked_asm!("ldr x0, [x0, #8]", "ret");
+}
+
+/// glibc's `tls_index`, what a general-dynamic access passes to `__tls_get_addr`, as `RelocateThreadLocals` fills it in.
+#[repr(C)]
+pub struct TlsIndex {
+    /// The module's block, as a TP-relative offset.
+    block_offset: isize,
+    /// The variable's offset within the block.
+    offset: usize,
+}
+
+/// Every block is static, at the same offset from every thread's thread pointer, so nothing is ever allocated here.
+#[cfg_attr(not(any(test, fuzzing)), no_mangle)]
+pub unsafe extern "C" fn __tls_get_addr(index: *const TlsIndex) -> *mut c_void {
+    crate::syscall::thread_pointer::get_thread_pointer()
+        .byte_offset((*index).block_offset)
+        .byte_add((*index).offset)
+}
+
 static mut TLS_ALLOCATOR: MaybeUninit<Mutex<TlsAllocator>> = MaybeUninit::uninit();
 
 pub unsafe fn set_tls_allocator(miros_template: Option<TlsTemplate>) {
@@ -79,13 +139,11 @@ impl TlsAllocator {
 
     pub unsafe fn initialize_thread_tls(&self, thread_pointer: *mut c_void) {
         if let Some(template) = &self.miros_template {
-            let miros_offset = size_of::<ThreadControlBlock>() as isize;
-
             debug_assert_eq!(
-                thread_pointer.byte_offset(miros_offset) as usize % template.alignment,
+                thread_pointer.byte_offset(MIROS_BLOCK_OFFSET) as usize % template.alignment,
                 0
             );
-            Self::initialize_block(template, miros_offset, thread_pointer);
+            Self::initialize_block(template, MIROS_BLOCK_OFFSET, thread_pointer);
         }
 
         for allocation in self.registry.iter() {
@@ -1,18 +1,27 @@
 use std::{
     ffi::c_void,
     mem::offset_of,
+    ptr,
     sync::atomic::{AtomicU32, Ordering},
 };
 
 use strum::FromRepr;
 
-use crate::utils::mremap_allocator::MreMapAllocator;
+use crate::{
+    syscall::thread_pointer::get_thread_pointer, utils::mremap_allocator::MreMapAllocator,
+};
 
+// Compilers read the stack protector canary from `fs:0x28`.
+#[cfg(target_arch = "x86_64")]
 const _: () = assert!(offset_of!(ThreadControlBlock, canary) == 0x28);
 
+/// On x86-64 (TLS variant II) the thread pointer is the TCB's address, and its first word points to itself (`fs:0`). On
+/// arm64 (variant I) it points at the TCB's last 16 bytes, the ABI's `tcbhead_t`, right below the program's TLS block.
 #[repr(C)]
 pub struct ThreadControlBlock {
+    #[cfg(target_arch = "x86_64")]
     pub thread_pointee: [u8; 0],
+    #[cfg(target_arch = "x86_64")]
     pub thread_pointer_register: *mut c_void,
     pub tid: i32,
     pub detach_state: AtomicDetachState,
@@ -20,6 +29,67 @@ pub struct ThreadControlBlock {
     pub region: *mut [u8],
     pub canary: usize,
     pub dynamic_thread_vector: DynamicThreadVector,
+    #[cfg(target_arch = "aarch64")]
+    pub thread_pointee: [u8; 0],
+    /// glibc's `tcbhead_t`: the DTV and a private word, which compiled code never reads.
+    #[cfg(target_arch = "aarch64")]
+    pub header: [*mut c_void; 2],
+}
+
+/// The bytes of the TCB at and above the thread pointer, which variant I's TLS offsets count from.
+#[cfg(target_arch = "aarch64")]
+pub const TCB_HEADER_SIZE: usize = 16;
+
+#[cfg(target_arch = "aarch64")]
+const _: () = assert!(
+    size_of::<ThreadControlBlock>() - offset_of!(ThreadControlBlock, thread_pointee)
+        == TCB_HEADER_SIZE
+);
+
+impl ThreadControlBlock {
+    /// The TCB of the thread whose thread pointer is `thread_pointer`.
+    pub fn from_thread_pointer(thread_pointer: *mut c_void) -> *mut Self {
+        thread_pointer
+            .wrapping_byte_sub(offset_of!(ThreadControlBlock, thread_pointee))
+            .cast()
+    }
+
+    /// The calling thread's TCB.
+    pub unsafe fn current() -> *mut Self {
+        Self::from_thread_pointer(get_thread_pointer())
+    }
+
+    /// Initializes a new thread's TCB at `this`, in the region `region` that also holds its stack and TLS.
+    pub unsafe fn initialize(
+        this: *mut Self,
+        tid: i32,
+        detach_state: DetachState,
+        region: *mut [u8],
+        canary: usize,
+    ) {
+        this.write(Self {
+            #[cfg(target_arch = "x86_64")]
+            thread_pointee: [],
+            #[cfg(target_arch = "x86_64")]
+            thread_pointer_register: Self::thread_pointer(this),
+            tid,
+            detach_state: AtomicDetachState::new(detach_state),
+            return_value: ptr::null_mut(),
+            region,
+            canary,
+            dynamic_thread_vector: DynamicThreadVector::new(),
+            #[cfg(target_arch = "aarch64")]
+            thread_pointee: [],
+            #[cfg(target_arch = "aarch64")]
+            header: [ptr::null_mut(); 2],
+        });
+    }
+
+    /// The value this thread's thread pointer register holds.
+    pub fn thread_pointer(this: *mut Self) -> *mut c_void {
+        this.wrapping_byte_add(offset_of!(ThreadControlBlock, thread_pointee))
+            .cast()
+    }
 }
 
 /// The detach/reap handshake between `pthread_detach` and the exiting thread; see `libc::threads::self_detach`.
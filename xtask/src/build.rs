@@ -1,9 +1,46 @@
 use std::{
+    fs,
     path::{Path, PathBuf},
     process::Command,
 };
 
-pub const TARGET: &str = "x86_64-unknown-linux-gnu";
+use clap::ValueEnum;
+
+/// The architecture to build miros (and the examples) for.
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Arch {
+    #[default]
+    X86_64,
+    /// Cross-built with the `aarch64-linux-gnu-` toolchain; run under `qemu-aarch64`.
+    Aarch64,
+}
+
+impl Arch {
+    /// The name `crt/` keeps this architecture's startup sources under.
+    pub fn name(self) -> &'static str {
+        self.target().split('-').next().unwrap()
+    }
+
+    pub fn target(self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64-unknown-linux-gnu",
+            Arch::Aarch64 => "aarch64-unknown-linux-gnu",
+        }
+    }
+
+    /// The C compiler, which also links miros when cross-building.
+    pub fn c_compiler(self) -> &'static str {
+        match self {
+            Arch::X86_64 => "gcc",
+            Arch::Aarch64 => "aarch64-linux-gnu-gcc",
+        }
+    }
+
+    /// Whether binaries built for this architecture run directly on the host.
+    pub fn is_native(self) -> bool {
+        self.target().starts_with(std::env::consts::ARCH)
+    }
+}
 
 /// The workspace root, `xtask`'s manifest lives one level under it, so this is invocation-independent.
 pub fn workspace_root() -> PathBuf {
@@ -13,24 +50,20 @@ pub fn workspace_root() -> PathBuf {
         .to_path_buf()
 }
 
-/// Build `libmiros.so` (release) and return its path.
-pub fn run() -> PathBuf {
+/// Build `libmiros.so` (release) for `arch` and return its path.
+pub fn run(arch: Arch) -> PathBuf {
     let root = workspace_root();
     let aliases_version_script = crate::aliases::generate();
 
-    let status = Command::new("cargo")
-        .current_dir(&root)
-        .env(
-            "RUSTFLAGS",
-            "-C target-cpu=native -Z unstable-options -C panic=immediate-abort -Z tls-model=initial-exec --cfg miros_aliases",
-        )
+    let version_script_argument = format!(
+        "link-arg=-Wl,--version-script,{}",
+        aliases_version_script.display()
+    );
+    let status = cargo_rustc(arch, "", &root.join("target"))
         .args([
-            "rustc",
-            "-Z",
-            "build-std=core,alloc,std",
-            "--target",
-            TARGET,
-            "--release",
+            // The manifest also lists `rlib` for the fuzz targets; building both changes how the cdylib is linked.
+            "--crate-type",
+            "cdylib",
             "--",
             "-C",
             "link-arg=-nostartfiles",
@@ -41,17 +74,14 @@ pub fn run() -> PathBuf {
             "link-arg=-Wl,-Bsymbolic",
             "-C",
             "link-arg=-Wl,-e,_start",
+            "-C",
+            &version_script_argument,
         ])
-        .arg("-C")
-        .arg(format!(
-            "link-arg=-Wl,--version-script,{}",
-            aliases_version_script.display()
-        ))
         .status()
         .expect("failed to spawn cargo");
     assert!(status.success(), "release build failed");
 
-    let miros = root.join(format!("target/{TARGET}/release/libmiros.so"));
+    let miros = root.join(format!("target/{}/release/libmiros.so", arch.target()));
     assert!(
         miros.exists(),
         "libmiros.so not found at {}",
@@ -59,3 +89,85 @@ pub fn run() -> PathBuf {
     );
     miros
 }
+
+/// Build `libmiros.a` (release, `--cfg miros_static`) and the startup objects for `arch`, returning the directory holding them.
+///
+/// The directory is laid out for `gcc -static-pie -B <dir>`: `rcrt1.o`, `crti.o` and `crtn.o` replace the system's, and
+/// `libc.a` is miros, with empty `libm.a`, `libpthread.a`, `libdl.a` and `librt.a` beside it since miros provides those too.
+pub fn run_static(arch: Arch) -> PathBuf {
+    let root = workspace_root();
+    crate::aliases::generate();
+
+    // Its own target directory: the cfg change would otherwise rebuild std for every switch between the two.
+    let target_dir = root.join("target/static");
+    let status = cargo_rustc(arch, "--cfg miros_static", &target_dir)
+        .args(["--crate-type", "staticlib"])
+        .status()
+        .expect("failed to spawn cargo");
+    assert!(status.success(), "static release build failed");
+
+    let output = target_dir.join(format!("{}/sysroot", arch.target()));
+    fs::create_dir_all(&output).expect("create the static sysroot");
+    let archive = target_dir.join(format!("{}/release/libmiros.a", arch.target()));
+    fs::copy(&archive, output.join("libc.a")).expect("copy libmiros.a");
+    fs::copy(&archive, output.join("libmiros.a")).expect("copy libmiros.a");
+
+    for stub in ["libm.a", "libpthread.a", "libdl.a", "librt.a"] {
+        // `ar` won't create an archive with no members; an empty one is just the magic string.
+        fs::write(output.join(stub), "!<arch>\n").expect("write an empty archive");
+    }
+
+    let sources = root.join("crt").join(arch.name());
+    for object in ["rcrt1", "crti", "crtn"] {
+        let status = Command::new(arch.c_compiler())
+            .arg("-c")
+            .arg("-o")
+            .arg(output.join(format!("{object}.o")))
+            .arg(sources.join(format!("{object}.S")))
+            .status()
+            .expect("failed to spawn the C compiler");
+        assert!(status.success(), "assembling {object}.S failed");
+    }
+
+    output
+}
+
+/// `cargo rustc` building miros in release for `arch`; the caller adds the crate type and anything after `--`.
+fn cargo_rustc(arch: Arch, extra_rustflags: &str, target_dir: &Path) -> Command {
+    let target = arch.target();
+
+    let mut rustflags = String::from(
+        "-Z unstable-options -C panic=immediate-abort -Z tls-model=initial-exec --cfg miros_aliases",
+    );
+    // The host's CPU says nothing about the machine a cross-built miros runs on.
+    if arch.is_native() {
+        rustflags.insert_str(0, "-C target-cpu=native ");
+    }
+    if !extra_rustflags.is_empty() {
+        rustflags.push(' ');
+        rustflags.push_str(extra_rustflags);
+    }
+
+    let mut command = Command::new("cargo");
+    // rustc links with the host's `cc` unless told otherwise.
+    if !arch.is_native() {
+        let linker_variable = format!(
+            "CARGO_TARGET_{}_LINKER",
+            target.to_uppercase().replace('-', "_")
+        );
+        command.env(linker_variable, arch.c_compiler());
+    }
+    command
+        .current_dir(workspace_root())
+        .env("RUSTFLAGS", rustflags)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .args([
+            "rustc",
+            "-Z",
+            "build-std=core,alloc,std",
+            "--target",
+            target,
+            "--release",
+        ]);
+    command
+}
@@ -1,6 +1,20 @@
-use std::{fs, process::Command};
+use std::{fs, path::Path, process::Command};
 
-use crate::build;
+use clap::Args;
+
+use crate::build::{self, Arch};
+
+#[derive(Args)]
+pub struct ExamplesArgs {
+    #[arg(long, value_enum, default_value_t)]
+    arch: Arch,
+    /// Run each example after building it; foreign architectures run under `qemu-<arch>` with the cross sysroot.
+    #[arg(long)]
+    run: bool,
+    /// Link each example into a static-pie with libmiros.a (see `build --static`), into `examples/bin/static`.
+    #[arg(long = "static")]
+    static_link: bool,
+}
 
 /// A C example: source stem under `examples/`, plus its extra `gcc` flags (libs, builtins).
 struct Example {
@@ -8,6 +22,26 @@ struct Example {
     flags: &'static [&'static str],
 }
 
+/// Examples of what only a dynamic linker does; a static-pie has no interpreter to audit or to report loaded objects.
+const DYNAMIC_ONLY: &[&str] = &[
+    "audit_module",
+    "audit_puts",
+    "debug_rendezvous",
+    "init_fini",
+    "init_fini_library",
+    "dlmopen_isolation",
+    "dlmopen_counter",
+    "dlmopen_plugin",
+    "dlmopen_memory",
+    "tls_library",
+    "tls_shared",
+    "musl_stdio",
+];
+
+/// Examples built with `musl-gcc`, against musl's headers and start files, to exercise the musl personality. They are
+/// skipped when it isn't installed, as it rarely is, and when cross-building: it targets the host.
+const MUSL: &[&str] = &["musl_stdio"];
+
 const EXAMPLES: &[Example] = &[
     Example {
         stem: "print_deadbeef",
@@ -41,6 +75,30 @@ const EXAMPLES: &[Example] = &[
         stem: "stdio_buffer",
         flags: &[],
     },
+    Example {
+        stem: "stdio_read",
+        flags: &["-O2"],
+    },
+    Example {
+        stem: "stdio_seek",
+        flags: &[],
+    },
+    Example {
+        stem: "stdio_memory",
+        flags: &[],
+    },
+    Example {
+        stem: "printf_float",
+        flags: &[],
+    },
+    Example {
+        stem: "printf_family",
+        flags: &[],
+    },
+    Example {
+        stem: "scanf_family",
+        flags: &[],
+    },
     Example {
         stem: "list_dir",
         flags: &[],
@@ -53,29 +111,119 @@ const EXAMPLES: &[Example] = &[
         stem: "putchar_unlocked_o2",
         flags: &["-O2"],
     },
+    Example {
+        stem: "debug_rendezvous",
+        flags: &[],
+    },
+    Example {
+        stem: "audit_module",
+        flags: &["-shared", "-fPIC"],
+    },
+    Example {
+        stem: "audit_puts",
+        flags: &[],
+    },
+    Example {
+        stem: "vdso_clock",
+        flags: &[],
+    },
+    Example {
+        stem: "init_fini",
+        flags: &["-Wl,-init=legacy_init", "-Wl,-fini=legacy_fini"],
+    },
+    Example {
+        stem: "init_fini_library",
+        flags: &["-shared", "-fPIC"],
+    },
+    Example {
+        stem: "dlmopen_isolation",
+        flags: &[],
+    },
+    Example {
+        stem: "dlmopen_counter",
+        flags: &["-shared", "-fPIC"],
+    },
+    // Built before `dlmopen_memory`, which embeds it.
+    Example {
+        stem: "dlmopen_plugin",
+        flags: &["-shared", "-fPIC"],
+    },
+    Example {
+        stem: "dlmopen_memory",
+        flags: &["-lpthread"],
+    },
+    // Built before `tls_shared`, which links against it by file name.
+    Example {
+        stem: "tls_library",
+        flags: &["-shared", "-fPIC"],
+    },
+    Example {
+        stem: "tls_shared",
+        flags: &["-lpthread", "-Lexamples/bin", "-l:tls_library"],
+    },
+    Example {
+        stem: "musl_stdio",
+        flags: &["-lpthread"],
+    },
 ];
 
-pub fn run() {
-    let miros = build::run();
+pub fn run(args: ExamplesArgs) {
     let root = build::workspace_root();
-    let interpreter = format!("-Wl,--dynamic-linker={}", miros.display());
+    let (bin_dir, link_flags) = if args.static_link {
+        // The sysroot's crt objects and libc.a stand in for the system's; crtbeginS.o and libgcc still come from gcc.
+        let sysroot = build::run_static(args.arch);
+        let link_flags = vec![
+            "-static-pie".to_string(),
+            format!("-B{}", sysroot.display()),
+            format!("-L{}", sysroot.display()),
+        ];
+        (root.join("examples/bin/static"), link_flags)
+    } else {
+        let miros = build::run(args.arch);
+        let bin_dir = root.join("examples/bin");
+        let interpreter = format!("-Wl,--dynamic-linker={}", miros.display());
+        // A RUNPATH, so examples find the shared objects built beside them by bare name.
+        let runpath = format!("-Wl,--enable-new-dtags,-rpath,{}", bin_dir.display());
+        (bin_dir, vec![interpreter, runpath])
+    };
+    fs::create_dir_all(&bin_dir).expect("create the examples' output directory");
 
-    let bin_dir = root.join("examples/bin");
-    fs::create_dir_all(&bin_dir).expect("create examples/bin");
-
-    for example in EXAMPLES {
-        let status = Command::new("gcc")
+    let musl = args.arch.is_native() && musl_gcc_installed();
+    if !musl && !args.static_link {
+        println!("skipping the musl examples: no native musl-gcc");
+    }
+    let examples: Vec<&Example> = EXAMPLES
+        .iter()
+        .filter(|example| !(args.static_link && DYNAMIC_ONLY.contains(&example.stem)))
+        .filter(|example| musl || !MUSL.contains(&example.stem))
+        .collect();
+    for example in &examples {
+        let compiler = if MUSL.contains(&example.stem) {
+            "musl-gcc"
+        } else {
+            args.arch.c_compiler()
+        };
+        let status = Command::new(compiler)
             .current_dir(&root)
             .arg("-o")
             .arg(bin_dir.join(example.stem))
             .arg(format!("examples/{}.c", example.stem))
             .args(example.flags)
-            .arg(&interpreter)
+            .args(&link_flags)
             .status()
             .expect("failed to spawn gcc");
         assert!(status.success(), "compiling {} failed", example.stem);
     }
 
+    if args.run {
+        run_examples(args.arch, &bin_dir, &examples);
+    }
+
+    // The Rust example doesn't use miros; it only checks the toolchain, so a cross or static build has no use for it.
+    if !args.arch.is_native() || args.static_link {
+        return;
+    }
+
     // The Rust example is its own cargo project.
     let status = Command::new("cargo")
         .current_dir(&root)
@@ -89,3 +237,55 @@ pub fn run() {
         .expect("failed to spawn cargo");
     assert!(status.success(), "building hello_world failed");
 }
+
+fn musl_gcc_installed() -> bool {
+    Command::new("musl-gcc")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Runs every built example, panicking with the names of those that failed.
+fn run_examples(arch: Arch, bin_dir: &Path, examples: &[&Example]) {
+    // `-L` points qemu at the cross toolchain's libraries; miros's own absolute path is used as is, since qemu
+    // falls back to the host path when the sysroot has no such file. Debian's cross toolchains report no sysroot and
+    // keep their libraries under `/usr/<triple>` instead.
+    let emulator = (!arch.is_native()).then(|| {
+        let output = Command::new(arch.c_compiler())
+            .arg("-print-sysroot")
+            .output()
+            .expect("failed to spawn the cross compiler");
+        let sysroot = String::from_utf8(output.stdout).expect("sysroot is UTF-8");
+        let sysroot = match sysroot.trim() {
+            "" | "/" => {
+                let triple = arch.c_compiler().trim_end_matches("-gcc");
+                format!("/usr/{triple}")
+            }
+            sysroot => sysroot.to_string(),
+        };
+        let qemu = format!("qemu-{}", arch.name());
+        (qemu, sysroot)
+    });
+
+    let mut failed = Vec::new();
+    // Shared objects (the audit module, libraries examples load) are not programs.
+    for example in examples
+        .iter()
+        .filter(|example| !example.flags.contains(&"-shared"))
+    {
+        let binary = bin_dir.join(example.stem);
+        let mut command = match &emulator {
+            Some((qemu, sysroot)) => {
+                let mut command = Command::new(qemu);
+                command.arg("-L").arg(sysroot).arg(&binary);
+                command
+            }
+            None => Command::new(&binary),
+        };
+        let status = command.status().expect("failed to spawn example");
+        if !status.success() {
+            failed.push(example.stem);
+        }
+    }
+    assert!(failed.is_empty(), "examples failed: {}", failed.join(", "));
+}
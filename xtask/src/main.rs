@@ -10,11 +10,17 @@ mod examples;
 #[command(name = "xtask", about = "Development tasks for miros")]
 enum Xtask {
     /// Build libmiros.so (release)
-    Build,
+    Build {
+        #[arg(long, value_enum, default_value_t)]
+        arch: build::Arch,
+        /// Build libmiros.a and the crt objects for static-pie programs instead
+        #[arg(long = "static")]
+        static_link: bool,
+    },
     /// Regenerate the alias asm/version script from linked_aliases.def without building
     RegenerateAliases,
     /// Build miros + compile the example programs against it
-    Examples,
+    Examples(examples::ExamplesArgs),
     /// Run a binary under miros (patches a copy's interpreter)
     Demo(demo::DemoArgs),
     /// Run benchmarks comparing miros against glibc
@@ -23,13 +29,18 @@ enum Xtask {
 
 fn main() {
     match Xtask::parse() {
-        Xtask::Build => {
-            build::run();
+        Xtask::Build { arch, static_link } => {
+            if static_link {
+                let sysroot = build::run_static(arch);
+                println!("{}", sysroot.display());
+            } else {
+                build::run(arch);
+            }
         }
         Xtask::RegenerateAliases => {
             aliases::generate();
         }
-        Xtask::Examples => examples::run(),
+        Xtask::Examples(args) => examples::run(args),
         Xtask::Demo(args) => demo::run(args),
         Xtask::Bench(args) => bench::run(args),
     }